@@ -0,0 +1,111 @@
+//! Benchmark del hot path del StrategyEngine: ticks por segundo leyendo la
+//! posición de SQLite (antes) vs. del `PositionBook` en memoria (después).
+//!
+//! `cargo bench --bench position_book`
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+use the_chassis::engine::commands::ExecutionCommand;
+use the_chassis::engine::position_book::PositionBook;
+use the_chassis::engine::strategy::StrategyEngine;
+use the_chassis::price_feed::{PriceSource, PriceUpdate};
+use the_chassis::state_manager::{ExitRules, PositionState, StateManager, TpRung};
+
+const MINT: &str = "BENCHMint1111111111111111111111111111111111";
+
+fn position() -> PositionState {
+    PositionState {
+        id: None,
+        token_mint: MINT.to_string(),
+        symbol: "BENCH".to_string(),
+        entry_price: 0.001,
+        amount_sol: 1.0,
+        current_price: 0.001,
+        stop_loss_percent: -20.0,
+        trailing_enabled: true,
+        trailing_distance_percent: 25.0,
+        trailing_activation_threshold: 20.0,
+        trailing_highest_price: Some(0.001),
+        trailing_current_sl: Some(-20.0),
+        tp_ladder: vec![TpRung::new(100.0, 50.0), TpRung::new(200.0, 50.0)],
+        active: true,
+        created_at: 0,
+        updated_at: 0,
+        token_amount: 0.0,
+        realized_pnl_sol: 0.0,
+        exit_rules: ExitRules::default(),
+    }
+}
+
+/// Tick plano (precio = entrada): no dispara SL/TP, mide solo el coste del lookup + lógica
+fn tick() -> PriceUpdate {
+    PriceUpdate {
+        token_mint: MINT.to_string(),
+        symbol: "BENCH".to_string(),
+        price_usd: 0.15,
+        price_native: 0.001,
+        liquidity_usd: 50_000.0,
+        volume_24h: 100_000.0,
+        price_change_24h: 0.0,
+        source: PriceSource::WebSocket,
+        received_at: Instant::now(),
+    }
+}
+
+fn bench_tick_lookup(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let state_manager = Arc::new(rt.block_on(async {
+        let sm = StateManager::new("file:bench_positions?mode=memory&cache=shared")
+            .await
+            .unwrap();
+        sm.upsert_position(position()).await.unwrap();
+        sm
+    }));
+
+    let mut group = c.benchmark_group("tick_lookup");
+    group.throughput(Throughput::Elements(1));
+
+    // Antes: una lectura SQLite por PriceUpdate
+    group.bench_function("sqlite_get_position", |b| {
+        b.to_async(&rt).iter(|| async {
+            let pos = state_manager.get_position(MINT).await.unwrap();
+            criterion::black_box(pos);
+        })
+    });
+
+    // Después: lookup en el libro en memoria
+    let book = PositionBook::from_positions(vec![position()]);
+    group.bench_function("position_book_get", |b| {
+        b.iter(|| criterion::black_box(book.get(MINT).cloned()))
+    });
+
+    group.finish();
+}
+
+fn bench_process_price_tick(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let state_manager = Arc::new(rt.block_on(async {
+        StateManager::new("file:bench_engine?mode=memory&cache=shared")
+            .await
+            .unwrap()
+    }));
+    let mut engine = StrategyEngine::new(
+        state_manager,
+        PositionBook::from_positions(vec![position()]),
+    );
+    let (cmd_tx, _cmd_rx) = mpsc::channel::<ExecutionCommand>(1024);
+
+    let mut group = c.benchmark_group("strategy_engine");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("process_price_tick", |b| {
+        b.iter(|| rt.block_on(engine.process_price_tick(tick(), &cmd_tx)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_tick_lookup, bench_process_price_tick);
+criterion_main!(benches);
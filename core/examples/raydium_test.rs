@@ -37,8 +37,8 @@ async fn main() -> anyhow::Result<()> {
 
     match client.find_pool(sol_mint, usdc_mint).await {
         Ok(pool) => {
-            println!("✅ Pool encontrado: {}", pool.name);
-            println!("   AMM ID: {}", pool.amm_id);
+            println!("✅ Pool encontrado: {} [{}]", pool.name(), pool.kind());
+            println!("   Pool ID: {}", pool.address());
             println!();
         }
         Err(e) => {
@@ -52,8 +52,8 @@ async fn main() -> anyhow::Result<()> {
 
     match client.find_pool(sol_mint, usdt_mint).await {
         Ok(pool) => {
-            println!("✅ Pool encontrado: {}", pool.name);
-            println!("   AMM ID: {}", pool.amm_id);
+            println!("✅ Pool encontrado: {} [{}]", pool.name(), pool.kind());
+            println!("   Pool ID: {}", pool.address());
             println!();
         }
         Err(e) => {
@@ -67,7 +67,7 @@ async fn main() -> anyhow::Result<()> {
 
     match client.find_pool(sol_mint, fake_mint).await {
         Ok(pool) => {
-            println!("✅ Pool encontrado: {}", pool.name);
+            println!("✅ Pool encontrado: {}", pool.name());
         }
         Err(e) => {
             println!("✅ Error esperado: {}\n", e);
@@ -20,6 +20,13 @@
 //!
 //! **Opción B:** Parsear el AMM state (752+ bytes) que tiene reserves parciales.
 //! Menos fiable porque las reserves on-chain del AMM incluyen fees acumulados.
+//!
+//! ## Concentrated Liquidity (Raydium CLMM / Orca Whirlpool)
+//! ```text
+//!   Estado: { sqrt_price (Q64.64), liquidity L activa, ticks con liquidity_net }
+//!   Dentro de un rango:  Δy = L · Δ√P      Δx = L · Δ(1/√P)
+//!   Al cruzar un tick:   L += liquidity_net (subiendo) / L -= liquidity_net (bajando)
+//! ```
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -242,6 +249,12 @@ impl RaydiumPoolState {
 
         ((price_after - price_before) / price_before) * 100.0
     }
+
+    /// Mayor venta (base tokens) cuyo impacto no supera `max_impact_pct`.
+    /// Inversa de `price_impact_for_sell`.
+    pub fn max_sell_for_impact(&self, max_impact_pct: f64) -> f64 {
+        self.base_reserve() * max_sell_fraction_for_impact(max_impact_pct)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -326,6 +339,211 @@ pub fn build_vault_tracker(pairs: Vec<VaultPair>) -> (VaultTracker, HashMap<Stri
     (Arc::new(RwLock::new(tracker_map)), vault_to_mint)
 }
 
+// ═══════════════════════════════════════════════════════════════════
+// CONSTANT PRODUCT QUOTE (Raydium V4 / CPMM)
+// ═══════════════════════════════════════════════════════════════════
+
+/// Salida exacta (raw) de un swap x·y=k con fee cobrado sobre la entrada.
+/// Replica el redondeo on-chain: fee redondeado hacia arriba, salida hacia abajo.
+pub fn constant_product_amount_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> u64 {
+    if reserve_in == 0 || reserve_out == 0 || amount_in == 0 || fee_denominator == 0 {
+        return 0;
+    }
+    let amount_in = amount_in as u128;
+    let fee = (amount_in * fee_numerator as u128).div_ceil(fee_denominator as u128);
+    let amount_in_net = amount_in.saturating_sub(fee);
+
+    let out = (reserve_out as u128 * amount_in_net) / (reserve_in as u128 + amount_in_net);
+    out as u64
+}
+
+/// Fracción de la reserva base que se puede vender moviendo el precio como
+/// mucho `max_impact_pct` (%). En x·y=k el precio cae con el cuadrado:
+/// `P'/P = (x / (x + Δx))²`  →  `Δx / x = 1/√(1 - impacto) - 1`.
+pub fn max_sell_fraction_for_impact(max_impact_pct: f64) -> f64 {
+    let impact = (max_impact_pct.abs() / 100.0).min(1.0);
+    if impact >= 1.0 {
+        return f64::INFINITY;
+    }
+    1.0 / (1.0 - impact).sqrt() - 1.0
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// CONCENTRATED LIQUIDITY QUOTE (Raydium CLMM / Orca Whirlpool)
+// ═══════════════════════════════════════════════════════════════════
+
+/// Tick inicializado con su liquidez neta (lo único necesario para cotizar)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClTick {
+    pub tick: i32,
+    pub liquidity_net: i128,
+}
+
+/// Resultado de recorrer los ticks para un swap exact-in
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClQuote {
+    /// Entrada consumida (raw, fee incluido)
+    pub amount_in: u64,
+    /// Salida estimada (raw)
+    pub amount_out: u64,
+    /// Fee total pagado (raw, token de entrada)
+    pub fee_amount: u64,
+    /// Ticks inicializados cruzados
+    pub ticks_crossed: u32,
+    /// Impacto de precio (%) excluyendo el fee
+    pub price_impact_pct: f64,
+    /// false si la liquidez cargada no alcanzó para toda la entrada
+    pub fully_filled: bool,
+}
+
+/// √P de un tick: √(1.0001^tick)
+pub fn tick_to_sqrt_price(tick: i32) -> f64 {
+    1.0001f64.powf(tick as f64 / 2.0)
+}
+
+/// Convierte un sqrt_price en formato Q64.64 a f64
+pub fn sqrt_price_from_x64(sqrt_price_x64: u128) -> f64 {
+    sqrt_price_x64 as f64 / 18_446_744_073_709_551_616.0 // 2^64
+}
+
+/// Cotiza un swap exact-in recorriendo los ticks inicializados.
+///
+/// - `ticks` ordenados ascendentemente (solo los cargados; fuera de ellos no hay liquidez conocida).
+/// - `zero_for_one`: true = entra token0 y el precio baja.
+/// - `fee_rate`: fracción (0.0025 = 0.25%).
+///
+/// Precisión f64: error relativo ~1e-12, suficiente para derivar `min_amount_out`.
+pub fn quote_concentrated(
+    sqrt_price: f64,
+    liquidity: u128,
+    ticks: &[ClTick],
+    amount_in: u64,
+    zero_for_one: bool,
+    fee_rate: f64,
+) -> ClQuote {
+    let mut sqrt_p = sqrt_price;
+    let mut liq = liquidity as f64;
+    let mut remaining = amount_in as f64;
+    let mut amount_out = 0.0f64;
+    let mut fee_total = 0.0f64;
+    let mut ticks_crossed = 0u32;
+
+    // Ticks en la dirección del swap
+    let path: Vec<&ClTick> = if zero_for_one {
+        ticks
+            .iter()
+            .rev()
+            .filter(|t| tick_to_sqrt_price(t.tick) < sqrt_p)
+            .collect()
+    } else {
+        ticks
+            .iter()
+            .filter(|t| tick_to_sqrt_price(t.tick) > sqrt_p)
+            .collect()
+    };
+
+    for next in path {
+        if remaining <= 0.0 {
+            break;
+        }
+        let sqrt_target = tick_to_sqrt_price(next.tick);
+
+        if liq > 0.0 {
+            let net_available = remaining * (1.0 - fee_rate);
+
+            // Entrada neta máxima para llegar al siguiente tick
+            let max_in = if zero_for_one {
+                liq * (1.0 / sqrt_target - 1.0 / sqrt_p)
+            } else {
+                liq * (sqrt_target - sqrt_p)
+            };
+
+            if net_available < max_in {
+                // El swap termina dentro de este rango
+                let sqrt_new = if zero_for_one {
+                    1.0 / (1.0 / sqrt_p + net_available / liq)
+                } else {
+                    sqrt_p + net_available / liq
+                };
+                amount_out += if zero_for_one {
+                    liq * (sqrt_p - sqrt_new)
+                } else {
+                    liq * (1.0 / sqrt_p - 1.0 / sqrt_new)
+                };
+                fee_total += remaining - net_available;
+                remaining = 0.0;
+                break;
+            }
+
+            amount_out += if zero_for_one {
+                liq * (sqrt_p - sqrt_target)
+            } else {
+                liq * (1.0 / sqrt_p - 1.0 / sqrt_target)
+            };
+            let gross = max_in / (1.0 - fee_rate);
+            fee_total += gross - max_in;
+            remaining -= gross;
+        }
+
+        // Cruzar el tick
+        sqrt_p = sqrt_target;
+        liq += if zero_for_one {
+            -(next.liquidity_net as f64)
+        } else {
+            next.liquidity_net as f64
+        };
+        liq = liq.max(0.0);
+        ticks_crossed += 1;
+    }
+
+    // Sin más ticks cargados: consumir contra la liquidez activa restante
+    if remaining > 0.0 && liq > 0.0 {
+        let net = remaining * (1.0 - fee_rate);
+        let sqrt_new = if zero_for_one {
+            1.0 / (1.0 / sqrt_p + net / liq)
+        } else {
+            sqrt_p + net / liq
+        };
+        amount_out += if zero_for_one {
+            liq * (sqrt_p - sqrt_new)
+        } else {
+            liq * (1.0 / sqrt_p - 1.0 / sqrt_new)
+        };
+        fee_total += remaining - net;
+        remaining = 0.0;
+    }
+
+    let consumed = amount_in as f64 - remaining.max(0.0);
+    let net_in = consumed - fee_total;
+
+    // Precio spot en unidades out/in
+    let spot = if zero_for_one {
+        sqrt_price * sqrt_price
+    } else {
+        1.0 / (sqrt_price * sqrt_price)
+    };
+    let price_impact_pct = if net_in > 0.0 && spot > 0.0 {
+        ((spot - amount_out / net_in) / spot * 100.0).max(0.0)
+    } else {
+        0.0
+    };
+
+    ClQuote {
+        amount_in: consumed.round() as u64,
+        amount_out: amount_out.max(0.0).floor() as u64,
+        fee_amount: fee_total.max(0.0).ceil() as u64,
+        ticks_crossed,
+        price_impact_pct,
+        fully_filled: remaining <= 0.0,
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // PRICE CONVERSION
 // ═══════════════════════════════════════════════════════════════════
@@ -403,6 +621,65 @@ mod tests {
         // Debería ser aprox -1% (el precio baja cuando inyectas más tokens)
         assert!(impact < 0.0);
         assert!(impact > -2.0);
+
+        // La venta máxima para un 2% de impacto mueve el precio justo un 2%
+        let max_sell = state.max_sell_for_impact(2.0);
+        assert!((state.price_impact_for_sell(max_sell) + 2.0).abs() < 1e-9);
+        assert!(state.price_impact_for_sell(max_sell * 1.1) < -2.0);
+        assert_eq!(max_sell_fraction_for_impact(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_constant_product_amount_out() {
+        // 1000/1000 reserves, 10 in, 0.25% fee → 10 - 0.025 = 9.975 → out = 9.876...
+        let out = constant_product_amount_out(1_000_000, 1_000_000, 10_000, 2500, 1_000_000);
+        assert_eq!(out, 9_876);
+        assert_eq!(constant_product_amount_out(0, 1_000, 10, 25, 10_000), 0);
+    }
+
+    #[test]
+    fn test_concentrated_single_range_matches_constant_product() {
+        // Sin ticks y sin fee, un rango infinito se comporta como x·y=k con
+        // reservas virtuales x = L/√P, y = L·√P
+        let liquidity: u128 = 1_000_000_000;
+        let sqrt_p = 1.0;
+        let quote = quote_concentrated(sqrt_p, liquidity, &[], 1_000_000, true, 0.0);
+
+        let x = liquidity as f64 / sqrt_p;
+        let y = liquidity as f64 * sqrt_p;
+        let expected = y * 1_000_000.0 / (x + 1_000_000.0);
+
+        assert!(quote.fully_filled);
+        assert!((quote.amount_out as f64 - expected).abs() <= 1.0);
+        assert!(quote.price_impact_pct > 0.0);
+    }
+
+    #[test]
+    fn test_concentrated_crosses_ticks() {
+        let liquidity: u128 = 1_000_000;
+        // Liquidez solo entre los ticks -100 y 100
+        let ticks = vec![
+            ClTick {
+                tick: -100,
+                liquidity_net: 1_000_000,
+            },
+            ClTick {
+                tick: 100,
+                liquidity_net: -1_000_000,
+            },
+        ];
+
+        // Swap enorme: agota el rango y se queda sin liquidez
+        let quote = quote_concentrated(1.0, liquidity, &ticks, 10_000_000, true, 0.003);
+        assert_eq!(quote.ticks_crossed, 1);
+        assert!(!quote.fully_filled);
+        assert!(quote.amount_in < 10_000_000);
+
+        // Swap pequeño: se queda dentro del rango
+        let small = quote_concentrated(1.0, liquidity, &ticks, 100, false, 0.003);
+        assert_eq!(small.ticks_crossed, 0);
+        assert!(small.fully_filled);
+        assert!(small.amount_out < 100);
     }
 
     #[test]
@@ -17,13 +17,18 @@
 //! ```
 
 use anyhow::{anyhow, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
 use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
-use crate::engine::{DecisionEngine, TokenContext};
+use crate::config::AppConfig;
+use crate::engine::honeypot::HoneypotFilter;
+use crate::engine::{DecisionEngine, ExecutionParams, TokenContext};
 use crate::executor_v2::{ExecutorConfig, TradeExecutor};
 use crate::price_feed::PriceCache;
 use crate::sensors::dexscreener::DexScreenerSensor;
@@ -100,7 +105,9 @@ impl AutoBuyer {
         };
 
         Ok(Self {
-            engine: DecisionEngine::new(),
+            engine: DecisionEngine::with_settings(
+                &AppConfig::load().map(|c| c.filters).unwrap_or_default(),
+            ),
             executor: Arc::new(TradeExecutor::new(config)),
             helius_sensor,
             dexscreener_sensor: DexScreenerSensor::new(),
@@ -109,6 +116,26 @@ impl AutoBuyer {
         })
     }
 
+    /// Publica las decisiones del DecisionEngine en el EventBus (gRPC streaming)
+    pub fn with_event_bus(mut self, bus: &crate::engine::events::EventBus) -> Self {
+        self.engine.set_decision_sink(bus.decision_sink());
+        self
+    }
+
+    /// Activa el filtro anti-honeypot: antes de comprar simula compra + venta
+    /// inmediata pagadas por `payer` (sin firmar ni enviar) en los venues del
+    /// executor, bonding curve de Pump.fun incluida
+    pub fn with_honeypot_check(mut self, rpc_url: &str, payer: Pubkey) -> Result<Self> {
+        let venues = self.executor.probe_venues();
+        let simulator = Arc::new(RpcClient::new_with_commitment(
+            rpc_url.to_string(),
+            CommitmentConfig::confirmed(),
+        ));
+        self.engine
+            .add_filter(Box::new(HoneypotFilter::new(venues, simulator, payer)));
+        Ok(self)
+    }
+
     /// Registra un nuevo precio en el historial de momentum para un token.
     /// Debe llamarse desde el loop de monitoreo cada vez que llega un PriceUpdate.
     pub async fn record_price_tick(&self, token_mint: &str, price: f64) {
@@ -185,8 +212,9 @@ impl AutoBuyer {
         }
     }
 
-    /// Ejecuta el proceso completo de compra inteligente
-    pub async fn buy(&self, config: &AutoBuyConfig, wallet: &Keypair) -> Result<BuyResult> {
+    /// Construye el contexto real y lo pasa por el DecisionEngine (filtros +
+    /// actuadores). La decisión se publica en el EventBus si hay uno conectado.
+    pub async fn evaluate(&self, config: &AutoBuyConfig) -> Result<ExecutionParams> {
         println!("🤖 AUTO-BUYER v2.0: Analizando {}...", config.token_mint);
 
         // 1. Construir Contexto con datos REALES
@@ -210,6 +238,12 @@ impl AutoBuyer {
             "   ⏱️  Edad: {} min | Liq: ${:.0} | Vol5m: ${:.0}",
             ctx.age_minutes, ctx.liquidity_usd, ctx.volume_5m
         );
+        println!(
+            "   🔥 LP quemado: {} | Slot lag: {}",
+            ctx.lp_burned_pct
+                .map_or("no medible".to_string(), |pct| format!("{:.1}%", pct)),
+            ctx.slot_lag
+        );
 
         // 2. Evaluar Decision Engine (Filtros + Actuadores)
         let exec_params = match self.engine.evaluate(&ctx) {
@@ -225,6 +259,13 @@ impl AutoBuyer {
             exec_params.maturity_stage, exec_params.priority_fee_lamports, exec_params.slippage_bps
         );
 
+        Ok(exec_params)
+    }
+
+    /// Ejecuta el proceso completo de compra inteligente
+    pub async fn buy(&self, config: &AutoBuyConfig, wallet: &Keypair) -> Result<BuyResult> {
+        let exec_params = self.evaluate(config).await?;
+
         // 3. Ejecución con parámetros optimizados del Engine
         let swap_result = self
             .executor
@@ -277,14 +318,40 @@ impl AutoBuyer {
 
         let dex_future = self.dexscreener_sensor.get_token_market_data(mint);
         let momentum_future = self.calculate_momentum_slope(mint);
+        let slot_lag_future = self.helius_sensor.measure_slot_lag();
 
         // Ejecutar helius y dexscreener en paralelo, momentum es local (instantáneo)
         let momentum_slope = momentum_future.await;
-        let (helius_result, dex_result) = tokio::join!(helius_future, dex_future);
+        let (helius_result, dex_result, slot_lag_result) =
+            tokio::join!(helius_future, dex_future, slot_lag_future);
 
         let helius_data = helius_result?;
         let market_data = dex_result?;
 
+        // LP del pool principal: sin dato decide `filters.unmeasured_lp`
+        let lp_burned_pct = match self
+            .helius_sensor
+            .lp_burned_pct(&market_data.dex_id, &market_data.pair_address)
+            .await
+        {
+            Ok(pct) => Some(pct),
+            Err(e) => {
+                eprintln!(
+                    "⚠️ [SENSORS] LP de {} no medible: {}",
+                    market_data.pair_address, e
+                );
+                None
+            }
+        };
+        // Sin medir el lag no sabemos si nuestras TXs llegarán a tiempo
+        let slot_lag = match slot_lag_result {
+            Ok(lag) => lag,
+            Err(e) => {
+                eprintln!("⚠️ [SENSORS] Slot lag no medible: {}", e);
+                u64::MAX
+            }
+        };
+
         println!(
             "   ⚡ On-Chain: Auth_mint={:?} | Auth_freeze={:?} | Edad={}min | Top10={:.1}%",
             helius_data.security.mint_authority,
@@ -328,13 +395,20 @@ impl AutoBuyer {
             unique_wallets_ratio: helius_data.unique_wallets_ratio,
             // ✅ REAL: Calculado desde balances de los top holders
             top_10_holders_pct: helius_data.top_10_holders_pct,
-            // ✅ REAL: Calculado desde el mayor holder (heurística dev wallet)
+            // ✅ REAL: Mayor wallet sin contar pools ni bonding curve (heurística dev wallet)
             dev_wallet_pct: helius_data.dev_wallet_pct,
             // ✅ REAL: Authority data on-chain
             mint_authority: helius_data.security.mint_authority,
             freeze_authority: helius_data.security.freeze_authority,
-            // Asumimos LP quemado si no tenemos dato (conservative = safer)
-            lp_burned_pct: 100.0,
+            // ✅ REAL: LP emitido vs supply del LP mint del pool principal
+            lp_burned_pct,
+            // ✅ REAL: Extensiones Token-2022 del mint
+            transfer_fee_bps: helius_data.security.transfer_fee_bps,
+            permanent_delegate: helius_data.security.permanent_delegate,
+            transfer_hook_program: helius_data.security.transfer_hook_program,
+            non_transferable: helius_data.security.non_transferable,
+            // ✅ REAL: Lag del RPC (u64::MAX si no se pudo medir: bloquea la compra)
+            slot_lag,
         })
     }
 }
@@ -0,0 +1,350 @@
+//! # Circuit Breaker de Portfolio
+//!
+//! Bloquea las COMPRAS cuando el PnL realizado (tabla `trades` del `StateManager`)
+//! cruza alguno de los límites de riesgo:
+//! - Pérdida acumulada en las últimas 24h
+//! - Racha de trades perdedores consecutivos
+//! - Drawdown desde el pico de equity
+//!
+//! Las salidas (SL/TP/panic) NUNCA se bloquean: con el breaker disparado lo que
+//! queremos es cerrar riesgo, no quedarnos atrapados en las posiciones abiertas.
+//!
+//! El estado (disparado, motivo, rearme programado, capital de referencia) vive
+//! en SQLite para que un reinicio del bot no rearme el sistema a mitad de una
+//! racha perdedora ni re-base el capital con el balance ya mermado.
+
+use anyhow::Result;
+use chrono::Utc;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::state_manager::{BreakerState, StateManager};
+use crate::telegram::TelegramNotifier;
+
+/// Flag global: cuando true, ningún camino de compra debe ejecutar
+pub static BUYS_HALTED: AtomicBool = AtomicBool::new(false);
+
+/// Ventana del PnL diario (rolling)
+const DAILY_WINDOW_SECS: i64 = 24 * 3600;
+
+/// Verifica si el Circuit Breaker de portfolio está bloqueando compras
+pub fn buys_halted() -> bool {
+    BUYS_HALTED.load(Ordering::Relaxed)
+}
+
+// ============================================================================
+// LÍMITES Y EVALUACIÓN
+// ============================================================================
+
+/// Límites de riesgo del portfolio
+#[derive(Debug, Clone)]
+pub struct BreakerLimits {
+    /// Pérdida máxima en 24h, en % del capital de referencia (Ej: 10.0)
+    pub max_daily_loss_pct: f64,
+    /// Trades perdedores seguidos antes de parar (Ej: 4)
+    pub max_consecutive_losses: u32,
+    /// Drawdown máximo desde el pico de equity, en % (Ej: 20.0)
+    pub max_drawdown_pct: f64,
+    /// Capital base en SOL sobre el que se miden los porcentajes.
+    /// Solo fija la referencia la primera vez: después manda la persistida.
+    pub capital_sol: f64,
+    /// Horas hasta el rearme automático tras un disparo
+    pub cooldown_hours: i64,
+}
+
+impl Default for BreakerLimits {
+    fn default() -> Self {
+        Self {
+            max_daily_loss_pct: 10.0,
+            max_consecutive_losses: 4,
+            max_drawdown_pct: 20.0,
+            capital_sol: 1.0,
+            cooldown_hours: 24,
+        }
+    }
+}
+
+impl BreakerLimits {
+    pub fn with_capital(mut self, capital_sol: f64) -> Self {
+        self.capital_sol = capital_sol;
+        self
+    }
+}
+
+/// Motivo de disparo
+#[derive(Debug, Clone, PartialEq)]
+pub enum TripReason {
+    DailyLoss { pnl_sol: f64, pct: f64 },
+    ConsecutiveLosses(u32),
+    Drawdown { pct: f64 },
+}
+
+impl fmt::Display for TripReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TripReason::DailyLoss { pnl_sol, pct } => {
+                write!(f, "Pérdida 24h {:.4} SOL ({:.1}%)", pnl_sol, pct)
+            }
+            TripReason::ConsecutiveLosses(n) => write!(f, "{} trades perdedores seguidos", n),
+            TripReason::Drawdown { pct } => write!(f, "Drawdown {:.1}% desde el pico", pct),
+        }
+    }
+}
+
+/// Capital de referencia: el persistido si existe, si no el de `limits`
+pub fn capital_baseline(state: &BreakerState, limits: &BreakerLimits) -> f64 {
+    state
+        .capital_sol
+        .filter(|capital| *capital > 0.0)
+        .unwrap_or(limits.capital_sol)
+}
+
+/// Evalúa la serie de PnL realizado `(timestamp, pnl_sol)` en orden cronológico.
+///
+/// `since` es el último rearme: nada anterior cuenta para ninguna métrica.
+pub fn evaluate(
+    pnl: &[(i64, f64)],
+    since: i64,
+    now: i64,
+    limits: &BreakerLimits,
+) -> Option<TripReason> {
+    let series: Vec<&(i64, f64)> = pnl.iter().filter(|(ts, _)| *ts >= since).collect();
+
+    // 1. Pérdida rolling 24h
+    let window_start = now - DAILY_WINDOW_SECS;
+    let daily_pnl: f64 = series
+        .iter()
+        .filter(|(ts, _)| *ts >= window_start)
+        .map(|(_, p)| p)
+        .sum();
+    if limits.capital_sol > 0.0 {
+        let pct = daily_pnl / limits.capital_sol * 100.0;
+        if pct <= -limits.max_daily_loss_pct {
+            return Some(TripReason::DailyLoss {
+                pnl_sol: daily_pnl,
+                pct,
+            });
+        }
+    }
+
+    // 2. Racha perdedora (desde el trade más reciente hacia atrás)
+    let streak = series.iter().rev().take_while(|(_, p)| *p < 0.0).count() as u32;
+    if limits.max_consecutive_losses > 0 && streak >= limits.max_consecutive_losses {
+        return Some(TripReason::ConsecutiveLosses(streak));
+    }
+
+    // 3. Drawdown desde el pico de equity
+    let mut equity = limits.capital_sol;
+    let mut peak = equity;
+    for (_, p) in &series {
+        equity += p;
+        peak = peak.max(equity);
+    }
+    if peak > 0.0 {
+        let pct = (peak - equity) / peak * 100.0;
+        if pct >= limits.max_drawdown_pct {
+            return Some(TripReason::Drawdown { pct });
+        }
+    }
+
+    None
+}
+
+// ============================================================================
+// PORTFOLIO BREAKER
+// ============================================================================
+
+pub struct PortfolioBreaker {
+    state_manager: Arc<StateManager>,
+    telegram: Arc<TelegramNotifier>,
+    limits: BreakerLimits,
+}
+
+impl PortfolioBreaker {
+    pub fn new(
+        state_manager: Arc<StateManager>,
+        telegram: Arc<TelegramNotifier>,
+        limits: BreakerLimits,
+    ) -> Self {
+        Self {
+            state_manager,
+            telegram,
+            limits,
+        }
+    }
+
+    /// Un ciclo de evaluación: rearma si toca, o dispara si se cruzó un límite.
+    /// Sincroniza el flag global `BUYS_HALTED` con el estado persistido.
+    pub async fn check(&self) -> Result<BreakerState> {
+        let now = Utc::now().timestamp();
+        let mut state = self.state_manager.get_breaker_state().await?;
+
+        // Primer arranque: el capital actual queda como referencia para siempre
+        let capital_sol = capital_baseline(&state, &self.limits);
+        if state.capital_sol != Some(capital_sol) {
+            state.capital_sol = Some(capital_sol);
+            self.state_manager.save_breaker_state(state.clone()).await?;
+        }
+
+        if state.tripped {
+            if state.reset_at.is_some_and(|reset_at| now >= reset_at) {
+                let reason = state.reason.take().unwrap_or_default();
+                state = BreakerState {
+                    last_reset_at: now,
+                    capital_sol: state.capital_sol,
+                    ..BreakerState::default()
+                };
+                self.state_manager.save_breaker_state(state.clone()).await?;
+                BUYS_HALTED.store(false, Ordering::Relaxed);
+
+                println!("✅ [CIRCUIT BREAKER] Rearmado. Compras habilitadas.");
+                let _ = self
+                    .telegram
+                    .send_circuit_breaker_alert(false, &reason, None)
+                    .await;
+            } else {
+                BUYS_HALTED.store(true, Ordering::Relaxed);
+            }
+            return Ok(state);
+        }
+
+        let pnl = self
+            .state_manager
+            .get_realized_pnl_since(state.last_reset_at)
+            .await?;
+
+        let limits = self.limits.clone().with_capital(capital_sol);
+        match evaluate(&pnl, state.last_reset_at, now, &limits) {
+            Some(reason) => {
+                let reason_text = reason.to_string();
+                state.tripped = true;
+                state.reason = Some(reason_text.clone());
+                state.tripped_at = Some(now);
+                state.reset_at = Some(now + self.limits.cooldown_hours * 3600);
+                self.state_manager.save_breaker_state(state.clone()).await?;
+                BUYS_HALTED.store(true, Ordering::Relaxed);
+
+                eprintln!(
+                    "⛔ [CIRCUIT BREAKER] ¡TRIPPED! {}. Compras bloqueadas, salidas activas.",
+                    reason_text
+                );
+                let _ = self
+                    .telegram
+                    .send_circuit_breaker_alert(true, &reason_text, state.reset_at)
+                    .await;
+            }
+            None => BUYS_HALTED.store(false, Ordering::Relaxed),
+        }
+
+        Ok(state)
+    }
+
+    /// Loop de vigilancia en background
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        loop {
+            if let Err(e) = self.check().await {
+                // Sin estado fiable preferimos no abrir posiciones nuevas
+                BUYS_HALTED.store(true, Ordering::Relaxed);
+                eprintln!("⚠️ [CIRCUIT BREAKER] Error evaluando PnL: {}", e);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn limits() -> BreakerLimits {
+        BreakerLimits::default().with_capital(2.0)
+    }
+
+    #[test]
+    fn test_daily_loss_trips() {
+        // -0.25 SOL sobre 2 SOL = -12.5%
+        let pnl = vec![(NOW - 600, -0.15), (NOW - 300, 0.05), (NOW - 60, -0.15)];
+        assert!(matches!(
+            evaluate(&pnl, 0, NOW, &limits()),
+            Some(TripReason::DailyLoss { .. })
+        ));
+
+        // Las mismas pérdidas fuera de la ventana de 24h no cuentan
+        let old: Vec<(i64, f64)> = pnl.iter().map(|(ts, p)| (ts - 2 * 86_400, *p)).collect();
+        assert_eq!(evaluate(&old, 0, NOW, &limits()), None);
+    }
+
+    #[test]
+    fn test_consecutive_losses_trip() {
+        let pnl = vec![
+            (NOW - 500, 0.10),
+            (NOW - 400, -0.01),
+            (NOW - 300, -0.01),
+            (NOW - 200, -0.01),
+            (NOW - 100, -0.01),
+        ];
+        assert_eq!(
+            evaluate(&pnl, 0, NOW, &limits()),
+            Some(TripReason::ConsecutiveLosses(4))
+        );
+
+        // Un ganador rompe la racha
+        let mut broken = pnl.clone();
+        broken.push((NOW - 50, 0.01));
+        assert_eq!(evaluate(&broken, 0, NOW, &limits()), None);
+    }
+
+    #[test]
+    fn test_drawdown_from_peak_trips() {
+        // Equity 2.0 → 3.0 (pico) → 2.3: drawdown 23%, pérdidas diarias netas positivas
+        let pnl = vec![
+            (NOW - 3_000, 1.0),
+            (NOW - 2_000, -0.35),
+            (NOW - 1_000, 0.01),
+            (NOW - 500, -0.36),
+        ];
+        match evaluate(&pnl, 0, NOW, &limits()) {
+            Some(TripReason::Drawdown { pct }) => assert!((pct - 23.0).abs() < 0.5),
+            other => panic!("esperado Drawdown, obtenido {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_capital_baseline_survives_restarts() {
+        // Primer arranque: sin capital persistido manda el balance actual
+        let fresh = BreakerState::default();
+        assert_eq!(capital_baseline(&fresh, &limits()), 2.0);
+
+        // Reinicio tras perder 0.5 SOL: la referencia sigue siendo la original
+        let persisted = BreakerState {
+            capital_sol: Some(2.0),
+            ..BreakerState::default()
+        };
+        let restarted = BreakerLimits::default().with_capital(1.5);
+        assert_eq!(capital_baseline(&persisted, &restarted), 2.0);
+
+        // -0.25 SOL: 12.5% sobre la referencia (dispara), no el 16.7% del balance nuevo
+        let pnl = vec![(NOW - 60, -0.25)];
+        let limits = restarted.with_capital(capital_baseline(&persisted, &limits()));
+        assert!(matches!(
+            evaluate(&pnl, 0, NOW, &limits),
+            Some(TripReason::DailyLoss { pct, .. }) if (pct + 12.5).abs() < 1e-9
+        ));
+    }
+
+    #[test]
+    fn test_reset_clears_history() {
+        let pnl = vec![(NOW - 400, -0.2), (NOW - 300, -0.2), (NOW - 200, 0.01)];
+        assert!(evaluate(&pnl, 0, NOW, &limits()).is_some());
+        // Tras un rearme solo cuenta lo posterior
+        assert_eq!(evaluate(&pnl, NOW - 250, NOW, &limits()), None);
+    }
+}
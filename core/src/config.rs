@@ -6,9 +6,19 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
 
+use crate::engine::types::UnmeasuredLp;
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AppConfig {
     pub global_settings: GlobalSettings,
+    #[serde(default)]
+    pub reconciler: ReconcilerSettings,
+    #[serde(default)]
+    pub sliced_exit: SlicedExitSettings,
+    #[serde(default)]
+    pub exits: ExitSettings,
+    #[serde(default)]
+    pub filters: FilterSettings,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -17,6 +27,157 @@ pub struct GlobalSettings {
     pub jito_tip_lamports: u64,
     pub auto_execute: bool,
     pub monitor_interval_sec: u64,
+    /// Espera máxima (ms) por el quote de cada venue al rankear rutas
+    #[serde(default = "default_quote_timeout_ms")]
+    pub quote_timeout_ms: u64,
+}
+
+fn default_quote_timeout_ms() -> u64 {
+    crate::venue::DEFAULT_QUOTE_TIMEOUT.as_millis() as u64
+}
+
+/// Reconciliador periódico DB ↔ wallet (sección opcional de settings.json)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ReconcilerSettings {
+    pub enabled: bool,
+    pub interval_sec: u64,
+    /// Adoptar como posición los tokens de la wallet que la DB no conoce
+    pub adopt_untracked: bool,
+    /// SL/TP con los que se arman las posiciones adoptadas
+    pub default_sl_percent: f64,
+    pub default_tp_percent: f64,
+    /// Segundo escalón de TP de las posiciones adoptadas
+    pub default_tp2_percent: f64,
+    /// % de la posición que vende cada escalón de TP
+    pub default_tp_sell_percent: f64,
+    /// Trailing stop de las posiciones adoptadas
+    pub default_trailing_distance_percent: f64,
+    pub default_trailing_activation_threshold: f64,
+    /// Valor mínimo (SOL) para adoptar: por debajo es polvo o spam airdropeado
+    pub min_adopt_value_sol: f64,
+    /// Desviación tolerada (%) entre los tokens esperados y los reales
+    pub drift_tolerance_pct: f64,
+    /// Mints que nunca se adoptan
+    pub ignore_mints: Vec<String>,
+}
+
+/// Stablecoins y majors que viven en la wallet como saldo, nunca como posición
+pub const DEFAULT_IGNORE_MINTS: &[&str] = &[
+    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", // USDC
+    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", // USDT
+    "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo", // PYUSD
+    "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN",  // JUP
+    "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R", // RAY
+    "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So",  // mSOL
+    "J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn", // jitoSOL
+    "bSo13r4TkiE4KumL71LsHTPpL2euBYLFx6h9HP3piy1",  // bSOL
+];
+
+impl Default for ReconcilerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_sec: 60,
+            // Adoptar es opt-in: cualquier airdrop con precio acabaría armado con SL/TP
+            adopt_untracked: false,
+            default_sl_percent: -50.0,
+            default_tp_percent: 100.0,
+            default_tp2_percent: 200.0,
+            default_tp_sell_percent: 50.0,
+            default_trailing_distance_percent: 25.0,
+            default_trailing_activation_threshold: 20.0,
+            min_adopt_value_sol: 0.01,
+            drift_tolerance_pct: 2.0,
+            ignore_mints: DEFAULT_IGNORE_MINTS.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+}
+
+/// Ventas troceadas (TWAP) en pools finos (sección opcional de settings.json)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct SlicedExitSettings {
+    pub enabled: bool,
+    /// Impacto máximo en precio (%) de cada trozo
+    pub max_impact_pct: f64,
+    /// Espera entre trozos
+    pub interval_ms: u64,
+    /// Tope de trozos: el último vende lo que quede
+    pub max_slices: u32,
+    /// Stop-loss: caída (%) del precio de fill desde el primer trozo que
+    /// dispara la venta del resto de golpe (0 = nunca)
+    pub escalate_drop_pct: f64,
+}
+
+impl Default for SlicedExitSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_impact_pct: 3.0,
+            interval_ms: 2_000,
+            max_slices: 5,
+            escalate_drop_pct: 10.0,
+        }
+    }
+}
+
+/// Salidas con las que se arman las compras que no traen las suyas (gRPC)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ExitSettings {
+    pub stop_loss_percent: f64,
+    pub trailing_enabled: bool,
+    pub trailing_distance_percent: f64,
+    pub trailing_activation_threshold: f64,
+    /// Escalera de TP en el formato de `/ladder` (`50:25,100:75`)
+    pub tp_ladder: String,
+}
+
+impl Default for ExitSettings {
+    fn default() -> Self {
+        Self {
+            stop_loss_percent: -40.0,
+            trailing_enabled: true,
+            trailing_distance_percent: 15.0,
+            trailing_activation_threshold: 10.0,
+            tp_ladder: "50:100".to_string(),
+        }
+    }
+}
+
+/// Umbrales de los filtros de seguridad del `DecisionEngine` (sección opcional de settings.json)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct FilterSettings {
+    /// Retraso máximo (slots) del RPC frente a la punta del cluster
+    pub max_slot_lag: u64,
+    /// Por debajo de esta edad (min) se exige `early_min_lp_burned_pct`
+    pub early_age_minutes: u64,
+    pub early_min_lp_burned_pct: f64,
+    /// LP quemado mínimo (%) fuera de la zona temprana
+    pub min_lp_burned_pct: f64,
+    /// Qué hacer si el LP no se puede medir (CLMM, Orca, Meteora o RPC caído)
+    pub unmeasured_lp: UnmeasuredLp,
+    pub max_top_10_holders_pct: f64,
+    /// % máximo de la mayor wallet que no es un PDA (proxy del dev)
+    pub max_dev_wallet_pct: f64,
+    pub min_unique_wallets_ratio: f64,
+}
+
+impl Default for FilterSettings {
+    fn default() -> Self {
+        Self {
+            max_slot_lag: 25,
+            early_age_minutes: 15,
+            early_min_lp_burned_pct: 100.0,
+            min_lp_burned_pct: 95.0,
+            unmeasured_lp: UnmeasuredLp::Reject,
+            max_top_10_holders_pct: 20.0,
+            max_dev_wallet_pct: 5.0,
+            min_unique_wallets_ratio: 0.20,
+        }
+    }
 }
 
 impl AppConfig {
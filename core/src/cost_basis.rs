@@ -0,0 +1,378 @@
+//! # Cost Basis — Lotes de compra y PnL por posición
+//!
+//! Cada compra (inicial o DCA vía `/rbuy`) abre un `Lot` con sus tokens y el SOL
+//! pagado. Las ventas se expresan como FRACCIÓN de lo que hay en la wallet (el
+//! executor vende un % del balance) y se valoran con dos métodos a la vez:
+//!
+//! - **WAVG** (media ponderada): el coste liberado es `fracción × coste restante`.
+//!   Es el método contable de la posición: `PositionState.amount_sol` es el coste
+//!   WAVG restante y `entry_price` el precio medio.
+//! - **FIFO**: se consumen primero los lotes más antiguos.
+//!
+//! Con una sola compra ambos coinciden; divergen tras DCA a precios distintos.
+
+use serde::{Deserialize, Serialize};
+
+use crate::reconciler::RECONCILE_TRADE_TYPE;
+use crate::state_manager::TradeRecord;
+
+/// Por encima de esta fracción una venta se trata como salida total (ruido de f64)
+const FULL_EXIT_FRACTION: f64 = 0.999;
+
+/// Lote de compra (tabla `position_lots`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lot {
+    pub id: Option<i64>,
+    pub token_mint: String,
+    /// Firma de la compra: clave de idempotencia
+    pub signature: String,
+    pub tokens: f64,
+    pub tokens_remaining: f64,
+    pub cost_sol: f64,
+    pub opened_at: i64,
+}
+
+impl Lot {
+    pub fn new(
+        token_mint: &str,
+        signature: &str,
+        tokens: f64,
+        cost_sol: f64,
+        opened_at: i64,
+    ) -> Self {
+        Self {
+            id: None,
+            token_mint: token_mint.to_string(),
+            signature: signature.to_string(),
+            tokens,
+            tokens_remaining: tokens,
+            cost_sol,
+            opened_at,
+        }
+    }
+
+    /// Coste por token (SOL)
+    pub fn unit_cost(&self) -> f64 {
+        if self.tokens > 0.0 {
+            self.cost_sol / self.tokens
+        } else {
+            0.0
+        }
+    }
+
+    /// Coste FIFO de los tokens que quedan en el lote
+    pub fn remaining_cost(&self) -> f64 {
+        self.tokens_remaining * self.unit_cost()
+    }
+}
+
+/// Coste liberado por una venta, en ambos métodos
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SellCost {
+    pub tokens: f64,
+    pub cost_wavg_sol: f64,
+    pub cost_fifo_sol: f64,
+    /// SOL recibido (None = desconocido: venta fuera del bot)
+    pub proceeds_sol: Option<f64>,
+}
+
+impl SellCost {
+    pub fn pnl_wavg(&self) -> Option<f64> {
+        self.proceeds_sol.map(|p| p - self.cost_wavg_sol)
+    }
+
+    pub fn pnl_fifo(&self) -> Option<f64> {
+        self.proceeds_sol.map(|p| p - self.cost_fifo_sol)
+    }
+
+    /// PnL % sobre el coste WAVG liberado
+    pub fn pnl_wavg_pct(&self) -> Option<f64> {
+        if self.cost_wavg_sol > 0.0 {
+            self.pnl_wavg().map(|pnl| pnl / self.cost_wavg_sol * 100.0)
+        } else {
+            None
+        }
+    }
+}
+
+// ============================================================================
+// LOT BOOK
+// ============================================================================
+
+/// Estado de cost basis de UNA posición: lotes abiertos + coste WAVG + realizados
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LotBook {
+    /// Lotes en orden de apertura (FIFO)
+    pub lots: Vec<Lot>,
+    /// Coste restante por media ponderada (= `PositionState.amount_sol`)
+    pub wavg_cost_sol: f64,
+    pub realized_wavg_sol: f64,
+    pub realized_fifo_sol: f64,
+}
+
+impl LotBook {
+    pub fn tokens_held(&self) -> f64 {
+        self.lots.iter().map(|l| l.tokens_remaining).sum()
+    }
+
+    /// Coste restante por FIFO
+    pub fn fifo_cost_sol(&self) -> f64 {
+        self.lots.iter().map(Lot::remaining_cost).sum()
+    }
+
+    /// Precio medio de entrada WAVG (SOL por token)
+    pub fn avg_entry_price(&self) -> Option<f64> {
+        let tokens = self.tokens_held();
+        (tokens > 0.0).then(|| self.wavg_cost_sol / tokens)
+    }
+
+    pub fn unrealized_wavg(&self, price: f64) -> f64 {
+        self.tokens_held() * price - self.wavg_cost_sol
+    }
+
+    pub fn unrealized_fifo(&self, price: f64) -> f64 {
+        self.tokens_held() * price - self.fifo_cost_sol()
+    }
+
+    /// Sin tokens ni coste: la posición está plana
+    pub fn is_flat(&self) -> bool {
+        self.tokens_held() <= 0.0 && self.wavg_cost_sol <= 0.0
+    }
+
+    /// Añade una compra
+    pub fn buy(&mut self, lot: Lot) {
+        self.wavg_cost_sol += lot.cost_sol;
+        if lot.tokens > 0.0 {
+            self.lots.push(lot);
+        }
+    }
+
+    /// Vende `fraction` (0..=1) de lo que queda. Consume lotes FIFO y reduce el
+    /// coste WAVG en la misma proporción.
+    pub fn sell(&mut self, fraction: f64, proceeds_sol: Option<f64>) -> SellCost {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let full_exit = fraction >= FULL_EXIT_FRACTION;
+
+        let held = self.tokens_held();
+        let tokens = if full_exit { held } else { held * fraction };
+        let cost_wavg_sol = if full_exit {
+            self.wavg_cost_sol
+        } else {
+            self.wavg_cost_sol * fraction
+        };
+
+        let mut cost_fifo_sol = 0.0;
+        let mut to_consume = tokens;
+        for lot in self.lots.iter_mut() {
+            if to_consume <= 0.0 {
+                break;
+            }
+            let taken = if full_exit {
+                lot.tokens_remaining
+            } else {
+                lot.tokens_remaining.min(to_consume)
+            };
+            cost_fifo_sol += taken * lot.unit_cost();
+            lot.tokens_remaining -= taken;
+            to_consume -= taken;
+        }
+        // Posición sin tokens conocidos (p.ej. /track legacy): FIFO = WAVG
+        if held <= 0.0 {
+            cost_fifo_sol = cost_wavg_sol;
+        }
+
+        self.wavg_cost_sol = (self.wavg_cost_sol - cost_wavg_sol).max(0.0);
+
+        let cost = SellCost {
+            tokens,
+            cost_wavg_sol,
+            cost_fifo_sol,
+            proceeds_sol,
+        };
+        if let (Some(wavg), Some(fifo)) = (cost.pnl_wavg(), cost.pnl_fifo()) {
+            self.realized_wavg_sol += wavg;
+            self.realized_fifo_sol += fifo;
+        }
+        cost
+    }
+}
+
+// ============================================================================
+// REPLAY (BACKFILL DESDE `trades`)
+// ============================================================================
+
+/// Efecto de un trade del historial sobre el `LotBook` de su mint
+#[derive(Debug, Clone, PartialEq)]
+pub enum TradeStep {
+    Buy(Lot),
+    /// Fracción vendida de lo que queda y SOL recibido (`None` si los tokens
+    /// salieron fuera del bot)
+    Sell {
+        fraction: f64,
+        proceeds_sol: Option<f64>,
+    },
+}
+
+/// Clasifica un trade contra el estado actual del libro (sin aplicarlo).
+///
+/// - Compras: `trade_type` con "BUY", o `RECONCILE` con SOL positivo.
+/// - Ventas: la fracción vendida sale del coste que liberó el trade. El router
+///   siempre registró `pnl = recibido − invertido × pct`, así que
+///   `recibido − pnl` es exactamente el coste liberado. Sin PnL → salida total.
+/// - `RECONCILE` negativo: tokens que salieron fuera del bot, sin SOL recibido
+///   conocido; `amount_sol` es el coste que liberan.
+pub fn trade_step(trade: &TradeRecord, book: &LotBook) -> TradeStep {
+    let is_buy = trade.trade_type.contains("BUY")
+        || (trade.trade_type == RECONCILE_TRADE_TYPE && trade.amount_sol > 0.0);
+
+    if is_buy {
+        return TradeStep::Buy(Lot::new(
+            &trade.token_mint,
+            &trade.signature,
+            trade.tokens_amount.abs(),
+            trade.amount_sol.abs(),
+            trade.timestamp,
+        ));
+    }
+
+    let (fraction, proceeds_sol) = if trade.trade_type == RECONCILE_TRADE_TYPE {
+        (
+            released_fraction(trade.amount_sol.abs(), book.wavg_cost_sol),
+            None,
+        )
+    } else if let Some(pnl) = trade.pnl_sol {
+        (
+            released_fraction(trade.amount_sol - pnl, book.wavg_cost_sol),
+            Some(trade.amount_sol),
+        )
+    } else {
+        (1.0, Some(trade.amount_sol))
+    };
+
+    TradeStep::Sell {
+        fraction,
+        proceeds_sol,
+    }
+}
+
+/// Reconstruye el `LotBook` de un mint a partir de su historial de trades
+/// (orden cronológico, clasificado con `trade_step`). Usado por la migración
+/// que backfillea las posiciones existentes.
+///
+/// Una salida total cierra el ciclo: una recompra posterior empieza de cero.
+pub fn replay_trades(trades: &[TradeRecord]) -> LotBook {
+    let mut book = LotBook::default();
+
+    for trade in trades {
+        match trade_step(trade, &book) {
+            TradeStep::Buy(lot) => book.buy(lot),
+            TradeStep::Sell {
+                fraction,
+                proceeds_sol,
+            } => {
+                book.sell(fraction, proceeds_sol);
+                if book.is_flat() {
+                    // Ciclo cerrado: los realizados pertenecen a la posición anterior
+                    book = LotBook::default();
+                }
+            }
+        }
+    }
+
+    book
+}
+
+/// Fracción de la posición que libera `cost_released` (sin coste conocido → todo)
+fn released_fraction(cost_released: f64, wavg_cost: f64) -> f64 {
+    if wavg_cost > 0.0 {
+        (cost_released / wavg_cost).clamp(0.0, 1.0)
+    } else {
+        1.0
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(
+        trade_type: &str,
+        amount_sol: f64,
+        tokens: f64,
+        pnl: Option<f64>,
+        ts: i64,
+    ) -> TradeRecord {
+        TradeRecord {
+            id: None,
+            signature: format!("SIG_{}", ts),
+            token_mint: "MINT".to_string(),
+            symbol: "TST".to_string(),
+            trade_type: trade_type.to_string(),
+            amount_sol,
+            tokens_amount: tokens,
+            price: 0.0,
+            pnl_sol: pnl,
+            pnl_percent: None,
+            route: "test".to_string(),
+            price_impact_pct: 0.0,
+            fee_sol: 0.0,
+            timestamp: ts,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_dca_then_partial_exit() {
+        let mut book = LotBook::default();
+        // 1 SOL → 1000 tokens (0.001), DCA 1 SOL → 2000 tokens (0.0005)
+        book.buy(Lot::new("MINT", "B1", 1_000.0, 1.0, 1));
+        book.buy(Lot::new("MINT", "B2", 2_000.0, 1.0, 2));
+        assert!(close(book.tokens_held(), 3_000.0));
+        assert!(close(book.avg_entry_price().unwrap(), 2.0 / 3_000.0));
+
+        // TP1 vende el 50% (1500 tokens) por 1.5 SOL
+        let cost = book.sell(0.5, Some(1.5));
+        assert!(close(cost.tokens, 1_500.0));
+        assert!(close(cost.cost_wavg_sol, 1.0));
+        // FIFO: 1000 tokens del lote 1 (1 SOL) + 500 del lote 2 (0.25 SOL)
+        assert!(close(cost.cost_fifo_sol, 1.25));
+        assert!(close(book.realized_wavg_sol, 0.5));
+        assert!(close(book.realized_fifo_sol, 0.25));
+
+        // Quedan 1500 tokens del lote 2
+        assert!(close(book.wavg_cost_sol, 1.0));
+        assert!(close(book.fifo_cost_sol(), 0.75));
+        assert!(close(book.unrealized_wavg(0.001), 0.5));
+        assert!(close(book.unrealized_fifo(0.001), 0.75));
+
+        book.sell(1.0, Some(0.5));
+        assert!(book.is_flat());
+    }
+
+    #[test]
+    fn test_replay_rebuilds_dca_position() {
+        let trades = vec![
+            // Ciclo anterior, cerrado por SL
+            trade("MANUAL_BUY", 0.5, 500.0, None, 1),
+            trade("AUTO_SL", 0.3, 500_000_000.0, Some(-0.2), 2),
+            // Posición actual: compra + DCA + TP1 del 50% con el PnL del router
+            trade("MANUAL_BUY", 1.0, 1_000.0, None, 3),
+            trade("MANUAL_BUY", 1.0, 2_000.0, None, 4),
+            trade("AUTO_TP1", 1.5, 1_500_000_000.0, Some(0.5), 5),
+        ];
+
+        let book = replay_trades(&trades);
+        assert_eq!(book.lots.len(), 2);
+        assert!(close(book.tokens_held(), 1_500.0));
+        assert!(close(book.wavg_cost_sol, 1.0));
+        assert!(close(book.realized_wavg_sol, 0.5));
+        assert!(close(book.realized_fifo_sol, 0.25));
+    }
+}
@@ -0,0 +1,232 @@
+//! # Direct Swap Helpers
+//!
+//! Piezas comunes a los venues directos (Raydium, Orca, Meteora):
+//! medición de profundidad de pools, preparación de ATAs / WSOL alrededor de
+//! la instrucción de swap y envío por RPC o como bundle de Jito con fallback.
+
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, VersionedTransaction},
+};
+use std::str::FromStr;
+
+use crate::amm_math::{parse_spl_token_account_amount, parse_spl_token_account_mint};
+
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Balance del vault de `reference_mint` para cada par de vaults (0 si no se pudo leer).
+/// Sirve para comparar profundidad entre pools del mismo par.
+pub fn measure_vault_depths(
+    rpc: &RpcClient,
+    vault_pairs: &[[Pubkey; 2]],
+    reference_mint: &Pubkey,
+) -> Vec<u64> {
+    let vaults: Vec<Pubkey> = vault_pairs.iter().flatten().copied().collect();
+
+    let accounts = match rpc.get_multiple_accounts(&vaults) {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            eprintln!("⚠️  No se pudo medir liquidez de los pools: {}", e);
+            return vec![0; vault_pairs.len()];
+        }
+    };
+
+    accounts
+        .chunks(2)
+        .map(|pair| {
+            pair.iter()
+                .flatten()
+                .find(|acc| {
+                    parse_spl_token_account_mint(&acc.data)
+                        .map(|mint| Pubkey::new_from_array(mint) == *reference_mint)
+                        .unwrap_or(false)
+                })
+                .and_then(|acc| parse_spl_token_account_amount(&acc.data))
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+/// Índice y profundidad del pool más profundo
+pub fn deepest(depths: &[u64]) -> Option<(usize, u64)> {
+    depths
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, depth)| **depth)
+        .map(|(idx, depth)| (idx, *depth))
+}
+
+/// Rodea `swap_ix` con las instrucciones necesarias para operar con SOL nativo:
+///
+/// 1. Crea (idempotente) el ATA de salida.
+/// 2. Si la entrada es WSOL: crea el ATA de WSOL, deposita `amount_in` y `sync_native`.
+/// 3. Swap.
+/// 4. Si entrada o salida es WSOL: cierra el ATA de WSOL (devuelve SOL nativo).
+pub fn wrap_swap_instructions(
+    owner: &Pubkey,
+    input_mint: &Pubkey,
+    output_mint: &Pubkey,
+    output_token_program: &Pubkey,
+    amount_in: u64,
+    swap_ix: Instruction,
+) -> Result<Vec<Instruction>> {
+    use spl_associated_token_account::{
+        get_associated_token_address, instruction::create_associated_token_account_idempotent,
+    };
+
+    let wsol = Pubkey::from_str(WSOL_MINT)?;
+    let wsol_ata = get_associated_token_address(owner, &wsol);
+    let mut ixs = Vec::with_capacity(6);
+
+    ixs.push(create_associated_token_account_idempotent(
+        owner,
+        owner,
+        output_mint,
+        output_token_program,
+    ));
+
+    if *input_mint == wsol {
+        ixs.push(create_associated_token_account_idempotent(
+            owner,
+            owner,
+            &wsol,
+            &spl_token::id(),
+        ));
+        ixs.push(system_instruction::transfer(owner, &wsol_ata, amount_in));
+        ixs.push(spl_token::instruction::sync_native(
+            &spl_token::id(),
+            &wsol_ata,
+        )?);
+    }
+
+    ixs.push(swap_ix);
+
+    if *input_mint == wsol || *output_mint == wsol {
+        ixs.push(spl_token::instruction::close_account(
+            &spl_token::id(),
+            &wsol_ata,
+            owner,
+            owner,
+            &[],
+        )?);
+    }
+
+    Ok(ixs)
+}
+
+/// Firma y envía por RPC estándar (espera confirmación)
+pub fn send_instructions(
+    rpc: &RpcClient,
+    instructions: &[Instruction],
+    user_keypair: &Keypair,
+) -> Result<String> {
+    let recent_blockhash = rpc.get_latest_blockhash()?;
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&user_keypair.pubkey()),
+        &[user_keypair],
+        recent_blockhash,
+    );
+    Ok(rpc.send_and_confirm_transaction(&tx)?.to_string())
+}
+
+/// Envía como bundle de Jito (swap + tip). Si Jito falla, reintenta por RPC.
+pub async fn send_instructions_with_jito(
+    rpc: &RpcClient,
+    instructions: &[Instruction],
+    jito_tip_lamports: u64,
+    user_keypair: &Keypair,
+) -> Result<String> {
+    let recent_blockhash = rpc.get_latest_blockhash()?;
+
+    let swap_tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&user_keypair.pubkey()),
+        &[user_keypair],
+        recent_blockhash,
+    );
+    let versioned_swap = VersionedTransaction::from(swap_tx);
+
+    let tip_ix =
+        crate::jito::JitoClient::create_tip_instruction(&user_keypair.pubkey(), jito_tip_lamports);
+    let tip_tx = Transaction::new_signed_with_payer(
+        &[tip_ix],
+        Some(&user_keypair.pubkey()),
+        &[user_keypair],
+        recent_blockhash,
+    );
+
+    let bundle = vec![versioned_swap.clone(), VersionedTransaction::from(tip_tx)];
+
+    match crate::jito::JitoClient::new().send_bundle(bundle).await {
+        Ok(bundle_id) => {
+            let sig = versioned_swap.signatures[0].to_string();
+            println!(
+                "✅ [DIRECT+JITO] Bundle enviado. ID: {}, Tx: {}",
+                bundle_id, sig
+            );
+            Ok(sig)
+        }
+        Err(e) => {
+            eprintln!("⚠️ Jito falló: {}. Enviando directo via RPC...", e);
+            send_instructions(rpc, instructions, user_keypair)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deepest_pool() {
+        assert_eq!(deepest(&[10, 50, 20]), Some((1, 50)));
+        assert_eq!(deepest(&[]), None);
+    }
+
+    #[test]
+    fn test_wrap_buy_with_wsol() {
+        let owner = Pubkey::new_unique();
+        let wsol = Pubkey::from_str(WSOL_MINT).unwrap();
+        let token = Pubkey::new_unique();
+        let swap_ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]);
+
+        let ixs = wrap_swap_instructions(
+            &owner,
+            &wsol,
+            &token,
+            &spl_token::id(),
+            1_000,
+            swap_ix.clone(),
+        )
+        .unwrap();
+
+        // ATA salida, ATA WSOL, transfer, sync_native, swap, close
+        assert_eq!(ixs.len(), 6);
+        assert_eq!(ixs[4], swap_ix);
+        assert_eq!(ixs[5].program_id, spl_token::id());
+    }
+
+    #[test]
+    fn test_wrap_token_to_token_has_no_wsol_handling() {
+        let owner = Pubkey::new_unique();
+        let swap_ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]);
+
+        let ixs = wrap_swap_instructions(
+            &owner,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &spl_token::id(),
+            1_000,
+            swap_ix,
+        )
+        .unwrap();
+
+        assert_eq!(ixs.len(), 2);
+    }
+}
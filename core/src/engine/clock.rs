@@ -0,0 +1,86 @@
+//! # Engine Clock
+//!
+//! El `StrategyEngine` lee la hora para las reglas de salida (tiempo en
+//! posición, momentum estancado). En vivo es el reloj del sistema; en el replay
+//! de grabaciones (`engine::replay`) es un `SimClock` que avanza con la marca de
+//! tiempo de cada tick, así dos replays de la misma grabación deciden igual.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, Instant};
+
+pub trait Clock: Send + Sync {
+    /// Instante monotónico (para medir duraciones)
+    fn now(&self) -> Instant;
+    /// Hora de pared en segundos Unix (para comparar con `created_at`)
+    fn unix_now(&self) -> i64;
+}
+
+/// Reloj real del sistema
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn unix_now(&self) -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+}
+
+/// Reloj simulado: solo avanza cuando el driver del replay lo mueve
+pub struct SimClock {
+    origin: Instant,
+    origin_ms: i64,
+    now_ms: AtomicI64,
+}
+
+impl SimClock {
+    /// Arranca en `start_ms` (milisegundos Unix)
+    pub fn new(start_ms: i64) -> Self {
+        Self {
+            origin: Instant::now(),
+            origin_ms: start_ms,
+            now_ms: AtomicI64::new(start_ms),
+        }
+    }
+
+    /// Mueve el reloj a `unix_ms`. Nunca retrocede: un tick desordenado en la
+    /// grabación no puede deshacer tiempo ya transcurrido.
+    pub fn set(&self, unix_ms: i64) {
+        self.now_ms.fetch_max(unix_ms, Ordering::SeqCst);
+    }
+
+    pub fn now_ms(&self) -> i64 {
+        self.now_ms.load(Ordering::SeqCst)
+    }
+}
+
+impl Clock for SimClock {
+    fn now(&self) -> Instant {
+        let elapsed_ms = (self.now_ms() - self.origin_ms).max(0) as u64;
+        self.origin + Duration::from_millis(elapsed_ms)
+    }
+
+    fn unix_now(&self) -> i64 {
+        self.now_ms().div_euclid(1000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sim_clock_only_moves_forward() {
+        let clock = SimClock::new(1_700_000_000_000);
+        let start = clock.now();
+
+        clock.set(1_700_000_090_500);
+        assert_eq!(clock.unix_now(), 1_700_000_090);
+        assert_eq!(clock.now() - start, Duration::from_millis(90_500));
+
+        clock.set(1_700_000_010_000);
+        assert_eq!(clock.now_ms(), 1_700_000_090_500);
+    }
+}
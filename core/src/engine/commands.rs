@@ -1,33 +1,88 @@
+use intelligence_rs::strategy_engine::SellReason;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum CommandType {
-    TakeProfit1,
-    TakeProfit2,
+    /// Escalón de la escalera de TP (0-based)
+    TakeProfit(u8),
     StopLoss,
+    /// Salida completa por una regla de la posición (ver `engine::exit_rules`)
+    Exit(SellReason),
+    /// Venta manual pedida desde fuera del engine (gRPC `ExecuteTrade`)
+    Manual,
+}
+
+impl CommandType {
+    /// Etiqueta usada en `trades.trade_type` y `orders.command_type`
+    /// (`AUTO_TP1` es el primer escalón, igual que el antiguo TP1)
+    pub fn trade_type(&self) -> String {
+        match self {
+            CommandType::TakeProfit(rung) => format!("AUTO_TP{}", *rung as u16 + 1),
+            CommandType::StopLoss => "AUTO_SL".to_string(),
+            CommandType::Exit(reason) => format!("AUTO_EXIT_{}", reason.as_str()),
+            CommandType::Manual => "MANUAL_SELL".to_string(),
+        }
+    }
+
+    pub fn from_trade_type(trade_type: &str) -> Option<Self> {
+        match trade_type {
+            "AUTO_SL" => return Some(CommandType::StopLoss),
+            "MANUAL_SELL" => return Some(CommandType::Manual),
+            _ => {}
+        }
+        if let Some(reason) = trade_type.strip_prefix("AUTO_EXIT_") {
+            return SellReason::parse(reason).map(CommandType::Exit);
+        }
+        let level: u16 = trade_type.strip_prefix("AUTO_TP")?.parse().ok()?;
+        let rung = u8::try_from(level.checked_sub(1)?).ok()?;
+        Some(CommandType::TakeProfit(rung))
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum ExecutionCommand {
-    TakeProfit1 {
+    TakeProfit {
         mint: String,
         symbol: String,
+        /// Escalón de la escalera (0-based)
+        rung: u8,
+        /// % del balance actual (ya convertido desde el % de la posición original)
         sell_amount_pct: u8,
-        entry_price: f64,
         amount_invested: f64,
     },
-    TakeProfit2 {
+    StopLoss {
         mint: String,
         symbol: String,
-        sell_amount_pct: u8,
         amount_invested: f64,
+        is_emergency: bool,
     },
-    StopLoss {
+    /// Cierre total por una regla de salida (tiempo, break-even, momentum, liquidez)
+    Exit {
         mint: String,
         symbol: String,
         amount_invested: f64,
-        is_emergency: bool,
+        reason: SellReason,
+    },
+    /// Venta manual: pasa por el mismo journal y bloqueo por mint que las del engine
+    ManualSell {
+        mint: String,
+        symbol: String,
+        /// % del balance actual
+        sell_amount_pct: u8,
+        amount_invested: f64,
     },
 }
 
+impl ExecutionCommand {
+    pub fn mint(&self) -> &str {
+        match self {
+            ExecutionCommand::TakeProfit { mint, .. }
+            | ExecutionCommand::StopLoss { mint, .. }
+            | ExecutionCommand::Exit { mint, .. }
+            | ExecutionCommand::ManualSell { mint, .. } => mint,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ExecutionFeedback {
     /// El actuador falló definitivamente tras agotar los reintentos
@@ -42,3 +97,29 @@ pub enum ExecutionFeedback {
         command_type: CommandType,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trade_type_roundtrip() {
+        for cmd in [
+            CommandType::StopLoss,
+            CommandType::TakeProfit(0),
+            CommandType::TakeProfit(4),
+            CommandType::Exit(SellReason::TimeLimit),
+            CommandType::Exit(SellReason::LiquidityFloor),
+            CommandType::Manual,
+        ] {
+            assert_eq!(CommandType::from_trade_type(&cmd.trade_type()), Some(cmd));
+        }
+        // Órdenes journaleadas antes de la escalera
+        assert_eq!(
+            CommandType::from_trade_type("AUTO_TP2"),
+            Some(CommandType::TakeProfit(1))
+        );
+        assert_eq!(CommandType::from_trade_type("AUTO_TP0"), None);
+        assert_eq!(CommandType::from_trade_type("MANUAL_BUY"), None);
+    }
+}
@@ -0,0 +1,150 @@
+//! # Event Bus (Fan-out de eventos internos)
+//!
+//! Los canales del pipeline (`PriceFeed → StrategyEngine → Router`) son mpsc
+//! punto a punto. Este bus replica cada evento en un `broadcast` para que
+//! observadores externos (gRPC streaming, analytics) lo vean sin interferir
+//! en el camino crítico: si nadie escucha, el coste es un `send` sin receptores.
+
+use tokio::sync::{broadcast, mpsc};
+
+use crate::engine::commands::ExecutionFeedback;
+use crate::engine::types::RejectionReason;
+use crate::price_feed::PriceUpdate;
+use intelligence_rs::strategy_engine::SellReason;
+
+/// Resultado de una evaluación del DecisionEngine
+#[derive(Debug, Clone)]
+pub enum DecisionOutcome {
+    ExecuteBuy {
+        confidence: f64,
+        slippage_bps: u16,
+        priority_fee_lamports: u64,
+    },
+    ExecuteSell {
+        reason: SellReason,
+        amount_percent: u8,
+    },
+    Rejected(RejectionReason),
+    Hold,
+}
+
+/// Decisión publicada por el DecisionEngine para un token concreto
+#[derive(Debug, Clone)]
+pub struct DecisionEvent {
+    pub mint: String,
+    pub symbol: String,
+    pub outcome: DecisionOutcome,
+    pub timestamp_ms: i64,
+}
+
+impl DecisionEvent {
+    pub fn new(mint: &str, symbol: &str, outcome: DecisionOutcome) -> Self {
+        Self {
+            mint: mint.to_string(),
+            symbol: symbol.to_string(),
+            outcome,
+            timestamp_ms: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+/// Bus compartido de eventos en vivo
+pub struct EventBus {
+    prices: broadcast::Sender<PriceUpdate>,
+    executions: broadcast::Sender<ExecutionFeedback>,
+    decisions: broadcast::Sender<DecisionEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (prices, _) = broadcast::channel(capacity);
+        let (executions, _) = broadcast::channel(capacity);
+        let (decisions, _) = broadcast::channel(capacity);
+        Self {
+            prices,
+            executions,
+            decisions,
+        }
+    }
+
+    pub fn subscribe_prices(&self) -> broadcast::Receiver<PriceUpdate> {
+        self.prices.subscribe()
+    }
+
+    pub fn subscribe_executions(&self) -> broadcast::Receiver<ExecutionFeedback> {
+        self.executions.subscribe()
+    }
+
+    pub fn subscribe_decisions(&self) -> broadcast::Receiver<DecisionEvent> {
+        self.decisions.subscribe()
+    }
+
+    /// Sender para que el DecisionEngine publique sus decisiones
+    pub fn decision_sink(&self) -> broadcast::Sender<DecisionEvent> {
+        self.decisions.clone()
+    }
+
+    /// Intercala el bus en el canal de precios: devuelve un nuevo receptor
+    /// con los mismos eventos y replica cada uno en el broadcast.
+    pub fn tap_prices(&self, rx: mpsc::Receiver<PriceUpdate>) -> mpsc::Receiver<PriceUpdate> {
+        tap(rx, self.prices.clone())
+    }
+
+    /// Igual que `tap_prices` pero para el feedback del ExecutionRouter
+    pub fn tap_executions(
+        &self,
+        rx: mpsc::Receiver<ExecutionFeedback>,
+    ) -> mpsc::Receiver<ExecutionFeedback> {
+        tap(rx, self.executions.clone())
+    }
+}
+
+/// Capacidad del canal reemitido (igual que los canales del pipeline en lib.rs)
+const TAP_CAPACITY: usize = 1024;
+
+fn tap<T: Clone + Send + 'static>(
+    mut rx: mpsc::Receiver<T>,
+    broadcast_tx: broadcast::Sender<T>,
+) -> mpsc::Receiver<T> {
+    let (out_tx, out_rx) = mpsc::channel(TAP_CAPACITY);
+
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            // Err = no hay suscriptores, no es un fallo
+            let _ = broadcast_tx.send(event.clone());
+            if out_tx.send(event).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    out_rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::commands::CommandType;
+
+    #[tokio::test]
+    async fn test_tap_forwards_and_broadcasts() {
+        let bus = EventBus::new(16);
+        let mut observer = bus.subscribe_executions();
+
+        let (tx, rx) = mpsc::channel(8);
+        let mut tapped = bus.tap_executions(rx);
+
+        tx.send(ExecutionFeedback::Success {
+            mint: "Mint111".to_string(),
+            command_type: CommandType::StopLoss,
+        })
+        .await
+        .unwrap();
+
+        let forwarded = tapped.recv().await.unwrap();
+        let observed = observer.recv().await.unwrap();
+
+        assert!(matches!(forwarded, ExecutionFeedback::Success { .. }));
+        assert!(matches!(observed, ExecutionFeedback::Success { ref mint, .. } if mint == "Mint111"));
+    }
+}
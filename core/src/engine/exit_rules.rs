@@ -0,0 +1,296 @@
+//! # Exit Rules (Salidas por tiempo, break-even, momentum y liquidez)
+//!
+//! Reglas por posición que complementan SL/TP/trailing. Se guardan con la
+//! posición (`ExitRules`) y el `StrategyEngine` las evalúa en cada tick; cada
+//! una cierra la posición completa con su propio `SellReason`.
+//!
+//! La evaluación es pura: el engine aporta la foto del tick (`ExitSnapshot`) y
+//! el seguimiento del momentum (`StaleTracker`), que es lo único con estado.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use intelligence_rs::strategy_engine::SellReason;
+
+use crate::engine::momentum::MomentumSensor;
+use crate::state_manager::ExitRules;
+
+/// Puntos del sensor de momentum por posición
+const MOMENTUM_WINDOW: usize = 12;
+
+/// Estado de la posición en el tick actual
+#[derive(Debug, Clone)]
+pub struct ExitSnapshot {
+    pub gain_percent: f64,
+    /// Segundos desde `created_at`
+    pub held_secs: i64,
+    /// Liquidez del pool en USD (0 = el feed no la reporta)
+    pub liquidity_usd: f64,
+    /// Tiempo seguido con la pendiente por debajo del umbral
+    pub stale_for: Option<Duration>,
+}
+
+/// ¿Hay que subir el stop a break-even en este tick?
+pub fn should_arm_break_even(rules: &ExitRules, gain_percent: f64) -> bool {
+    !rules.break_even_armed
+        && rules
+            .break_even_after_percent
+            .is_some_and(|trigger| gain_percent >= trigger)
+}
+
+/// Primera regla que pide salir. La liquidez va primero (riesgo de rug), luego
+/// el break-even (protege capital) y por último las salidas por inactividad.
+pub fn evaluate(rules: &ExitRules, snapshot: &ExitSnapshot) -> Option<SellReason> {
+    if let Some(floor) = rules.min_liquidity_usd {
+        if snapshot.liquidity_usd > 0.0 && snapshot.liquidity_usd < floor {
+            return Some(SellReason::LiquidityFloor);
+        }
+    }
+
+    if rules.break_even_armed && snapshot.gain_percent <= 0.0 {
+        return Some(SellReason::BreakEven);
+    }
+
+    if let Some(max_hold) = rules.max_hold_secs {
+        if snapshot.held_secs >= max_hold {
+            return Some(SellReason::TimeLimit);
+        }
+    }
+
+    if let (Some(_), Some(minutes)) = (rules.stale_slope_threshold, rules.stale_minutes) {
+        let window = Duration::from_secs(minutes as u64 * 60);
+        if snapshot.stale_for.is_some_and(|stale| stale >= window) {
+            return Some(SellReason::StaleMomentum);
+        }
+    }
+
+    None
+}
+
+/// Aplica un parámetro de `/update` a las reglas. `None` si la clave no es
+/// una regla de salida; `off` desactiva la regla.
+///
+/// `hold=<min>`, `be=<gain%>`, `stale=<%/min>:<min>`, `liq=<usd>`
+pub fn apply_update(rules: &mut ExitRules, param: &str) -> Option<Result<()>> {
+    let (key, value) = param.split_once('=')?;
+    let off = value.eq_ignore_ascii_case("off");
+
+    let result = match key {
+        "hold" => parse_positive(value, off).map(|minutes| {
+            rules.max_hold_secs = minutes.map(|m| (m * 60.0) as i64);
+        }),
+        "be" => parse_positive(value, off).map(|trigger| {
+            // Umbral nuevo: se vuelve a armar cuando se alcance
+            rules.break_even_after_percent = trigger;
+            rules.break_even_armed = false;
+        }),
+        "liq" => parse_positive(value, off).map(|floor| {
+            rules.min_liquidity_usd = floor;
+        }),
+        "stale" => parse_stale(value, off).map(|rule| {
+            rules.stale_slope_threshold = rule.map(|(slope, _)| slope);
+            rules.stale_minutes = rule.map(|(_, minutes)| minutes);
+        }),
+        _ => return None,
+    };
+    Some(result.with_context(|| format!("Parámetro '{}' inválido", param)))
+}
+
+fn parse_positive(value: &str, off: bool) -> Result<Option<f64>> {
+    if off {
+        return Ok(None);
+    }
+    let parsed: f64 = value.parse()?;
+    if parsed <= 0.0 {
+        bail!("debe ser positivo");
+    }
+    Ok(Some(parsed))
+}
+
+fn parse_stale(value: &str, off: bool) -> Result<Option<(f64, u32)>> {
+    if off {
+        return Ok(None);
+    }
+    let (slope, minutes) = value
+        .split_once(':')
+        .context("se espera pendiente:minutos")?;
+    let minutes: u32 = minutes.parse()?;
+    if minutes == 0 {
+        bail!("los minutos deben ser positivos");
+    }
+    Ok(Some((slope.parse()?, minutes)))
+}
+
+/// `hold 120m | BE +30% 🛡️ | stale <0.5%/min 10m | liq $5000`
+pub fn format_rules(rules: &ExitRules) -> String {
+    let mut parts = Vec::new();
+    if let Some(secs) = rules.max_hold_secs {
+        parts.push(format!("hold {}m", secs / 60));
+    }
+    if let Some(trigger) = rules.break_even_after_percent {
+        let armed = if rules.break_even_armed {
+            " 🛡️"
+        } else {
+            ""
+        };
+        parts.push(format!("BE +{:.0}%{}", trigger, armed));
+    }
+    if let (Some(slope), Some(minutes)) = (rules.stale_slope_threshold, rules.stale_minutes) {
+        parts.push(format!("stale <{}%/min {}m", slope, minutes));
+    }
+    if let Some(floor) = rules.min_liquidity_usd {
+        parts.push(format!("liq ${:.0}", floor));
+    }
+    if parts.is_empty() {
+        return "—".to_string();
+    }
+    parts.join(" | ")
+}
+
+/// Momentum de la ganancia (%) de una posición y desde cuándo está plano
+pub struct StaleTracker {
+    sensor: MomentumSensor,
+    below_since: Option<Instant>,
+}
+
+impl Default for StaleTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StaleTracker {
+    pub fn new() -> Self {
+        Self {
+            sensor: MomentumSensor::new(MOMENTUM_WINDOW),
+            below_since: None,
+        }
+    }
+
+    /// Registra la ganancia del tick y devuelve cuánto lleva la pendiente
+    /// (%/min) por debajo de `threshold` (None si ahora mismo está por encima)
+    pub fn update(&mut self, gain_percent: f64, threshold: f64, now: Instant) -> Option<Duration> {
+        self.sensor.update_at(gain_percent, now);
+
+        if self.sensor.slope() >= threshold {
+            self.below_since = None;
+            return None;
+        }
+        let since = *self.below_since.get_or_insert(now);
+        Some(now.saturating_duration_since(since))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(gain_percent: f64) -> ExitSnapshot {
+        ExitSnapshot {
+            gain_percent,
+            held_secs: 60,
+            liquidity_usd: 50_000.0,
+            stale_for: None,
+        }
+    }
+
+    #[test]
+    fn test_no_rules_never_exit() {
+        let rules = ExitRules::default();
+        assert!(!should_arm_break_even(&rules, 500.0));
+        assert_eq!(evaluate(&rules, &snapshot(-90.0)), None);
+    }
+
+    #[test]
+    fn test_break_even_arms_then_exits_at_entry() {
+        let mut rules = ExitRules {
+            break_even_after_percent: Some(30.0),
+            ..ExitRules::default()
+        };
+        assert!(!should_arm_break_even(&rules, 29.0));
+        assert!(should_arm_break_even(&rules, 31.0));
+
+        rules.break_even_armed = true;
+        assert!(!should_arm_break_even(&rules, 40.0));
+        assert_eq!(evaluate(&rules, &snapshot(5.0)), None);
+        assert_eq!(
+            evaluate(&rules, &snapshot(-0.5)),
+            Some(SellReason::BreakEven)
+        );
+    }
+
+    #[test]
+    fn test_each_rule_has_its_reason() {
+        let rules = ExitRules {
+            max_hold_secs: Some(3_600),
+            stale_slope_threshold: Some(0.5),
+            stale_minutes: Some(10),
+            min_liquidity_usd: Some(10_000.0),
+            ..ExitRules::default()
+        };
+
+        let mut held = snapshot(10.0);
+        held.held_secs = 3_600;
+        assert_eq!(evaluate(&rules, &held), Some(SellReason::TimeLimit));
+
+        let mut stale = snapshot(10.0);
+        stale.stale_for = Some(Duration::from_secs(9 * 60));
+        assert_eq!(evaluate(&rules, &stale), None);
+        stale.stale_for = Some(Duration::from_secs(10 * 60));
+        assert_eq!(evaluate(&rules, &stale), Some(SellReason::StaleMomentum));
+
+        // Liquidez desconocida (0) no dispara; por debajo del mínimo gana a las demás
+        let mut drained = held.clone();
+        drained.liquidity_usd = 0.0;
+        assert_eq!(evaluate(&rules, &drained), Some(SellReason::TimeLimit));
+        drained.liquidity_usd = 4_000.0;
+        assert_eq!(evaluate(&rules, &drained), Some(SellReason::LiquidityFloor));
+    }
+
+    #[test]
+    fn test_apply_update_params() {
+        let mut rules = ExitRules {
+            break_even_armed: true,
+            ..ExitRules::default()
+        };
+
+        assert!(apply_update(&mut rules, "sl=-20").is_none());
+        assert!(apply_update(&mut rules, "hold=90").unwrap().is_ok());
+        assert!(apply_update(&mut rules, "be=25").unwrap().is_ok());
+        assert!(apply_update(&mut rules, "stale=0.5:10").unwrap().is_ok());
+        assert!(apply_update(&mut rules, "liq=5000").unwrap().is_ok());
+        assert_eq!(rules.max_hold_secs, Some(5_400));
+        assert!(!rules.break_even_armed);
+        assert_eq!(
+            (rules.stale_slope_threshold, rules.stale_minutes),
+            (Some(0.5), Some(10))
+        );
+        assert_eq!(
+            format_rules(&rules),
+            "hold 90m | BE +25% | stale <0.5%/min 10m | liq $5000"
+        );
+
+        assert!(apply_update(&mut rules, "stale=0.5").unwrap().is_err());
+        assert!(apply_update(&mut rules, "hold=-5").unwrap().is_err());
+        assert!(apply_update(&mut rules, "liq=off").unwrap().is_ok());
+        assert_eq!(rules.min_liquidity_usd, None);
+    }
+
+    #[test]
+    fn test_stale_tracker_counts_flat_time() {
+        let mut tracker = StaleTracker::new();
+        let start = Instant::now();
+
+        // Ganancia plana: la pendiente no supera el umbral desde el primer tick
+        assert_eq!(tracker.update(12.0, 0.5, start), Some(Duration::ZERO));
+        let later = start + Duration::from_secs(300);
+        assert_eq!(
+            tracker.update(12.0, 0.5, later),
+            Some(Duration::from_secs(300))
+        );
+
+        // Umbral negativo: cualquier pendiente plana cuenta como momentum y reinicia
+        assert_eq!(tracker.update(12.0, -1.0, later), None);
+        assert_eq!(tracker.update(12.0, 0.5, later), Some(Duration::ZERO));
+    }
+}
@@ -3,32 +3,18 @@
 //! Implementación de los módulos de seguridad para el Decision Engine.
 //! Cada struc implementa el trait `TradeFilter`.
 
-use crate::engine::types::{FilterResult, RejectionReason, TokenContext, TradeFilter};
+use crate::engine::types::{
+    FilterResult, RejectionReason, TokenContext, TradeFilter, UnmeasuredLp,
+};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 /// 1. Circuit Breaker Global
 ///
-/// Detiene todo si el PnL diario supera un límite
-pub struct CircuitBreaker {
-    max_daily_drawdown: f64,
-    current_day_pnl: Arc<Mutex<f64>>,
-}
-
-impl CircuitBreaker {
-    pub fn new(max_drawdown: f64) -> Self {
-        Self {
-            max_daily_drawdown: max_drawdown, // Ej: -10.0%
-            current_day_pnl: Arc::new(Mutex::new(0.0)),
-        }
-    }
-
-    pub fn update_pnl(&self, pnl: f64) {
-        let mut total = self.current_day_pnl.lock().unwrap();
-        *total += pnl;
-    }
-}
+/// Rechaza toda compra mientras el `PortfolioBreaker` (PnL realizado persistido)
+/// esté disparado
+pub struct CircuitBreaker;
 
 impl TradeFilter for CircuitBreaker {
     fn name(&self) -> &'static str {
@@ -36,8 +22,7 @@ impl TradeFilter for CircuitBreaker {
     }
 
     fn check(&self, _ctx: &TokenContext) -> FilterResult {
-        let pnl = *self.current_day_pnl.lock().unwrap();
-        if pnl <= self.max_daily_drawdown {
+        if crate::circuit_breaker::buys_halted() {
             return FilterResult::Rejected(RejectionReason::CircuitBreakerTriggered);
         }
         FilterResult::Approved
@@ -162,3 +147,381 @@ impl TradeFilter for MomentumFilter {
         FilterResult::Approved
     }
 }
+
+/// 6. Token-2022 Extension Filter
+///
+/// Rechaza mints con extensiones que permiten confiscar o bloquear la venta
+pub struct Token2022ExtensionFilter;
+
+impl TradeFilter for Token2022ExtensionFilter {
+    fn name(&self) -> &'static str {
+        "Token2022Extensions"
+    }
+
+    fn check(&self, ctx: &TokenContext) -> FilterResult {
+        if ctx.non_transferable {
+            return FilterResult::Rejected(RejectionReason::NonTransferable);
+        }
+        if ctx.permanent_delegate.is_some() {
+            return FilterResult::Rejected(RejectionReason::PermanentDelegate);
+        }
+        if ctx.transfer_hook_program.is_some() {
+            return FilterResult::Rejected(RejectionReason::TransferHook);
+        }
+        FilterResult::Approved
+    }
+}
+
+/// 7. LP Burn Filter
+///
+/// Exige un porcentaje mínimo de LP quemado o bloqueado (anti-rug pull)
+pub struct LpBurnFilter {
+    min_burned_pct: f64, // Ej: 95.0
+    unmeasured: UnmeasuredLp,
+}
+
+impl LpBurnFilter {
+    pub fn new(min_burned_pct: f64) -> Self {
+        Self {
+            min_burned_pct,
+            unmeasured: UnmeasuredLp::Reject,
+        }
+    }
+
+    /// Qué hacer cuando el LP no se pudo medir
+    pub fn with_unmeasured(mut self, policy: UnmeasuredLp) -> Self {
+        self.unmeasured = policy;
+        self
+    }
+}
+
+impl TradeFilter for LpBurnFilter {
+    fn name(&self) -> &'static str {
+        "LpBurn"
+    }
+
+    fn check(&self, ctx: &TokenContext) -> FilterResult {
+        match ctx.lp_burned_pct {
+            Some(pct) if pct < self.min_burned_pct => {
+                FilterResult::Rejected(RejectionReason::LpNotBurned)
+            }
+            Some(_) => FilterResult::Approved,
+            None => self.unmeasured.verdict(),
+        }
+    }
+}
+
+/// 8. Holder Concentration Filter
+///
+/// Rechaza tokens donde el Top 10 Holders controla demasiado supply
+pub struct HolderConcentrationFilter {
+    max_top_10_pct: f64, // Ej: 20.0
+}
+
+impl HolderConcentrationFilter {
+    pub fn new(max_top_10_pct: f64) -> Self {
+        Self { max_top_10_pct }
+    }
+}
+
+impl TradeFilter for HolderConcentrationFilter {
+    fn name(&self) -> &'static str {
+        "HolderConcentration"
+    }
+
+    fn check(&self, ctx: &TokenContext) -> FilterResult {
+        if ctx.top_10_holders_pct > self.max_top_10_pct {
+            return FilterResult::Rejected(RejectionReason::HighConcentration);
+        }
+        FilterResult::Approved
+    }
+}
+
+/// 9. Dev Wallet Filter
+///
+/// Rechaza tokens donde la wallet del dev puede hundir el precio de un golpe.
+/// No identifica al creador: `dev_wallet_pct` es la mayor wallet que no es un
+/// PDA (proxy del deployer), así que el rechazo es `HighConcentration`
+pub struct DevWalletFilter {
+    max_dev_pct: f64, // Ej: 5.0
+}
+
+impl DevWalletFilter {
+    pub fn new(max_dev_pct: f64) -> Self {
+        Self { max_dev_pct }
+    }
+}
+
+impl TradeFilter for DevWalletFilter {
+    fn name(&self) -> &'static str {
+        "DevWallet"
+    }
+
+    fn check(&self, ctx: &TokenContext) -> FilterResult {
+        if ctx.dev_wallet_pct > self.max_dev_pct {
+            return FilterResult::Rejected(RejectionReason::HighConcentration);
+        }
+        FilterResult::Approved
+    }
+}
+
+/// 10. Early Burn Gate
+///
+/// En la zona de alto riesgo (tokens jóvenes) solo se entra con el LP
+/// completamente quemado; después aplica el `LpBurnFilter` normal
+pub struct EarlyBurnGate {
+    early_age_minutes: u64,  // Ej: 15
+    min_early_burn_pct: f64, // Ej: 100.0
+    unmeasured: UnmeasuredLp,
+}
+
+impl EarlyBurnGate {
+    pub fn new(early_age_minutes: u64, min_early_burn_pct: f64) -> Self {
+        Self {
+            early_age_minutes,
+            min_early_burn_pct,
+            unmeasured: UnmeasuredLp::Reject,
+        }
+    }
+
+    /// Qué hacer cuando el LP de un token joven no se pudo medir
+    pub fn with_unmeasured(mut self, policy: UnmeasuredLp) -> Self {
+        self.unmeasured = policy;
+        self
+    }
+}
+
+impl TradeFilter for EarlyBurnGate {
+    fn name(&self) -> &'static str {
+        "EarlyBurnGate"
+    }
+
+    fn check(&self, ctx: &TokenContext) -> FilterResult {
+        if ctx.age_minutes >= self.early_age_minutes {
+            return FilterResult::Approved;
+        }
+        match ctx.lp_burned_pct {
+            Some(pct) if pct < self.min_early_burn_pct => {
+                FilterResult::Rejected(RejectionReason::TooEarlyNoBurn)
+            }
+            Some(_) => FilterResult::Approved,
+            None => self.unmeasured.verdict(),
+        }
+    }
+}
+
+/// 11. Network Congestion Filter
+///
+/// Con el RPC atrasado nuestras TXs llegan tarde y el fill es impredecible
+pub struct NetworkCongestionFilter {
+    max_slot_lag: u64, // Ej: 25 slots (~10s)
+}
+
+impl NetworkCongestionFilter {
+    pub fn new(max_slot_lag: u64) -> Self {
+        Self { max_slot_lag }
+    }
+}
+
+impl TradeFilter for NetworkCongestionFilter {
+    fn name(&self) -> &'static str {
+        "NetworkCongestion"
+    }
+
+    fn check(&self, ctx: &TokenContext) -> FilterResult {
+        if ctx.slot_lag > self.max_slot_lag {
+            return FilterResult::Rejected(RejectionReason::NetworkCongestion);
+        }
+        FilterResult::Approved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> TokenContext {
+        TokenContext {
+            mint: "Mint111".to_string(),
+            symbol: "TEST".to_string(),
+            age_minutes: 30,
+            liquidity_usd: 50_000.0,
+            volume_5m: 10_000.0,
+            price_usd: 0.001,
+            momentum_slope: 1.0,
+            unique_wallets_ratio: 0.6,
+            top_10_holders_pct: 15.0,
+            dev_wallet_pct: 2.0,
+            mint_authority: None,
+            freeze_authority: None,
+            lp_burned_pct: Some(100.0),
+            transfer_fee_bps: 0,
+            permanent_delegate: None,
+            transfer_hook_program: None,
+            non_transferable: false,
+            slot_lag: 0,
+        }
+    }
+
+    fn rejection(filter: &dyn TradeFilter, ctx: &TokenContext) -> Option<RejectionReason> {
+        match filter.check(ctx) {
+            FilterResult::Approved => None,
+            FilterResult::Rejected(reason) => Some(reason),
+        }
+    }
+
+    #[test]
+    fn test_token_2022_extensions() {
+        let filter = Token2022ExtensionFilter;
+
+        // El transfer fee solo se contabiliza, no se rechaza
+        let fee_only = TokenContext {
+            transfer_fee_bps: 300,
+            ..ctx()
+        };
+        assert_eq!(rejection(&filter, &fee_only), None);
+
+        let delegate = TokenContext {
+            permanent_delegate: Some("Delegate111".to_string()),
+            ..ctx()
+        };
+        assert_eq!(
+            rejection(&filter, &delegate),
+            Some(RejectionReason::PermanentDelegate)
+        );
+
+        let hook = TokenContext {
+            transfer_hook_program: Some("Hook111".to_string()),
+            ..ctx()
+        };
+        assert_eq!(
+            rejection(&filter, &hook),
+            Some(RejectionReason::TransferHook)
+        );
+
+        let frozen = TokenContext {
+            non_transferable: true,
+            ..delegate
+        };
+        assert_eq!(
+            rejection(&filter, &frozen),
+            Some(RejectionReason::NonTransferable)
+        );
+    }
+
+    #[test]
+    fn test_lp_burn() {
+        let filter = LpBurnFilter::new(95.0);
+        assert_eq!(rejection(&filter, &ctx()), None);
+
+        let unburned = TokenContext {
+            lp_burned_pct: Some(80.0),
+            ..ctx()
+        };
+        assert_eq!(
+            rejection(&filter, &unburned),
+            Some(RejectionReason::LpNotBurned)
+        );
+
+        let at_threshold = TokenContext {
+            lp_burned_pct: Some(95.0),
+            ..ctx()
+        };
+        assert_eq!(rejection(&filter, &at_threshold), None);
+
+        // Sin medición decide la política: por defecto no se compra
+        let unmeasured = TokenContext {
+            lp_burned_pct: None,
+            ..ctx()
+        };
+        assert_eq!(
+            rejection(&filter, &unmeasured),
+            Some(RejectionReason::LpUnmeasured)
+        );
+        let lenient = LpBurnFilter::new(95.0).with_unmeasured(UnmeasuredLp::Allow);
+        assert_eq!(rejection(&lenient, &unmeasured), None);
+    }
+
+    #[test]
+    fn test_holder_concentration() {
+        let filter = HolderConcentrationFilter::new(20.0);
+        assert_eq!(rejection(&filter, &ctx()), None);
+
+        let whales = TokenContext {
+            top_10_holders_pct: 45.0,
+            ..ctx()
+        };
+        assert_eq!(
+            rejection(&filter, &whales),
+            Some(RejectionReason::HighConcentration)
+        );
+    }
+
+    #[test]
+    fn test_dev_wallet_share() {
+        let filter = DevWalletFilter::new(5.0);
+        assert_eq!(rejection(&filter, &ctx()), None);
+
+        let dev_heavy = TokenContext {
+            dev_wallet_pct: 12.5,
+            ..ctx()
+        };
+        assert_eq!(
+            rejection(&filter, &dev_heavy),
+            Some(RejectionReason::HighConcentration)
+        );
+    }
+
+    #[test]
+    fn test_early_burn_gate() {
+        let gate = EarlyBurnGate::new(15, 100.0);
+        let partial_burn = TokenContext {
+            lp_burned_pct: Some(96.0),
+            ..ctx()
+        };
+
+        // Maduro: el gate no aplica
+        assert_eq!(rejection(&gate, &partial_burn), None);
+
+        let early = TokenContext {
+            age_minutes: 5,
+            ..partial_burn
+        };
+        assert_eq!(
+            rejection(&gate, &early),
+            Some(RejectionReason::TooEarlyNoBurn)
+        );
+
+        let early_burned = TokenContext {
+            lp_burned_pct: Some(100.0),
+            ..early.clone()
+        };
+        assert_eq!(rejection(&gate, &early_burned), None);
+
+        let early_unmeasured = TokenContext {
+            lp_burned_pct: None,
+            ..early
+        };
+        assert_eq!(
+            rejection(&gate, &early_unmeasured),
+            Some(RejectionReason::LpUnmeasured)
+        );
+        let lenient = EarlyBurnGate::new(15, 100.0).with_unmeasured(UnmeasuredLp::Allow);
+        assert_eq!(rejection(&lenient, &early_unmeasured), None);
+    }
+
+    #[test]
+    fn test_network_congestion() {
+        let filter = NetworkCongestionFilter::new(25);
+        assert_eq!(rejection(&filter, &ctx()), None);
+
+        let lagging = TokenContext {
+            slot_lag: 40,
+            ..ctx()
+        };
+        assert_eq!(
+            rejection(&filter, &lagging),
+            Some(RejectionReason::NetworkCongestion)
+        );
+    }
+}
@@ -0,0 +1,744 @@
+//! # Honeypot Filter — Round-trip simulado antes de comprar
+//!
+//! Construye en una sola TX una compra mínima seguida de la venta inmediata
+//! de lo comprado y la pasa por `simulateTransaction`. Nada se firma ni se envía.
+//!
+//! ```text
+//!   [compra: WSOL → token] [venta: token → WSOL (sin cerrar el WSOL)]
+//!            │                          │
+//!            ▼                          ▼
+//!   ATA token post-sim          ATA WSOL post-sim = SOL recibido
+//! ```
+//!
+//! El round-trip se arma en el primer venue del executor (bonding curve de
+//! Pump.fun incluida) que cotiza la compra y la entrega como instrucciones.
+//!
+//! Se rechaza con `RejectionReason::Honeypot` si la venta revierte, si un
+//! transfer hook bloquea el movimiento, si el impuesto efectivo del
+//! round-trip (frente a los quotes del venue) es anormal o si la venta no se
+//! puede cotizar o construir después de cotizar la compra. Sin venue que
+//! cotice la compra, o con el simulador (RPC) caído, no hay veredicto.
+//!
+//! La llamada a `simulateTransaction` pasa por `TransactionSimulator` para
+//! poder sustituir el RPC por un stub en tests.
+
+use anyhow::Result;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::TokenInstruction;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::amm_math::parse_spl_token_account_amount;
+use crate::direct_swap::WSOL_MINT;
+use crate::engine::types::{FilterResult, RejectionReason, TokenContext, TradeFilter};
+use crate::pumpfun::min_with_slippage;
+use crate::token_2022::{associated_token_address, token_2022_program_id};
+use crate::venue::{QuoteRequest, SwapVenue, VenuePayload};
+
+/// Tamaño de la compra simulada (0.001 SOL)
+pub const DEFAULT_PROBE_LAMPORTS: u64 = 1_000_000;
+
+/// Impuesto efectivo máximo tolerado en el round-trip (%)
+pub const DEFAULT_MAX_TAX_PCT: f64 = 10.0;
+
+/// Sin mínimo de salida: el round-trip mide, no protege
+const PROBE_SLIPPAGE_BPS: u16 = 10_000;
+
+// ============================================================================
+// SIMULACIÓN
+// ============================================================================
+
+/// Resultado de simular una TX
+#[derive(Debug, Clone, Default)]
+pub struct SimulationOutcome {
+    /// Error de la TX (None = éxito)
+    pub error: Option<String>,
+    /// Instrucción que revirtió
+    pub failed_instruction: Option<usize>,
+    pub logs: Vec<String>,
+    /// Balance pre-simulación de cada token account pedido (None = no existe)
+    pub pre_token_balances: Vec<Option<u64>>,
+    /// Balance post-simulación de cada token account pedido (None = no existe)
+    pub token_balances: Vec<Option<u64>>,
+}
+
+/// Frontera con `simulateTransaction`
+pub trait TransactionSimulator: Send + Sync {
+    /// Simula `tx` sin firmas y devuelve el balance previo y final de `token_accounts`
+    fn simulate(&self, tx: &Transaction, token_accounts: &[Pubkey]) -> Result<SimulationOutcome>;
+}
+
+impl TransactionSimulator for RpcClient {
+    fn simulate(&self, tx: &Transaction, token_accounts: &[Pubkey]) -> Result<SimulationOutcome> {
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(CommitmentConfig::confirmed()),
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: token_accounts.iter().map(|a| a.to_string()).collect(),
+            }),
+            ..Default::default()
+        };
+        let pre_token_balances = self
+            .get_multiple_accounts(token_accounts)?
+            .into_iter()
+            .map(|account| account.and_then(|acc| parse_spl_token_account_amount(&acc.data)))
+            .collect();
+        let result = self.simulate_transaction_with_config(tx, config)?.value;
+
+        let failed_instruction = match &result.err {
+            Some(TransactionError::InstructionError(idx, _)) => Some(*idx as usize),
+            _ => None,
+        };
+        let token_balances = match result.accounts {
+            Some(accounts) => accounts
+                .into_iter()
+                .map(|account| {
+                    account
+                        .and_then(|ui| ui.decode::<Account>())
+                        .and_then(|acc| parse_spl_token_account_amount(&acc.data))
+                })
+                .collect(),
+            None => vec![None; token_accounts.len()],
+        };
+
+        Ok(SimulationOutcome {
+            error: result.err.map(|e| e.to_string()),
+            failed_instruction,
+            logs: result.logs.unwrap_or_default(),
+            pre_token_balances,
+            token_balances,
+        })
+    }
+}
+
+// ============================================================================
+// ROUND-TRIP
+// ============================================================================
+
+/// TX de compra + venta lista para simular
+pub struct RoundTripProbe {
+    pub transaction: Transaction,
+    /// Nº de instrucciones de la compra (las siguientes son de la venta)
+    pub buy_instructions: usize,
+    /// Tokens que el venue dice que recibiríamos
+    pub tokens_quoted: u64,
+    /// Tokens que se venden (lo que llegaría con el impuesto máximo tolerado)
+    pub tokens_sold: u64,
+    /// SOL que el venue dice que recibiríamos por `tokens_sold`
+    pub expected_sol_out: u64,
+    /// La venta cobra en la cuenta WSOL. Los venues que pagan SOL nativo
+    /// (curva de Pump.fun) no dejan rastro medible: su venta solo se juzga
+    /// por revertir o no
+    pub measures_sol_out: bool,
+    /// Cuentas a leer post-simulación: [WSOL, ATA SPL Token, ATA Token-2022]
+    pub watched: [Pubkey; 3],
+}
+
+/// Veredicto del round-trip simulado
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoundTripVerdict {
+    Sellable {
+        effective_tax_pct: f64,
+    },
+    SellReverted(String),
+    TransferHookReverted(String),
+    AbnormalTax(f64),
+    /// La compra cotiza pero la venta no se puede cotizar o construir
+    Unroutable(String),
+    /// La compra revierte (sin liquidez, wallet sin SOL...): no concluyente
+    Inconclusive(String),
+}
+
+fn instructions(payload: VenuePayload) -> Result<Vec<Instruction>> {
+    match payload {
+        VenuePayload::Instructions(ixs) => Ok(ixs),
+        VenuePayload::Transaction(_) => {
+            anyhow::bail!("El venue entrega TX pre-armada: no se puede encadenar compra + venta")
+        }
+    }
+}
+
+fn closes_account(ix: &Instruction, account: &Pubkey) -> bool {
+    ix.program_id == spl_token::id()
+        && matches!(
+            TokenInstruction::unpack(&ix.data),
+            Ok(TokenInstruction::CloseAccount)
+        )
+        && ix.accounts.first().map(|meta| meta.pubkey) == Some(*account)
+}
+
+/// Cotiza y construye el round-trip de `probe_lamports` en el primer venue
+/// de `venues` que cotiza la compra y la entrega como instrucciones (Jupiter
+/// manda la TX pre-armada y no se puede encadenar). `Ok(None)` si ninguno
+/// puede; `Err` si la venta no se cotiza o construye en ese venue.
+pub async fn build_round_trip(
+    venues: &[Arc<dyn SwapVenue>],
+    payer: &Pubkey,
+    mint: &str,
+    probe_lamports: u64,
+    max_tax_pct: f64,
+) -> Result<Option<RoundTripProbe>> {
+    let buy_request = QuoteRequest {
+        input_mint: WSOL_MINT.to_string(),
+        output_mint: mint.to_string(),
+        amount_in: probe_lamports,
+        slippage_bps: PROBE_SLIPPAGE_BPS,
+        jito_tip: 0,
+    };
+
+    for venue in venues.iter().filter(|v| v.supports(mint)) {
+        let buy_quote = match venue.quote(&buy_request).await {
+            Ok(quote) if quote.amount_out > 0 => quote,
+            _ => continue,
+        };
+        let Ok(VenuePayload::Instructions(buy_ixs)) =
+            venue.build_instructions(&buy_quote, payer).await
+        else {
+            continue;
+        };
+
+        println!("   🍯 Round-trip en {}", venue.name());
+        return sell_leg(
+            venue.as_ref(),
+            buy_quote.amount_out,
+            buy_ixs,
+            payer,
+            mint,
+            max_tax_pct,
+        )
+        .await
+        .map(Some);
+    }
+    Ok(None)
+}
+
+/// Encadena tras `buy_ixs` la venta de lo comprado en el mismo `venue`
+async fn sell_leg(
+    venue: &dyn SwapVenue,
+    tokens_quoted: u64,
+    mut ixs: Vec<Instruction>,
+    payer: &Pubkey,
+    mint: &str,
+    max_tax_pct: f64,
+) -> Result<RoundTripProbe> {
+    let tokens_sold = min_with_slippage(tokens_quoted, (max_tax_pct * 100.0) as u16);
+    let sell_request = QuoteRequest {
+        input_mint: mint.to_string(),
+        output_mint: WSOL_MINT.to_string(),
+        amount_in: tokens_sold,
+        slippage_bps: PROBE_SLIPPAGE_BPS,
+        jito_tip: 0,
+    };
+    let sell_quote = venue.quote(&sell_request).await?;
+
+    let buy_instructions = ixs.len();
+    let mut sell_ixs = instructions(venue.build_instructions(&sell_quote, payer).await?)?;
+
+    // Sin cerrar el WSOL de la venta: su saldo post-simulación es el SOL recibido
+    let wsol = Pubkey::from_str(WSOL_MINT)?;
+    let wsol_account = associated_token_address(payer, &wsol, &spl_token::id());
+    if sell_ixs
+        .last()
+        .is_some_and(|ix| closes_account(ix, &wsol_account))
+    {
+        sell_ixs.pop();
+    }
+    let measures_sol_out = sell_ixs
+        .iter()
+        .any(|ix| ix.accounts.iter().any(|meta| meta.pubkey == wsol_account));
+    ixs.extend(sell_ixs);
+
+    let mint = Pubkey::from_str(mint)?;
+    Ok(RoundTripProbe {
+        transaction: Transaction::new_with_payer(&ixs, Some(payer)),
+        buy_instructions,
+        tokens_quoted,
+        tokens_sold,
+        expected_sol_out: sell_quote.amount_out,
+        measures_sol_out,
+        watched: [
+            wsol_account,
+            associated_token_address(payer, &mint, &spl_token::id()),
+            associated_token_address(payer, &mint, &token_2022_program_id()),
+        ],
+    })
+}
+
+/// Interpreta la simulación de `probe`. Lo recibido sale de la variación de
+/// los ATAs (post − pre), así un saldo previo del token no maquilla el impuesto.
+pub fn judge_round_trip(
+    probe: &RoundTripProbe,
+    outcome: &SimulationOutcome,
+    transfer_hook_program: Option<&str>,
+    max_tax_pct: f64,
+) -> RoundTripVerdict {
+    if let Some(error) = &outcome.error {
+        let hook_failed = transfer_hook_program.is_some_and(|hook| {
+            outcome
+                .logs
+                .iter()
+                .any(|log| log.contains(hook) && log.contains("failed"))
+        });
+        if hook_failed {
+            return RoundTripVerdict::TransferHookReverted(error.clone());
+        }
+        return match outcome.failed_instruction {
+            Some(idx) if idx >= probe.buy_instructions => {
+                RoundTripVerdict::SellReverted(error.clone())
+            }
+            _ => RoundTripVerdict::Inconclusive(format!("la compra revierte: {}", error)),
+        };
+    }
+
+    let delta = |idx: usize| {
+        let balance = |balances: &[Option<u64>]| balances.get(idx).copied().flatten().unwrap_or(0);
+        balance(&outcome.token_balances) as i128 - balance(&outcome.pre_token_balances) as i128
+    };
+    let sol_out = delta(0).max(0) as u64;
+    let tokens_received = (probe.tokens_sold as i128 + delta(1) + delta(2)).max(0) as u64;
+
+    // Lo que perdemos en cada pata frente al quote (fees del pool ya incluidos)
+    let buy_ratio = tokens_received as f64 / probe.tokens_quoted.max(1) as f64;
+    let sell_ratio = if probe.measures_sol_out {
+        sol_out as f64 / probe.expected_sol_out.max(1) as f64
+    } else {
+        1.0
+    };
+    let effective_tax_pct = (1.0 - buy_ratio * sell_ratio) * 100.0;
+
+    if effective_tax_pct > max_tax_pct {
+        RoundTripVerdict::AbnormalTax(effective_tax_pct)
+    } else {
+        RoundTripVerdict::Sellable { effective_tax_pct }
+    }
+}
+
+// ============================================================================
+// FILTRO
+// ============================================================================
+
+/// 12. Honeypot Filter
+///
+/// Simula comprar y vender inmediatamente antes de arriesgar capital real
+pub struct HoneypotFilter {
+    /// Venues donde probar el round-trip, en orden de preferencia
+    venues: Vec<Arc<dyn SwapVenue>>,
+    simulator: Arc<dyn TransactionSimulator>,
+    /// Wallet que paga la simulación (debe tener SOL para la compra)
+    payer: Pubkey,
+    probe_lamports: u64,
+    max_tax_pct: f64,
+}
+
+impl HoneypotFilter {
+    pub fn new(
+        venues: Vec<Arc<dyn SwapVenue>>,
+        simulator: Arc<dyn TransactionSimulator>,
+        payer: Pubkey,
+    ) -> Self {
+        Self {
+            venues,
+            simulator,
+            payer,
+            probe_lamports: DEFAULT_PROBE_LAMPORTS,
+            max_tax_pct: DEFAULT_MAX_TAX_PCT,
+        }
+    }
+
+    pub fn with_max_tax_pct(mut self, max_tax_pct: f64) -> Self {
+        self.max_tax_pct = max_tax_pct;
+        self
+    }
+
+    /// `Err` solo si falla el simulador; sin ruta de venta hay veredicto
+    fn probe(&self, ctx: &TokenContext) -> Result<RoundTripVerdict> {
+        // `check` es síncrono y el quote/build de los venues es async
+        let probe = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(build_round_trip(
+                &self.venues,
+                &self.payer,
+                &ctx.mint,
+                self.probe_lamports,
+                self.max_tax_pct,
+            ))
+        });
+        let probe = match probe {
+            Ok(Some(probe)) => probe,
+            Ok(None) => {
+                return Ok(RoundTripVerdict::Inconclusive(
+                    "ningún venue cotiza la compra".to_string(),
+                ))
+            }
+            Err(e) => return Ok(RoundTripVerdict::Unroutable(e.to_string())),
+        };
+        let outcome = self
+            .simulator
+            .simulate(&probe.transaction, &probe.watched)?;
+
+        Ok(judge_round_trip(
+            &probe,
+            &outcome,
+            ctx.transfer_hook_program.as_deref(),
+            self.max_tax_pct,
+        ))
+    }
+}
+
+impl TradeFilter for HoneypotFilter {
+    fn name(&self) -> &'static str {
+        "Honeypot"
+    }
+
+    fn check(&self, ctx: &TokenContext) -> FilterResult {
+        match self.probe(ctx) {
+            Ok(RoundTripVerdict::Sellable { effective_tax_pct }) => {
+                println!(
+                    "   🍯 Round-trip simulado OK (impuesto efectivo {:.2}%)",
+                    effective_tax_pct
+                );
+                FilterResult::Approved
+            }
+            Ok(RoundTripVerdict::Inconclusive(e)) => {
+                println!("   🍯 Round-trip no concluyente ({})", e);
+                FilterResult::Approved
+            }
+            Ok(verdict) => {
+                println!("   🍯 HONEYPOT detectado: {:?}", verdict);
+                FilterResult::Rejected(RejectionReason::Honeypot)
+            }
+            // Simulador (RPC) caído: no hay veredicto, el resto de filtros sigue activo
+            Err(e) => {
+                eprintln!("⚠️ [Honeypot] Simulación no disponible: {}", e);
+                FilterResult::Approved
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::venue::VenueQuote;
+    use async_trait::async_trait;
+    use solana_sdk::instruction::AccountMeta;
+    use std::sync::Mutex;
+
+    const TOKEN: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    /// Venue a precio fijo: 1 lamport = 10 tokens (y vuelta)
+    struct StubVenue {
+        /// `false` = no hay quote para la compra (token fuera del venue)
+        buyable: bool,
+        /// `false` = no hay quote para la venta
+        sellable: bool,
+        /// Cobra la venta en SOL nativo, sin cuenta WSOL (como la curva de Pump.fun)
+        native_sol: bool,
+    }
+
+    fn venue(buyable: bool, sellable: bool, native_sol: bool) -> Arc<dyn SwapVenue> {
+        Arc::new(StubVenue {
+            buyable,
+            sellable,
+            native_sol,
+        })
+    }
+
+    #[async_trait]
+    impl SwapVenue for StubVenue {
+        fn name(&self) -> &'static str {
+            "stub"
+        }
+
+        fn supports(&self, _mint: &str) -> bool {
+            true
+        }
+
+        async fn quote(&self, request: &QuoteRequest) -> Result<VenueQuote> {
+            let out = if request.input_mint == WSOL_MINT {
+                if !self.buyable {
+                    anyhow::bail!("Sin pool");
+                }
+                request.amount_in * 10
+            } else if self.sellable {
+                request.amount_in / 10
+            } else {
+                anyhow::bail!("Sin ruta de venta");
+            };
+            Ok(VenueQuote::new(self.name(), request, out))
+        }
+
+        async fn build_instructions(
+            &self,
+            quote: &VenueQuote,
+            owner: &Pubkey,
+        ) -> Result<VenuePayload> {
+            let wsol = Pubkey::from_str(WSOL_MINT)?;
+            let wsol_account = associated_token_address(owner, &wsol, &spl_token::id());
+            if self.native_sol {
+                let swap = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]);
+                return Ok(VenuePayload::Instructions(vec![swap]));
+            }
+            let swap = Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[1],
+                vec![AccountMeta::new(wsol_account, false)],
+            );
+            let close = spl_token::instruction::close_account(
+                &spl_token::id(),
+                &wsol_account,
+                owner,
+                owner,
+                &[],
+            )?;
+            Ok(VenuePayload::Instructions(
+                if quote.input_mint == WSOL_MINT {
+                    vec![swap.clone(), swap, close]
+                } else {
+                    vec![swap, close]
+                },
+            ))
+        }
+    }
+
+    /// Devuelve un resultado fijo (o falla como un RPC caído) y guarda la TX simulada
+    struct StubSimulator {
+        outcome: Option<SimulationOutcome>,
+        simulated: Mutex<Option<Transaction>>,
+    }
+
+    impl StubSimulator {
+        fn new(outcome: SimulationOutcome) -> Arc<Self> {
+            Arc::new(Self {
+                outcome: Some(outcome),
+                simulated: Mutex::new(None),
+            })
+        }
+
+        fn unavailable() -> Arc<Self> {
+            Arc::new(Self {
+                outcome: None,
+                simulated: Mutex::new(None),
+            })
+        }
+    }
+
+    impl TransactionSimulator for StubSimulator {
+        fn simulate(&self, tx: &Transaction, _accounts: &[Pubkey]) -> Result<SimulationOutcome> {
+            *self.simulated.lock().unwrap() = Some(tx.clone());
+            self.outcome
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("RPC timeout"))
+        }
+    }
+
+    fn ctx(transfer_hook_program: Option<&str>) -> TokenContext {
+        TokenContext {
+            mint: TOKEN.to_string(),
+            symbol: "TEST".to_string(),
+            age_minutes: 30,
+            liquidity_usd: 50_000.0,
+            volume_5m: 10_000.0,
+            price_usd: 0.001,
+            momentum_slope: 1.0,
+            unique_wallets_ratio: 0.6,
+            top_10_holders_pct: 15.0,
+            dev_wallet_pct: 2.0,
+            mint_authority: None,
+            freeze_authority: None,
+            lp_burned_pct: Some(100.0),
+            transfer_fee_bps: 0,
+            permanent_delegate: None,
+            transfer_hook_program: transfer_hook_program.map(str::to_string),
+            non_transferable: false,
+            slot_lag: 0,
+        }
+    }
+
+    fn filter(simulator: Arc<StubSimulator>) -> HoneypotFilter {
+        HoneypotFilter::new(
+            vec![venue(true, true, false)],
+            simulator,
+            Pubkey::new_unique(),
+        )
+    }
+
+    fn is_honeypot(result: FilterResult) -> bool {
+        matches!(result, FilterResult::Rejected(RejectionReason::Honeypot))
+    }
+
+    // Probe por defecto: 1_000_000 lamports → 10M tokens; se venden 9M → 900_000 lamports
+    fn sold(sol_out: u64, tokens_left: u64) -> SimulationOutcome {
+        SimulationOutcome {
+            token_balances: vec![Some(sol_out), Some(tokens_left), None],
+            ..Default::default()
+        }
+    }
+
+    fn reverted(idx: usize, logs: &[&str]) -> SimulationOutcome {
+        SimulationOutcome {
+            error: Some("custom program error: 0x1".to_string()),
+            failed_instruction: Some(idx),
+            logs: logs.iter().map(|l| l.to_string()).collect(),
+            pre_token_balances: vec![None; 3],
+            token_balances: vec![None; 3],
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_clean_round_trip_is_approved() {
+        let simulator = StubSimulator::new(sold(900_000, 1_000_000));
+        let result = filter(simulator.clone()).check(&ctx(None));
+        assert!(matches!(result, FilterResult::Approved));
+
+        // Compra (3 ixs) + venta sin el cierre del WSOL (1 ix)
+        let tx = simulator.simulated.lock().unwrap().clone().unwrap();
+        assert_eq!(tx.message.instructions.len(), 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_failed_sell_is_honeypot() {
+        let result = filter(StubSimulator::new(reverted(3, &[]))).check(&ctx(None));
+        assert!(is_honeypot(result));
+
+        // Si revierte la compra no hay veredicto
+        let result = filter(StubSimulator::new(reverted(1, &[]))).check(&ctx(None));
+        assert!(matches!(result, FilterResult::Approved));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unquotable_sell_is_honeypot() {
+        let simulator = StubSimulator::new(sold(900_000, 1_000_000));
+        let honeypot = HoneypotFilter::new(
+            vec![venue(true, false, false)],
+            simulator.clone(),
+            Pubkey::new_unique(),
+        );
+        assert!(is_honeypot(honeypot.check(&ctx(None))));
+        assert!(simulator.simulated.lock().unwrap().is_none());
+
+        // Solo un fallo del simulador deja pasar el token sin veredicto
+        let result = filter(StubSimulator::unavailable()).check(&ctx(None));
+        assert!(matches!(result, FilterResult::Approved));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_no_venue_for_the_buy_is_inconclusive() {
+        let simulator = StubSimulator::new(reverted(3, &[]));
+        let honeypot = HoneypotFilter::new(
+            vec![venue(false, true, false), venue(false, false, false)],
+            simulator.clone(),
+            Pubkey::new_unique(),
+        );
+        assert!(matches!(honeypot.check(&ctx(None)), FilterResult::Approved));
+        assert!(simulator.simulated.lock().unwrap().is_none());
+
+        let no_venues = HoneypotFilter::new(vec![], simulator, Pubkey::new_unique());
+        assert!(matches!(
+            no_venues.check(&ctx(None)),
+            FilterResult::Approved
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_round_trip_runs_on_first_venue_that_quotes_the_buy() {
+        // Raydium sin pool, el token sigue en la curva (cobra SOL nativo)
+        let venues = vec![venue(false, true, false), venue(true, true, true)];
+
+        // Sin WSOL no hay SOL medible: la venta que no revierte es vendible
+        let simulator = StubSimulator::new(sold(0, 1_000_000));
+        let honeypot = HoneypotFilter::new(venues.clone(), simulator.clone(), Pubkey::new_unique());
+        assert!(matches!(honeypot.check(&ctx(None)), FilterResult::Approved));
+
+        // Compra (1 ix) + venta (1 ix) en la curva
+        let tx = simulator.simulated.lock().unwrap().clone().unwrap();
+        assert_eq!(tx.message.instructions.len(), 2);
+
+        // ...pero si revierte sigue siendo honeypot
+        let honeypot = HoneypotFilter::new(
+            venues,
+            StubSimulator::new(reverted(1, &[])),
+            Pubkey::new_unique(),
+        );
+        assert!(is_honeypot(honeypot.check(&ctx(None))));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_transfer_hook_revert_is_honeypot() {
+        let hook = "Hook111111111111111111111111111111111111111";
+        let logs = [format!(
+            "Program {} failed: custom program error: 0x0",
+            hook
+        )];
+        let logs: Vec<&str> = logs.iter().map(String::as_str).collect();
+
+        // Aunque revierta en la compra, el hook bloquea la transferencia
+        let result = filter(StubSimulator::new(reverted(1, &logs))).check(&ctx(Some(hook)));
+        assert!(is_honeypot(result));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_abnormal_tax_is_honeypot() {
+        // La venta rinde la mitad de lo cotizado → ~50% de impuesto
+        let result = filter(StubSimulator::new(sold(450_000, 1_000_000))).check(&ctx(None));
+        assert!(is_honeypot(result));
+
+        // 5% en la venta: dentro del máximo
+        let result = filter(StubSimulator::new(sold(855_000, 1_000_000))).check(&ctx(None));
+        assert!(matches!(result, FilterResult::Approved));
+    }
+
+    #[test]
+    fn test_judge_round_trip_tax() {
+        let probe = RoundTripProbe {
+            transaction: Transaction::default(),
+            buy_instructions: 3,
+            tokens_quoted: 1_000,
+            tokens_sold: 900,
+            expected_sol_out: 100,
+            measures_sol_out: true,
+            watched: [Pubkey::default(); 3],
+        };
+
+        // Llegan 950 de los 1_000 tokens cotizados (5%) y la venta rinde lo cotizado
+        let verdict = judge_round_trip(&probe, &sold(100, 50), None, 10.0);
+        assert!(matches!(
+            verdict,
+            RoundTripVerdict::Sellable { effective_tax_pct } if (effective_tax_pct - 5.0).abs() < 1e-9
+        ));
+
+        // El sobrante puede estar en el ATA de Token-2022; la venta rinde un 20% menos
+        let outcome = SimulationOutcome {
+            token_balances: vec![Some(80), None, Some(100)],
+            ..Default::default()
+        };
+        assert!(matches!(
+            judge_round_trip(&probe, &outcome, None, 10.0),
+            RoundTripVerdict::AbnormalTax(tax) if (tax - 20.0).abs() < 1e-9
+        ));
+
+        // Saldo previo de 500 tokens: llegan 500 de 1_000 (50%) y la venta de 900
+        // tira del saldo viejo. Sin el delta pre/post parecería un 0%
+        let outcome = SimulationOutcome {
+            pre_token_balances: vec![Some(10), Some(500), None],
+            token_balances: vec![Some(110), Some(100), None],
+            ..Default::default()
+        };
+        assert!(matches!(
+            judge_round_trip(&probe, &outcome, None, 10.0),
+            RoundTripVerdict::AbnormalTax(tax) if (tax - 50.0).abs() < 1e-9
+        ));
+    }
+}
@@ -4,23 +4,36 @@
 //! Sigue el patrón "Safety-Critical Pipeline".
 
 pub mod actuators;
+pub mod clock;
 pub mod filters;
+pub mod honeypot;
 pub mod momentum;
+pub mod order_journal;
+pub mod position_book;
+pub mod replay;
 pub mod types;
 pub mod commands;
+pub mod events;
+pub mod exit_rules;
 pub mod router;
 pub mod strategy;
+pub mod tp_ladder;
 
 // Re-exportar tipos para uso externo (AutoBuyer)
 pub use self::actuators::{AdaptiveSlippageCalculator, DynamicTipCalculator};
 pub use self::types::{FilterResult, RejectionReason, TokenContext, TradeFilter}; // ✅ Exportación Explicita
 
 use self::filters::{
-    AuthorityFilter, CircuitBreaker, MomentumFilter, TokenCooldown, WashTradingFilter,
+    AuthorityFilter, CircuitBreaker, DevWalletFilter, EarlyBurnGate, HolderConcentrationFilter,
+    LpBurnFilter, MomentumFilter, NetworkCongestionFilter, Token2022ExtensionFilter,
+    TokenCooldown, WashTradingFilter,
 };
 
+use self::events::{DecisionEvent, DecisionOutcome};
+use crate::config::FilterSettings;
 use intelligence_rs::strategy_engine::{MarketData, Strategy, TradeAction, SellReason};
 use chrono::Utc;
+use tokio::sync::broadcast;
 
 /// Decisión final del Engine unificando Estrategia, Filtros y Actuadores
 #[derive(Debug)]
@@ -42,6 +55,8 @@ pub struct DecisionEngine {
     strategies: Vec<Box<dyn Strategy + Send + Sync>>,
     tip_calculator: DynamicTipCalculator,
     slippage_calculator: AdaptiveSlippageCalculator,
+    /// Publicación opcional de decisiones hacia el EventBus (gRPC streaming)
+    decision_sink: Option<broadcast::Sender<DecisionEvent>>,
 }
 
 impl Default for DecisionEngine {
@@ -53,18 +68,37 @@ impl Default for DecisionEngine {
 impl DecisionEngine {
     /// Crea un nuevo motor con configuración estándar de seguridad
     pub fn new() -> Self {
+        Self::with_settings(&FilterSettings::default())
+    }
+
+    /// Crea el motor con los umbrales de `settings.json` (`filters`)
+    pub fn with_settings(settings: &FilterSettings) -> Self {
         let mut engine = Self {
             filters: Vec::new(),
             strategies: Vec::new(),
             tip_calculator: DynamicTipCalculator::new(),
             slippage_calculator: AdaptiveSlippageCalculator::new(),
+            decision_sink: None,
         };
 
         // Cargar filtros de seguridad básicos
-        engine.add_filter(Box::new(CircuitBreaker::new(-10.0)));
+        engine.add_filter(Box::new(CircuitBreaker));
         engine.add_filter(Box::new(TokenCooldown::new(240)));
         engine.add_filter(Box::new(AuthorityFilter));
-        engine.add_filter(Box::new(WashTradingFilter::new(0.20)));
+        engine.add_filter(Box::new(Token2022ExtensionFilter));
+        engine.add_filter(Box::new(NetworkCongestionFilter::new(settings.max_slot_lag)));
+        engine.add_filter(Box::new(
+            EarlyBurnGate::new(settings.early_age_minutes, settings.early_min_lp_burned_pct)
+                .with_unmeasured(settings.unmeasured_lp),
+        ));
+        engine.add_filter(Box::new(
+            LpBurnFilter::new(settings.min_lp_burned_pct).with_unmeasured(settings.unmeasured_lp),
+        ));
+        engine.add_filter(Box::new(HolderConcentrationFilter::new(
+            settings.max_top_10_holders_pct,
+        )));
+        engine.add_filter(Box::new(DevWalletFilter::new(settings.max_dev_wallet_pct)));
+        engine.add_filter(Box::new(WashTradingFilter::new(settings.min_unique_wallets_ratio)));
         engine.add_filter(Box::new(MomentumFilter::new(0.0)));
 
         engine
@@ -80,13 +114,28 @@ impl DecisionEngine {
         self.strategies.push(strategy);
     }
 
+    /// Conecta el engine al EventBus: cada decisión se publica como `DecisionEvent`
+    pub fn set_decision_sink(&mut self, sink: broadcast::Sender<DecisionEvent>) {
+        self.decision_sink = Some(sink);
+    }
+
+    fn publish(&self, ctx: &TokenContext, outcome: DecisionOutcome) {
+        if let Some(sink) = &self.decision_sink {
+            // Err = no hay suscriptores conectados
+            let _ = sink.send(DecisionEvent::new(&ctx.mint, &ctx.symbol, outcome));
+        }
+    }
+
     /// (Legacy) Evalúa una oportunidad de trading saltándose las estrategias
     pub fn evaluate(&self, ctx: &TokenContext) -> Result<ExecutionParams, RejectionReason> {
         // 1. Ejecutar Pipeline de Filtros (Fail-Fast)
         for filter in &self.filters {
             match filter.check(ctx) {
                 FilterResult::Approved => continue,
-                FilterResult::Rejected(reason) => return Err(reason),
+                FilterResult::Rejected(reason) => {
+                    self.publish(ctx, DecisionOutcome::Rejected(reason.clone()));
+                    return Err(reason);
+                }
             }
         }
 
@@ -100,6 +149,15 @@ impl DecisionEngine {
             .slippage_calculator
             .calculate_slippage(ctx.momentum_slope, maturity);
 
+        self.publish(
+            ctx,
+            DecisionOutcome::ExecuteBuy {
+                confidence: 1.0,
+                slippage_bps,
+                priority_fee_lamports: priority_fee,
+            },
+        );
+
         Ok(ExecutionParams {
             priority_fee_lamports: priority_fee,
             slippage_bps,
@@ -109,12 +167,35 @@ impl DecisionEngine {
 
     /// (Nuevo) Evalúa el contexto completo: Estrategias -> Filtros -> Actuadores
     pub fn evaluate_with_strategy(&mut self, ctx: &TokenContext) -> EngineDecision {
+        let decision = self.decide(ctx);
+
+        let outcome = match &decision {
+            EngineDecision::ExecuteBuy(params, confidence, _, _) => DecisionOutcome::ExecuteBuy {
+                confidence: *confidence,
+                slippage_bps: params.slippage_bps,
+                priority_fee_lamports: params.priority_fee_lamports,
+            },
+            EngineDecision::ExecuteSell(reason, amount_percent) => DecisionOutcome::ExecuteSell {
+                reason: *reason,
+                amount_percent: *amount_percent,
+            },
+            EngineDecision::RejectedByFilter(reason) => DecisionOutcome::Rejected(reason.clone()),
+            EngineDecision::Hold => DecisionOutcome::Hold,
+        };
+        self.publish(ctx, outcome);
+
+        decision
+    }
+
+    fn decide(&mut self, ctx: &TokenContext) -> EngineDecision {
         // 1. Convertir TokenContext a MarketData para las Estrategias
         let market_data = MarketData {
+            token_mint: ctx.mint.clone(),
             timestamp_ms: Utc::now().timestamp_millis() as u64,
             price: ctx.price_usd,
             volume_24h: ctx.volume_5m * 288.0, // Estimación simple
             liquidity: ctx.liquidity_usd,
+            pool_reserves: None,
         };
 
         // 2. Consultar al Strategy Engine
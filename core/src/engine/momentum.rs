@@ -35,7 +35,11 @@ impl MomentumSensor {
 
     /// Actualiza el sensor con un nuevo valor (O(1))
     pub fn update(&mut self, value: f64) {
-        let now = Instant::now();
+        self.update_at(value, Instant::now());
+    }
+
+    /// Igual que `update` con el instante del dato (reloj simulado en replay)
+    pub fn update_at(&mut self, value: f64, now: Instant) {
         let point = DataPoint {
             value,
             timestamp: now,
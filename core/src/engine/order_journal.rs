@@ -0,0 +1,484 @@
+//! # Order Journal — Reconciliación al arranque
+//!
+//! El `ExecutionRouter` escribe cada salida en la tabla `orders` antes y después
+//! de enviarla (PENDING → SUBMITTED(firma) → CONFIRMED | FAILED). Si el proceso
+//! cae a mitad de una venta, al arrancar resolvemos las órdenes abiertas contra
+//! la red ANTES de que el StrategyEngine vuelva a disparar SL/TP:
+//!
+//! - SUBMITTED: estado de la firma en el ledger (con historial).
+//! - PENDING (sin firma): variación del balance del token frente al snapshot
+//!   `tokens_before` tomado antes de enviar.
+//!
+//! Una orden que aterrizó aplica su fill a la posición (idempotente) con los
+//! importes reales leídos de su TX (tokens vendidos, SOL recibido, fee). Una que
+//! no, queda FAILED y la posición sigue viva: el engine re-disparará la salida.
+
+use anyhow::Result;
+use chrono::Utc;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{UiTransactionEncoding, UiTransactionTokenBalance};
+use std::str::FromStr;
+
+use crate::engine::commands::CommandType;
+use crate::engine::router::apply_fill_to_position;
+use crate::state_manager::{OrderRecord, OrderStatus, StateManager, TradeRecord};
+use crate::token_2022::fetch_token_balance;
+
+/// Pasado este tiempo una TX sin confirmar ya no puede aterrizar (blockhash expirado)
+pub const ORDER_EXPIRY_SECS: i64 = 150;
+
+/// Firmas recientes de la wallet revisadas para localizar una venta PENDING
+const FILL_SEARCH_LIMIT: usize = 20;
+
+/// Veredicto sobre una orden abierta
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderResolution {
+    /// La venta se ejecutó on-chain
+    Landed,
+    /// No aterrizó o revirtió
+    Failed(String),
+    /// Aún podría aterrizar: esperar estos segundos y volver a mirar
+    Wait(i64),
+}
+
+/// Decide el destino de una orden abierta a partir de lo observado on-chain.
+///
+/// `signature_status`: `Some(true)` = OK, `Some(false)` = revertida, `None` = desconocida.
+/// `tokens_now`: balance raw actual del token en la wallet.
+pub fn resolve_order(
+    order: &OrderRecord,
+    signature_status: Option<bool>,
+    tokens_now: Option<u64>,
+    now: i64,
+) -> OrderResolution {
+    let age = now - order.created_at;
+
+    match order.status {
+        OrderStatus::Confirmed => return OrderResolution::Landed,
+        OrderStatus::Failed => {
+            return OrderResolution::Failed(order.error.clone().unwrap_or_default())
+        }
+        OrderStatus::Submitted if order.signature.is_some() => {
+            return match signature_status {
+                Some(true) => OrderResolution::Landed,
+                Some(false) => OrderResolution::Failed("TX revertida on-chain".to_string()),
+                None if age < ORDER_EXPIRY_SECS => OrderResolution::Wait(ORDER_EXPIRY_SECS - age),
+                None => OrderResolution::Failed("Firma no encontrada (blockhash expirado)".into()),
+            };
+        }
+        _ => {}
+    }
+
+    // PENDING: no sabemos la firma, el balance es la única verdad
+    if age < ORDER_EXPIRY_SECS {
+        return OrderResolution::Wait(ORDER_EXPIRY_SECS - age);
+    }
+
+    match (order.tokens_before, tokens_now) {
+        (Some(before), Some(current)) if before > 0 => {
+            let expected_sold = before as u128 * order.sell_pct as u128 / 100;
+            let sold = before.saturating_sub(current) as u128;
+            // Mitad de lo esperado basta: el resto puede ser ruido de transfer fees
+            if expected_sold > 0 && sold * 2 >= expected_sold {
+                OrderResolution::Landed
+            } else {
+                OrderResolution::Failed("Balance sin cambios: la venta no aterrizó".to_string())
+            }
+        }
+        _ => OrderResolution::Failed("Sin balance de referencia: se reintentará la salida".into()),
+    }
+}
+
+/// Lo que una venta movió en la wallet, leído de su TX on-chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct SellFill {
+    pub signature: String,
+    /// Tokens raw que salieron de la wallet
+    pub tokens_sold: u64,
+    /// SOL recibido, antes de descontar la fee de red
+    pub sol_received: f64,
+    pub fee_sol: f64,
+}
+
+/// Fill de una venta a partir de los balances pre/post de su TX.
+///
+/// `sol_pre`/`sol_post` son lamports de la wallet (fee payer); los tokens, raw.
+/// Si no salieron tokens la TX no es una venta del mint: `None`.
+pub fn fill_from_balances(
+    signature: &str,
+    sol_pre: u64,
+    sol_post: u64,
+    fee_lamports: u64,
+    tokens_pre: u64,
+    tokens_post: u64,
+) -> Option<SellFill> {
+    let tokens_sold = tokens_pre
+        .checked_sub(tokens_post)
+        .filter(|sold| *sold > 0)?;
+    let received = (sol_post as i128 + fee_lamports as i128 - sol_pre as i128).max(0);
+
+    Some(SellFill {
+        signature: signature.to_string(),
+        tokens_sold,
+        sol_received: received as f64 / 1e9,
+        fee_sol: fee_lamports as f64 / 1e9,
+    })
+}
+
+/// Reconciliación de arranque. Devuelve cuántas órdenes se resolvieron.
+pub async fn reconcile_open_orders(
+    state_manager: &StateManager,
+    rpc: &RpcClient,
+    wallet: &Pubkey,
+) -> Result<usize> {
+    let mut pending = state_manager.get_open_orders().await?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    println!(
+        "🧾 [JOURNAL] {} órdenes abiertas de la sesión anterior. Reconciliando...",
+        pending.len()
+    );
+
+    let mut resolved = 0;
+
+    // Una pasada resuelve todo lo expirado; si algo es reciente, esperamos a que
+    // expire su blockhash y la segunda pasada ya no puede devolver Wait
+    for _ in 0..2 {
+        let mut waiting = Vec::new();
+        let mut max_wait = 0;
+
+        for order in pending {
+            let signature_status = order
+                .signature
+                .as_deref()
+                .and_then(|sig| signature_status(rpc, sig));
+            let tokens_now = Pubkey::from_str(&order.token_mint)
+                .ok()
+                .and_then(|mint| fetch_token_balance(rpc, wallet, &mint).ok());
+
+            match resolve_order(&order, signature_status, tokens_now, Utc::now().timestamp()) {
+                OrderResolution::Wait(secs) => {
+                    max_wait = max_wait.max(secs);
+                    waiting.push(order);
+                }
+                OrderResolution::Landed => {
+                    let fill = match order.signature.as_deref() {
+                        Some(sig) => fetch_sell_fill(rpc, wallet, &order.token_mint, sig),
+                        None => find_sell_fill(rpc, wallet, &order.token_mint, order.created_at),
+                    };
+                    let landed = LandedFill { fill, tokens_now };
+                    apply_resolution(state_manager, &order, OrderResolution::Landed, landed)
+                        .await?;
+                    resolved += 1;
+                }
+                resolution => {
+                    apply_resolution(state_manager, &order, resolution, LandedFill::default())
+                        .await?;
+                    resolved += 1;
+                }
+            }
+        }
+
+        if waiting.is_empty() {
+            break;
+        }
+
+        println!(
+            "⏳ [JOURNAL] {} órdenes aún pueden aterrizar. Esperando {}s...",
+            waiting.len(),
+            max_wait
+        );
+        tokio::time::sleep(std::time::Duration::from_secs(max_wait.max(1) as u64)).await;
+        pending = waiting;
+    }
+
+    Ok(resolved)
+}
+
+/// Lo observado on-chain de una orden que aterrizó
+#[derive(Debug, Default)]
+struct LandedFill {
+    /// Importes de su TX; None si no se pudo localizar
+    fill: Option<SellFill>,
+    /// Balance raw actual: da los tokens vendidos aunque falte la TX
+    tokens_now: Option<u64>,
+}
+
+async fn apply_resolution(
+    state_manager: &StateManager,
+    order: &OrderRecord,
+    resolution: OrderResolution,
+    landed: LandedFill,
+) -> Result<()> {
+    let Some(order_id) = order.id else {
+        return Ok(());
+    };
+
+    match resolution {
+        OrderResolution::Landed => {
+            println!(
+                "   ✅ Orden #{} {} {} aterrizó. Aplicando fill.",
+                order_id, order.command_type, order.symbol
+            );
+            let LandedFill { fill, tokens_now } = landed;
+            let signature = fill
+                .as_ref()
+                .map(|f| f.signature.clone())
+                .or_else(|| order.signature.clone())
+                .unwrap_or_else(|| format!("RECONCILED_{}", order_id));
+            let proceeds = fill.as_ref().map(|f| f.sol_received);
+
+            let cost = match CommandType::from_trade_type(&order.command_type) {
+                Some(cmd_type) => {
+                    apply_fill_to_position(
+                        state_manager,
+                        &order.token_mint,
+                        &cmd_type,
+                        &signature,
+                        order.sell_pct,
+                        proceeds,
+                    )
+                    .await
+                }
+                None => None,
+            };
+
+            let tokens_sold = fill.as_ref().map(|f| f.tokens_sold).or_else(|| {
+                order
+                    .tokens_before
+                    .zip(tokens_now)
+                    .map(|(before, now)| before.saturating_sub(now))
+            });
+            let tokens_amount = tokens_sold.unwrap_or(0) as f64;
+
+            // Sin la TX no hay SOL recibido: los tokens sí, el PnL no
+            let invested_portion = cost
+                .map(|c| c.cost_wavg_sol)
+                .unwrap_or(order.amount_invested * (order.sell_pct as f64 / 100.0));
+            let (pnl_sol, pnl_percent) = match proceeds {
+                Some(sol) if invested_portion > 0.0 => (
+                    Some(sol - invested_portion),
+                    Some(((sol / invested_portion) - 1.0) * 100.0),
+                ),
+                Some(sol) => (Some(sol - invested_portion), Some(0.0)),
+                None => (None, None),
+            };
+
+            let trade = TradeRecord {
+                id: None,
+                signature,
+                token_mint: order.token_mint.clone(),
+                symbol: order.symbol.clone(),
+                trade_type: order.command_type.clone(),
+                amount_sol: proceeds.unwrap_or(0.0),
+                tokens_amount,
+                price: match proceeds {
+                    Some(sol) if tokens_amount > 0.0 => sol / tokens_amount,
+                    _ => 0.0,
+                },
+                pnl_sol,
+                pnl_percent,
+                route: "Boot Reconcile".to_string(),
+                price_impact_pct: 0.0,
+                fee_sol: fill.as_ref().map(|f| f.fee_sol).unwrap_or(0.0),
+                timestamp: Utc::now().timestamp(),
+            };
+            // Si el router llegó a registrarlo, la firma UNIQUE lo rechaza: correcto
+            let _ = state_manager.record_trade(trade).await;
+
+            state_manager
+                .update_order_status(order_id, OrderStatus::Confirmed, None, None)
+                .await
+        }
+        OrderResolution::Failed(reason) => {
+            println!(
+                "   ❌ Orden #{} {} {} no aterrizó: {}",
+                order_id, order.command_type, order.symbol, reason
+            );
+            state_manager
+                .update_order_status(order_id, OrderStatus::Failed, None, Some(reason))
+                .await
+        }
+        OrderResolution::Wait(_) => Ok(()),
+    }
+}
+
+/// Estado de la firma buscando también en el historial del ledger
+fn signature_status(rpc: &RpcClient, signature: &str) -> Option<bool> {
+    let sig = Signature::from_str(signature).ok()?;
+    rpc.get_signature_status_with_commitment_and_history(&sig, CommitmentConfig::confirmed(), true)
+        .ok()?
+        .map(|result| result.is_ok())
+}
+
+/// Importes de una venta a partir de su firma. None si la TX no movió el mint.
+fn fetch_sell_fill(
+    rpc: &RpcClient,
+    wallet: &Pubkey,
+    mint: &str,
+    signature: &str,
+) -> Option<SellFill> {
+    let sig = Signature::from_str(signature).ok()?;
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+    };
+    let tx = rpc.get_transaction_with_config(&sig, config).ok()?;
+    let meta = tx.transaction.meta?;
+    if meta.err.is_some() {
+        return None;
+    }
+
+    // La wallet firma las ventas: es el fee payer (índice 0)
+    let owner = wallet.to_string();
+    let wallet_tokens = |balances: Option<Vec<UiTransactionTokenBalance>>| -> u64 {
+        balances
+            .unwrap_or_default()
+            .iter()
+            .filter(|b| b.mint == mint)
+            .filter(|b| Option::<String>::from(b.owner.clone()).as_deref() == Some(owner.as_str()))
+            .filter_map(|b| b.ui_token_amount.amount.parse::<u64>().ok())
+            .sum()
+    };
+
+    fill_from_balances(
+        signature,
+        *meta.pre_balances.first()?,
+        *meta.post_balances.first()?,
+        meta.fee,
+        wallet_tokens(meta.pre_token_balances.into()),
+        wallet_tokens(meta.post_token_balances.into()),
+    )
+}
+
+/// Una orden PENDING no guardó su firma: buscamos entre las TX recientes de la
+/// wallet la primera venta del mint posterior a la orden.
+fn find_sell_fill(rpc: &RpcClient, wallet: &Pubkey, mint: &str, since: i64) -> Option<SellFill> {
+    let config = GetConfirmedSignaturesForAddress2Config {
+        before: None,
+        until: None,
+        limit: Some(FILL_SEARCH_LIMIT),
+        commitment: Some(CommitmentConfig::confirmed()),
+    };
+    let signatures = rpc
+        .get_signatures_for_address_with_config(wallet, config)
+        .ok()?;
+
+    // Vienen de la más reciente a la más antigua: recorremos en orden cronológico
+    signatures
+        .iter()
+        .rev()
+        .filter(|s| s.err.is_none() && s.block_time.is_some_and(|t| t >= since))
+        .find_map(|s| fetch_sell_fill(rpc, wallet, mint, &s.signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn order(status: OrderStatus, signature: Option<&str>, age: i64) -> OrderRecord {
+        OrderRecord {
+            id: Some(1),
+            token_mint: "MINT".to_string(),
+            symbol: "TST".to_string(),
+            command_type: "AUTO_SL".to_string(),
+            sell_pct: 100,
+            amount_invested: 0.5,
+            tokens_before: Some(1_000_000),
+            status,
+            signature: signature.map(str::to_string),
+            error: None,
+            created_at: NOW - age,
+            updated_at: NOW - age,
+        }
+    }
+
+    #[test]
+    fn test_submitted_follows_signature_status() {
+        let submitted = order(OrderStatus::Submitted, Some("SIG"), 600);
+        assert_eq!(
+            resolve_order(&submitted, Some(true), None, NOW),
+            OrderResolution::Landed
+        );
+        assert!(matches!(
+            resolve_order(&submitted, Some(false), None, NOW),
+            OrderResolution::Failed(_)
+        ));
+        assert!(matches!(
+            resolve_order(&submitted, None, None, NOW),
+            OrderResolution::Failed(_)
+        ));
+
+        // Reciente y sin estado: aún puede aterrizar
+        let fresh = order(OrderStatus::Submitted, Some("SIG"), 30);
+        assert_eq!(
+            resolve_order(&fresh, None, None, NOW),
+            OrderResolution::Wait(ORDER_EXPIRY_SECS - 30)
+        );
+    }
+
+    #[test]
+    fn test_pending_uses_balance_delta() {
+        let pending = order(OrderStatus::Pending, None, 600);
+
+        // ATA vaciado → la venta aterrizó
+        assert_eq!(
+            resolve_order(&pending, None, Some(0), NOW),
+            OrderResolution::Landed
+        );
+        // Balance intacto → no aterrizó
+        assert!(matches!(
+            resolve_order(&pending, None, Some(1_000_000), NOW),
+            OrderResolution::Failed(_)
+        ));
+        // Sin balance legible → FAILED (el engine reintentará)
+        assert!(matches!(
+            resolve_order(&pending, None, None, NOW),
+            OrderResolution::Failed(_)
+        ));
+
+        // TP parcial del 50%: vender 480k de 1M cuenta como aterrizado
+        let tp = OrderRecord {
+            sell_pct: 50,
+            command_type: "AUTO_TP1".to_string(),
+            ..order(OrderStatus::Pending, None, 600)
+        };
+        assert_eq!(
+            resolve_order(&tp, None, Some(520_000), NOW),
+            OrderResolution::Landed
+        );
+    }
+
+    #[test]
+    fn test_fill_from_tx_balances() {
+        // Vende 400k tokens; la wallet pasa de 1.0 a 1.2449 SOL pagando 5000 lamports
+        let fill = fill_from_balances(
+            "SIG",
+            1_000_000_000,
+            1_244_900_000,
+            5_000,
+            1_000_000,
+            600_000,
+        )
+        .unwrap();
+        assert_eq!(fill.tokens_sold, 400_000);
+        assert!((fill.sol_received - 0.2449 - 0.000005).abs() < 1e-12);
+        assert!((fill.fee_sol - 0.000005).abs() < 1e-12);
+
+        // Una TX que no sacó tokens del mint no es la venta
+        assert!(
+            fill_from_balances("SIG", 1_000_000_000, 999_995_000, 5_000, 600_000, 600_000)
+                .is_none()
+        );
+        assert!(fill_from_balances("SIG", 1_000_000_000, 900_000_000, 5_000, 0, 500_000).is_none());
+    }
+}
@@ -0,0 +1,152 @@
+//! # Position Book (Libro de posiciones en memoria)
+//!
+//! Copia en memoria de las posiciones activas que consulta el `StrategyEngine`
+//! en cada tick, en lugar de leer SQLite por cada `PriceUpdate`.
+//!
+//! Es write-through: la DB sigue siendo la fuente de verdad. Telegram, gRPC y el
+//! Router escriben en el `StateManager`, y éste publica el estado resultante por
+//! el canal de `PositionEvent` que drena el engine. El trailing SL lo escribe el
+//! propio engine, así que lo actualiza aquí directamente antes de persistirlo
+//! (igual que el break-even de las reglas de salida).
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::state_manager::{PositionEvent, PositionState, StateManager};
+
+#[derive(Debug, Default)]
+pub struct PositionBook {
+    positions: HashMap<String, PositionState>,
+}
+
+impl PositionBook {
+    /// Carga las posiciones activas al arrancar
+    pub async fn load(state_manager: &StateManager) -> Result<Self> {
+        let positions = state_manager.get_active_positions().await?;
+        Ok(Self::from_positions(positions))
+    }
+
+    pub fn from_positions(positions: Vec<PositionState>) -> Self {
+        let mut book = Self::default();
+        for pos in positions {
+            book.apply(PositionEvent::Upserted(Box::new(pos)));
+        }
+        book
+    }
+
+    /// Posición activa para el mint (None si no existe o está cerrada)
+    pub fn get(&self, token_mint: &str) -> Option<&PositionState> {
+        self.positions.get(token_mint)
+    }
+
+    /// Aplica un cambio ya persistido
+    pub fn apply(&mut self, event: PositionEvent) {
+        match event {
+            PositionEvent::Upserted(pos) if pos.active => {
+                self.positions.insert(pos.token_mint.clone(), *pos);
+            }
+            PositionEvent::Upserted(pos) => {
+                self.positions.remove(&pos.token_mint);
+            }
+            PositionEvent::Closed(mint) => {
+                self.positions.remove(&mint);
+            }
+        }
+    }
+
+    /// Refleja en memoria el nuevo estado del trailing SL
+    pub fn update_trailing_sl(&mut self, token_mint: &str, highest_price: f64, current_sl: f64) {
+        if let Some(pos) = self.positions.get_mut(token_mint) {
+            pos.trailing_highest_price = Some(highest_price);
+            pos.trailing_current_sl = Some(current_sl);
+        }
+    }
+
+    /// Refleja en memoria el stop movido a break-even
+    pub fn arm_break_even(&mut self, token_mint: &str) {
+        if let Some(pos) = self.positions.get_mut(token_mint) {
+            pos.exit_rules.break_even_armed = true;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_manager::{ExitRules, TpRung};
+
+    fn position(mint: &str) -> PositionState {
+        PositionState {
+            id: Some(1),
+            token_mint: mint.to_string(),
+            symbol: "TEST".to_string(),
+            entry_price: 0.001,
+            amount_sol: 1.0,
+            current_price: 0.001,
+            stop_loss_percent: -20.0,
+            trailing_enabled: false,
+            trailing_distance_percent: 5.0,
+            trailing_activation_threshold: 10.0,
+            trailing_highest_price: None,
+            trailing_current_sl: None,
+            tp_ladder: vec![TpRung::new(100.0, 50.0)],
+            active: true,
+            created_at: 0,
+            updated_at: 0,
+            token_amount: 0.0,
+            realized_pnl_sol: 0.0,
+            exit_rules: ExitRules::default(),
+        }
+    }
+
+    #[test]
+    fn test_events_keep_book_in_sync() {
+        let mut book = PositionBook::from_positions(vec![position("MINT_A")]);
+        assert_eq!(book.len(), 1);
+
+        // Fill del primer escalón: el StateManager publica el estado completo actualizado
+        let mut after_tp = position("MINT_A");
+        after_tp.tp_ladder[0].triggered = true;
+        after_tp.amount_sol = 0.5;
+        book.apply(PositionEvent::Upserted(Box::new(after_tp)));
+        let pos = book.get("MINT_A").unwrap();
+        assert!(pos.tp_ladder[0].triggered);
+        assert_eq!(pos.amount_sol, 0.5);
+
+        // /track de un token nuevo
+        book.apply(PositionEvent::Upserted(Box::new(position("MINT_B"))));
+        assert_eq!(book.len(), 2);
+
+        // /untrack o venta total
+        book.apply(PositionEvent::Closed("MINT_A".to_string()));
+        assert!(book.get("MINT_A").is_none());
+
+        // Un upsert inactivo también saca la posición del libro
+        let mut inactive = position("MINT_B");
+        inactive.active = false;
+        book.apply(PositionEvent::Upserted(Box::new(inactive)));
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn test_trailing_update_in_place() {
+        let mut book = PositionBook::from_positions(vec![position("MINT_A")]);
+        book.update_trailing_sl("MINT_A", 0.002, -10.0);
+
+        let pos = book.get("MINT_A").unwrap();
+        assert_eq!(pos.trailing_highest_price, Some(0.002));
+        assert_eq!(pos.trailing_current_sl, Some(-10.0));
+
+        // Mint desconocido: no-op
+        book.update_trailing_sl("MINT_X", 1.0, -1.0);
+        assert_eq!(book.len(), 1);
+    }
+}
@@ -0,0 +1,316 @@
+//! # Replay — Reproducción determinista de grabaciones del PriceFeed
+//!
+//! Pasa una grabación de `tick_recorder` por `StrategyEngine::run_loop` con un
+//! `SimClock` y un router simulado en lugar del `ExecutionRouter`:
+//!
+//! ```text
+//!   ticks.jsonl ──▶ driver ──tick──▶ StrategyEngine ──ExecutionCommand──▶ MockRouter
+//!                     ▲  (SimClock)        │ ack                             │ fill instantáneo
+//!                     └────────────────────┘◀──PositionEvent / Feedback──────┘
+//! ```
+//!
+//! El driver avanza en lockstep: manda un tick, espera el ack del engine, llena
+//! al instante los comandos emitidos y solo entonces manda el siguiente. Así la
+//! misma grabación produce siempre la misma lista de comandos, que se puede
+//! versionar y comparar con `diff` al tocar la lógica de salida.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use crate::engine::clock::{Clock, SimClock};
+use crate::engine::commands::{CommandType, ExecutionCommand, ExecutionFeedback};
+use crate::engine::exit_rules;
+use crate::engine::position_book::PositionBook;
+use crate::engine::strategy::StrategyEngine;
+use crate::state_manager::{PositionEvent, PositionState, StateManager};
+use crate::tick_recorder::RecordedTick;
+
+/// Un comando emitido por el engine durante el replay
+#[derive(Debug, Clone)]
+pub struct ReplayCommand {
+    /// Hora simulada (ms Unix) del tick que lo disparó
+    pub at_ms: i64,
+    /// Precio (SOL) de ese tick
+    pub price_native: f64,
+    pub command: ExecutionCommand,
+}
+
+impl ReplayCommand {
+    /// Línea estable para comparar replays con `diff`
+    pub fn to_line(&self) -> String {
+        let at = chrono::DateTime::from_timestamp_millis(self.at_ms)
+            .map(|t| t.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+            .unwrap_or_else(|| self.at_ms.to_string());
+
+        let (mint, symbol, detail) = match &self.command {
+            ExecutionCommand::TakeProfit {
+                mint,
+                symbol,
+                sell_amount_pct,
+                ..
+            }
+            | ExecutionCommand::ManualSell {
+                mint,
+                symbol,
+                sell_amount_pct,
+                ..
+            } => (mint, symbol, format!("sell={}%", sell_amount_pct)),
+            ExecutionCommand::StopLoss { mint, symbol, .. } => {
+                (mint, symbol, "sell=100%".to_string())
+            }
+            ExecutionCommand::Exit { mint, symbol, .. } => (mint, symbol, "sell=100%".to_string()),
+        };
+
+        format!(
+            "{} {} {} {} {} price={}",
+            at,
+            command_type(&self.command).trade_type(),
+            symbol,
+            mint,
+            detail,
+            self.price_native
+        )
+    }
+}
+
+/// Resultado de un replay
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub ticks: usize,
+    pub commands: Vec<ReplayCommand>,
+}
+
+impl ReplayReport {
+    /// Una línea por comando, en el orden en que se emitieron
+    pub fn to_lines(&self) -> String {
+        self.commands.iter().map(|c| c.to_line() + "\n").collect()
+    }
+}
+
+fn command_type(command: &ExecutionCommand) -> CommandType {
+    match command {
+        ExecutionCommand::TakeProfit { rung, .. } => CommandType::TakeProfit(*rung),
+        ExecutionCommand::StopLoss { .. } => CommandType::StopLoss,
+        ExecutionCommand::Exit { reason, .. } => CommandType::Exit(*reason),
+        ExecutionCommand::ManualSell { .. } => CommandType::Manual,
+    }
+}
+
+/// Router simulado: cada comando se llena entero al instante y se refleja en
+/// la posición igual que `router::apply_fill_to_position`.
+struct MockRouter {
+    positions: HashMap<String, PositionState>,
+}
+
+impl MockRouter {
+    fn new(positions: &[PositionState]) -> Self {
+        Self {
+            positions: positions
+                .iter()
+                .filter(|p| p.active)
+                .map(|p| (p.token_mint.clone(), p.clone()))
+                .collect(),
+        }
+    }
+
+    /// El engine arma el break-even en su libro sin publicar evento; lo seguimos
+    /// aquí para que la posición re-publicada tras un TP parcial no lo pierda.
+    fn observe(&mut self, tick: &RecordedTick) {
+        if let Some(pos) = self.positions.get_mut(&tick.token_mint) {
+            let gain = (tick.price_native - pos.entry_price) / pos.entry_price * 100.0;
+            if exit_rules::should_arm_break_even(&pos.exit_rules, gain) {
+                pos.exit_rules.break_even_armed = true;
+            }
+        }
+    }
+
+    fn fill(&mut self, command: &ExecutionCommand) -> (String, CommandType, PositionEvent) {
+        let cmd_type = command_type(command);
+        let (mint, pct) = match command {
+            ExecutionCommand::TakeProfit {
+                mint,
+                sell_amount_pct,
+                ..
+            }
+            | ExecutionCommand::ManualSell {
+                mint,
+                sell_amount_pct,
+                ..
+            } => (mint.clone(), *sell_amount_pct),
+            ExecutionCommand::StopLoss { mint, .. } | ExecutionCommand::Exit { mint, .. } => {
+                (mint.clone(), 100)
+            }
+        };
+
+        let partial = match (&cmd_type, self.positions.get_mut(&mint)) {
+            (CommandType::TakeProfit(rung), Some(pos)) if pct < 100 => {
+                if let Some(step) = pos.tp_ladder.get_mut(*rung as usize) {
+                    step.triggered = true;
+                }
+                Some(pos.clone())
+            }
+            (CommandType::Manual, Some(pos)) if pct < 100 => Some(pos.clone()),
+            _ => None,
+        };
+
+        let event = match partial {
+            Some(pos) => PositionEvent::Upserted(Box::new(pos)),
+            None => {
+                self.positions.remove(&mint);
+                PositionEvent::Closed(mint.clone())
+            }
+        };
+        (mint, cmd_type, event)
+    }
+}
+
+/// Reproduce `ticks` (en orden de grabación) sobre `positions`
+pub async fn replay(ticks: &[RecordedTick], positions: Vec<PositionState>) -> Result<ReplayReport> {
+    let start_ms = ticks.first().map(|t| t.recorded_at_ms).unwrap_or_default();
+    let clock = Arc::new(SimClock::new(start_ms));
+
+    // DB en memoria propia: el engine persiste ahí su trailing/break-even
+    let db_path = format!(
+        "file:replay_{}_{}?mode=memory&cache=shared",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    );
+    let state_manager = Arc::new(StateManager::new(&db_path).await?);
+
+    let mut router = MockRouter::new(&positions);
+    let book = PositionBook::from_positions(positions);
+
+    let (price_tx, price_rx) = mpsc::channel(1);
+    let (cmd_tx, mut cmd_rx) = mpsc::channel(64);
+    let (feedback_tx, feedback_rx) = mpsc::channel(64);
+    let (position_tx, position_rx) = mpsc::unbounded_channel();
+    let (ack_tx, mut ack_rx) = mpsc::unbounded_channel();
+
+    let engine = StrategyEngine::new(state_manager, book)
+        .with_clock(clock.clone())
+        .with_tick_ack(ack_tx);
+    let engine_handle = tokio::spawn(engine.run_loop(price_rx, cmd_tx, feedback_rx, position_rx));
+
+    let mut report = ReplayReport::default();
+    for tick in ticks {
+        clock.set(tick.recorded_at_ms);
+        router.observe(tick);
+
+        price_tx
+            .send(tick.to_update(clock.now()))
+            .await
+            .context("El engine se detuvo durante el replay")?;
+        ack_rx
+            .recv()
+            .await
+            .context("El engine se detuvo durante el replay")?;
+        report.ticks += 1;
+
+        // Todo lo emitido por este tick ya está en el canal: se llena antes del siguiente
+        while let Ok(command) = cmd_rx.try_recv() {
+            let (mint, command_type, event) = router.fill(&command);
+            let _ = position_tx.send(event);
+            let _ = feedback_tx
+                .send(ExecutionFeedback::Success { mint, command_type })
+                .await;
+            report.commands.push(ReplayCommand {
+                at_ms: clock.now_ms(),
+                price_native: tick.price_native,
+                command,
+            });
+        }
+    }
+
+    drop(price_tx);
+    drop(feedback_tx);
+    drop(position_tx);
+    engine_handle.await?;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_feed::PriceSource;
+    use crate::state_manager::{ExitRules, TpRung};
+
+    const START_MS: i64 = 1_760_000_000_000;
+
+    fn position(mint: &str, exit_rules: ExitRules) -> PositionState {
+        PositionState {
+            id: None,
+            token_mint: mint.to_string(),
+            symbol: mint.to_string(),
+            entry_price: 1.0,
+            current_price: 1.0,
+            amount_sol: 0.5,
+            stop_loss_percent: -30.0,
+            trailing_enabled: false,
+            trailing_distance_percent: 15.0,
+            trailing_activation_threshold: 10.0,
+            trailing_highest_price: None,
+            trailing_current_sl: None,
+            tp_ladder: vec![TpRung::new(50.0, 50.0), TpRung::new(100.0, 50.0)],
+            active: true,
+            created_at: START_MS / 1000,
+            updated_at: START_MS / 1000,
+            token_amount: 0.0,
+            realized_pnl_sol: 0.0,
+            exit_rules,
+        }
+    }
+
+    fn tick(mint: &str, secs: i64, price: f64) -> RecordedTick {
+        RecordedTick {
+            recorded_at_ms: START_MS + secs * 1000,
+            feed_latency_us: 0,
+            source: PriceSource::Geyser,
+            token_mint: mint.to_string(),
+            symbol: mint.to_string(),
+            price_native: price,
+            price_usd: price * 150.0,
+            liquidity_usd: 50_000.0,
+            volume_24h: 0.0,
+            price_change_24h: 0.0,
+            pool_reserves: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_is_deterministic() {
+        let ticks = vec![
+            tick("LADDER", 1, 1.2),
+            tick("LADDER", 2, 1.6), // TP1
+            tick("LADDER", 3, 1.7),
+            tick("HOLD", 4, 1.1),
+            tick("LADDER", 5, 2.1),    // TP2 (último escalón, cierra)
+            tick("LADDER", 6, 0.5),    // ya cerrada: nada
+            tick("HOLD", 3_605, 1.05), // 1h en posición
+        ];
+        let positions = vec![
+            position("LADDER", ExitRules::default()),
+            position(
+                "HOLD",
+                ExitRules {
+                    max_hold_secs: Some(3_600),
+                    ..ExitRules::default()
+                },
+            ),
+        ];
+
+        let first = replay(&ticks, positions.clone()).await.unwrap();
+        assert_eq!(first.ticks, ticks.len());
+        assert_eq!(
+            first.to_lines(),
+            "2025-10-09T08:53:22.000Z AUTO_TP1 LADDER LADDER sell=50% price=1.6\n\
+             2025-10-09T08:53:25.000Z AUTO_TP2 LADDER LADDER sell=100% price=2.1\n\
+             2025-10-09T09:53:25.000Z AUTO_EXIT_TIME_LIMIT HOLD HOLD sell=100% price=1.05\n"
+        );
+
+        let second = replay(&ticks, positions).await.unwrap();
+        assert_eq!(first.to_lines(), second.to_lines());
+    }
+}
@@ -1,23 +1,95 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
 use tokio::sync::mpsc;
 use tokio::time::{sleep, Duration};
 use crate::engine::commands::{ExecutionCommand, ExecutionFeedback, CommandType};
+use intelligence_rs::strategy_engine::SellReason;
 use crate::executor_v2::TradeExecutor;
-use crate::state_manager::StateManager;
+use crate::config::SlicedExitSettings;
+use crate::jupiter::SwapResult;
+use crate::sliced_exit::{can_retry_sell, PartialSellError};
+use crate::cost_basis::SellCost;
+use crate::state_manager::{OrderRecord, OrderStatus, StateManager};
 use crate::telegram::TelegramNotifier;
-use solana_sdk::signature::Keypair;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+/// Tope del snapshot de balance que el journal toma antes de cada venta
+const BALANCE_SNAPSHOT_TIMEOUT: Duration = Duration::from_millis(400);
+
+/// Lo que el router usa del executor (un stub lo sustituye en tests)
+#[async_trait]
+pub trait SellExecutor: Send + Sync {
+    /// Venta de un solo swap con reintentos escalando slippage y tip
+    async fn sell(
+        &self,
+        mint: &str,
+        wallet: Option<&Keypair>,
+        pct: u8,
+        is_emergency: bool,
+    ) -> Result<SwapResult>;
+
+    /// Venta troceada (TWAP); `PartialSellError` si falla tras vender algún trozo
+    async fn sliced_sell(
+        &self,
+        mint: &str,
+        wallet: Option<&Keypair>,
+        pct: u8,
+        is_emergency: bool,
+        settings: &SlicedExitSettings,
+        escalate: bool,
+    ) -> Result<SwapResult>;
+
+    /// Balance raw del token (None si el RPC no responde). Bloqueante.
+    fn token_balance(&self, wallet: &Pubkey, mint: &str) -> Option<u64>;
+}
+
+#[async_trait]
+impl SellExecutor for TradeExecutor {
+    async fn sell(
+        &self,
+        mint: &str,
+        wallet: Option<&Keypair>,
+        pct: u8,
+        is_emergency: bool,
+    ) -> Result<SwapResult> {
+        self.execute_sell_with_retry(mint.to_string(), wallet, pct, is_emergency).await
+    }
+
+    async fn sliced_sell(
+        &self,
+        mint: &str,
+        wallet: Option<&Keypair>,
+        pct: u8,
+        is_emergency: bool,
+        settings: &SlicedExitSettings,
+        escalate: bool,
+    ) -> Result<SwapResult> {
+        self.execute_sliced_sell(mint, wallet, pct, is_emergency, settings, escalate).await
+    }
+
+    fn token_balance(&self, wallet: &Pubkey, mint: &str) -> Option<u64> {
+        TradeExecutor::token_balance(self, wallet, mint)
+    }
+}
 
 pub struct ExecutionRouter {
-    executor: Arc<TradeExecutor>,
+    executor: Arc<dyn SellExecutor>,
     state_manager: Arc<StateManager>,
     telegram: Arc<TelegramNotifier>,
     wallet_kp: Option<Arc<Keypair>>,
     feedback_tx: mpsc::Sender<ExecutionFeedback>,
+    /// Ventas troceadas (TWAP); None = cada salida en un solo swap
+    sliced_exit: Option<SlicedExitSettings>,
+    /// Una venta a la vez por mint: una manual no puede cruzarse con un SL/TP
+    mint_locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
 }
 
 impl ExecutionRouter {
     pub fn new(
-        executor: Arc<TradeExecutor>,
+        executor: Arc<dyn SellExecutor>,
         state_manager: Arc<StateManager>,
         telegram: Arc<TelegramNotifier>,
         wallet_kp: Option<Keypair>,
@@ -29,9 +101,17 @@ impl ExecutionRouter {
             telegram,
             wallet_kp: wallet_kp.map(Arc::new),
             feedback_tx,
+            sliced_exit: None,
+            mint_locks: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Trocea las salidas en pools finos si `settings.enabled`
+    pub fn with_sliced_exit(mut self, settings: SlicedExitSettings) -> Self {
+        self.sliced_exit = settings.enabled.then_some(settings);
+        self
+    }
+
     pub async fn run_dashboard(self: Arc<Self>, mut cmd_rx: mpsc::Receiver<ExecutionCommand>) {
         println!("⚙️ Execution Router online. Listos para actuación...");
 
@@ -45,6 +125,16 @@ impl ExecutionRouter {
     }
 
     async fn process_command(&self, command: ExecutionCommand) {
+        // El fallo ya se alertó, journaleó y reportó al engine por feedback
+        let _ = self.execute(command).await;
+    }
+
+    /// Ejecuta un comando esperando a que termine cualquier otra venta del mismo
+    /// mint. Devuelve la firma del fill.
+    pub async fn execute(&self, command: ExecutionCommand) -> Result<String> {
+        let lock = self.mint_lock(command.mint());
+        let _guard = lock.lock().await;
+
         match command {
             ExecutionCommand::StopLoss {
                 mint,
@@ -53,30 +143,58 @@ impl ExecutionRouter {
                 is_emergency,
             } => {
                 println!("🚨 [RUTEO] Iniciando Emergency Sell para {}", symbol);
-                self.execute_with_backoff(&mint, &symbol, amount_invested, 100, is_emergency, "AUTO_SL", CommandType::StopLoss).await;
+                self.execute_with_backoff(&mint, &symbol, amount_invested, 100, is_emergency, "AUTO_SL", CommandType::StopLoss).await
             }
-            ExecutionCommand::TakeProfit1 {
+            ExecutionCommand::TakeProfit {
                 mint,
                 symbol,
+                rung,
                 sell_amount_pct,
                 amount_invested,
-                .. 
             } => {
-                println!("💰 [RUTEO] Procesando TAKE PROFIT 1 para {}", symbol);
-                self.execute_with_backoff(&mint, &symbol, amount_invested, sell_amount_pct, false, "AUTO_TP1", CommandType::TakeProfit1).await;
+                let cmd_type = CommandType::TakeProfit(rung);
+                let trade_type = cmd_type.trade_type();
+                println!("💰 [RUTEO] Procesando TAKE PROFIT {} para {} ({}% del balance)", rung + 1, symbol, sell_amount_pct);
+                self.execute_with_backoff(&mint, &symbol, amount_invested, sell_amount_pct, false, &trade_type, cmd_type).await
+            }
+            ExecutionCommand::Exit {
+                mint,
+                symbol,
+                amount_invested,
+                reason,
+            } => {
+                // Un pool drenándose no espera: mismos reintentos que el SL de emergencia
+                let is_emergency = reason == SellReason::LiquidityFloor;
+                let cmd_type = CommandType::Exit(reason);
+                let trade_type = cmd_type.trade_type();
+                println!("🚪 [RUTEO] Salida por regla {} para {}", reason.as_str(), symbol);
+                self.execute_with_backoff(&mint, &symbol, amount_invested, 100, is_emergency, &trade_type, cmd_type).await
             }
-            ExecutionCommand::TakeProfit2 {
+            ExecutionCommand::ManualSell {
                 mint,
                 symbol,
                 sell_amount_pct,
                 amount_invested,
             } => {
-                println!("💰💰 [RUTEO] Procesando TAKE PROFIT 2 para {}", symbol);
-                self.execute_with_backoff(&mint, &symbol, amount_invested, sell_amount_pct, false, "AUTO_TP2", CommandType::TakeProfit2).await;
+                // Un SL/TP pudo cerrar la posición mientras esperábamos el bloqueo
+                let active = matches!(self.state_manager.get_position(&mint).await, Ok(Some(p)) if p.active);
+                if !active {
+                    bail!("No hay posición activa para {}", mint);
+                }
+                let cmd_type = CommandType::Manual;
+                let trade_type = cmd_type.trade_type();
+                println!("🖐️ [RUTEO] Venta manual de {} ({}% del balance)", symbol, sell_amount_pct);
+                self.execute_with_backoff(&mint, &symbol, amount_invested, sell_amount_pct, false, &trade_type, cmd_type).await
             }
         }
     }
 
+    fn mint_lock(&self, mint: &str) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self.mint_locks.lock().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(locks.entry(mint.to_string()).or_default())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn execute_with_backoff(
         &self,
         mint: &str,
@@ -86,20 +204,36 @@ impl ExecutionRouter {
         is_emergency: bool,
         trade_type: &str,
         cmd_type: CommandType,
-    ) {
+    ) -> Result<String> {
+        let sliced = match cmd_type {
+            // Un pool drenándose no da tiempo a trocear
+            CommandType::Exit(SellReason::LiquidityFloor) => None,
+            _ => self.sliced_exit.as_ref(),
+        };
+        // Troceada o no, mientras no se haya vendido nada reintentar es seguro
         let max_attempts = if is_emergency { 5 } else { 3 };
         let mut delay_ms = 500;
         let kp_ref = self.wallet_kp.as_deref();
 
+        // Journal ANTES de tocar la red: si caemos a mitad de venta, el boot lo reconcilia
+        let order_id = self.journal_pending(mint, symbol, invested, pct, &cmd_type).await;
+
         let mut final_result = None;
 
         for attempt in 1..=max_attempts {
-            let result = self.executor.execute_sell_with_retry(
-                mint.to_string(),
-                kp_ref,
-                pct,
-                is_emergency,
-            ).await;
+            let result = match sliced {
+                Some(settings) => {
+                    self.executor.sliced_sell(
+                        mint,
+                        kp_ref,
+                        pct,
+                        is_emergency,
+                        settings,
+                        cmd_type == CommandType::StopLoss,
+                    ).await
+                }
+                None => self.executor.sell(mint, kp_ref, pct, is_emergency).await,
+            };
 
             match result {
                 Ok(res) => {
@@ -109,9 +243,17 @@ impl ExecutionRouter {
                 Err(e) => {
                     eprintln!("⚠️ [RUTEO] Intento {}/{} fallido para {} ({}): {}", attempt, max_attempts, symbol, trade_type, e);
 
-                    if attempt == max_attempts {
+                    // Tras vender algún trozo, repetir la venta entera vendería de más
+                    if attempt == max_attempts || !can_retry_sell(&e) {
+                        // Lo ya vendido se aplica antes del FAILED: la posición queda con el resto
+                        if let Some(partial) = e.downcast_ref::<PartialSellError>() {
+                            self.apply_partial_fill(symbol, mint, invested, partial, trade_type).await;
+                        }
+
                         let error_msg = format!("❌ <b>Fallo definitivo ({}) en {}</b>: {}\nPosición sigue abierta. ¡Revisa manualmente!", trade_type, symbol, e);
                         let _ = self.telegram.send_error_alert(&error_msg).await;
+
+                        self.journal_update(order_id, OrderStatus::Failed, None, Some(e.to_string())).await;
                         
                         let _ = self.feedback_tx.send(ExecutionFeedback::Failure {
                             mint: mint.to_string(),
@@ -119,7 +261,7 @@ impl ExecutionRouter {
                             reason: e.to_string(),
                         }).await;
                         
-                        return;
+                        return Err(e);
                     }
 
                     sleep(Duration::from_millis(delay_ms)).await;
@@ -128,18 +270,93 @@ impl ExecutionRouter {
             }
         }
 
-        if let Some(res) = final_result {
-            self.post_execution_cleanup(symbol, mint, invested, pct, res, trade_type, cmd_type).await;
+        let Some(res) = final_result else {
+            bail!("{} sin resultado para {}", trade_type, symbol);
+        };
+        let signature = res.signature.clone();
+        self.journal_update(order_id, OrderStatus::Submitted, Some(signature.clone()), None).await;
+        self.post_execution_cleanup(symbol, mint, invested, pct, res, trade_type, cmd_type).await;
+        // CONFIRMED = fill ya aplicado a la posición
+        self.journal_update(order_id, OrderStatus::Confirmed, None, None).await;
+        Ok(signature)
+    }
+
+    /// Registra la orden como PENDING. Un fallo del journal NUNCA bloquea una salida.
+    async fn journal_pending(
+        &self,
+        mint: &str,
+        symbol: &str,
+        invested: f64,
+        pct: u8,
+        cmd_type: &CommandType,
+    ) -> Option<i64> {
+        let tokens_before = self.snapshot_balance(mint).await;
+        let now = chrono::Utc::now().timestamp();
+
+        let order = OrderRecord {
+            id: None,
+            token_mint: mint.to_string(),
+            symbol: symbol.to_string(),
+            command_type: cmd_type.trade_type(),
+            sell_pct: pct,
+            amount_invested: invested,
+            tokens_before,
+            status: OrderStatus::Pending,
+            signature: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        match self.state_manager.create_order(order).await {
+            Ok(id) => Some(id),
+            Err(e) => {
+                eprintln!("❌ DB ERROR registrando orden {} para {}: {}", cmd_type.trade_type(), symbol, e);
+                None
+            }
+        }
+    }
+
+    /// Balance raw previo a la venta, leído fuera del runtime y con tope de tiempo:
+    /// un RPC lento deja la orden sin snapshot antes que retrasar la salida.
+    async fn snapshot_balance(&self, mint: &str) -> Option<u64> {
+        let wallet = self.wallet_kp.as_ref()?.pubkey();
+        let executor = Arc::clone(&self.executor);
+        let token_mint = mint.to_string();
+        let snapshot = tokio::task::spawn_blocking(move || executor.token_balance(&wallet, &token_mint));
+
+        match tokio::time::timeout(BALANCE_SNAPSHOT_TIMEOUT, snapshot).await {
+            Ok(Ok(balance)) => balance,
+            _ => {
+                eprintln!("⚠️ [JOURNAL] Sin snapshot de balance para {} (RPC lento)", mint);
+                None
+            }
         }
     }
 
+    async fn journal_update(
+        &self,
+        order_id: Option<i64>,
+        status: OrderStatus,
+        signature: Option<String>,
+        error: Option<String>,
+    ) {
+        let Some(id) = order_id else {
+            return;
+        };
+        if let Err(e) = self.state_manager.update_order_status(id, status, signature, error).await {
+            eprintln!("❌ DB ERROR actualizando orden #{} a {}: {}", id, status.as_str(), e);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn post_execution_cleanup(
         &self,
         symbol: &str,
         mint: &str,
         invested: f64,
         pct: u8,
-        res: crate::jupiter::SwapResult,
+        res: SwapResult,
         trade_type: &str,
         cmd_type: CommandType,
     ) {
@@ -148,9 +365,72 @@ impl ExecutionRouter {
             true
         ).await;
 
+        let cost = apply_fill_to_position(
+            &self.state_manager,
+            mint,
+            &cmd_type,
+            &res.signature,
+            pct,
+            Some(res.output_amount),
+        )
+        .await;
+        self.record_sell_trade(symbol, mint, invested * (pct as f64 / 100.0), cost, &res, trade_type).await;
+
+        let _ = self.feedback_tx.send(ExecutionFeedback::Success {
+            mint: mint.to_string(),
+            command_type: cmd_type,
+        }).await;
+    }
+
+    /// Venta troceada interrumpida: lo vendido (tokens del fill / balance previo)
+    /// se aplica a los lotes sin cerrar la posición ni marcar el escalón de TP
+    async fn apply_partial_fill(
+        &self,
+        symbol: &str,
+        mint: &str,
+        invested: f64,
+        partial: &PartialSellError,
+        trade_type: &str,
+    ) {
+        let res = &partial.filled;
+        let fraction = if partial.balance_before > 0 {
+            (res.input_amount / partial.balance_before as f64).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let _ = self.telegram.send_message(
+            &format!("⚠️ <b>{} PARCIAL para {}</b>: vendido {:.1}% del balance\nTx: {}", trade_type, symbol, fraction * 100.0, res.signature),
+            true
+        ).await;
+
+        let cost = match self
+            .state_manager
+            .record_sell_fill(mint, &res.signature, fraction, Some(res.output_amount))
+            .await
+        {
+            Ok(cost) => cost,
+            Err(e) => {
+                eprintln!("❌ DB ERROR aplicando fill parcial {} a {}: {}", res.signature, mint, e);
+                None
+            }
+        };
+        self.record_sell_trade(symbol, mint, invested * fraction, cost, res, trade_type).await;
+    }
+
+    /// Registra el trade de una venta con su PnL. Coste liberado según los lotes
+    /// (WAVG); sin cost basis, `fallback_cost` (prorrateo de lo invertido).
+    async fn record_sell_trade(
+        &self,
+        symbol: &str,
+        mint: &str,
+        fallback_cost: f64,
+        cost: Option<SellCost>,
+        res: &SwapResult,
+        trade_type: &str,
+    ) {
         let sol_received = res.output_amount;
-        
-        let invested_portion = invested * (pct as f64 / 100.0);
+        let invested_portion = cost.map(|c| c.cost_wavg_sol).unwrap_or(fallback_cost);
         let pnl_sol = sol_received - invested_portion;
         let pnl_pct = if invested_portion > 0.0 {
             ((sol_received / invested_portion) - 1.0) * 100.0
@@ -184,24 +464,42 @@ impl ExecutionRouter {
         if let Err(e) = self.state_manager.record_trade(trade).await {
             eprintln!("❌ DB ERROR registrando {} para {}: {}", trade_type, symbol, e);
         }
+    }
+}
 
-        if trade_type == "AUTO_SL" || pct == 100 {
-            let _ = self.state_manager.close_position(mint).await;
-        } else if trade_type == "AUTO_TP1" {
-            let _ = self.state_manager.mark_tp_triggered(mint).await;
-            let remaining = invested * (1.0 - (pct as f64 / 100.0));
-            let _ = self.state_manager.update_amount_invested(mint, remaining).await;
-        } else if trade_type == "AUTO_TP2" {
-            let _ = self.state_manager.mark_tp2_triggered(mint).await;
-            let remaining = invested * (1.0 - (pct as f64 / 100.0));
-            let _ = self.state_manager.update_amount_invested(mint, remaining).await;
+/// Refleja una venta ejecutada en la posición: consume sus lotes y marca el escalón de TP.
+/// Idempotente por firma: el boot puede re-aplicarla al reconciliar una orden que
+/// quedó a medias. Devuelve el coste liberado (None si ya estaba aplicada).
+pub async fn apply_fill_to_position(
+    state_manager: &StateManager,
+    mint: &str,
+    cmd_type: &CommandType,
+    signature: &str,
+    pct: u8,
+    proceeds_sol: Option<f64>,
+) -> Option<SellCost> {
+    let cost = match state_manager
+        .record_sell_fill(mint, signature, pct as f64 / 100.0, proceeds_sol)
+        .await
+    {
+        Ok(cost) => cost,
+        Err(e) => {
+            eprintln!("❌ DB ERROR aplicando fill {} a {}: {}", signature, mint, e);
+            None
         }
+    };
 
-        let _ = self.feedback_tx.send(ExecutionFeedback::Success {
-            mint: mint.to_string(),
-            command_type: cmd_type,
-        }).await;
+    match cmd_type {
+        CommandType::TakeProfit(rung) if pct < 100 => {
+            let _ = state_manager.mark_rung_triggered(mint, *rung).await;
+        }
+        CommandType::Manual if pct < 100 => {}
+        _ => {
+            let _ = state_manager.close_position(mint).await;
+        }
     }
+
+    cost
 }
 
 #[cfg(test)]
@@ -212,8 +510,145 @@ mod tests {
     use solana_sdk::signature::Keypair;
     use crate::engine::commands::{CommandType, ExecutionCommand, ExecutionFeedback};
     use crate::executor_v2::{TradeExecutor, ExecutorConfig};
-    use crate::state_manager::StateManager;
+    use crate::state_manager::{ExitRules, PositionState, StateManager};
     use crate::telegram::TelegramNotifier;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const BALANCE_RAW: u64 = 1_000_000;
+
+    /// Executor de venta troceada que ejecuta el trozo 1 (40% del balance) y
+    /// falla en el trozo 2, como `execute_sliced_sell` tras agotar el dump final
+    struct FailsOnSecondSlice {
+        sliced_calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl SellExecutor for FailsOnSecondSlice {
+        async fn sell(
+            &self,
+            _mint: &str,
+            _wallet: Option<&Keypair>,
+            _pct: u8,
+            _is_emergency: bool,
+        ) -> Result<SwapResult> {
+            bail!("sin venta de un solo swap en este test")
+        }
+
+        async fn sliced_sell(
+            &self,
+            _mint: &str,
+            _wallet: Option<&Keypair>,
+            _pct: u8,
+            _is_emergency: bool,
+            _settings: &SlicedExitSettings,
+            _escalate: bool,
+        ) -> Result<SwapResult> {
+            self.sliced_calls.fetch_add(1, Ordering::SeqCst);
+            Err(PartialSellError {
+                filled: SwapResult {
+                    signature: "SLICE_1".to_string(),
+                    input_amount: 400_000.0,
+                    output_amount: 0.5,
+                    route: "Raydium + Jito".to_string(),
+                    price_impact_pct: 2.5,
+                    fee_sol: 0.0001,
+                },
+                balance_before: BALANCE_RAW,
+                cause: anyhow::anyhow!("trozo 2: blockhash expirado"),
+            }
+            .into())
+        }
+
+        fn token_balance(&self, _wallet: &Pubkey, _mint: &str) -> Option<u64> {
+            Some(BALANCE_RAW)
+        }
+    }
+
+    fn open_position(mint: &str) -> PositionState {
+        let now = chrono::Utc::now().timestamp();
+        PositionState {
+            id: None,
+            token_mint: mint.to_string(),
+            symbol: "PART".to_string(),
+            entry_price: 0.001,
+            current_price: 0.001,
+            amount_sol: 1.0,
+            stop_loss_percent: -30.0,
+            trailing_enabled: false,
+            trailing_distance_percent: 0.0,
+            trailing_activation_threshold: 0.0,
+            trailing_highest_price: None,
+            trailing_current_sl: None,
+            tp_ladder: vec![],
+            active: true,
+            created_at: now,
+            updated_at: now,
+            token_amount: 0.0,
+            realized_pnl_sol: 0.0,
+            exit_rules: ExitRules::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_partial_sliced_sell_applies_filled_slices() {
+        let mint = "PartialMint1111111111111111111111111111111";
+        let state_manager = Arc::new(
+            StateManager::new("file:test_partial_sliced_sell?mode=memory&cache=shared")
+                .await
+                .unwrap(),
+        );
+        state_manager
+            .record_buy_fill(open_position(mint), "BUY_1", 1_000.0, 1.0)
+            .await
+            .unwrap();
+
+        let executor = Arc::new(FailsOnSecondSlice {
+            sliced_calls: AtomicUsize::new(0),
+        });
+        let (feedback_tx, mut feedback_rx) = mpsc::channel::<ExecutionFeedback>(10);
+        let router = ExecutionRouter::new(
+            executor.clone(),
+            Arc::clone(&state_manager),
+            Arc::new(TelegramNotifier::new()),
+            Some(Keypair::new()),
+            feedback_tx,
+        )
+        .with_sliced_exit(SlicedExitSettings {
+            enabled: true,
+            ..SlicedExitSettings::default()
+        });
+
+        let err = router
+            .execute(ExecutionCommand::StopLoss {
+                mint: mint.to_string(),
+                symbol: "PART".to_string(),
+                amount_invested: 1.0,
+                is_emergency: true,
+            })
+            .await
+            .unwrap_err();
+        assert!(!can_retry_sell(&err));
+        // Tras vender un trozo no se repite la venta entera
+        assert_eq!(executor.sliced_calls.load(Ordering::SeqCst), 1);
+
+        // El 40% vendido sale de la posición; el resto sigue abierto
+        let position = state_manager.get_position(mint).await.unwrap().unwrap();
+        assert!(position.active);
+        assert!((position.token_amount - 600.0).abs() < 1e-6);
+        assert!((position.amount_sol - 0.6).abs() < 1e-9);
+
+        let trades = state_manager.get_trade_history(10).await.unwrap();
+        let fill = trades.iter().find(|t| t.signature == "SLICE_1").unwrap();
+        assert_eq!(fill.trade_type, "AUTO_SL");
+        assert!((fill.pnl_sol.unwrap() - 0.1).abs() < 1e-9);
+
+        match feedback_rx.recv().await {
+            Some(ExecutionFeedback::Failure { command_type, .. }) => {
+                assert_eq!(command_type, CommandType::StopLoss)
+            }
+            other => panic!("Se esperaba Failure, llegó {:?}", other),
+        }
+    }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_actuator_feedback_loop_on_failure() {
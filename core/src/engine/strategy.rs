@@ -2,58 +2,90 @@ use std::sync::Arc;
 use tokio::sync::mpsc;
 use std::collections::{HashMap, HashSet};
 use crate::price_feed::PriceUpdate;
+use crate::engine::clock::{Clock, SystemClock};
 use crate::engine::commands::{ExecutionCommand, ExecutionFeedback, CommandType};
-use crate::state_manager::StateManager;
+use crate::engine::exit_rules::{self, ExitSnapshot, StaleTracker};
+use crate::engine::position_book::PositionBook;
+use crate::engine::tp_ladder;
+use crate::state_manager::{PositionEvent, StateManager};
 use crate::trailing_sl::TrailingStopLoss;
 
 pub struct StrategyEngine {
     state_manager: Arc<StateManager>,
+    /// Posiciones activas en memoria (write-through, ver `PositionBook`)
+    book: PositionBook,
     sell_attempted: HashSet<String>,
-    tp1_attempted: HashSet<String>,
-    tp2_attempted: HashSet<String>,
+    /// Bloqueo de reintento por escalón de TP: (mint, escalón)
+    tp_attempted: HashSet<(String, u8)>,
     trailing_monitors: HashMap<String, TrailingStopLoss>,
+    /// Momentum por mint para la regla de estancamiento
+    stale_trackers: HashMap<String, StaleTracker>,
+    clock: Arc<dyn Clock>,
+    /// Aviso tras procesar cada tick (el replay avanza en lockstep con él)
+    tick_ack: Option<mpsc::UnboundedSender<()>>,
 }
 
 impl StrategyEngine {
-    pub fn new(state_manager: Arc<StateManager>) -> Self {
+    pub fn new(state_manager: Arc<StateManager>, book: PositionBook) -> Self {
         Self {
             state_manager,
+            book,
             sell_attempted: HashSet::new(),
-            tp1_attempted: HashSet::new(),
-            tp2_attempted: HashSet::new(),
+            tp_attempted: HashSet::new(),
             trailing_monitors: HashMap::new(),
+            stale_trackers: HashMap::new(),
+            clock: Arc::new(SystemClock),
+            tick_ack: None,
         }
     }
 
+    /// Sustituye el reloj del sistema (replay con `SimClock`)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Avisa por `ack_tx` cada vez que termina de procesar un tick
+    pub fn with_tick_ack(mut self, ack_tx: mpsc::UnboundedSender<()>) -> Self {
+        self.tick_ack = Some(ack_tx);
+        self
+    }
+
     pub async fn run_loop(
         mut self,
         mut price_rx: mpsc::Receiver<PriceUpdate>,
         cmd_tx: mpsc::Sender<ExecutionCommand>,
         mut feedback_rx: mpsc::Receiver<ExecutionFeedback>,
+        mut position_rx: mpsc::UnboundedReceiver<PositionEvent>,
     ) {
-        println!("🧠 Strategy Engine en línea. ECU operativa.");
+        println!("🧠 Strategy Engine en línea. ECU operativa ({} posiciones en libro).", self.book.len());
 
         // Circuit Breaker System Variables
         let mut failed_execution_count = 0;
-        let mut last_failure_time = tokio::time::Instant::now();
+        let mut last_failure_time = self.clock.now();
         let circuit_breaker_threshold = 3;
         let circuit_breaker_window = std::time::Duration::from_secs(60);
         let mut is_circuit_breaker_tripped = false;
 
         loop {
             tokio::select! {
-                // CANAL 1: Telemetría de Mercado (Alta frecuencia)
-                Some(tick) = price_rx.recv() => {
-                    if !is_circuit_breaker_tripped {
-                        self.process_price_tick(tick, &cmd_tx).await;
+                // Orden fijo: los cambios de posición y el feedback ya emitidos se
+                // aplican antes del siguiente tick (y el replay es determinista)
+                biased;
+
+                // CANAL 3: Cambios de posiciones ya persistidos (Telegram, Router, gRPC)
+                Some(event) = position_rx.recv() => {
+                    if let PositionEvent::Closed(mint) = &event {
+                        self.stale_trackers.remove(mint);
                     }
+                    self.book.apply(event);
                 }
 
                 // CANAL 2: Diagnóstico Interno (Resolución de fallos)
                 Some(feedback) = feedback_rx.recv() => {
                     // Update Circuit Breaker
                     if let ExecutionFeedback::Failure { .. } = feedback {
-                        let now = tokio::time::Instant::now();
+                        let now = self.clock.now();
                         if now.duration_since(last_failure_time) > circuit_breaker_window {
                             failed_execution_count = 1;
                         } else {
@@ -78,6 +110,16 @@ impl StrategyEngine {
                     self.process_feedback(feedback).await;
                 }
 
+                // CANAL 1: Telemetría de Mercado (Alta frecuencia)
+                Some(tick) = price_rx.recv() => {
+                    if !is_circuit_breaker_tripped {
+                        self.process_price_tick(tick, &cmd_tx).await;
+                    }
+                    if let Some(ack_tx) = &self.tick_ack {
+                        let _ = ack_tx.send(());
+                    }
+                }
+
                 else => {
                     println!("🛑 Señal de apagado recibida en ECU. Terminando loop.");
                     break;
@@ -86,9 +128,9 @@ impl StrategyEngine {
         }
     }
 
-    async fn process_price_tick(&mut self, tick: PriceUpdate, cmd_tx: &mpsc::Sender<ExecutionCommand>) {
-        let target = match self.state_manager.get_position(&tick.token_mint).await {
-            Ok(Some(p)) if p.active => p,
+    pub async fn process_price_tick(&mut self, tick: PriceUpdate, cmd_tx: &mpsc::Sender<ExecutionCommand>) {
+        let target = match self.book.get(&tick.token_mint) {
+            Some(p) if p.active => p.clone(),
             _ => return,
         };
 
@@ -126,6 +168,7 @@ impl StrategyEngine {
              let trailing_current_sl = tsl.current_sl_percent;
              let trailing_highest_price = tsl.peak_price;
              let mint_clone = target.token_mint.clone();
+             self.book.update_trailing_sl(&mint_clone, trailing_highest_price, trailing_current_sl);
              
              tokio::spawn(async move {
                  // Ignore errors softly for telemetry updates
@@ -133,27 +176,17 @@ impl StrategyEngine {
              });
         }
 
-        // --- TAKE PROFIT 1 ---
-        let tp_target = target.tp_percent.unwrap_or(100.0);
-        if !target.tp_triggered && current_gain_percent >= tp_target && !self.tp1_attempted.contains(&target.token_mint) {
-            self.tp1_attempted.insert(target.token_mint.clone());
-            let _ = cmd_tx.send(ExecutionCommand::TakeProfit1 {
-                mint: target.token_mint.clone(),
-                symbol: target.symbol.clone(),
-                sell_amount_pct: target.tp_amount_percent.unwrap_or(50.0) as u8,
-                entry_price: target.entry_price,
-                amount_invested: target.amount_sol,
-            }).await;
-        }
-
-        // --- TAKE PROFIT 2 ---
-        if let Some(tp2_target) = target.tp2_percent {
-            if !target.tp2_triggered && current_gain_percent >= tp2_target && !self.tp2_attempted.contains(&target.token_mint) {
-                self.tp2_attempted.insert(target.token_mint.clone());
-                let _ = cmd_tx.send(ExecutionCommand::TakeProfit2 {
+        // --- TAKE PROFIT LADDER ---
+        // Solo el siguiente escalón pendiente: el resto espera a que éste quede marcado
+        if let Some((rung, step)) = tp_ladder::next_rung(&target.tp_ladder) {
+            let lock = (target.token_mint.clone(), rung);
+            if current_gain_percent >= step.target_percent && !self.tp_attempted.contains(&lock) {
+                self.tp_attempted.insert(lock);
+                let _ = cmd_tx.send(ExecutionCommand::TakeProfit {
                     mint: target.token_mint.clone(),
                     symbol: target.symbol.clone(),
-                    sell_amount_pct: target.tp2_amount_percent.unwrap_or(100.0) as u8,
+                    rung,
+                    sell_amount_pct: tp_ladder::sell_pct_of_balance(&target.tp_ladder, rung as usize),
                     amount_invested: target.amount_sol,
                 }).await;
             }
@@ -171,6 +204,44 @@ impl StrategyEngine {
                 is_emergency: true,
             }).await;
         }
+
+        // --- EXIT RULES (tiempo, break-even, momentum, liquidez) ---
+        let rules = &target.exit_rules;
+        if exit_rules::should_arm_break_even(rules, current_gain_percent) {
+            println!("🛡️ [ECU] {} +{:.1}%: stop movido a break-even", target.symbol, current_gain_percent);
+            self.book.arm_break_even(&target.token_mint);
+            let state_mgr_clone = Arc::clone(&self.state_manager);
+            let mint_clone = target.token_mint.clone();
+            tokio::spawn(async move {
+                let _ = state_mgr_clone.arm_break_even(&mint_clone).await;
+            });
+        }
+
+        let stale_for = rules.stale_slope_threshold.and_then(|threshold| {
+            self.stale_trackers
+                .entry(target.token_mint.clone())
+                .or_default()
+                .update(current_gain_percent, threshold, self.clock.now())
+        });
+        let snapshot = ExitSnapshot {
+            gain_percent: current_gain_percent,
+            held_secs: self.clock.unix_now() - target.created_at,
+            liquidity_usd: tick.liquidity_usd,
+            stale_for,
+        };
+
+        if let Some(reason) = exit_rules::evaluate(rules, &snapshot) {
+            if !self.sell_attempted.contains(&target.token_mint) {
+                self.sell_attempted.insert(target.token_mint.clone());
+                println!("🚪 [ECU] Regla de salida {} para {}", reason.as_str(), target.symbol);
+                let _ = cmd_tx.send(ExecutionCommand::Exit {
+                    mint: target.token_mint.clone(),
+                    symbol: target.symbol.clone(),
+                    amount_invested: target.amount_sol,
+                    reason,
+                }).await;
+            }
+        }
     }
 
     async fn process_feedback(&mut self, feedback: ExecutionFeedback) {
@@ -179,15 +250,14 @@ impl StrategyEngine {
                 println!("⚠️ [ECU] Recibido fallo del actuador para {}: {}. Liberando bloqueos.", mint, reason);
                 
                 match command_type {
-                    CommandType::StopLoss => {
+                    CommandType::StopLoss | CommandType::Exit(_) => {
                         self.sell_attempted.remove(&mint);
                     }
-                    CommandType::TakeProfit1 => {
-                        self.tp1_attempted.remove(&mint);
-                    }
-                    CommandType::TakeProfit2 => {
-                        self.tp2_attempted.remove(&mint);
+                    CommandType::TakeProfit(rung) => {
+                        self.tp_attempted.remove(&(mint, rung));
                     }
+                    // Las ventas manuales no toman bloqueos del engine
+                    CommandType::Manual => {}
                 }
             }
             ExecutionFeedback::Success { mint: _mint, command_type: _command_type } => {
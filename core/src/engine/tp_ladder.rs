@@ -0,0 +1,252 @@
+//! # Take Profit Ladder (Escalera de salidas)
+//!
+//! Sustituye a los TP1/TP2 fijos: una posición lleva N escalones ordenados,
+//! p.ej. vender 25% a +50%, 25% a +100%, 25% a +300% y dejar el resto al
+//! trailing SL.
+//!
+//! `sell_percent` se expresa sobre la posición ORIGINAL, pero el executor vende
+//! un % del balance actual: `sell_pct_of_balance` hace la conversión a partir
+//! de lo que ya vendieron los escalones ejecutados.
+//!
+//! Los escalones se disparan estrictamente en orden. Si el precio salta varios
+//! de golpe, el siguiente espera a que el anterior quede marcado como ejecutado
+//! para no calcular dos ventas sobre el mismo balance.
+
+use anyhow::{bail, Context, Result};
+
+use crate::state_manager::TpRung;
+
+/// Siguiente escalón pendiente: (índice, escalón)
+pub fn next_rung(ladder: &[TpRung]) -> Option<(u8, &TpRung)> {
+    ladder
+        .iter()
+        .enumerate()
+        .find(|(_, rung)| !rung.triggered)
+        .map(|(i, rung)| (i as u8, rung))
+}
+
+/// % de la posición original que sigue en cartera tras los escalones ejecutados
+pub fn remaining_percent(ladder: &[TpRung]) -> f64 {
+    let sold: f64 = ladder
+        .iter()
+        .filter(|rung| rung.triggered)
+        .map(|rung| rung.sell_percent)
+        .sum();
+    (100.0 - sold).max(0.0)
+}
+
+/// % del balance actual que debe vender el escalón `rung`
+pub fn sell_pct_of_balance(ladder: &[TpRung], rung: usize) -> u8 {
+    let Some(step) = ladder.get(rung) else {
+        return 0;
+    };
+    let remaining = remaining_percent(ladder);
+    if remaining <= 0.0 {
+        return 100;
+    }
+    (step.sell_percent / remaining * 100.0)
+        .round()
+        .clamp(1.0, 100.0) as u8
+}
+
+/// Parsea `50:25,100:25,300:25` (objetivo%:venta%) en una escalera ordenada
+/// por objetivo. La suma de ventas no puede pasar del 100%.
+pub fn parse_ladder(spec: &str) -> Result<Vec<TpRung>> {
+    let mut ladder = Vec::new();
+
+    for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let (target, sell) = item
+            .split_once(':')
+            .with_context(|| format!("Escalón '{}' inválido: se espera objetivo:venta", item))?;
+        let target: f64 = target
+            .trim()
+            .trim_start_matches('+')
+            .parse()
+            .with_context(|| format!("Objetivo inválido en '{}'", item))?;
+        let sell: f64 = sell
+            .trim()
+            .parse()
+            .with_context(|| format!("Venta inválida en '{}'", item))?;
+
+        if target <= 0.0 {
+            bail!("El objetivo de '{}' debe ser positivo", item);
+        }
+        if sell <= 0.0 || sell > 100.0 {
+            bail!("La venta de '{}' debe estar entre 0 y 100", item);
+        }
+        ladder.push(TpRung::new(target, sell));
+    }
+
+    if ladder.is_empty() {
+        bail!("Escalera vacía");
+    }
+
+    ladder.sort_by(|a, b| a.target_percent.total_cmp(&b.target_percent));
+    if ladder
+        .windows(2)
+        .any(|pair| pair[0].target_percent == pair[1].target_percent)
+    {
+        bail!("Objetivos repetidos en la escalera");
+    }
+
+    let total: f64 = ladder.iter().map(|rung| rung.sell_percent).sum();
+    if total > 100.0 + 1e-9 {
+        bail!("Los escalones venden {:.0}% (> 100%)", total);
+    }
+
+    Ok(ladder)
+}
+
+/// Nueva escalera para una posición viva: los escalones ya ejecutados se
+/// conservan (ya vendieron) y los nuevos quedan pendientes detrás.
+///
+/// Los nuevos deben apuntar por encima del último escalón ejecutado (si no, se
+/// dispararían fuera de orden) y, sumados a lo ya vendido, no pasar del 100%.
+pub fn replace_pending(current: &[TpRung], new_rungs: Vec<TpRung>) -> Result<Vec<TpRung>> {
+    let mut ladder: Vec<TpRung> = current.iter().filter(|r| r.triggered).cloned().collect();
+
+    let highest_triggered = ladder
+        .iter()
+        .map(|rung| rung.target_percent)
+        .fold(f64::NEG_INFINITY, f64::max);
+    if let Some(rung) = new_rungs
+        .iter()
+        .find(|rung| rung.target_percent <= highest_triggered)
+    {
+        bail!(
+            "El objetivo +{:.0}% no supera el último escalón ejecutado (+{:.0}%)",
+            rung.target_percent,
+            highest_triggered
+        );
+    }
+
+    let sold = 100.0 - remaining_percent(&ladder);
+    let total: f64 = sold + new_rungs.iter().map(|rung| rung.sell_percent).sum::<f64>();
+    if total > 100.0 + 1e-9 {
+        bail!(
+            "Ya se vendió un {:.0}%: la escalera vendería {:.0}% (> 100%)",
+            sold,
+            total
+        );
+    }
+
+    ladder.extend(new_rungs);
+    Ok(ladder)
+}
+
+/// Mueve el objetivo del siguiente escalón pendiente. Sin escalones pendientes,
+/// añade uno que vende lo que quede.
+pub fn retarget_next(ladder: &mut Vec<TpRung>, target_percent: f64) {
+    let next = ladder.iter_mut().find(|rung| !rung.triggered);
+    match next {
+        Some(rung) => rung.target_percent = target_percent,
+        None => {
+            let remaining = remaining_percent(ladder);
+            ladder.push(TpRung::new(target_percent, remaining));
+        }
+    }
+}
+
+/// `+50%→25% ✅ | +100%→25% | +300%→25%`
+pub fn format_ladder(ladder: &[TpRung]) -> String {
+    if ladder.is_empty() {
+        return "—".to_string();
+    }
+    ladder
+        .iter()
+        .map(|rung| {
+            format!(
+                "+{:.0}%→{:.0}%{}",
+                rung.target_percent,
+                rung.sell_percent,
+                if rung.triggered { " ✅" } else { "" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triggered(mut rung: TpRung) -> TpRung {
+        rung.triggered = true;
+        rung
+    }
+
+    #[test]
+    fn test_sell_pct_converts_to_balance() {
+        // 25% a +50%, 25% a +100%, 25% a +300%, el 25% restante al trailing
+        let mut ladder = parse_ladder("100:25, 50:25, +300:25").unwrap();
+        assert_eq!(ladder[0].target_percent, 50.0);
+        assert_eq!(next_rung(&ladder).map(|(i, _)| i), Some(0));
+        assert_eq!(sell_pct_of_balance(&ladder, 0), 25);
+
+        // Tras el primero queda el 75%: 25 de 75 = 33% del balance
+        ladder[0] = triggered(ladder[0].clone());
+        assert_eq!(next_rung(&ladder).map(|(i, _)| i), Some(1));
+        assert_eq!(sell_pct_of_balance(&ladder, 1), 33);
+
+        // Tras el segundo queda el 50%: 25 de 50 = la mitad
+        ladder[1] = triggered(ladder[1].clone());
+        assert_eq!(sell_pct_of_balance(&ladder, 2), 50);
+
+        ladder[2] = triggered(ladder[2].clone());
+        assert!(next_rung(&ladder).is_none());
+        assert_eq!(remaining_percent(&ladder), 25.0);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_ladders() {
+        assert!(parse_ladder("").is_err());
+        assert!(parse_ladder("50").is_err());
+        assert!(parse_ladder("50:60,100:50").is_err());
+        assert!(parse_ladder("50:25,50:25").is_err());
+        assert!(parse_ladder("-10:25").is_err());
+    }
+
+    #[test]
+    fn test_update_keeps_executed_rungs() {
+        let ladder = vec![
+            triggered(TpRung::new(100.0, 50.0)),
+            TpRung::new(200.0, 50.0),
+        ];
+
+        let replaced = replace_pending(&ladder, parse_ladder("150:25,400:25").unwrap()).unwrap();
+        assert_eq!(replaced.len(), 3);
+        assert!(replaced[0].triggered);
+        assert_eq!(
+            next_rung(&replaced).map(|(i, r)| (i, r.target_percent)),
+            Some((1, 150.0))
+        );
+
+        let mut retargeted = ladder.clone();
+        retarget_next(&mut retargeted, 250.0);
+        assert_eq!(retargeted[1].target_percent, 250.0);
+
+        // Todo ejecutado: el nuevo objetivo vende lo que quede
+        let mut done = vec![triggered(TpRung::new(100.0, 60.0))];
+        retarget_next(&mut done, 300.0);
+        assert_eq!(done[1].sell_percent, 40.0);
+        assert_eq!(sell_pct_of_balance(&done, 1), 100);
+    }
+
+    #[test]
+    fn test_replace_rejects_oversold_or_out_of_order() {
+        let ladder = vec![
+            triggered(TpRung::new(100.0, 50.0)),
+            TpRung::new(200.0, 50.0),
+        ];
+
+        // 50% vendido + 60% nuevo > 100%
+        assert!(replace_pending(&ladder, parse_ladder("150:30,300:30").unwrap()).is_err());
+        // Objetivo por debajo del escalón ya ejecutado
+        assert!(replace_pending(&ladder, parse_ladder("80:25,300:25").unwrap()).is_err());
+        assert!(replace_pending(&ladder, parse_ladder("100:25").unwrap()).is_err());
+        // Sin escalones ejecutados cualquier escalera válida entra
+        assert!(
+            replace_pending(&[TpRung::new(100.0, 50.0)], parse_ladder("20:100").unwrap()).is_ok()
+        );
+    }
+}
@@ -39,6 +39,8 @@ pub enum RejectionReason {
     AuthoritiesNotRevoked,
     /// Liquidez no bloqueada o quemada (<95%)
     LpNotBurned,
+    /// LP imposible de medir (pool sin LP fungible o RPC caído) con `UnmeasuredLp::Reject`
+    LpUnmeasured,
     /// Concentración excesiva en Top 10 Holders (>20%)
     HighConcentration,
     /// Momentum (Slope de Precio) insuficiente (<0.20/min)
@@ -57,6 +59,14 @@ pub enum RejectionReason {
     NarrativeExposureLimit,
     /// Congestión de red extrema (Slot lag alto)
     NetworkCongestion,
+    /// Token-2022 con Permanent Delegate (puede mover/quemar nuestros tokens)
+    PermanentDelegate,
+    /// Token-2022 con Transfer Hook (un programa externo puede bloquear la venta)
+    TransferHook,
+    /// Token-2022 Non-Transferable (imposible vender)
+    NonTransferable,
+    /// Round-trip simulado fallido: la venta revierte o el impuesto es abusivo
+    Honeypot,
 }
 
 impl fmt::Display for RejectionReason {
@@ -89,7 +99,15 @@ pub struct TokenContext {
     pub dev_wallet_pct: f64,
     pub mint_authority: Option<String>,
     pub freeze_authority: Option<String>,
-    pub lp_burned_pct: f64,
+    /// % del LP quemado (None = no medible: CLMM/Orca/Meteora o RPC caído)
+    pub lp_burned_pct: Option<f64>,
+    /// Transfer fee de Token-2022 en bps (0 = sin fee / SPL clásico)
+    pub transfer_fee_bps: u16,
+    pub permanent_delegate: Option<String>,
+    pub transfer_hook_program: Option<String>,
+    pub non_transferable: bool,
+    /// Slots de retraso del RPC frente a la punta del cluster
+    pub slot_lag: u64,
 }
 
 impl TokenContext {
@@ -99,6 +117,27 @@ impl TokenContext {
     }
 }
 
+/// Política de los filtros de LP cuando `lp_burned_pct` no se pudo medir
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnmeasuredLp {
+    /// Sin dato no se compra (rechazo `LpUnmeasured`)
+    #[default]
+    Reject,
+    /// Sin dato el filtro no aplica; el resto del pipeline decide
+    Allow,
+}
+
+impl UnmeasuredLp {
+    /// Veredicto de un filtro de LP sin medición
+    pub fn verdict(self) -> FilterResult {
+        match self {
+            UnmeasuredLp::Reject => FilterResult::Rejected(RejectionReason::LpUnmeasured),
+            UnmeasuredLp::Allow => FilterResult::Approved,
+        }
+    }
+}
+
 /// Trait que todo filtro debe implementar
 pub trait TradeFilter: Send + Sync {
     /// Nombre del filtro para logs
@@ -0,0 +1,436 @@
+//! # Órdenes de entrada en reposo (limit / breakout)
+//!
+//! `/buy`, `/rbuy` y el AutoBuyer compran al instante. Una `EntryOrder` deja la
+//! compra en espera hasta que el precio la dispare:
+//! - `Below`: compra límite cuando el precio cae a `trigger_price` o menos.
+//! - `Above`: ruptura cuando supera `trigger_price` con momentum positivo.
+//!
+//! Las órdenes viven en SQLite (`entry_orders`) y este loop las evalúa contra
+//! el broadcast de precios del `EventBus`, fuera del camino crítico del
+//! `StrategyEngine`. Cada pocos segundos recarga las abiertas (así ve las que
+//! crea o cancela Telegram), expira las vencidas y suscribe sus mints al feed.
+//!
+//! Disparar, cancelar y expirar compiten por la misma transición `OPEN → X`
+//! en la DB: solo uno gana, así que `/cancel` nunca llega tarde a medias.
+
+use chrono::Utc;
+use solana_sdk::signature::Keypair;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+use crate::engine::momentum::MomentumSensor;
+use crate::executor_v2::TradeExecutor;
+use crate::price_feed::{FeedCommand, MonitoredToken, PriceUpdate};
+use crate::state_manager::{
+    EntryOrder, EntryOrderStatus, EntryTrigger, ExitRules, PositionState, StateManager, TradeRecord,
+};
+use crate::telegram::TelegramNotifier;
+
+/// Puntos del sensor de momentum por mint (ruptura)
+const MOMENTUM_WINDOW: usize = 12;
+
+/// Priority fee de las compras disparadas (igual que `/buy`)
+const ENTRY_PRIORITY_FEE_LAMPORTS: u64 = 100_000;
+
+/// ¿El tick dispara la orden? `slope` = pendiente del precio del mint (ver `MomentumSensor`)
+pub fn should_trigger(order: &EntryOrder, price: f64, slope: f64) -> bool {
+    if price <= 0.0 {
+        return false;
+    }
+    match order.trigger {
+        EntryTrigger::Below => price <= order.trigger_price,
+        EntryTrigger::Above => price >= order.trigger_price && slope > 0.0,
+    }
+}
+
+pub struct EntryOrderManager {
+    state_manager: Arc<StateManager>,
+    executor: Arc<TradeExecutor>,
+    telegram: Arc<TelegramNotifier>,
+    wallet_keypair: Option<Arc<Keypair>>,
+    feed_tx: Option<mpsc::Sender<FeedCommand>>,
+}
+
+impl EntryOrderManager {
+    pub fn new(
+        state_manager: Arc<StateManager>,
+        executor: Arc<TradeExecutor>,
+        telegram: Arc<TelegramNotifier>,
+        wallet_keypair: Option<Keypair>,
+    ) -> Self {
+        Self {
+            state_manager,
+            executor,
+            telegram,
+            wallet_keypair: wallet_keypair.map(Arc::new),
+            feed_tx: None,
+        }
+    }
+
+    /// Suscribe al PriceFeed los mints con órdenes abiertas
+    pub fn with_feed(mut self, feed_tx: mpsc::Sender<FeedCommand>) -> Self {
+        self.feed_tx = Some(feed_tx);
+        self
+    }
+
+    /// Órdenes que quedaron TRIGGERED al caer el proceso: no sabemos si la
+    /// compra aterrizó. Se marcan FAILED; si hubo fill, el reconciliador adopta
+    /// los tokens como posición.
+    pub async fn recover_interrupted(&self) -> anyhow::Result<usize> {
+        let interrupted = self
+            .state_manager
+            .get_entry_orders(EntryOrderStatus::Triggered)
+            .await?;
+        for order in &interrupted {
+            if let Some(id) = order.id {
+                self.state_manager
+                    .finish_entry_order(
+                        id,
+                        EntryOrderStatus::Failed,
+                        None,
+                        Some("Interrumpida por reinicio".to_string()),
+                    )
+                    .await?;
+            }
+        }
+        Ok(interrupted.len())
+    }
+
+    pub async fn run(
+        self: Arc<Self>,
+        mut prices: broadcast::Receiver<PriceUpdate>,
+        refresh_interval: Duration,
+    ) {
+        match self.recover_interrupted().await {
+            Ok(0) => {}
+            Ok(n) => println!(
+                "⚠️ [ENTRY] {} órdenes interrumpidas marcadas como FAILED",
+                n
+            ),
+            Err(e) => eprintln!("❌ [ENTRY] Error recuperando órdenes: {}", e),
+        }
+
+        let mut open: Vec<EntryOrder> = Vec::new();
+        let mut momentum: HashMap<String, MomentumSensor> = HashMap::new();
+        let mut subscribed: HashSet<String> = HashSet::new();
+        let mut refresh = tokio::time::interval(refresh_interval);
+
+        loop {
+            tokio::select! {
+                _ = refresh.tick() => {
+                    open = self.refresh(&mut subscribed).await;
+                    momentum.retain(|mint, _| open.iter().any(|o| &o.token_mint == mint));
+                }
+
+                tick = prices.recv() => {
+                    let tick = match tick {
+                        Ok(tick) => tick,
+                        // Nos saltamos ticks viejos: solo importa el precio actual
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+                    if !open.iter().any(|o| o.token_mint == tick.token_mint) {
+                        continue;
+                    }
+
+                    let sensor = momentum
+                        .entry(tick.token_mint.clone())
+                        .or_insert_with(|| MomentumSensor::new(MOMENTUM_WINDOW));
+                    sensor.update(tick.price_native);
+                    let slope = sensor.slope();
+
+                    // Con compras bloqueadas las órdenes siguen en reposo
+                    if crate::telegram::commands::CommandHandler::is_hibernating()
+                        || crate::circuit_breaker::buys_halted()
+                    {
+                        continue;
+                    }
+
+                    let now = Utc::now().timestamp();
+                    let (fire, keep): (Vec<_>, Vec<_>) = open.drain(..).partition(|o| {
+                        o.token_mint == tick.token_mint
+                            && o.expires_at > now
+                            && should_trigger(o, tick.price_native, slope)
+                    });
+                    open = keep;
+
+                    for order in fire {
+                        let manager = Arc::clone(&self);
+                        let price = tick.price_native;
+                        tokio::spawn(async move {
+                            manager.execute(order, price).await;
+                        });
+                    }
+                }
+            }
+        }
+
+        println!("🛑 [ENTRY] Feed de precios cerrado. Órdenes de entrada detenidas.");
+    }
+
+    /// Expira las vencidas y devuelve las que siguen abiertas
+    async fn refresh(&self, subscribed: &mut HashSet<String>) -> Vec<EntryOrder> {
+        let orders = match self
+            .state_manager
+            .get_entry_orders(EntryOrderStatus::Open)
+            .await
+        {
+            Ok(orders) => orders,
+            Err(e) => {
+                eprintln!("❌ [ENTRY] DB ERROR cargando órdenes: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let now = Utc::now().timestamp();
+        let mut open = Vec::with_capacity(orders.len());
+        for order in orders {
+            let Some(id) = order.id else { continue };
+
+            if order.expires_at <= now {
+                if let Ok(true) = self
+                    .state_manager
+                    .transition_open_entry_order(id, EntryOrderStatus::Expired)
+                    .await
+                {
+                    let _ = self
+                        .telegram
+                        .send_message(
+                            &format!(
+                                "⌛ <b>ENTRY ORDER #{} EXPIRED</b>\n{} {} @ <code>{:.10} SOL</code>",
+                                id,
+                                order.symbol,
+                                order.trigger.as_str(),
+                                order.trigger_price
+                            ),
+                            true,
+                        )
+                        .await;
+                }
+                continue;
+            }
+
+            if let Some(feed_tx) = &self.feed_tx {
+                if subscribed.insert(order.token_mint.clone()) {
+                    let _ = feed_tx
+                        .send(FeedCommand::Subscribe(MonitoredToken {
+                            mint: order.token_mint.clone(),
+                            symbol: order.symbol.clone(),
+                            pool_account: None,
+                            coin_vault: None,
+                            pc_vault: None,
+                            token_decimals: 6,
+                        }))
+                        .await;
+                }
+            }
+            open.push(order);
+        }
+        open
+    }
+
+    async fn execute(&self, order: EntryOrder, trigger_tick_price: f64) {
+        let Some(id) = order.id else { return };
+
+        // Cerrojo: si /cancel o la expiración ganaron, no compramos
+        match self
+            .state_manager
+            .transition_open_entry_order(id, EntryOrderStatus::Triggered)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                eprintln!("❌ [ENTRY] DB ERROR reclamando orden #{}: {}", id, e);
+                return;
+            }
+        }
+
+        println!(
+            "🎯 [ENTRY] Orden #{} disparada: {} {} SOL @ {:.10}",
+            id, order.symbol, order.amount_sol, trigger_tick_price
+        );
+
+        let result = self
+            .executor
+            .execute_buy_with_custom_params(
+                &order.token_mint,
+                self.wallet_keypair.as_deref(),
+                order.amount_sol,
+                ENTRY_PRIORITY_FEE_LAMPORTS,
+                order.slippage_bps,
+            )
+            .await;
+
+        let res = match result {
+            Ok(res) if res.output_amount > 0.0 => res,
+            Ok(res) => {
+                // Firma sin tokens confirmados: el reconciliador ajustará si aterrizó
+                self.fail(id, &order, Some(res.signature), "Sin tokens recibidos")
+                    .await;
+                return;
+            }
+            Err(e) => {
+                self.fail(id, &order, None, &e.to_string()).await;
+                return;
+            }
+        };
+
+        let price = order.amount_sol / res.output_amount;
+        let now = Utc::now().timestamp();
+        let position = PositionState {
+            id: None,
+            token_mint: order.token_mint.clone(),
+            symbol: order.symbol.clone(),
+            entry_price: price,
+            current_price: price,
+            amount_sol: order.amount_sol,
+            stop_loss_percent: order.exit.stop_loss_percent,
+            trailing_enabled: order.exit.trailing_enabled,
+            trailing_distance_percent: order.exit.trailing_distance_percent,
+            trailing_activation_threshold: order.exit.trailing_activation_threshold,
+            trailing_highest_price: Some(price),
+            trailing_current_sl: Some(order.exit.stop_loss_percent),
+            tp_ladder: order.exit.tp_ladder.clone(),
+            active: true,
+            created_at: now,
+            updated_at: now,
+            token_amount: 0.0,
+            realized_pnl_sol: 0.0,
+            exit_rules: ExitRules::default(),
+        };
+
+        if let Err(e) = self
+            .state_manager
+            .record_buy_fill(
+                position,
+                &res.signature,
+                res.output_amount,
+                order.amount_sol,
+            )
+            .await
+        {
+            eprintln!("❌ [ENTRY] DB ERROR guardando posición de #{}: {}", id, e);
+        }
+
+        let trade = TradeRecord {
+            id: None,
+            signature: res.signature.clone(),
+            token_mint: order.token_mint.clone(),
+            symbol: order.symbol.clone(),
+            trade_type: "LIMIT_BUY".to_string(),
+            amount_sol: order.amount_sol,
+            tokens_amount: res.output_amount,
+            price,
+            pnl_sol: None,
+            pnl_percent: None,
+            route: format!("Entry Order #{}", id),
+            price_impact_pct: res.price_impact_pct,
+            fee_sol: res.fee_sol,
+            timestamp: now,
+        };
+        if let Err(e) = self.state_manager.record_trade(trade).await {
+            eprintln!("❌ [ENTRY] DB ERROR registrando trade de #{}: {}", id, e);
+        }
+
+        if let Err(e) = self
+            .state_manager
+            .finish_entry_order(
+                id,
+                EntryOrderStatus::Filled,
+                Some(res.signature.clone()),
+                None,
+            )
+            .await
+        {
+            eprintln!("❌ [ENTRY] DB ERROR cerrando orden #{}: {}", id, e);
+        }
+
+        let _ = self
+            .telegram
+            .send_message(
+                &format!(
+                    "<b>🎯 ENTRY ORDER #{} FILLED</b>\n\
+                     <b>⬢ Asset:</b>  <code>{}</code>\n\
+                     <b>⬢ Tokens:</b> <code>{:.2}</code>\n\
+                     <b>⬢ Entry:</b>  <code>{:.10} SOL</code>\n\
+                     <b>⬢ Tx:</b> <a href='https://solscan.io/tx/{}'>VIEW</a>\n\
+                     <i>🛡️ MONITORING ARMED</i>",
+                    id, order.symbol, res.output_amount, price, res.signature
+                ),
+                true,
+            )
+            .await;
+    }
+
+    async fn fail(&self, id: i64, order: &EntryOrder, signature: Option<String>, reason: &str) {
+        eprintln!("❌ [ENTRY] Orden #{} fallida: {}", id, reason);
+        if let Err(e) = self
+            .state_manager
+            .finish_entry_order(
+                id,
+                EntryOrderStatus::Failed,
+                signature,
+                Some(reason.to_string()),
+            )
+            .await
+        {
+            eprintln!("❌ [ENTRY] DB ERROR cerrando orden #{}: {}", id, e);
+        }
+        let _ = self
+            .telegram
+            .send_message(
+                &format!(
+                    "❌ <b>ENTRY ORDER #{} FAILED</b> ({})\n{}",
+                    id, order.symbol, reason
+                ),
+                true,
+            )
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(trigger: EntryTrigger, trigger_price: f64) -> EntryOrder {
+        EntryOrder {
+            id: Some(1),
+            token_mint: "MINT".to_string(),
+            symbol: "TEST".to_string(),
+            amount_sol: 0.1,
+            trigger,
+            trigger_price,
+            slippage_bps: 100,
+            exit: Default::default(),
+            status: EntryOrderStatus::Open,
+            signature: None,
+            error: None,
+            created_at: 0,
+            expires_at: i64::MAX,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_limit_triggers_at_or_below() {
+        let limit = order(EntryTrigger::Below, 0.001);
+        assert!(!should_trigger(&limit, 0.0011, 0.0));
+        assert!(should_trigger(&limit, 0.001, 0.0));
+        assert!(should_trigger(&limit, 0.0009, -5.0));
+        // Tick sin precio (feed caído) nunca compra
+        assert!(!should_trigger(&limit, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_breakout_needs_positive_momentum() {
+        let breakout = order(EntryTrigger::Above, 0.002);
+        assert!(!should_trigger(&breakout, 0.0019, 1.0));
+        assert!(!should_trigger(&breakout, 0.0021, 0.0));
+        assert!(!should_trigger(&breakout, 0.0021, -0.5));
+        assert!(should_trigger(&breakout, 0.0021, 0.3));
+    }
+}
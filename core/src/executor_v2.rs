@@ -9,18 +9,34 @@ use base64::{engine::general_purpose, Engine as _};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
-    program_pack::Pack,
     pubkey::Pubkey,
     signature::{Keypair, Signature, Signer},
     transaction::VersionedTransaction,
 };
-use spl_token::state::Account as TokenAccount;
 use std::str::FromStr;
+use std::sync::Arc;
 
+use crate::config::SlicedExitSettings;
+use crate::direct_swap::{send_instructions, send_instructions_with_jito, WSOL_MINT};
 use crate::jito::JitoClient;
 use crate::jupiter::{BuyResult, JupiterClient, SwapResult};
-use crate::raydium::RaydiumClient;
+use crate::meteora::MeteoraClient;
+use crate::orca::OrcaClient;
+use crate::pumpfun::{PumpFunClient, PUMP_TOKEN_DECIMALS};
+use crate::quote_engine::LocalQuoteEngine;
+use crate::raydium::{RaydiumClient, RaydiumPool};
+use crate::sliced_exit::{
+    fill_price, merge_fills, next_slice_percent, should_escalate, PartialSellError,
+};
+use crate::token_2022::{
+    associated_token_address, fetch_mint, fetch_token_balance, mint_token_program,
+    parse_token_account_amount, MintInfo,
+};
 use crate::validation::FinancialValidator;
+use crate::venue::{
+    rank_venues, JitoBundled, QuoteRequest, SwapVenue, VenuePayload, VenueQuote,
+    DEFAULT_QUOTE_TIMEOUT,
+};
 
 /// Configuración del executor
 #[derive(Debug, Clone)]
@@ -56,9 +72,20 @@ impl ExecutorConfig {
 pub struct TradeExecutor {
     config: ExecutorConfig,
     rpc_client: RpcClient,
-    jupiter: JupiterClient,
-    raydium: Option<RaydiumClient>,
+    jupiter: Arc<JupiterClient>,
+    raydium: Option<Arc<RaydiumClient>>,
+    pumpfun: Option<Arc<PumpFunClient>>,
     jito_client: JitoClient,
+    /// Venues de compra (RPC estándar), rankeados por salida neta en cada trade
+    buy_venues: Vec<Arc<dyn SwapVenue>>,
+    /// Venues de salida (empaquetados con Jito), rankeados por SOL neto
+    sell_venues: Vec<Arc<dyn SwapVenue>>,
+    /// Reservas trackeadas por los feeds: quotes de venta sin HTTP
+    local_quotes: Option<Arc<LocalQuoteEngine>>,
+    /// Venue que ejecuta los quotes locales (los vaults trackeados son de Raydium)
+    reserve_venue: Option<Arc<dyn SwapVenue>>,
+    /// Espera máxima por el quote de cada venue al rankear
+    quote_timeout: std::time::Duration,
 }
 
 impl TradeExecutor {
@@ -70,7 +97,7 @@ impl TradeExecutor {
         let raydium = match RaydiumClient::new(config.rpc_url.clone()) {
             Ok(client) => {
                 println!("✅ Raydium Client: Activado (Modo Directo)");
-                Some(client)
+                Some(Arc::new(client))
             }
             Err(e) => {
                 eprintln!(
@@ -78,7 +105,7 @@ impl TradeExecutor {
                     e
                 );
                 match RaydiumClient::new(config.rpc_url.clone()) {
-                    Ok(c) => Some(c),
+                    Ok(c) => Some(Arc::new(c)),
                     Err(_) => {
                         eprintln!("❌ Raydium Client: Fallo fatal en inicialización.");
                         None
@@ -87,15 +114,93 @@ impl TradeExecutor {
             }
         };
 
+        let pumpfun = match PumpFunClient::new(config.rpc_url.clone()) {
+            Ok(client) => Some(Arc::new(client)),
+            Err(e) => {
+                eprintln!("⚠️  Pump.fun Client desactivado: {}", e);
+                None
+            }
+        };
+
+        let orca = match OrcaClient::new(config.rpc_url.clone()) {
+            Ok(client) => Some(Arc::new(client)),
+            Err(e) => {
+                eprintln!("⚠️  Orca Client desactivado: {}", e);
+                None
+            }
+        };
+
+        let meteora = match MeteoraClient::new(config.rpc_url.clone()) {
+            Ok(client) => Some(Arc::new(client)),
+            Err(e) => {
+                eprintln!("⚠️  Meteora Client desactivado: {}", e);
+                None
+            }
+        };
+
+        let jupiter = Arc::new(JupiterClient::new());
+
+        // Mismo orden de preferencia ante empate de quote: directos primero
+        let mut buy_venues: Vec<Arc<dyn SwapVenue>> = Vec::new();
+        let mut sell_venues: Vec<Arc<dyn SwapVenue>> = Vec::new();
+        let mut reserve_venue: Option<Arc<dyn SwapVenue>> = None;
+        if let Some(client) = &raydium {
+            let raydium_jito: Arc<dyn SwapVenue> =
+                Arc::new(JitoBundled::new(client.clone(), "Raydium + Jito"));
+            buy_venues.push(client.clone());
+            sell_venues.push(raydium_jito.clone());
+            reserve_venue = Some(raydium_jito);
+        }
+        if let Some(client) = orca {
+            buy_venues.push(client.clone());
+            sell_venues.push(Arc::new(JitoBundled::new(client, "Orca + Jito")));
+        }
+        if let Some(client) = meteora {
+            buy_venues.push(client.clone());
+            sell_venues.push(Arc::new(JitoBundled::new(client, "Meteora + Jito")));
+        }
+        buy_venues.push(jupiter.clone());
+        sell_venues.push(jupiter.clone());
+
         Self {
             config,
             rpc_client,
-            jupiter: JupiterClient::new(),
+            jupiter,
             raydium,
+            pumpfun,
             jito_client: JitoClient::new(),
+            buy_venues,
+            sell_venues,
+            local_quotes: None,
+            reserve_venue,
+            quote_timeout: DEFAULT_QUOTE_TIMEOUT,
         }
     }
 
+    /// Conecta el libro de reservas de los feeds: las ventas con reservas frescas
+    /// se cotizan localmente y van directas a Raydium sin ranking HTTP
+    pub fn with_local_quotes(mut self, engine: Arc<LocalQuoteEngine>) -> Self {
+        self.local_quotes = Some(engine);
+        self
+    }
+
+    /// Espera máxima por el quote de cada venue (los lentos no entran al ranking)
+    pub fn with_quote_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.quote_timeout = timeout;
+        self
+    }
+
+    /// Venues donde el token se puede comprar: la bonding curve de Pump.fun
+    /// (si está activa) y los venues de compra, en el orden en que se prueban
+    pub fn probe_venues(&self) -> Vec<Arc<dyn SwapVenue>> {
+        let mut venues: Vec<Arc<dyn SwapVenue>> = Vec::new();
+        if let Some(pumpfun) = &self.pumpfun {
+            venues.push(pumpfun.clone());
+        }
+        venues.extend(self.buy_venues.iter().cloned());
+        venues
+    }
+
     /// ⚡ DYNAMIC PRIORITY FEE — Consulta Helius para el fee óptimo real.
     ///
     /// Usa `getPriorityFeeEstimate` de Helius RPC con nivel "High" para equilibrar
@@ -153,6 +258,187 @@ impl TradeExecutor {
         lamports as f64 / 1_000_000_000.0
     }
 
+    /// Firma única de un trade simulado: los lotes son únicos por firma y una
+    /// constante haría que solo la primera compra dry-run abriera posición
+    fn simulated_signature(token_mint: &str) -> String {
+        format!(
+            "SIM_{}_{}",
+            token_mint,
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        )
+    }
+
+    /// Cotiza en `venues` y ejecuta en el de mejor salida neta; si la ejecución
+    /// falla prueba el siguiente. Devuelve el quote ejecutado y la firma.
+    ///
+    /// `amount_sent` es lo que sale de la wallet: con transfer fee Token-2022 el
+    /// request se cotiza sobre lo que llega al pool, pero se envía el total.
+    async fn route_swap(
+        &self,
+        venues: &[Arc<dyn SwapVenue>],
+        request: &QuoteRequest,
+        amount_sent: u64,
+        keypair: &Keypair,
+    ) -> Result<(VenueQuote, String)> {
+        let mut last_error = None;
+
+        for (venue, mut quote) in rank_venues(venues, request, self.quote_timeout).await {
+            quote.amount_in = amount_sent;
+            match self.execute_quote(venue.as_ref(), &quote, keypair).await {
+                Ok(sig) => return Ok((quote, sig)),
+                Err(e) => {
+                    eprintln!(
+                        "⚠️ [{}] falló: {}. Probando siguiente venue...",
+                        venue.name(),
+                        e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            anyhow::anyhow!(
+                "Ningún venue devolvió quote para {} → {}",
+                request.input_mint,
+                request.output_mint
+            )
+        }))
+    }
+
+    /// Construye y envía `quote` en `venue`
+    async fn execute_quote(
+        &self,
+        venue: &dyn SwapVenue,
+        quote: &VenueQuote,
+        keypair: &Keypair,
+    ) -> Result<String> {
+        println!("🚀 [{}] Ejecutando vía {}...", venue.name(), quote.route);
+        let payload = venue.build_instructions(quote, &keypair.pubkey()).await?;
+        let sig = self
+            .submit_payload(payload, quote.jito_tip, keypair)
+            .await?;
+        println!("✅ [{}] Sig: {}", venue.name(), sig);
+        Ok(sig)
+    }
+
+    /// Quote sin I/O desde las reservas trackeadas, listo para `reserve_venue`.
+    /// `None` si no hay engine, venue o reservas frescas para el par, o si
+    /// Raydium ejecutaría en un pool distinto del que se cotizó.
+    async fn quote_locally(
+        &self,
+        request: &QuoteRequest,
+    ) -> Option<(Arc<dyn SwapVenue>, VenueQuote)> {
+        let engine = self.local_quotes.as_ref()?;
+        let venue = self.reserve_venue.clone()?;
+        let local =
+            engine.quote_exact_in(&request.input_mint, &request.output_mint, request.amount_in)?;
+
+        // Las reservas (y el fee) son de un pool AMM v4 concreto; `build_instructions`
+        // usa el pool más profundo del par (cacheado), que puede ser otro
+        let pool = self
+            .raydium
+            .as_ref()?
+            .find_pool(&request.input_mint, &request.output_mint)
+            .await
+            .ok()?;
+        let same_pool = matches!(pool, RaydiumPool::AmmV4(_))
+            && pool
+                .vaults()
+                .map(|vaults| local.priced_on(&vaults.map(|v| v.to_string())))
+                .unwrap_or(false);
+        if !same_pool {
+            println!(
+                "   📐 Quote local descartado: Raydium ejecutaría en {} [{}], no en el pool trackeado",
+                pool.name(),
+                pool.kind()
+            );
+            return None;
+        }
+
+        println!(
+            "   📐 Quote local: {} → {} raw (fee {}, impacto {:.2}%, reservas de hace {}ms)",
+            local.amount_in,
+            local.amount_out,
+            local.fee_amount,
+            local.price_impact_pct,
+            local.reserves_age.as_millis()
+        );
+
+        let mut quote = venue.quote_with_output(request, local.amount_out);
+        quote.route = format!("{} (reservas locales)", quote.route);
+        quote.price_impact_pct = local.price_impact_pct;
+        Some((venue, quote))
+    }
+
+    /// Firma y envía lo construido por un venue. Con `jito_tip > 0` va como
+    /// bundle de Jito (swap + tip) con fallback a RPC estándar.
+    async fn submit_payload(
+        &self,
+        payload: VenuePayload,
+        jito_tip: u64,
+        keypair: &Keypair,
+    ) -> Result<String> {
+        let mut transaction = match payload {
+            VenuePayload::Instructions(ixs) if jito_tip > 0 => {
+                return send_instructions_with_jito(&self.rpc_client, &ixs, jito_tip, keypair)
+                    .await;
+            }
+            VenuePayload::Instructions(ixs) => {
+                return send_instructions(&self.rpc_client, &ixs, keypair);
+            }
+            VenuePayload::Transaction(tx) => tx,
+        };
+
+        let user_pubkey = keypair.pubkey();
+        let recent_blockhash = self
+            .rpc_client
+            .get_latest_blockhash()
+            .context("Error obteniendo blockhash reciente")?;
+        transaction.message.set_recent_blockhash(recent_blockhash);
+        let signed_tx = VersionedTransaction::try_new(transaction.message, &[keypair])
+            .context("Error firmando transacción con keypair")?;
+
+        println!("📡 Broadcasting transacción a Solana...");
+        if jito_tip == 0 {
+            return Ok(self
+                .send_transaction_with_retry(&signed_tx, 3)
+                .await?
+                .to_string());
+        }
+
+        println!(
+            "🛡️  Preparando Jito Bundle con Tip ({} SOL)...",
+            jito_tip as f64 / 1_000_000_000.0
+        );
+        let tip_ix = JitoClient::create_tip_instruction(&user_pubkey, jito_tip);
+        let tip_msg = solana_sdk::message::Message::new(&[tip_ix], Some(&user_pubkey));
+        let mut tip_tx = solana_sdk::transaction::Transaction::new_unsigned(tip_msg);
+        tip_tx.sign(&[keypair], recent_blockhash);
+
+        let bundle = vec![signed_tx.clone(), VersionedTransaction::from(tip_tx)];
+
+        match self.jito_client.send_bundle(bundle).await {
+            Ok(bundle_id) => {
+                println!("✅ Bundle enviado a Jito. ID: {}", bundle_id);
+                Ok(signed_tx.signatures[0].to_string())
+            }
+            Err(e) => {
+                eprintln!("⚠️  Jito falló: {}. Fallback a RPC standard...", e);
+                Ok(self
+                    .send_transaction_with_retry(&signed_tx, 3)
+                    .await?
+                    .to_string())
+            }
+        }
+    }
+
+    /// Mint on-chain (SPL clásico o Token-2022). `None` si el RPC no responde.
+    fn mint_info(&self, token_mint: &str) -> Option<MintInfo> {
+        let mint = Pubkey::from_str(token_mint).ok()?;
+        fetch_mint(&self.rpc_client, &mint).ok()
+    }
+
     /// Actuador asíncrono con control de tracción para slippage dinámico y Jito Tips (Zero-Allocation)
     pub async fn execute_sell_with_retry(
         &self,
@@ -248,6 +534,117 @@ impl TradeExecutor {
         }
     }
 
+    /// Venta troceada (TWAP): reparte `amount_percent` del balance en trozos que
+    /// mueven el precio como mucho `settings.max_impact_pct`, cada uno con los
+    /// reintentos de `execute_sell_with_retry`, y los devuelve unidos en un solo
+    /// `SwapResult`. Con `escalate` (stop-loss), si el precio de fill cae
+    /// `escalate_drop_pct` desde el primer trozo, el resto sale de golpe.
+    ///
+    /// Si un trozo falla después de haber vendido otros, el resto también sale
+    /// de golpe; si eso también falla, el error es un `PartialSellError` para
+    /// que el router no reintente la venta entera (vendería de más).
+    pub async fn execute_sliced_sell(
+        &self,
+        token_mint: &str,
+        wallet_keypair: Option<&Keypair>,
+        amount_percent: u8,
+        is_emergency: bool,
+        settings: &SlicedExitSettings,
+        escalate: bool,
+    ) -> Result<SwapResult> {
+        // Sin wallet (simulación) o sin libro de reservas no hay con qué dimensionar los trozos
+        let (Some(keypair), Some(quotes)) = (wallet_keypair, self.local_quotes.as_ref()) else {
+            return self
+                .execute_sell_with_retry(
+                    token_mint.to_string(),
+                    wallet_keypair,
+                    amount_percent,
+                    is_emergency,
+                )
+                .await;
+        };
+
+        let balance_before = self
+            .token_balance(&keypair.pubkey(), token_mint)
+            .context("No se pudo leer el balance del token para trocear la venta")?;
+        let target = (balance_before as f64 * amount_percent.min(100) as f64 / 100.0) as u64;
+
+        let mut fills: Vec<SwapResult> = Vec::new();
+        let mut sold: u64 = 0;
+        let mut dump_rest = false;
+
+        while sold < target {
+            let balance = balance_before.saturating_sub(sold);
+            let last_slice = dump_rest || fills.len() + 1 >= settings.max_slices as usize;
+            let max_chunk = quotes.max_sell_for_impact(token_mint, settings.max_impact_pct);
+            let pct = next_slice_percent(balance, target - sold, max_chunk, last_slice);
+            if pct == 0 {
+                break;
+            }
+
+            println!(
+                "🔪 [TWAP] Trozo {} de {}: {}% del balance (máx. {:.1}% de impacto)",
+                fills.len() + 1,
+                token_mint,
+                pct,
+                settings.max_impact_pct
+            );
+
+            let fill = match self
+                .execute_sell_with_retry(
+                    token_mint.to_string(),
+                    wallet_keypair,
+                    pct,
+                    is_emergency || dump_rest,
+                )
+                .await
+            {
+                Ok(fill) => fill,
+                Err(e) if fills.is_empty() => return Err(e),
+                Err(cause) if dump_rest => {
+                    let filled = merge_fills(&fills).context("Venta troceada sin trozos")?;
+                    return Err(PartialSellError {
+                        filled,
+                        balance_before,
+                        cause,
+                    }
+                    .into());
+                }
+                Err(e) => {
+                    eprintln!(
+                        "⚠️ [TWAP] Trozo fallido tras {} ejecutados: {}. Vendiendo el resto de golpe.",
+                        fills.len(),
+                        e
+                    );
+                    dump_rest = true;
+                    continue;
+                }
+            };
+
+            sold = sold.saturating_add(fill.input_amount as u64);
+            if escalate && !dump_rest {
+                let first_price = fills.first().map(fill_price).unwrap_or(fill_price(&fill));
+                if should_escalate(first_price, fill_price(&fill), settings.escalate_drop_pct) {
+                    println!(
+                        "☢️ [TWAP] El precio sigue cayendo en {}. Escalando a venta completa.",
+                        token_mint
+                    );
+                    dump_rest = true;
+                }
+            }
+            fills.push(fill);
+
+            if last_slice {
+                break;
+            }
+            if !dump_rest {
+                tokio::time::sleep(std::time::Duration::from_millis(settings.interval_ms)).await;
+            }
+        }
+
+        merge_fills(&fills).context("Venta troceada sin ningún trozo ejecutado")
+    }
+
     /// Envoltorio estándar para mantener compatibilidad con API existente
     pub async fn execute_emergency_sell(
         &self,
@@ -326,158 +723,100 @@ impl TradeExecutor {
             anyhow::bail!("No hay suficiente balance para vender");
         }
 
-        // ⚡ FAST PATH: Raydium Direct Sell (Prioridad Absoluta en Emergencias)
-        // Latencia: ~50-150ms vs ~300-500ms de Jupiter
-        if let Some(raydium) = &self.raydium {
-            let min_sol_out = raydium.calculate_min_amount_out(
-                amount_to_sell,
-                active_slippage,
-            );
-
-            println!("⚡ [FAST PATH] Intentando venta directa en Raydium...");
-            match raydium
-                .execute_sell_with_jito(
-                    &token_mint,
-                    amount_to_sell,
-                    min_sol_out,
-                    active_jito_tip,
-                    keypair,
-                )
-                .await
-            {
-                Ok(sig) => {
-                    // Estimación de SOL recibido: no podemos saberlo sin confirmar la TX,
-                    // pero estimamos como fallback para el TradeRecord
-                    let estimated_sol = min_sol_out as f64 / 1_000_000_000.0;
-                    println!("✅ [RAYDIUM FAST EXIT] Sig: {}", sig);
-                    return Ok(SwapResult {
-                        signature: sig,
-                        input_amount: amount_to_sell as f64,
-                        output_amount: estimated_sol,
-                        route: "Raydium Direct".to_string(),
-                        price_impact_pct: active_slippage as f64 / 100.0,
-                        fee_sol: Self::lamports_to_sol(active_jito_tip),
-                    });
-                }
-                Err(e) => {
-                    eprintln!(
-                        "⚠️ [FAST PATH] Raydium no disponible: {}. Activando STANDARD PATH (Jupiter)...",
-                        e
-                    );
+        // 🎰 CURVE PATH: Token pre-migración → vender directo contra la bonding curve
+        if let Some(pumpfun) = &self.pumpfun {
+            if let Some(curve) = pumpfun.active_curve(&token_mint).await {
+                println!("🎰 [CURVE PATH] Token en bonding curve de Pump.fun...");
+                match pumpfun
+                    .execute_sell(
+                        &token_mint,
+                        &curve,
+                        amount_to_sell,
+                        active_slippage,
+                        self.config.priority_fee,
+                        active_jito_tip,
+                        keypair,
+                    )
+                    .await
+                {
+                    Ok(res) => {
+                        return Ok(SwapResult {
+                            signature: res.signature,
+                            input_amount: amount_to_sell as f64,
+                            output_amount: res.sol_amount as f64 / 1_000_000_000.0,
+                            route: "Pump.fun Bonding Curve".to_string(),
+                            price_impact_pct: res.price_impact_pct,
+                            fee_sol: Self::lamports_to_sol(active_jito_tip),
+                        });
+                    }
+                    Err(e) => {
+                        eprintln!(
+                            "⚠️ [CURVE PATH] Pump.fun falló: {}. Probando Raydium/Jupiter...",
+                            e
+                        );
+                    }
                 }
             }
         }
 
-        // 2. STANDARD PATH: Obtener quote de Jupiter
-        println!("🔍 Consultando Jupiter para mejor ruta...");
-
-        const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
-
-        let quote = self
-            .jupiter
-            .get_quote(
-                &token_mint,
-                SOL_MINT,
-                amount_to_sell,
-                active_slippage, // ⚡ Inyección
-            )
-            .await?;
-
-        self.jupiter.print_quote_summary(&quote);
-
-        // 3. Obtener transacción firmable
-        println!("\n🔧 Generando transacción de swap...");
-        let swap_response = self
-            .jupiter
-            .get_swap_transaction(
-                &quote,
-                &user_pubkey.to_string(),
-                true, // unwrap WSOL a SOL nativo
-            )
-            .await?;
-
-        // 4. Deserializar transacción
-        println!("🔐 Firmando transacción con keypair...");
-        let tx_bytes = general_purpose::STANDARD
-            .decode(&swap_response.swap_transaction)
-            .context("Error decodificando transacción base64")?;
-
-        let mut transaction: VersionedTransaction =
-            bincode::deserialize(&tx_bytes).context("Error deserializando transacción")?;
-
-        let recent_blockhash = self
-            .rpc_client
-            .get_latest_blockhash()
-            .context("Error obteniendo blockhash reciente")?;
-        transaction.message.set_recent_blockhash(recent_blockhash);
-        let signed_tx = VersionedTransaction::try_new(transaction.message, &[keypair])
-            .context("Error firmando transacción con keypair")?;
-
-        // 5. Enviar transacción (Standard vs Jito)
-        println!("📡 Broadcasting transacción a Solana...");
-
-        let signature_str = if active_jito_tip > 0 {
+        // Token-2022: el transfer fee se retiene al enviar, el pool solo recibe
+        // el resto. Quote, min_amount_out y PnL se calculan sobre esa cantidad
+        let pool_amount_in = self
+            .mint_info(&token_mint)
+            .map(|m| m.amount_after_transfer_fee(amount_to_sell))
+            .unwrap_or(amount_to_sell);
+        if pool_amount_in < amount_to_sell {
             println!(
-                "🛡️  Preparando Jito Bundle con Tip ({} SOL)...",
-                active_jito_tip as f64 / 1_000_000_000.0
+                "💸 Transfer fee Token-2022: {} raw retenidos al vender",
+                amount_to_sell - pool_amount_in
             );
+        }
 
-            let tip_ix = JitoClient::create_tip_instruction(&user_pubkey, active_jito_tip); // ⚡ Inyección
-            let tip_msg = solana_sdk::message::Message::new(&[tip_ix], Some(&user_pubkey));
-            let mut tip_tx = solana_sdk::transaction::Transaction::new_unsigned(tip_msg);
-            tip_tx.sign(&[keypair], recent_blockhash);
-            let versioned_tip_tx = VersionedTransaction::from(tip_tx);
-
-            let bundle = vec![signed_tx.clone(), versioned_tip_tx];
+        let request = QuoteRequest {
+            input_mint: token_mint.clone(),
+            output_mint: WSOL_MINT.to_string(),
+            amount_in: pool_amount_in,
+            slippage_bps: active_slippage, // ⚡ Inyección
+            jito_tip: active_jito_tip,     // ⚡ Inyección
+        };
 
-            match self.jito_client.send_bundle(bundle).await {
-                Ok(bundle_id) => {
-                    println!("✅ Bundle enviado a Jito. ID: {}", bundle_id);
-                    signed_tx.signatures[0].to_string()
-                }
+        // 📐 LOCAL QUOTE: reservas frescas de Geyser/WebSocket → min_amount_out sin HTTP
+        let mut executed = None;
+        if let Some((venue, mut quote)) = self.quote_locally(&request).await {
+            quote.amount_in = amount_to_sell;
+            match self.execute_quote(venue.as_ref(), &quote, keypair).await {
+                Ok(sig) => executed = Some((quote, sig)),
                 Err(e) => {
-                    eprintln!("⚠️  Jito falló: {}. Fallback a RPC standard...", e);
-                    self.send_transaction_with_retry(&signed_tx, 3)
-                        .await?
-                        .to_string()
+                    eprintln!("⚠️ [LOCAL QUOTE] {} falló: {}", venue.name(), e);
                 }
             }
-        } else {
-            self.send_transaction_with_retry(&signed_tx, 3)
-                .await?
-                .to_string()
+        }
+
+        // ⚡ VENUES: Raydium / Orca / Meteora (con Jito) y Jupiter, ordenados por SOL neto
+        let (quote, signature) = match executed {
+            Some(executed) => executed,
+            None => {
+                println!("⚡ Cotizando venues de salida...");
+                self.route_swap(&self.sell_venues, &request, amount_to_sell, keypair)
+                    .await?
+            }
         };
-        let signature = solana_sdk::signature::Signature::from_str(&signature_str)
-            .unwrap_or(solana_sdk::signature::Signature::default());
 
         println!("✅ Transacción confirmada!\n");
         println!("🔗 Signature: {}", signature);
         println!("🔗 Solscan:   https://solscan.io/tx/{}\n", signature);
 
-        // 6. Construir resultado con validación estricta
-        let sol_received =
-            FinancialValidator::parse_price_safe(&quote.out_amount, "Jupiter out_amount")?
-                / 1_000_000_000.0;
-
+        // Construir resultado con validación estricta
+        let sol_received = quote.amount_out as f64 / 1_000_000_000.0;
         FinancialValidator::validate_sol_amount(sol_received, "SOL received")?;
 
-        let price_impact = FinancialValidator::parse_price_safe(
-            &quote.price_impact_pct,
-            "Jupiter price_impact_pct",
-        )?;
-
         let result = SwapResult {
-            signature: signature.to_string(),
+            signature,
             input_amount: amount_to_sell as f64,
             output_amount: sol_received,
-            route: quote
-                .route_plan
-                .iter()
-                .map(|r| r.swap_info.label.clone())
-                .collect::<Vec<_>>()
-                .join(" → "),
-            price_impact_pct: price_impact,
-            fee_sol: Self::lamports_to_sol(active_jito_tip),
+            route: quote.route,
+            price_impact_pct: quote.price_impact_pct,
+            fee_sol: Self::lamports_to_sol(quote.fee_lamports),
         };
 
         result.print_summary();
@@ -598,10 +937,10 @@ impl TradeExecutor {
     }
 
     /// Simula una compra (dry run) - V2
-    async fn simulate_buy_v2(&self, _token_mint: &str, amount_sol: f64) -> Result<SwapResult> {
+    async fn simulate_buy_v2(&self, token_mint: &str, amount_sol: f64) -> Result<SwapResult> {
         println!("🧪 Mode: DRY RUN V2 (HFT Mock)");
         Ok(SwapResult {
-            signature: "HFT_SIMULATION".to_string(),
+            signature: Self::simulated_signature(token_mint),
             input_amount: amount_sol,
             output_amount: amount_sol * 1000.0, // Mock rate
             route: "Simulated HFT Route".to_string(),
@@ -611,7 +950,8 @@ impl TradeExecutor {
     }
 
     /// Ejecuta una compra usando SOL
-    /// Prioridad: 1. Raydium Direct (Si caché) -> 2. Jupiter (Universal)
+    /// Prioridad: 0. Pump.fun Curve (pre-migración) -> 1. Venue con mejor salida neta
+    /// (Raydium / Orca / Meteora / Jupiter)
     pub async fn execute_buy(
         &self,
         token_mint: &str,
@@ -636,192 +976,94 @@ impl TradeExecutor {
             None => anyhow::bail!("Keypair requerido para ejecución de compra"),
         };
         const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+        let jito_tip = crate::config::AppConfig::load()
+            .map(|c| c.global_settings.jito_tip_lamports)
+            .unwrap_or(100_000);
 
-        // 1. INTENTO VÍA RAYDIUM DIRECT (Prioridad Absoluta)
-        if let Some(raydium) = &self.raydium {
-            let amount_in = (amount_sol * 1_000_000_000.0) as u64;
-
-            if let Ok(pool_info) = raydium.find_pool(SOL_MINT, &token_mint).await {
-                println!("⚡ [ULTRA-FAST PATH] Pool detectado: {}", pool_info.name);
-                println!("🚀 Intentando ejecución directa en Raydium...");
-
-                let oracle_quote = self
-                    .jupiter
-                    .get_quote(SOL_MINT, &token_mint, amount_in, 500)
-                    .await;
-
-                let (estimated_out, found_oracle) = match oracle_quote {
-                    Ok(q) => (
-                        FinancialValidator::parse_amount_safe(&q.out_amount, "Jup Estimate")
-                            .unwrap_or(0),
-                        true,
-                    ),
-                    Err(_) => {
-                        println!("⚠️ [ORACLE FAIL] Jupiter no conoce el token. Usando ejecución DEGEN...");
-                        (0, false)
-                    }
-                };
-
-                let min_out = if found_oracle && estimated_out > 0 {
-                    raydium.calculate_min_amount_out(estimated_out, self.config.slippage_bps)
-                } else {
-                    1 // 1 lamport mínimo
-                };
-
-                match raydium
-                    .execute_swap(SOL_MINT, &token_mint, amount_in, min_out, keypair)
+        // 0. BONDING CURVE PUMP.FUN (tokens pre-migración que Jupiter aún no indexa)
+        if let Some(pumpfun) = &self.pumpfun {
+            if let Some(curve) = pumpfun.active_curve(&token_mint).await {
+                println!(
+                    "🎰 [CURVE PATH] Token en bonding curve. Comprando directo en Pump.fun..."
+                );
+                let amount_in = (amount_sol * 1_000_000_000.0) as u64;
+
+                match pumpfun
+                    .execute_buy(
+                        &token_mint,
+                        &curve,
+                        amount_in,
+                        self.config.slippage_bps,
+                        self.config.priority_fee,
+                        jito_tip,
+                        keypair,
+                    )
                     .await
                 {
-                    Ok(sig) => {
-                        println!("✅ RAYDIUM SUCCESS: {}", sig);
-
-                        let tokens_received = if estimated_out > 0 {
-                            estimated_out as f64 / 1_000_000.0
-                        } else {
-                            0.0
-                        };
-                        let price_per_token = if tokens_received > 0.0 {
-                            amount_sol / tokens_received
-                        } else {
-                            0.0
-                        };
-
-                        let raydium_jito = crate::config::AppConfig::load()
-                            .map(|c| c.global_settings.jito_tip_lamports)
-                            .unwrap_or(100_000);
+                    Ok(res) => {
+                        let tokens_received =
+                            res.token_amount as f64 / 10f64.powi(PUMP_TOKEN_DECIMALS as i32);
                         return Ok(BuyResult {
-                            signature: sig,
+                            signature: res.signature,
                             sol_spent: amount_sol,
                             tokens_received,
-                            price_per_token,
-                            route: format!(
-                                "Raydium Direct (Oracle: {})",
-                                if found_oracle { "OK" } else { "BYPASSED" }
-                            ),
-                            price_impact_pct: 0.0,
-                            fee_sol: Self::lamports_to_sol(raydium_jito),
+                            price_per_token: amount_sol / tokens_received,
+                            route: "Pump.fun Bonding Curve".to_string(),
+                            price_impact_pct: res.price_impact_pct,
+                            fee_sol: Self::lamports_to_sol(jito_tip),
                         });
                     }
                     Err(e) => {
-                        eprintln!("❌ Raydium Swap failed: {}. Continuing to Jupiter...", e);
+                        eprintln!(
+                            "❌ Pump.fun buy failed: {}. Continuing to Raydium/Jupiter...",
+                            e
+                        );
                     }
                 }
             }
         }
 
-        // 2. FALLBACK/STANDARD: JUPITER AGGREGATOR
-        println!("🔄 [STANDARD PATH] Ruteando vía Jupiter Aggregator...");
-
-        let user_pubkey = keypair.pubkey();
-        println!("🎯 Token:        {}", token_mint);
-
-        let amount_lamports = (amount_sol * 1_000_000_000.0) as u64;
-
-        // 1. Obtener quote de Jupiter
-        println!("🔍 Consultando Jupiter para mejor ruta...");
-
-        let quote = self
-            .jupiter
-            .get_quote(
-                SOL_MINT,
-                &token_mint,
-                amount_lamports,
-                self.config.slippage_bps,
-            )
-            .await?;
-
-        self.jupiter.print_quote_summary(&quote);
-
-        let tokens_to_receive =
-            FinancialValidator::parse_price_safe(&quote.out_amount, "Jupiter tokens to receive")?;
-
-        if tokens_to_receive <= 0.0 {
-            anyhow::bail!("Jupiter quote inválido: 0 tokens a recibir");
-        }
-
-        let price_per_token = amount_sol / tokens_to_receive;
-        let price_impact = quote.price_impact_pct.parse::<f64>().unwrap_or(0.0);
-
-        println!("\n🔧 Generando transacción de swap...");
-        let swap_response = self
-            .jupiter
-            .get_swap_transaction(&quote, &user_pubkey.to_string(), true)
+        // 1. VENUES (Raydium / Orca / Meteora directos y Jupiter), mejor salida neta primero
+        println!("⚡ Cotizando venues de compra...");
+        let request = QuoteRequest {
+            input_mint: SOL_MINT.to_string(),
+            output_mint: token_mint.clone(),
+            amount_in: (amount_sol * 1_000_000_000.0) as u64,
+            slippage_bps: self.config.slippage_bps,
+            jito_tip,
+        };
+        let (quote, signature) = self
+            .route_swap(&self.buy_venues, &request, request.amount_in, keypair)
             .await?;
 
-        println!("🔐 Firmando transacción con keypair...");
-        let tx_bytes = general_purpose::STANDARD
-            .decode(&swap_response.swap_transaction)
-            .context("Error decodificando transacción base64")?;
-
-        let mut transaction: VersionedTransaction =
-            bincode::deserialize(&tx_bytes).context("Error deserializando transacción")?;
-
-        let recent_blockhash = self
-            .rpc_client
-            .get_latest_blockhash()
-            .context("Error obteniendo blockhash reciente")?;
-        transaction.message.set_recent_blockhash(recent_blockhash);
-        let signed_tx = VersionedTransaction::try_new(transaction.message, &[keypair])
-            .context("Error firmando transacción con keypair")?;
-
-        println!("📡 Broadcasting transacción a Solana...");
-        // ⚡ DYNAMIC FEE: Obtener fee óptimo de Helius en tiempo real
-        let dynamic_priority_fee = self.get_dynamic_priority_fee().await;
-        let jito_tip_lamports = crate::config::AppConfig::load()
-            .map(|c| c.global_settings.jito_tip_lamports)
-            .unwrap_or(100_000);
+        println!("✅ Compra confirmada!\n");
+        println!("🔗 Signature: {}", signature);
 
-        let signature_str = if jito_tip_lamports > 0 {
+        // Token-2022: el transfer fee se retiene al recibir, el coste de entrada
+        // se calcula sobre los tokens que realmente llegan a la wallet
+        let mint_info = self.mint_info(&token_mint);
+        let decimals = mint_info.as_ref().map(|m| m.decimals).unwrap_or(6);
+        let received_raw = mint_info
+            .as_ref()
+            .map(|m| m.amount_after_transfer_fee(quote.amount_out))
+            .unwrap_or(quote.amount_out);
+        if received_raw < quote.amount_out {
             println!(
-                "🛡️  Preparando Jito Bundle con Tip ({} SOL)...",
-                jito_tip_lamports as f64 / 1_000_000_000.0
+                "💸 Transfer fee Token-2022: {} raw retenidos",
+                quote.amount_out - received_raw
             );
-            let tip_ix = JitoClient::create_tip_instruction(&user_pubkey, jito_tip_lamports);
-            let tip_msg = solana_sdk::message::Message::new(&[tip_ix], Some(&user_pubkey));
-            let mut tip_tx = solana_sdk::transaction::Transaction::new_unsigned(tip_msg);
-            tip_tx.sign(&[keypair], recent_blockhash);
-
-            let bundle = vec![signed_tx.clone(), VersionedTransaction::from(tip_tx)];
-
-            match self.jito_client.send_bundle(bundle).await {
-                Ok(bundle_id) => {
-                    println!("✅ Bundle enviado a Jito. ID: {}", bundle_id);
-                    signed_tx.signatures[0].to_string()
-                }
-                Err(e) => {
-                    eprintln!("⚠️  Jito falló: {}. Fallback a RPC standard...", e);
-                    self.send_transaction_with_retry(&signed_tx, 3)
-                        .await?
-                        .to_string()
-                }
-            }
-        } else {
-            self.send_transaction_with_retry(&signed_tx, 3)
-                .await?
-                .to_string()
-        };
-        let signature = solana_sdk::signature::Signature::from_str(&signature_str)
-            .unwrap_or(solana_sdk::signature::Signature::default());
-
-        println!("✅ Compra confirmada!\n");
-        println!("🔗 Signature: {}", signature);
+        }
+        let tokens_received = received_raw as f64 / 10f64.powi(decimals as i32);
 
-        let result = BuyResult {
-            signature: signature.to_string(),
+        Ok(BuyResult {
+            signature,
             sol_spent: amount_sol,
-            tokens_received: tokens_to_receive,
-            price_per_token,
-            route: quote
-                .route_plan
-                .iter()
-                .map(|r| r.swap_info.label.clone())
-                .collect::<Vec<_>>()
-                .join(" → "),
-            price_impact_pct: price_impact,
-            fee_sol: Self::lamports_to_sol(jito_tip_lamports) + Self::microlamports_to_sol(dynamic_priority_fee),
-        };
-
-        Ok(result)
+            tokens_received,
+            price_per_token: amount_sol / tokens_received,
+            route: quote.route,
+            price_impact_pct: quote.price_impact_pct,
+            fee_sol: Self::lamports_to_sol(quote.fee_lamports),
+        })
     }
 
     /// Simula una compra (dry run)
@@ -836,7 +1078,7 @@ impl TradeExecutor {
         println!("   ✗ Transacción NO enviada\n");
 
         Ok(BuyResult {
-            signature: "SIMULATION_ONLY".to_string(),
+            signature: Self::simulated_signature(token_mint),
             sol_spent: amount_sol,
             tokens_received: 100000.0,
             price_per_token: amount_sol / 100000.0,
@@ -914,11 +1156,7 @@ impl TradeExecutor {
 
         self.log_simulated_trade(token_mint, output_sol)?;
 
-        let sig = format!(
-            "SIM_{}_{}",
-            token_mint,
-            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
-        );
+        let sig = Self::simulated_signature(token_mint);
 
         Ok(SwapResult {
             signature: sig,
@@ -1085,8 +1323,9 @@ impl TradeExecutor {
     fn get_token_account_balance(&self, wallet: &Pubkey, mint: &str) -> Result<(Pubkey, u64)> {
         let mint_pubkey = Pubkey::from_str(mint).context("Token mint inválido")?;
 
-        let token_account =
-            spl_associated_token_account::get_associated_token_address(wallet, &mint_pubkey);
+        // El ATA depende del token program del mint (SPL clásico o Token-2022)
+        let token_program = mint_token_program(&self.rpc_client, &mint_pubkey);
+        let token_account = associated_token_address(wallet, &mint_pubkey, &token_program);
 
         let mut retries = 5;
         let mut last_error = None;
@@ -1094,9 +1333,10 @@ impl TradeExecutor {
         while retries > 0 {
             match self.rpc_client.get_account(&token_account) {
                 Ok(account_data) => {
-                    let token_account_state = TokenAccount::unpack(&account_data.data)
-                        .context("Error parseando token account")?;
-                    return Ok((token_account, token_account_state.amount));
+                    let amount =
+                        parse_token_account_amount(&account_data.owner, &account_data.data)
+                            .context("Error parseando token account")?;
+                    return Ok((token_account, amount));
                 }
                 Err(e) => {
                     last_error = Some(e);
@@ -1163,6 +1403,12 @@ impl TradeExecutor {
         }
     }
 
+    /// Balance raw del token en la wallet (None si el RPC no responde)
+    pub fn token_balance(&self, wallet: &Pubkey, token_mint: &str) -> Option<u64> {
+        let mint = Pubkey::from_str(token_mint).ok()?;
+        fetch_token_balance(&self.rpc_client, wallet, &mint).ok()
+    }
+
     /// Ejecuta una compra DEGENERATE (Pure Raydium, Zero Safety Check)
     pub async fn execute_raydium_buy(
         &self,
@@ -1281,6 +1527,15 @@ mod tests {
         assert_eq!(TradeExecutor::lamports_to_sol(500_000_000), 0.5);
     }
 
+    #[test]
+    fn test_simulated_signatures_are_unique() {
+        let mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let first = TradeExecutor::simulated_signature(mint);
+        let second = TradeExecutor::simulated_signature(mint);
+        assert!(first.starts_with(&format!("SIM_{}_", mint)));
+        assert_ne!(first, second);
+    }
+
     #[test]
     fn test_microlamports_to_sol() {
         assert_eq!(TradeExecutor::microlamports_to_sol(1_000_000_000_000), 1.0);
@@ -56,6 +56,113 @@ pub struct AuditResponse {
     #[prost(double, tag = "3")]
     pub lp_locked_pct: f64,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamRequest {
+    /// Filtro por mint (vacío = todos los tokens)
+    #[prost(string, repeated, tag = "1")]
+    pub token_mints: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PriceTick {
+    #[prost(string, tag = "1")]
+    pub token_mint: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub symbol: ::prost::alloc::string::String,
+    #[prost(double, tag = "3")]
+    pub price_usd: f64,
+    #[prost(double, tag = "4")]
+    pub price_native: f64,
+    #[prost(double, tag = "5")]
+    pub liquidity_usd: f64,
+    #[prost(double, tag = "6")]
+    pub volume_24h: f64,
+    #[prost(double, tag = "7")]
+    pub price_change_24h: f64,
+    /// "Geyser", "WebSocket" o "DexScreener"
+    #[prost(string, tag = "8")]
+    pub source: ::prost::alloc::string::String,
+    #[prost(int64, tag = "9")]
+    pub timestamp_ms: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExecutionEvent {
+    #[prost(string, tag = "1")]
+    pub token_mint: ::prost::alloc::string::String,
+    /// CommandType del router (ej: "StopLoss")
+    #[prost(string, tag = "2")]
+    pub command_type: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub success: bool,
+    /// Motivo del fallo (vacío si success)
+    #[prost(string, tag = "4")]
+    pub reason: ::prost::alloc::string::String,
+    #[prost(int64, tag = "5")]
+    pub timestamp_ms: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DecisionEvent {
+    #[prost(string, tag = "1")]
+    pub token_mint: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub symbol: ::prost::alloc::string::String,
+    #[prost(enumeration = "DecisionKind", tag = "3")]
+    pub decision: i32,
+    /// RejectionReason (solo si REJECTED)
+    #[prost(string, tag = "4")]
+    pub rejection_reason: ::prost::alloc::string::String,
+    #[prost(double, tag = "5")]
+    pub confidence: f64,
+    #[prost(uint32, tag = "6")]
+    pub slippage_bps: u32,
+    #[prost(uint64, tag = "7")]
+    pub priority_fee_lamports: u64,
+    /// SellReason (solo si EXECUTE_SELL)
+    #[prost(string, tag = "8")]
+    pub sell_reason: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "9")]
+    pub sell_amount_percent: u32,
+    #[prost(int64, tag = "10")]
+    pub timestamp_ms: i64,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DecisionKind {
+    Unspecified = 0,
+    ExecuteBuy = 1,
+    ExecuteSell = 2,
+    Rejected = 3,
+    Hold = 4,
+}
+impl DecisionKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            DecisionKind::Unspecified => "DECISION_KIND_UNSPECIFIED",
+            DecisionKind::ExecuteBuy => "DECISION_KIND_EXECUTE_BUY",
+            DecisionKind::ExecuteSell => "DECISION_KIND_EXECUTE_SELL",
+            DecisionKind::Rejected => "DECISION_KIND_REJECTED",
+            DecisionKind::Hold => "DECISION_KIND_HOLD",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "DECISION_KIND_UNSPECIFIED" => Some(Self::Unspecified),
+            "DECISION_KIND_EXECUTE_BUY" => Some(Self::ExecuteBuy),
+            "DECISION_KIND_EXECUTE_SELL" => Some(Self::ExecuteSell),
+            "DECISION_KIND_REJECTED" => Some(Self::Rejected),
+            "DECISION_KIND_HOLD" => Some(Self::Hold),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 pub mod chassis_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -185,6 +292,75 @@ pub mod chassis_service_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// Streaming de ticks del PriceFeed
+        pub async fn stream_prices(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamRequest>,
+        ) -> Result<
+            tonic::Response<tonic::codec::Streaming<super::PriceTick>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/chassis.ChassisService/StreamPrices",
+            );
+            self.inner.server_streaming(request.into_request(), path, codec).await
+        }
+        /// Streaming de fills / fallos del ExecutionRouter
+        pub async fn stream_executions(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamRequest>,
+        ) -> Result<
+            tonic::Response<tonic::codec::Streaming<super::ExecutionEvent>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/chassis.ChassisService/StreamExecutions",
+            );
+            self.inner.server_streaming(request.into_request(), path, codec).await
+        }
+        /// Streaming de decisiones del DecisionEngine
+        pub async fn stream_decisions(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamRequest>,
+        ) -> Result<
+            tonic::Response<tonic::codec::Streaming<super::DecisionEvent>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/chassis.ChassisService/StreamDecisions",
+            );
+            self.inner.server_streaming(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -209,6 +385,39 @@ pub mod chassis_service_server {
             &self,
             request: tonic::Request<super::AuditRequest>,
         ) -> Result<tonic::Response<super::AuditResponse>, tonic::Status>;
+        /// Server streaming response type for the StreamPrices method.
+        type StreamPricesStream: futures_core::Stream<
+                Item = Result<super::PriceTick, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// Streaming de ticks del PriceFeed
+        async fn stream_prices(
+            &self,
+            request: tonic::Request<super::StreamRequest>,
+        ) -> Result<tonic::Response<Self::StreamPricesStream>, tonic::Status>;
+        /// Server streaming response type for the StreamExecutions method.
+        type StreamExecutionsStream: futures_core::Stream<
+                Item = Result<super::ExecutionEvent, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// Streaming de fills / fallos del ExecutionRouter
+        async fn stream_executions(
+            &self,
+            request: tonic::Request<super::StreamRequest>,
+        ) -> Result<tonic::Response<Self::StreamExecutionsStream>, tonic::Status>;
+        /// Server streaming response type for the StreamDecisions method.
+        type StreamDecisionsStream: futures_core::Stream<
+                Item = Result<super::DecisionEvent, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// Streaming de decisiones del DecisionEngine
+        async fn stream_decisions(
+            &self,
+            request: tonic::Request<super::StreamRequest>,
+        ) -> Result<tonic::Response<Self::StreamDecisionsStream>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct ChassisServiceServer<T: ChassisService> {
@@ -387,6 +596,129 @@ pub mod chassis_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/chassis.ChassisService/StreamPrices" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamPricesSvc<T: ChassisService>(pub Arc<T>);
+                    impl<
+                        T: ChassisService,
+                    > tonic::server::ServerStreamingService<super::StreamRequest>
+                    for StreamPricesSvc<T> {
+                        type Response = super::PriceTick;
+                        type ResponseStream = T::StreamPricesStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).stream_prices(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StreamPricesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/chassis.ChassisService/StreamExecutions" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamExecutionsSvc<T: ChassisService>(pub Arc<T>);
+                    impl<
+                        T: ChassisService,
+                    > tonic::server::ServerStreamingService<super::StreamRequest>
+                    for StreamExecutionsSvc<T> {
+                        type Response = super::ExecutionEvent;
+                        type ResponseStream = T::StreamExecutionsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).stream_executions(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StreamExecutionsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/chassis.ChassisService/StreamDecisions" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamDecisionsSvc<T: ChassisService>(pub Arc<T>);
+                    impl<
+                        T: ChassisService,
+                    > tonic::server::ServerStreamingService<super::StreamRequest>
+                    for StreamDecisionsSvc<T> {
+                        type Response = super::DecisionEvent;
+                        type ResponseStream = T::StreamDecisionsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).stream_decisions(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StreamDecisionsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -0,0 +1,682 @@
+//! # gRPC Control Plane (ChassisService)
+//!
+//! Sirve el `ChassisService` definido en `proto/chassis.proto` desde el proceso
+//! de monitoreo. Es el canal tipado para el tooling Python (auditorías, compras
+//! y lectura de posiciones) en lugar de parsear mensajes de Telegram.
+//!
+//! Corre en paralelo al `TelemetryServer` (WebSocket 9001) y comparte con él
+//! el `StateManager` y el `PriceCache`. Los RPCs `Stream*` reemiten los eventos
+//! del `EventBus` (ticks, fills y decisiones) con la granularidad original,
+//! sin el muestreo a 1 Hz del `TelemetryTick`.
+//!
+//! Con un `AutoBuyer` conectado (`with_auto_buyer`), las compras de
+//! `ExecuteTrade` pasan antes por el `DecisionEngine`: un token rechazado por
+//! los filtros no se compra y la decisión sale por `StreamDecisions`. Las
+//! compras se arman con las salidas de `settings.json` (`exits`); las ventas
+//! van al `ExecutionRouter` como `ManualSell`, con su journal y su bloqueo por
+//! mint, así no se cruzan con un SL/TP del engine.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use solana_sdk::signature::Keypair;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use crate::auto_buyer::{AutoBuyConfig, AutoBuyer};
+use crate::config::ExitSettings;
+use crate::engine::commands::{ExecutionCommand, ExecutionFeedback};
+use crate::engine::events::{DecisionEvent as EngineDecisionEvent, DecisionOutcome, EventBus};
+use crate::engine::router::ExecutionRouter;
+use crate::engine::tp_ladder;
+use crate::executor_v2::TradeExecutor;
+use crate::generated::chassis::chassis_service_server::{ChassisService, ChassisServiceServer};
+use crate::generated::chassis::{
+    AuditRequest, AuditResponse, DecisionEvent, DecisionKind, Empty, ExecutionEvent, Position,
+    PositionsResponse, PriceTick, StreamRequest, TradeRequest, TradeResponse,
+};
+use crate::jupiter::{BuyResult, SwapResult};
+use crate::price_feed::{FeedCommand, MonitoredToken, PriceCache, PriceUpdate};
+use crate::sensors::helius::{HeliusSensor, OnChainAnalysis};
+use crate::state_manager::{ExitRules, PositionState, StateManager, TradeRecord};
+use crate::telegram::commands::CommandHandler;
+
+/// Dirección por defecto del servidor gRPC (override con `CHASSIS_GRPC_ADDR`)
+pub const DEFAULT_GRPC_ADDR: &str = "127.0.0.1:50051";
+
+/// Buffer por cliente de streaming antes de aplicar backpressure
+const STREAM_BUFFER: usize = 256;
+
+pub struct ChassisGrpcServer {
+    state_manager: Arc<StateManager>,
+    price_cache: PriceCache,
+    executor: Arc<TradeExecutor>,
+    router: Arc<ExecutionRouter>,
+    helius: HeliusSensor,
+    wallet_kp: Option<Arc<Keypair>>,
+    feed_tx: mpsc::Sender<FeedCommand>,
+    events: Arc<EventBus>,
+    auto_buyer: Option<Arc<AutoBuyer>>,
+    exits: ExitSettings,
+}
+
+impl ChassisGrpcServer {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        state_manager: Arc<StateManager>,
+        price_cache: PriceCache,
+        executor: Arc<TradeExecutor>,
+        router: Arc<ExecutionRouter>,
+        rpc_url: String,
+        wallet_kp: Option<Keypair>,
+        feed_tx: mpsc::Sender<FeedCommand>,
+        events: Arc<EventBus>,
+    ) -> Self {
+        Self {
+            state_manager,
+            price_cache,
+            executor,
+            router,
+            helius: HeliusSensor::new(rpc_url),
+            wallet_kp: wallet_kp.map(Arc::new),
+            feed_tx,
+            events,
+            auto_buyer: None,
+            exits: ExitSettings::default(),
+        }
+    }
+
+    /// Filtra las compras con el `DecisionEngine` del AutoBuyer
+    pub fn with_auto_buyer(mut self, buyer: Arc<AutoBuyer>) -> Self {
+        self.auto_buyer = Some(buyer);
+        self
+    }
+
+    /// Salidas con las que se arman las compras de `ExecuteTrade`
+    pub fn with_exits(mut self, exits: ExitSettings) -> Self {
+        self.exits = exits;
+        self
+    }
+
+    /// Levanta el servidor tonic. Bloquea hasta que el transporte se cae.
+    pub async fn run(self, addr: &str) -> anyhow::Result<()> {
+        let addr: SocketAddr = addr.parse()?;
+        println!("🛰️  [GRPC] ChassisService online en {}", addr);
+
+        tonic::transport::Server::builder()
+            .add_service(ChassisServiceServer::new(self))
+            .serve(addr)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn buy(&self, mint: &str, amount_sol: f64) -> anyhow::Result<String> {
+        if amount_sol <= 0.0 {
+            anyhow::bail!("amount_sol debe ser > 0");
+        }
+        let exits = &self.exits;
+        let tp_ladder = tp_ladder::parse_ladder(&exits.tp_ladder)?;
+
+        let kp = self.wallet_kp.as_deref();
+        let res = match &self.auto_buyer {
+            Some(buyer) => {
+                let config = AutoBuyConfig {
+                    token_mint: mint.to_string(),
+                    symbol: None,
+                    amount_sol,
+                    slippage_bps: 200,
+                    add_to_monitoring: true,
+                    stop_loss_percent: exits.stop_loss_percent,
+                    trailing_enabled: exits.trailing_enabled,
+                    fast_mode: false,
+                };
+                // Slippage y priority fee los fija el DecisionEngine según la madurez del token
+                let params = buyer.evaluate(&config).await?;
+                let swap = self
+                    .executor
+                    .execute_buy_with_custom_params(
+                        mint,
+                        kp,
+                        amount_sol,
+                        params.priority_fee_lamports,
+                        params.slippage_bps,
+                    )
+                    .await?;
+                buy_result_from_swap(swap)?
+            }
+            None => self.executor.execute_buy(mint, kp, amount_sol).await?,
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let pos = PositionState {
+            id: None,
+            token_mint: mint.to_string(),
+            symbol: "GRPC".to_string(),
+            entry_price: res.price_per_token,
+            current_price: res.price_per_token,
+            amount_sol: res.sol_spent,
+            stop_loss_percent: exits.stop_loss_percent,
+            trailing_enabled: exits.trailing_enabled,
+            trailing_distance_percent: exits.trailing_distance_percent,
+            trailing_activation_threshold: exits.trailing_activation_threshold,
+            trailing_highest_price: Some(res.price_per_token),
+            trailing_current_sl: Some(exits.stop_loss_percent),
+            tp_ladder,
+            active: true,
+            created_at: now,
+            updated_at: now,
+            token_amount: 0.0,
+            realized_pnl_sol: 0.0,
+            exit_rules: ExitRules::default(),
+        };
+        if let Err(e) = self
+            .state_manager
+            .record_buy_fill(pos, &res.signature, res.tokens_received, res.sol_spent)
+            .await
+        {
+            eprintln!("❌ [GRPC] DB ERROR guardando posición {}: {}", mint, e);
+        }
+
+        let trade = TradeRecord {
+            id: None,
+            signature: res.signature.clone(),
+            token_mint: mint.to_string(),
+            symbol: "GRPC".to_string(),
+            trade_type: "MANUAL_BUY".to_string(),
+            amount_sol: res.sol_spent,
+            tokens_amount: res.tokens_received,
+            price: res.price_per_token,
+            pnl_sol: None,
+            pnl_percent: None,
+            route: format!("gRPC {}", res.route),
+            price_impact_pct: res.price_impact_pct,
+            fee_sol: res.fee_sol,
+            timestamp: now,
+        };
+        if let Err(e) = self.state_manager.record_trade(trade).await {
+            eprintln!("❌ [GRPC] DB ERROR registrando compra {}: {}", mint, e);
+        }
+
+        // Suscripción dinámica para que el StrategyEngine vigile la posición
+        let _ = self
+            .feed_tx
+            .send(FeedCommand::Subscribe(MonitoredToken {
+                mint: mint.to_string(),
+                symbol: "GRPC".to_string(),
+                pool_account: None,
+                coin_vault: None,
+                pc_vault: None,
+                token_decimals: 6,
+            }))
+            .await;
+
+        Ok(res.signature)
+    }
+
+    /// Venta manual. `amount_sol` se interpreta como la porción del capital
+    /// invertido a liquidar (0 = 100% de la posición). La ejecuta el router:
+    /// journal, reintentos, fill en los lotes y registro del trade.
+    async fn sell(&self, mint: &str, amount_sol: f64) -> anyhow::Result<String> {
+        let position = self
+            .state_manager
+            .get_position(mint)
+            .await?
+            .filter(|p| p.active)
+            .ok_or_else(|| anyhow::anyhow!("No hay posición activa para {}", mint))?;
+
+        self.router
+            .execute(ExecutionCommand::ManualSell {
+                mint: mint.to_string(),
+                symbol: position.symbol.clone(),
+                sell_amount_pct: sell_percent(amount_sol, position.amount_sol),
+                amount_invested: position.amount_sol,
+            })
+            .await
+    }
+}
+
+#[tonic::async_trait]
+impl ChassisService for ChassisGrpcServer {
+    async fn get_positions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<PositionsResponse>, Status> {
+        let positions = self
+            .state_manager
+            .get_active_positions()
+            .await
+            .map_err(|e| Status::internal(format!("DB Fault: {}", e)))?;
+
+        let cache = self.price_cache.read().await;
+        let positions = positions
+            .into_iter()
+            .map(|pos| {
+                let live = cache
+                    .get(&pos.token_mint)
+                    .map(|p| p.price_native)
+                    .filter(|p| *p > 0.0);
+                to_proto_position(&pos, live)
+            })
+            .collect();
+
+        Ok(Response::new(PositionsResponse { positions }))
+    }
+
+    async fn execute_trade(
+        &self,
+        request: Request<TradeRequest>,
+    ) -> Result<Response<TradeResponse>, Status> {
+        let req = request.into_inner();
+
+        let mint = crate::validation::FinancialValidator::validate_mint(&req.token_mint, "gRPC ExecuteTrade")
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let side = req.side.to_uppercase();
+        if side == "BUY" && CommandHandler::is_hibernating() {
+            return Err(Status::failed_precondition("Bot en HIBERNACIÓN"));
+        }
+        if side == "BUY" && crate::circuit_breaker::buys_halted() {
+            return Err(Status::failed_precondition(
+                "Circuit Breaker activo: compras bloqueadas",
+            ));
+        }
+
+        println!("🛰️  [GRPC] ExecuteTrade {} {} ({} SOL)", side, mint, req.amount_sol);
+
+        let result = match side.as_str() {
+            "BUY" => self.buy(&mint, req.amount_sol).await,
+            "SELL" => self.sell(&mint, req.amount_sol).await,
+            other => {
+                return Err(Status::invalid_argument(format!(
+                    "side inválido '{}': usa BUY o SELL",
+                    other
+                )))
+            }
+        };
+
+        let response = match result {
+            Ok(signature) => TradeResponse {
+                success: true,
+                signature,
+                error_message: String::new(),
+            },
+            Err(e) => TradeResponse {
+                success: false,
+                signature: String::new(),
+                error_message: e.to_string(),
+            },
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_token_audit(
+        &self,
+        request: Request<AuditRequest>,
+    ) -> Result<Response<AuditResponse>, Status> {
+        let mint = request.into_inner().token_mint;
+        crate::validation::FinancialValidator::validate_mint(&mint, "gRPC GetTokenAudit")
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let analysis = self
+            .helius
+            .analyze_token_full(&mint)
+            .await
+            .map_err(|e| Status::unavailable(format!("Helius: {}", e)))?;
+
+        Ok(Response::new(audit_from_analysis(&analysis)))
+    }
+
+    type StreamPricesStream = ReceiverStream<Result<PriceTick, Status>>;
+
+    async fn stream_prices(
+        &self,
+        request: Request<StreamRequest>,
+    ) -> Result<Response<Self::StreamPricesStream>, Status> {
+        let mints = request.into_inner().token_mints;
+        let rx = self.events.subscribe_prices();
+
+        Ok(Response::new(forward(rx, move |update: PriceUpdate| {
+            wants(&mints, &update.token_mint).then(|| to_price_tick(&update))
+        })))
+    }
+
+    type StreamExecutionsStream = ReceiverStream<Result<ExecutionEvent, Status>>;
+
+    async fn stream_executions(
+        &self,
+        request: Request<StreamRequest>,
+    ) -> Result<Response<Self::StreamExecutionsStream>, Status> {
+        let mints = request.into_inner().token_mints;
+        let rx = self.events.subscribe_executions();
+
+        Ok(Response::new(forward(rx, move |feedback: ExecutionFeedback| {
+            let event = to_execution_event(&feedback);
+            wants(&mints, &event.token_mint).then_some(event)
+        })))
+    }
+
+    type StreamDecisionsStream = ReceiverStream<Result<DecisionEvent, Status>>;
+
+    async fn stream_decisions(
+        &self,
+        request: Request<StreamRequest>,
+    ) -> Result<Response<Self::StreamDecisionsStream>, Status> {
+        let mints = request.into_inner().token_mints;
+        let rx = self.events.subscribe_decisions();
+
+        Ok(Response::new(forward(rx, move |decision: EngineDecisionEvent| {
+            wants(&mints, &decision.mint).then(|| to_decision_event(&decision))
+        })))
+    }
+}
+
+/// Puente broadcast → stream gRPC. Termina cuando el cliente se desconecta.
+fn forward<T, M, F>(mut rx: broadcast::Receiver<T>, map: F) -> ReceiverStream<Result<M, Status>>
+where
+    T: Clone + Send + 'static,
+    M: Send + 'static,
+    F: Fn(T) -> Option<M> + Send + 'static,
+{
+    let (tx, out_rx) = mpsc::channel(STREAM_BUFFER);
+
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if let Some(msg) = map(event) {
+                        if tx.send(Ok(msg)).await.is_err() {
+                            break; // Cliente desconectado
+                        }
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("⚠️ [GRPC] Cliente lento: {} eventos descartados", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    ReceiverStream::new(out_rx)
+}
+
+fn wants(filter: &[String], mint: &str) -> bool {
+    filter.is_empty() || filter.iter().any(|m| m == mint)
+}
+
+fn to_price_tick(update: &PriceUpdate) -> PriceTick {
+    PriceTick {
+        token_mint: update.token_mint.clone(),
+        symbol: update.symbol.clone(),
+        price_usd: update.price_usd,
+        price_native: update.price_native,
+        liquidity_usd: update.liquidity_usd,
+        volume_24h: update.volume_24h,
+        price_change_24h: update.price_change_24h,
+        source: format!("{:?}", update.source),
+        timestamp_ms: received_at_ms(update),
+    }
+}
+
+/// Hora de pared (ms Unix) a la que el feed recibió el tick
+fn received_at_ms(update: &PriceUpdate) -> i64 {
+    chrono::Utc::now().timestamp_millis() - update.received_at.elapsed().as_millis() as i64
+}
+
+fn to_execution_event(feedback: &ExecutionFeedback) -> ExecutionEvent {
+    let timestamp_ms = chrono::Utc::now().timestamp_millis();
+    match feedback {
+        ExecutionFeedback::Success { mint, command_type } => ExecutionEvent {
+            token_mint: mint.clone(),
+            command_type: format!("{:?}", command_type),
+            success: true,
+            reason: String::new(),
+            timestamp_ms,
+        },
+        ExecutionFeedback::Failure {
+            mint,
+            command_type,
+            reason,
+        } => ExecutionEvent {
+            token_mint: mint.clone(),
+            command_type: format!("{:?}", command_type),
+            success: false,
+            reason: reason.clone(),
+            timestamp_ms,
+        },
+    }
+}
+
+fn to_decision_event(decision: &EngineDecisionEvent) -> DecisionEvent {
+    let mut event = DecisionEvent {
+        token_mint: decision.mint.clone(),
+        symbol: decision.symbol.clone(),
+        timestamp_ms: decision.timestamp_ms,
+        ..Default::default()
+    };
+
+    match &decision.outcome {
+        DecisionOutcome::ExecuteBuy {
+            confidence,
+            slippage_bps,
+            priority_fee_lamports,
+        } => {
+            event.decision = DecisionKind::ExecuteBuy as i32;
+            event.confidence = *confidence;
+            event.slippage_bps = *slippage_bps as u32;
+            event.priority_fee_lamports = *priority_fee_lamports;
+        }
+        DecisionOutcome::ExecuteSell {
+            reason,
+            amount_percent,
+        } => {
+            event.decision = DecisionKind::ExecuteSell as i32;
+            event.sell_reason = format!("{:?}", reason);
+            event.sell_amount_percent = *amount_percent as u32;
+        }
+        DecisionOutcome::Rejected(reason) => {
+            event.decision = DecisionKind::Rejected as i32;
+            event.rejection_reason = reason.to_string();
+        }
+        DecisionOutcome::Hold => {
+            event.decision = DecisionKind::Hold as i32;
+        }
+    }
+
+    event
+}
+
+fn to_proto_position(pos: &PositionState, live_price: Option<f64>) -> Position {
+    let current_price = live_price.unwrap_or(pos.current_price);
+    let pnl_percent = if pos.entry_price > 0.0 {
+        ((current_price - pos.entry_price) / pos.entry_price) * 100.0
+    } else {
+        0.0
+    };
+
+    Position {
+        symbol: pos.symbol.clone(),
+        entry_price: pos.entry_price,
+        current_price,
+        pnl_percent,
+    }
+}
+
+/// Fill de `execute_buy_with_custom_params` en el formato de `execute_buy`
+fn buy_result_from_swap(swap: SwapResult) -> anyhow::Result<BuyResult> {
+    if swap.output_amount <= 0.0 {
+        anyhow::bail!("Compra sin tokens recibidos (tx {})", swap.signature);
+    }
+    Ok(BuyResult {
+        price_per_token: swap.input_amount / swap.output_amount,
+        signature: swap.signature,
+        sol_spent: swap.input_amount,
+        tokens_received: swap.output_amount,
+        route: swap.route,
+        price_impact_pct: swap.price_impact_pct,
+        fee_sol: swap.fee_sol,
+    })
+}
+
+fn sell_percent(amount_sol: f64, invested: f64) -> u8 {
+    if amount_sol <= 0.0 || invested <= 0.0 || amount_sol >= invested {
+        return 100;
+    }
+    ((amount_sol / invested) * 100.0).round().clamp(1.0, 100.0) as u8
+}
+
+/// Score 0-100 derivado de los mismos criterios que usan los filtros del DecisionEngine
+fn audit_from_analysis(analysis: &OnChainAnalysis) -> AuditResponse {
+    let mut score: i32 = 100;
+
+    if analysis.security.mint_authority.is_some() {
+        score -= 35;
+    }
+    if analysis.security.freeze_authority.is_some() {
+        score -= 35;
+    }
+    if analysis.top_10_holders_pct > 20.0 {
+        score -= 15;
+    }
+    if analysis.dev_wallet_pct > 5.0 {
+        score -= 10;
+    }
+    if analysis.unique_wallets_ratio < 0.20 {
+        score -= 5;
+    }
+    // Token-2022: extensiones que permiten confiscar o bloquear la venta
+    if analysis.security.permanent_delegate.is_some() || analysis.security.non_transferable {
+        score -= 35;
+    }
+    if analysis.security.transfer_hook_program.is_some() {
+        score -= 20;
+    }
+    let score = score.max(0);
+
+    let verdict = if score >= 70 {
+        "SAFE"
+    } else if score >= 40 {
+        "CAUTION"
+    } else {
+        "DANGER"
+    };
+
+    AuditResponse {
+        verdict: verdict.to_string(),
+        score,
+        // El estado del LP no se mide todavía on-chain
+        lp_locked_pct: 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::helius::OnChainSecurityData;
+
+    fn analysis(mint_auth: bool, freeze_auth: bool, top10: f64) -> OnChainAnalysis {
+        OnChainAnalysis {
+            security: OnChainSecurityData {
+                mint: "Mint111".to_string(),
+                decimals: 6,
+                supply: 1_000_000,
+                mint_authority: mint_auth.then(|| "Auth".to_string()),
+                freeze_authority: freeze_auth.then(|| "Auth".to_string()),
+                is_initialized: true,
+                token_program: spl_token::id().to_string(),
+                transfer_fee_bps: 0,
+                permanent_delegate: None,
+                transfer_hook_program: None,
+                non_transferable: false,
+            },
+            estimated_age_minutes: 30,
+            top_10_holders_pct: top10,
+            dev_wallet_pct: 1.0,
+            unique_wallets_ratio: 0.8,
+            total_holders: 500,
+        }
+    }
+
+    #[test]
+    fn test_audit_verdicts() {
+        let clean = audit_from_analysis(&analysis(false, false, 10.0));
+        assert_eq!(clean.score, 100);
+        assert_eq!(clean.verdict, "SAFE");
+
+        let rug = audit_from_analysis(&analysis(true, true, 60.0));
+        assert_eq!(rug.score, 15);
+        assert_eq!(rug.verdict, "DANGER");
+
+        let mut delegated = analysis(false, false, 10.0);
+        delegated.security.permanent_delegate = Some("Delegate".to_string());
+        let delegated = audit_from_analysis(&delegated);
+        assert_eq!(delegated.score, 65);
+        assert_eq!(delegated.verdict, "CAUTION");
+    }
+
+    #[test]
+    fn test_decision_event_mapping() {
+        let rejected = EngineDecisionEvent::new(
+            "Mint111",
+            "TEST",
+            DecisionOutcome::Rejected(crate::engine::RejectionReason::HighConcentration),
+        );
+        let event = to_decision_event(&rejected);
+        assert_eq!(event.decision, DecisionKind::Rejected as i32);
+        assert_eq!(event.rejection_reason, "HighConcentration");
+
+        let filter = vec!["Other".to_string()];
+        assert!(!wants(&filter, &event.token_mint));
+        assert!(wants(&[], &event.token_mint));
+    }
+
+    #[test]
+    fn test_price_tick_keeps_receive_time() {
+        let update = PriceUpdate {
+            token_mint: "Mint111".to_string(),
+            symbol: "TEST".to_string(),
+            price_usd: 0.15,
+            price_native: 0.001,
+            liquidity_usd: 10_000.0,
+            volume_24h: 0.0,
+            price_change_24h: 0.0,
+            source: crate::price_feed::PriceSource::Geyser,
+            received_at: std::time::Instant::now() - std::time::Duration::from_secs(2),
+        };
+        let tick = to_price_tick(&update);
+        let age_ms = chrono::Utc::now().timestamp_millis() - tick.timestamp_ms;
+        assert!((1_900..2_500).contains(&age_ms), "age {} ms", age_ms);
+    }
+
+    #[test]
+    fn test_buy_result_from_custom_params_fill() {
+        let swap = SwapResult {
+            signature: "SIG".to_string(),
+            input_amount: 0.5,
+            output_amount: 1_000.0,
+            route: "Jupiter Adjusted".to_string(),
+            price_impact_pct: 0.3,
+            fee_sol: 0.0001,
+        };
+        let res = buy_result_from_swap(swap.clone()).unwrap();
+        assert_eq!((res.sol_spent, res.tokens_received), (0.5, 1_000.0));
+        assert!((res.price_per_token - 0.0005).abs() < 1e-12);
+
+        let empty = SwapResult {
+            output_amount: 0.0,
+            ..swap
+        };
+        assert!(buy_result_from_swap(empty).is_err());
+    }
+
+    #[test]
+    fn test_sell_percent() {
+        assert_eq!(sell_percent(0.0, 1.0), 100);
+        assert_eq!(sell_percent(0.5, 1.0), 50);
+        assert_eq!(sell_percent(2.0, 1.0), 100);
+        assert_eq!(sell_percent(0.001, 1.0), 1);
+    }
+}
@@ -4,10 +4,14 @@
 //! Proporciona las mejores rutas de intercambio con slippage mínimo.
 
 use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
 
 use crate::validation::FinancialValidator;
+use crate::venue::{QuoteRequest, SwapVenue, VenuePayload, VenueQuote, BASE_TX_FEE_LAMPORTS};
 
 /// Cliente para interactuar con Jupiter Aggregator
 pub struct JupiterClient {
@@ -224,6 +228,64 @@ impl JupiterClient {
     }
 }
 
+#[async_trait]
+impl SwapVenue for JupiterClient {
+    fn name(&self) -> &'static str {
+        "Jupiter"
+    }
+
+    fn supports(&self, _mint: &str) -> bool {
+        true
+    }
+
+    async fn quote(&self, request: &QuoteRequest) -> Result<VenueQuote> {
+        let quote = self
+            .get_quote(
+                &request.input_mint,
+                &request.output_mint,
+                request.amount_in,
+                request.slippage_bps,
+            )
+            .await?;
+
+        let amount_out = FinancialValidator::parse_amount_safe(&quote.out_amount, "Jupiter out")?;
+        let mut venue_quote = VenueQuote::new(self.name(), request, amount_out);
+        venue_quote.price_impact_pct = quote.price_impact_pct.parse::<f64>().unwrap_or(0.0);
+        venue_quote.route = quote
+            .route_plan
+            .iter()
+            .map(|r| r.swap_info.label.clone())
+            .collect::<Vec<_>>()
+            .join(" → ");
+        // Jupiter siempre sale con Jito si hay tip configurado (TX extra del tip)
+        if request.jito_tip > 0 {
+            venue_quote.jito_tip = request.jito_tip;
+            venue_quote.fee_lamports += BASE_TX_FEE_LAMPORTS + request.jito_tip;
+        }
+        venue_quote.jupiter_quote = Some(quote);
+        Ok(venue_quote)
+    }
+
+    async fn build_instructions(&self, quote: &VenueQuote, owner: &Pubkey) -> Result<VenuePayload> {
+        let jupiter_quote = quote
+            .jupiter_quote
+            .as_ref()
+            .context("Quote sin respuesta original de Jupiter")?;
+
+        let swap_response = self
+            .get_swap_transaction(jupiter_quote, &owner.to_string(), true)
+            .await?;
+
+        let tx_bytes = general_purpose::STANDARD
+            .decode(&swap_response.swap_transaction)
+            .context("Error decodificando transacción base64")?;
+        let transaction: VersionedTransaction =
+            bincode::deserialize(&tx_bytes).context("Error deserializando transacción")?;
+
+        Ok(VenuePayload::Transaction(transaction))
+    }
+}
+
 /// Respuesta de Jupiter Quote API
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
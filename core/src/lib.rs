@@ -2,12 +2,10 @@
 //!
 //! v2.0.0-HFT - Asynchronous Execution & Dynamic Configuration
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use clap::{Parser, Subcommand};
-use solana_sdk::program_pack::Pack;
 use solana_sdk::signature::Keypair;
-use spl_token::state::Account as TokenAccount;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 
@@ -17,21 +15,39 @@ use std::sync::{Arc, Mutex};
 
 pub mod amm_math;
 pub mod auto_buyer;
+pub mod circuit_breaker;
 pub mod config;
+pub mod cost_basis;
+pub mod direct_swap;
 pub mod emergency;
+pub mod entry_orders;
 pub mod executor_v2;
 pub mod geyser;
+pub mod grpc_server;
 pub mod jito;
 pub mod jupiter;
 pub mod liquidity_monitor;
+pub mod meteora;
+pub mod migrations;
+pub mod orca;
+pub mod performance;
 pub mod price_feed;
+pub mod pumpfun;
+pub mod quote_engine;
 pub mod raydium;
+pub mod raydium_clmm;
+pub mod raydium_cpmm;
+pub mod reconciler;
 pub mod scanner;
+pub mod sliced_exit;
 pub mod state_manager;
 pub mod telegram; // El módulo telegram ahora incluye commands internamente
 pub mod telemetry_server;
+pub mod tick_recorder;
+pub mod token_2022;
 pub mod trailing_sl;
 pub mod validation;
+pub mod venue;
 pub mod wallet;
 pub mod websocket;
 pub mod ws_feed;
@@ -104,6 +120,117 @@ pub enum Commands {
     Scan,
     /// Inicia el monitor dinámico de posiciones (por defecto)
     Monitor,
+    /// Mantenimiento de la base de datos (migraciones de esquema)
+    Db {
+        /// Ruta de la DB SQLite
+        #[arg(long, default_value = state_manager::DEFAULT_DB_PATH)]
+        db: String,
+
+        #[command(subcommand)]
+        action: DbCommands,
+    },
+    /// Reproduce una grabación de ticks contra el StrategyEngine (sin red)
+    Replay {
+        /// Grabación JSONL (ver CHASSIS_RECORD_TICKS)
+        #[arg(long)]
+        ticks: String,
+
+        /// Posiciones iniciales (JSON: lista de PositionState)
+        #[arg(long)]
+        positions: String,
+
+        /// Fichero de salida con los comandos emitidos (por defecto, stdout)
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Informe de rendimiento del historial de trades (JSON o Markdown)
+    Report {
+        /// Ruta de la DB SQLite
+        #[arg(long, default_value = state_manager::DEFAULT_DB_PATH)]
+        db: String,
+
+        /// Formato de salida: markdown | json
+        #[arg(long, default_value = "markdown")]
+        format: String,
+
+        /// Fichero de salida (por defecto, stdout)
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Optimiza SL/trailing/TP con el backtester sobre grabaciones de ticks
+    Optimize {
+        /// Grabaciones JSONL (ver CHASSIS_RECORD_TICKS), una o varias
+        #[arg(long, num_args = 1.., required = true)]
+        ticks: Vec<String>,
+
+        /// Búsqueda: grid (producto completo) | random
+        #[arg(long, default_value = "grid")]
+        search: String,
+
+        /// Combinaciones a muestrear con --search random
+        #[arg(long, default_value_t = 200)]
+        samples: usize,
+
+        /// Semilla de la búsqueda aleatoria (reproducible)
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+
+        /// Stop loss (%) a probar, separados por comas
+        #[arg(long, default_value = "-20,-35,-50", allow_hyphen_values = true)]
+        stop_loss: String,
+
+        /// Distancia del trailing (%) a probar
+        #[arg(long, default_value = "10,15,25")]
+        trailing_distance: String,
+
+        /// Activación del trailing (% de ganancia) a probar
+        #[arg(long, default_value = "10,15,30")]
+        trailing_activation: String,
+
+        /// Escaleras de TP a probar, separadas por ';' (formato de /ladder)
+        #[arg(long, default_value = "50:50,100:50;100:100")]
+        tp_ladders: String,
+
+        /// Folds del walk-forward (train anclado + test siguiente)
+        #[arg(long, default_value_t = 3)]
+        folds: usize,
+
+        /// Métrica a maximizar: net_pnl | sharpe | profit_factor | expectancy | win_rate
+        #[arg(long, default_value = "net_pnl")]
+        metric: String,
+
+        /// Balance inicial simulado (SOL)
+        #[arg(long, default_value_t = 10.0)]
+        balance: f64,
+
+        /// % del balance por entrada
+        #[arg(long, default_value_t = 10.0)]
+        position_size: f64,
+
+        /// Hilos de trabajo (0 = todos los núcleos)
+        #[arg(long, default_value_t = 0)]
+        threads: usize,
+
+        /// Filas del ranking a mostrar (por defecto, todas)
+        #[arg(long)]
+        top: Option<usize>,
+
+        /// Fichero de salida (por defecto, stdout)
+        #[arg(long)]
+        out: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DbCommands {
+    /// Aplica las migraciones pendientes
+    Migrate {
+        /// Solo comprueba qué se aplicaría, sin modificar la DB
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Muestra la versión de esquema y las migraciones pendientes
+    Status,
 }
 
 /// Configuración del motor
@@ -127,6 +254,55 @@ pub async fn run() -> Result<()> {
             monitor,
         }) => handle_auto_buy_mode(mint, sol, symbol, monitor).await?,
         Some(Commands::Scan) => handle_scan_mode().await?,
+        Some(Commands::Db { db, action }) => handle_db_mode(&db, action).await?,
+        Some(Commands::Replay {
+            ticks,
+            positions,
+            out,
+        }) => handle_replay_mode(&ticks, &positions, out.as_deref()).await?,
+        Some(Commands::Report { db, format, out }) => {
+            handle_report_mode(&db, &format, out.as_deref()).await?
+        }
+        Some(Commands::Optimize {
+            ticks,
+            search,
+            samples,
+            seed,
+            stop_loss,
+            trailing_distance,
+            trailing_activation,
+            tp_ladders,
+            folds,
+            metric,
+            balance,
+            position_size,
+            threads,
+            top,
+            out,
+        }) => {
+            let grid = intelligence_rs::ParamGrid {
+                stop_loss_percent: parse_f64_list(&stop_loss)?,
+                trailing_distance_percent: parse_f64_list(&trailing_distance)?,
+                trailing_activation_threshold: parse_f64_list(&trailing_activation)?,
+                tp_ladders: parse_tp_ladders(&tp_ladders)?,
+            };
+            let objective = intelligence_rs::Objective::parse(&metric)
+                .with_context(|| format!("Métrica '{}' no soportada", metric))?;
+            let config = intelligence_rs::OptimizerConfig {
+                initial_balance: balance,
+                position_size_pct: position_size,
+                objective,
+                folds,
+                threads,
+            };
+            grid.validate()?;
+            let candidates = match search.as_str() {
+                "grid" => grid.grid(),
+                "random" => grid.random(samples, seed),
+                other => anyhow::bail!("Búsqueda '{}' no soportada (grid | random)", other),
+            };
+            handle_optimize_mode(&ticks, candidates, &config, top, out.as_deref())?
+        }
         _ => run_monitor_mode().await?,
     }
 
@@ -165,7 +341,22 @@ async fn handle_auto_buy_mode(
     let api_key = std::env::var("HELIUS_API_KEY").expect("HELIUS_API_KEY missing");
     let rpc_url = format!("{}{}", HELIUS_RPC, api_key);
     let keypair = load_keypair_from_env("WALLET_PRIVATE_KEY")?;
-    let buyer = AutoBuyer::new(rpc_url)?;
+
+    // Circuit Breaker de portfolio: el mismo estado persistido que el modo monitor
+    let sol_balance = solana_client::rpc_client::RpcClient::new(rpc_url.clone())
+        .get_balance(&solana_sdk::signer::Signer::pubkey(&keypair))? as f64
+        / 1_000_000_000.0;
+    let state_manager = Arc::new(StateManager::new(state_manager::DEFAULT_DB_PATH).await?);
+    let breaker = load_portfolio_breaker(
+        &state_manager,
+        &Arc::new(TelegramNotifier::new()),
+        sol_balance,
+    )
+    .await;
+    tokio::spawn(breaker.run(std::time::Duration::from_secs(30)));
+
+    let buyer = AutoBuyer::new(rpc_url.clone())?
+        .with_honeypot_check(&rpc_url, solana_sdk::signer::Signer::pubkey(&keypair))?;
 
     let config = AutoBuyConfig {
         token_mint: mint.clone(),
@@ -193,6 +384,25 @@ async fn handle_auto_buy_mode(
     Ok(())
 }
 
+/// Restaura el Circuit Breaker de portfolio desde la DB y sincroniza `BUYS_HALTED`.
+/// `sol_balance` solo fija el capital de referencia si aún no hay uno persistido.
+async fn load_portfolio_breaker(
+    state_manager: &Arc<StateManager>,
+    telegram: &Arc<TelegramNotifier>,
+    sol_balance: f64,
+) -> Arc<crate::circuit_breaker::PortfolioBreaker> {
+    let breaker = Arc::new(crate::circuit_breaker::PortfolioBreaker::new(
+        Arc::clone(state_manager),
+        Arc::clone(telegram),
+        crate::circuit_breaker::BreakerLimits::default().with_capital(sol_balance),
+    ));
+    if let Err(e) = breaker.check().await {
+        crate::circuit_breaker::BUYS_HALTED.store(true, std::sync::atomic::Ordering::Relaxed);
+        eprintln!("⚠️ [CIRCUIT BREAKER] Estado no disponible, compras bloqueadas: {}", e);
+    }
+    breaker
+}
+
 async fn handle_scan_mode() -> Result<()> {
     println!("╔════════════════════════════════════════════════════════════╗");
     println!("║         📡 NETWORK SCANNER - Pump.fun Telemetry          ║");
@@ -204,6 +414,164 @@ async fn handle_scan_mode() -> Result<()> {
     Ok(())
 }
 
+async fn handle_db_mode(db_path: &str, action: DbCommands) -> Result<()> {
+    let state_manager = StateManager::open(db_path).await?;
+
+    match action {
+        DbCommands::Status => {
+            let status = state_manager.schema_status().await?;
+            println!(
+                "🗄️  {} — esquema v{} (última: v{})",
+                db_path, status.current_version, status.latest_version
+            );
+            for applied in &status.applied {
+                let when = chrono::DateTime::from_timestamp(applied.applied_at, 0)
+                    .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default();
+                println!("   ✅ v{} {} ({})", applied.version, applied.name, when);
+            }
+            for (version, name) in &status.pending {
+                println!("   ⏳ v{} {}", version, name);
+            }
+            if status.is_up_to_date() {
+                println!("✅ Esquema al día.");
+            }
+        }
+        DbCommands::Migrate { dry_run } => {
+            let applied = state_manager.migrate(dry_run).await?;
+            if applied.is_empty() {
+                println!("✅ {}: nada que migrar.", db_path);
+            } else if dry_run {
+                println!(
+                    "🧪 DRY-RUN {}: se aplicarían las migraciones {:?} (sin cambios en la DB)",
+                    db_path, applied
+                );
+            } else {
+                println!("✅ {}: migraciones aplicadas {:?}", db_path, applied);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_replay_mode(ticks_path: &str, positions_path: &str, out: Option<&str>) -> Result<()> {
+    let ticks = tick_recorder::read_recording(ticks_path)?;
+    let positions: Vec<state_manager::PositionState> = serde_json::from_str(
+        &std::fs::read_to_string(positions_path)
+            .with_context(|| format!("No se pudo leer {}", positions_path))?,
+    )
+    .with_context(|| format!("{}: se espera una lista de posiciones", positions_path))?;
+
+    eprintln!(
+        "⏯️  Replay de {} ticks sobre {} posiciones...",
+        ticks.len(),
+        positions.len()
+    );
+    let report = engine::replay::replay(&ticks, positions).await?;
+
+    match out {
+        Some(path) => std::fs::write(path, report.to_lines())?,
+        None => print!("{}", report.to_lines()),
+    }
+    eprintln!(
+        "✅ Replay completado: {} ticks, {} comandos emitidos.",
+        report.ticks,
+        report.commands.len()
+    );
+    Ok(())
+}
+
+async fn handle_report_mode(db_path: &str, format: &str, out: Option<&str>) -> Result<()> {
+    let state_manager = StateManager::open(db_path).await?;
+    let history = state_manager
+        .get_trade_history(performance::REPORT_HISTORY_LIMIT)
+        .await?;
+    let report = performance::live_report(&history);
+
+    let rendered = match format {
+        "json" => report.to_json()?,
+        "markdown" | "md" => report.to_markdown(),
+        other => anyhow::bail!("Formato '{}' no soportado (markdown | json)", other),
+    };
+    match out {
+        Some(path) => std::fs::write(path, rendered)?,
+        None => print!("{}", rendered),
+    }
+    eprintln!(
+        "📊 Informe: {} trades cerrados de {} registros.",
+        report.trades,
+        history.len()
+    );
+    Ok(())
+}
+
+fn handle_optimize_mode(
+    tick_paths: &[String],
+    candidates: Vec<intelligence_rs::ParamSet>,
+    config: &intelligence_rs::OptimizerConfig,
+    top: Option<usize>,
+    out: Option<&str>,
+) -> Result<()> {
+    let mut data = Vec::new();
+    for path in tick_paths {
+        let ticks = tick_recorder::read_recording(path)?;
+        eprintln!("📼 {}: {} ticks", path, ticks.len());
+        data.extend(
+            ticks
+                .iter()
+                .map(tick_recorder::RecordedTick::to_market_data),
+        );
+    }
+
+    eprintln!(
+        "🔬 Optimizando {} combinaciones sobre {} ticks ({} folds walk-forward)...",
+        candidates.len(),
+        data.len(),
+        config.folds
+    );
+    let started = std::time::Instant::now();
+    let report = intelligence_rs::optimize(&data, candidates, config)?;
+
+    let rendered = report.to_markdown(top);
+    match out {
+        Some(path) => std::fs::write(path, rendered)?,
+        None => print!("{}", rendered),
+    }
+    eprintln!(
+        "✅ Optimización completada en {:.1}s ({} candidatos).",
+        started.elapsed().as_secs_f64(),
+        report.candidates.len()
+    );
+    Ok(())
+}
+
+/// "-20,-35,-50" → [-20.0, -35.0, -50.0]
+fn parse_f64_list(spec: &str) -> Result<Vec<f64>> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse()
+                .with_context(|| format!("Valor '{}' inválido en '{}'", s, spec))
+        })
+        .collect()
+}
+
+/// "50:50,100:50;100:100" → dos escaleras
+fn parse_tp_ladders(spec: &str) -> Result<Vec<Vec<intelligence_rs::TpStep>>> {
+    spec.split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|ladder| {
+            Ok(engine::tp_ladder::parse_ladder(ladder)?
+                .into_iter()
+                .map(|rung| intelligence_rs::TpStep::new(rung.target_percent, rung.sell_percent))
+                .collect())
+        })
+        .collect()
+}
+
 async fn run_monitor_mode() -> Result<()> {
     let obs_config = if std::env::var("RUST_LOG").is_ok() {
         observability::ObservabilityConfig::production()
@@ -234,7 +602,31 @@ async fn run_monitor_mode() -> Result<()> {
     println!("🏦 Balance Inicial: {:.4} SOL", sol_balance);
 
     // 2. DB Asíncrona (Connection Pool)
-    let state_manager = Arc::new(StateManager::new("trading_state.db").await?);
+    // Cada escritura de posiciones se replica al PositionBook del StrategyEngine
+    let (position_tx, position_rx) = tokio::sync::mpsc::unbounded_channel();
+    let state_manager = Arc::new(
+        StateManager::new(state_manager::DEFAULT_DB_PATH)
+            .await?
+            .with_position_events(position_tx),
+    );
+
+    // 2b. Journal de órdenes: resolver salidas que quedaron a medias antes de un crash
+    {
+        let rpc_for_journal = solana_client::rpc_client::RpcClient::new(rpc_url.clone());
+        let wallet_pubkey = solana_sdk::pubkey::Pubkey::from_str(&wallet_addr)
+            .expect("WALLET_ADDRESS inválida");
+        match crate::engine::order_journal::reconcile_open_orders(
+            &state_manager,
+            &rpc_for_journal,
+            &wallet_pubkey,
+        )
+        .await
+        {
+            Ok(0) => {}
+            Ok(n) => println!("✅ [JOURNAL] {} órdenes reconciliadas.", n),
+            Err(e) => eprintln!("❌ [JOURNAL] Error reconciliando órdenes: {}", e),
+        }
+    }
 
     // 3. Emergency System
     let emergency_monitor = Arc::new(Mutex::new(EmergencyMonitor::new(EmergencyConfig {
@@ -265,15 +657,22 @@ async fn run_monitor_mode() -> Result<()> {
                 }
             };
 
-            let ata = spl_associated_token_account::get_associated_token_address(
+            // ATA bajo el token program del mint (SPL clásico o Token-2022)
+            let token_program =
+                crate::token_2022::mint_token_program(&rpc_for_check, &mint_pubkey);
+            let ata = crate::token_2022::associated_token_address(
                 &wallet_pubkey,
                 &mint_pubkey,
+                &token_program,
             );
 
             let has_balance = match rpc_for_check.get_account(&ata) {
                 Ok(account_data) => {
-                    match TokenAccount::unpack(&account_data.data) {
-                        Ok(token_account) => token_account.amount > 0,
+                    match crate::token_2022::parse_token_account_amount(
+                        &account_data.owner,
+                        &account_data.data,
+                    ) {
+                        Ok(amount) => amount > 0,
                         Err(_) => false,
                     }
                 }
@@ -331,7 +730,15 @@ async fn run_monitor_mode() -> Result<()> {
     // 4. Executor & Keypair
     let executor_config =
         ExecutorConfig::new(rpc_url.clone(), !app_config.global_settings.auto_execute);
-    let executor = Arc::new(TradeExecutor::new(executor_config));
+    // Libro de reservas: lo llena el PriceFeed, lo consume el executor al vender
+    let local_quotes = Arc::new(crate::quote_engine::LocalQuoteEngine::default());
+    let executor = Arc::new(
+        TradeExecutor::new(executor_config)
+            .with_local_quotes(Arc::clone(&local_quotes))
+            .with_quote_timeout(std::time::Duration::from_millis(
+                app_config.global_settings.quote_timeout_ms,
+            )),
+    );
     let mut wallet_keypair: Option<Keypair> = None;
 
     if app_config.global_settings.auto_execute {
@@ -367,12 +774,47 @@ async fn run_monitor_mode() -> Result<()> {
         }
     }
 
-    let (price_rx, price_cache, feed_tx) = PriceFeed::start(feed_config, monitored_tokens);
-    let price_rx = price_rx;
-    let _buyer = Arc::new(crate::auto_buyer::AutoBuyer::new_with_cache(
-        rpc_url.clone(),
-        Some(Arc::clone(&price_cache)),
-    )?);
+    let (price_rx, price_cache, feed_tx) =
+        PriceFeed::start(feed_config, monitored_tokens, Arc::clone(&local_quotes));
+
+    // Event Bus: replica ticks, fills y decisiones para los streams gRPC
+    let event_bus = Arc::new(crate::engine::events::EventBus::new(1024));
+    let price_rx = event_bus.tap_prices(price_rx);
+
+    // Grabación de ticks para replay (opcional)
+    if let Ok(path) = std::env::var(tick_recorder::RECORD_TICKS_ENV) {
+        match tick_recorder::TickRecorder::open(&path) {
+            Ok(recorder) => {
+                let recorder = recorder.with_reserves(Arc::clone(&local_quotes));
+                tokio::spawn(recorder.run(event_bus.subscribe_prices()));
+            }
+            Err(e) => eprintln!("⚠️ [RECORDER] Grabación desactivada: {:#}", e),
+        }
+    }
+
+    // AutoBuyer: filtra las compras del gRPC y publica sus decisiones en el bus
+    let buyer = Arc::new(
+        crate::auto_buyer::AutoBuyer::new_with_cache(rpc_url.clone(), Some(Arc::clone(&price_cache)))?
+            .with_event_bus(&event_bus),
+    );
+    {
+        // Historial de momentum alimentado por el PriceFeed
+        let buyer = Arc::clone(&buyer);
+        let mut prices = event_bus.subscribe_prices();
+        tokio::spawn(async move {
+            loop {
+                match prices.recv().await {
+                    Ok(update) => {
+                        buyer
+                            .record_price_tick(&update.token_mint, update.price_native)
+                            .await
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
 
     // 6. Telemetry Server (WebSocket para la UI)
     let telemetry_server = Arc::new(crate::telemetry_server::TelemetryServer::new(
@@ -387,6 +829,48 @@ async fn run_monitor_mode() -> Result<()> {
 
     // 7. Telegram y Comandos
     let telegram = Arc::new(TelegramNotifier::new());
+
+    // 7a. Circuit Breaker de portfolio: restaurar estado persistido ANTES de aceptar compras
+    let breaker = load_portfolio_breaker(&state_manager, &telegram, sol_balance).await;
+    tokio::spawn(breaker.run(std::time::Duration::from_secs(30)));
+
+    // 7b. Reconciliador periódico DB ↔ wallet (ventas manuales, fills parciales, airdrops)
+    if app_config.reconciler.enabled {
+        let wallet_pubkey = solana_sdk::pubkey::Pubkey::from_str(&wallet_addr)
+            .expect("WALLET_ADDRESS inválida");
+        let reconciler = Arc::new(
+            crate::reconciler::PositionReconciler::new(
+                Arc::clone(&state_manager),
+                Arc::clone(&telegram),
+                rpc_url.clone(),
+                wallet_pubkey,
+                app_config.reconciler.clone(),
+            )
+            .with_feed(feed_tx.clone()),
+        );
+        let interval = std::time::Duration::from_secs(app_config.reconciler.interval_sec.max(10));
+        tokio::spawn(reconciler.run(interval));
+    }
+
+    // 7c. Órdenes de entrada en reposo (/limit): escuchan el bus de precios
+    let entry_keypair = if app_config.global_settings.auto_execute {
+        load_keypair_from_env("WALLET_PRIVATE_KEY").ok()
+    } else {
+        None
+    };
+    let entry_orders = Arc::new(
+        crate::entry_orders::EntryOrderManager::new(
+            Arc::clone(&state_manager),
+            Arc::clone(&executor),
+            Arc::clone(&telegram),
+            entry_keypair,
+        )
+        .with_feed(feed_tx.clone()),
+    );
+    tokio::spawn(entry_orders.run(
+        event_bus.subscribe_prices(),
+        std::time::Duration::from_secs(5),
+    ));
     let command_handler = Arc::new(CommandHandler::new());
 
     let cmd_handler_clone = Arc::clone(&command_handler);
@@ -395,6 +879,7 @@ async fn run_monitor_mode() -> Result<()> {
     let cmd_executor = Arc::clone(&executor);
     let cmd_state_manager = Arc::clone(&state_manager);
     let cmd_price_cache = Arc::clone(&price_cache);
+    let cmd_feed_tx = feed_tx.clone();
 
     tokio::spawn(async move {
         let _ = cmd_handler_clone
@@ -403,7 +888,7 @@ async fn run_monitor_mode() -> Result<()> {
                 cmd_executor,
                 cmd_config,
                 cmd_state_manager,
-                feed_tx,
+                cmd_feed_tx,
                 cmd_price_cache,
             )
             .await;
@@ -458,15 +943,46 @@ async fn run_monitor_mode() -> Result<()> {
     use crate::engine::commands::{ExecutionCommand, ExecutionFeedback};
     let (cmd_tx, cmd_rx) = tokio::sync::mpsc::channel::<ExecutionCommand>(1024);
     let (feedback_tx, feedback_rx) = tokio::sync::mpsc::channel::<ExecutionFeedback>(1024);
+    let feedback_rx = event_bus.tap_executions(feedback_rx);
 
-    let engine = crate::engine::strategy::StrategyEngine::new(Arc::clone(&state_manager));
+    let position_book = crate::engine::position_book::PositionBook::load(&state_manager).await?;
+    let engine =
+        crate::engine::strategy::StrategyEngine::new(Arc::clone(&state_manager), position_book);
     tokio::spawn(async move {
-        engine.run_loop(price_rx, cmd_tx, feedback_rx).await;
+        engine.run_loop(price_rx, cmd_tx, feedback_rx, position_rx).await;
     });
 
-    let router = crate::engine::router::ExecutionRouter::new(Arc::clone(&executor), Arc::clone(&state_manager), Arc::clone(&telegram), wallet_keypair, feedback_tx);
-    let router_handle = tokio::spawn(async move {
-        Arc::new(router).run_dashboard(cmd_rx).await;
+    let router = Arc::new(
+        crate::engine::router::ExecutionRouter::new(Arc::clone(&executor), Arc::clone(&state_manager), Arc::clone(&telegram), wallet_keypair, feedback_tx)
+            .with_sliced_exit(app_config.sliced_exit.clone()),
+    );
+    let router_handle = tokio::spawn(Arc::clone(&router).run_dashboard(cmd_rx));
+
+    // gRPC Control Plane (ChassisService para el tooling Python): sus ventas van por el router
+    let grpc_keypair = if app_config.global_settings.auto_execute {
+        load_keypair_from_env("WALLET_PRIVATE_KEY").ok()
+    } else {
+        None
+    };
+    let grpc_server = crate::grpc_server::ChassisGrpcServer::new(
+        Arc::clone(&state_manager),
+        Arc::clone(&price_cache),
+        Arc::clone(&executor),
+        Arc::clone(&router),
+        rpc_url.clone(),
+        grpc_keypair,
+        feed_tx.clone(),
+        Arc::clone(&event_bus),
+    )
+    .with_auto_buyer(Arc::clone(&buyer))
+    .with_exits(app_config.exits.clone());
+    let grpc_addr = std::env::var("CHASSIS_GRPC_ADDR")
+        .unwrap_or_else(|_| crate::grpc_server::DEFAULT_GRPC_ADDR.to_string());
+
+    tokio::spawn(async move {
+        if let Err(e) = grpc_server.run(&grpc_addr).await {
+            eprintln!("❌ [GRPC] Servidor caído: {}", e);
+        }
     });
 
     println!("✅ The Chassis está en marcha. Pulsa Ctrl+C para detener.\n");
@@ -0,0 +1,788 @@
+//! # Meteora DLMM Direct Swap
+//!
+//! Venue directo sobre pools DLMM (Dynamic Liquidity Market Maker) de Meteora,
+//! con la misma interfaz que `RaydiumClient`.
+//!
+//! La liquidez vive en bins discretos de precio constante:
+//! ```text
+//!   precio(bin) = (1 + bin_step / 10_000) ^ bin_id     (token Y por token X, raw)
+//!   X → Y: consume el Y del bin activo y baja de bin al agotarlo
+//!   Y → X: consume el X del bin activo y sube de bin al agotarlo
+//! ```
+//! Fee = base (base_factor · bin_step) + variable (volatilidad), sobre la entrada.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use crate::direct_swap::{
+    deepest, measure_vault_depths, send_instructions, send_instructions_with_jito,
+    wrap_swap_instructions, WSOL_MINT,
+};
+use crate::token_2022::token_2022_program_id;
+use crate::venue::{QuoteRequest, SwapVenue, VenuePayload, VenueQuote};
+
+// ============================================================================
+// CONSTANTS - Meteora DLMM Program
+// ============================================================================
+
+pub const DLMM_PROGRAM_ID: &str = "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo";
+
+/// Tamaño del account `LbPair` (discriminador Anchor incluido)
+pub const LB_PAIR_SIZE: usize = 904;
+
+/// Bins por `BinArray`
+pub const MAX_BIN_PER_ARRAY: i64 = 70;
+
+/// Bin arrays que se cargan en la dirección del swap (incluido el activo)
+const BIN_ARRAYS_PER_SWAP: i64 = 3;
+
+/// Precisión de las fees de DLMM (1e9 = 100%) y tope del 10%
+const FEE_PRECISION: u128 = 1_000_000_000;
+const MAX_FEE_RATE: u128 = 100_000_000;
+
+/// sha256("global:swap")[..8]
+const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+/// Offsets de los mints dentro de `LbPair` (para filtros memcmp)
+const TOKEN_X_MINT_OFFSET: usize = 88;
+const TOKEN_Y_MINT_OFFSET: usize = 120;
+
+/// Layout de `BinArray`: [disc 8][index 8][version 1][pad 7][lb_pair 32][bins 70 × 144]
+const BIN_ARRAY_BINS_OFFSET: usize = 56;
+const BIN_LEN: usize = 144;
+
+// ============================================================================
+// LB PAIR STATE
+// ============================================================================
+
+/// Estado on-chain de un LbPair (campos necesarios para swap/quote)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LbPairState {
+    pub address: Pubkey,
+    pub base_factor: u16,
+    pub variable_fee_control: u32,
+    pub base_fee_power_factor: u8,
+    pub volatility_accumulator: u32,
+    pub active_id: i32,
+    pub bin_step: u16,
+    pub status: u8,
+    pub token_x_mint: Pubkey,
+    pub token_y_mint: Pubkey,
+    pub reserve_x: Pubkey,
+    pub reserve_y: Pubkey,
+    pub oracle: Pubkey,
+    pub token_x_program: Pubkey,
+    pub token_y_program: Pubkey,
+}
+
+impl LbPairState {
+    /// Layout: [disc 8][static_params 32][variable_params 32][bump 1][bin_step_seed 2]
+    /// [pair_type 1][active_id 4][bin_step 2][status 1]...[mint_x 32 @88][mint_y 32]
+    /// [reserve_x 32][reserve_y 32]...[oracle 32 @552]...[program flags @880]
+    pub fn from_account_data(address: Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() < LB_PAIR_SIZE {
+            anyhow::bail!("LbPair demasiado corto: {} bytes", data.len());
+        }
+
+        let read_pubkey =
+            |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+        let token_program = |flag: u8| {
+            if flag == 1 {
+                token_2022_program_id()
+            } else {
+                spl_token::id()
+            }
+        };
+
+        Ok(Self {
+            address,
+            base_factor: u16::from_le_bytes(data[8..10].try_into().unwrap()),
+            variable_fee_control: u32::from_le_bytes(data[16..20].try_into().unwrap()),
+            base_fee_power_factor: data[34],
+            volatility_accumulator: u32::from_le_bytes(data[40..44].try_into().unwrap()),
+            active_id: i32::from_le_bytes(data[76..80].try_into().unwrap()),
+            bin_step: u16::from_le_bytes(data[80..82].try_into().unwrap()),
+            status: data[82],
+            token_x_mint: read_pubkey(TOKEN_X_MINT_OFFSET),
+            token_y_mint: read_pubkey(TOKEN_Y_MINT_OFFSET),
+            reserve_x: read_pubkey(152),
+            reserve_y: read_pubkey(184),
+            oracle: read_pubkey(552),
+            token_x_program: token_program(data[880]),
+            token_y_program: token_program(data[881]),
+        })
+    }
+
+    /// `Some(true)` si `mint` es token X (swap_for_y), `Some(false)` si es token Y
+    pub fn is_token_x(&self, mint: &Pubkey) -> Option<bool> {
+        if *mint == self.token_x_mint {
+            Some(true)
+        } else if *mint == self.token_y_mint {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> String {
+        format!("DLMM/{}", &self.address.to_string()[..6])
+    }
+
+    /// Fee total (fracción) con la volatilidad actual: base + variable, tope 10%
+    pub fn fee_rate(&self) -> f64 {
+        let base = self.base_factor as u128
+            * self.bin_step as u128
+            * 10
+            * 10u128.pow(self.base_fee_power_factor as u32);
+
+        let v = self.volatility_accumulator as u128 * self.bin_step as u128;
+        let variable = if self.variable_fee_control > 0 {
+            (v * v * self.variable_fee_control as u128).div_ceil(100_000_000_000)
+        } else {
+            0
+        };
+
+        (base + variable).min(MAX_FEE_RATE) as f64 / FEE_PRECISION as f64
+    }
+
+    /// Índices de los bin arrays a recorrer, en orden de swap
+    pub fn swap_bin_array_indexes(&self, swap_for_y: bool) -> Vec<i64> {
+        let current = bin_array_index(self.active_id);
+        (0..BIN_ARRAYS_PER_SWAP)
+            .map(|i| if swap_for_y { current - i } else { current + i })
+            .collect()
+    }
+}
+
+/// Índice del bin array que contiene `bin_id` (división con floor)
+pub fn bin_array_index(bin_id: i32) -> i64 {
+    (bin_id as i64).div_euclid(MAX_BIN_PER_ARRAY)
+}
+
+/// Precio de un bin (token Y por token X, raw)
+pub fn bin_price(bin_id: i32, bin_step: u16) -> f64 {
+    (1.0 + bin_step as f64 / 10_000.0).powi(bin_id)
+}
+
+/// Liquidez de un bin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DlmmBin {
+    pub bin_id: i32,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+/// Extrae los bins con liquidez de un `BinArray`
+pub fn parse_bin_array(data: &[u8]) -> Result<Vec<DlmmBin>> {
+    let end = BIN_ARRAY_BINS_OFFSET + MAX_BIN_PER_ARRAY as usize * BIN_LEN;
+    if data.len() < end {
+        anyhow::bail!("BinArray demasiado corto: {} bytes", data.len());
+    }
+
+    let index = i64::from_le_bytes(data[8..16].try_into().unwrap());
+
+    let bins = (0..MAX_BIN_PER_ARRAY as usize)
+        .filter_map(|i| {
+            let base = BIN_ARRAY_BINS_OFFSET + i * BIN_LEN;
+            let amount_x = u64::from_le_bytes(data[base..base + 8].try_into().unwrap());
+            let amount_y = u64::from_le_bytes(data[base + 8..base + 16].try_into().unwrap());
+            (amount_x > 0 || amount_y > 0).then_some(DlmmBin {
+                bin_id: (index * MAX_BIN_PER_ARRAY + i as i64) as i32,
+                amount_x,
+                amount_y,
+            })
+        })
+        .collect();
+
+    Ok(bins)
+}
+
+/// Resultado de recorrer bins para un swap exact-in
+#[derive(Debug, Clone, PartialEq)]
+pub struct DlmmQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub bins_crossed: u32,
+    pub fully_filled: bool,
+}
+
+/// Cotiza un swap exact-in recorriendo los bins desde `active_id`
+pub fn quote_bins(
+    bins: &[DlmmBin],
+    active_id: i32,
+    bin_step: u16,
+    amount_in: u64,
+    swap_for_y: bool,
+    fee_rate: f64,
+) -> DlmmQuote {
+    let mut path: Vec<&DlmmBin> = bins
+        .iter()
+        .filter(|b| {
+            if swap_for_y {
+                b.bin_id <= active_id && b.amount_y > 0
+            } else {
+                b.bin_id >= active_id && b.amount_x > 0
+            }
+        })
+        .collect();
+    if swap_for_y {
+        path.sort_by_key(|b| std::cmp::Reverse(b.bin_id));
+    } else {
+        path.sort_by_key(|b| b.bin_id);
+    }
+
+    let mut remaining = amount_in as f64;
+    let mut amount_out = 0.0f64;
+    let mut fee_total = 0.0f64;
+    let mut bins_crossed = 0u32;
+
+    for bin in path {
+        if remaining <= 0.0 {
+            break;
+        }
+        let price = bin_price(bin.bin_id, bin_step);
+
+        // Entrada neta que vacía el lado de salida del bin
+        let (max_out, max_in_net) = if swap_for_y {
+            (bin.amount_y as f64, bin.amount_y as f64 / price)
+        } else {
+            (bin.amount_x as f64, bin.amount_x as f64 * price)
+        };
+
+        let net_available = remaining * (1.0 - fee_rate);
+        if net_available < max_in_net {
+            amount_out += if swap_for_y {
+                net_available * price
+            } else {
+                net_available / price
+            };
+            fee_total += remaining - net_available;
+            remaining = 0.0;
+            break;
+        }
+
+        amount_out += max_out;
+        let gross = max_in_net / (1.0 - fee_rate);
+        fee_total += gross - max_in_net;
+        remaining -= gross;
+        bins_crossed += 1;
+    }
+
+    DlmmQuote {
+        amount_in: (amount_in as f64 - remaining.max(0.0)).round() as u64,
+        amount_out: amount_out.floor() as u64,
+        fee_amount: fee_total.ceil() as u64,
+        bins_crossed,
+        fully_filled: remaining <= 0.0,
+    }
+}
+
+// ============================================================================
+// PDAs & INSTRUCTIONS
+// ============================================================================
+
+pub fn program_id() -> Pubkey {
+    Pubkey::from_str(DLMM_PROGRAM_ID).unwrap()
+}
+
+pub fn derive_bin_array(lb_pair: &Pubkey, index: i64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"bin_array", lb_pair.as_ref(), &index.to_le_bytes()],
+        &program_id(),
+    )
+    .0
+}
+
+pub fn derive_event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &program_id()).0
+}
+
+/// Construye `swap` (exact-in). Los bin arrays van como remaining accounts
+/// en orden de recorrido. Las cuentas opcionales (bitmap extension, host fee)
+/// se pasan como el program id (= `None` en Anchor).
+#[allow(clippy::too_many_arguments)]
+pub fn build_swap_instruction(
+    pair: &LbPairState,
+    owner: &Pubkey,
+    input_mint: &Pubkey,
+    user_token_in: Pubkey,
+    user_token_out: Pubkey,
+    bin_arrays: &[Pubkey],
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<Instruction> {
+    pair.is_token_x(input_mint)
+        .context("El mint de entrada no pertenece al pool DLMM")?;
+
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&SWAP_DISCRIMINATOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+    let program = program_id();
+    let mut accounts = vec![
+        AccountMeta::new(pair.address, false),     // 0. LB Pair
+        AccountMeta::new_readonly(program, false), // 1. Bitmap Extension (None)
+        AccountMeta::new(pair.reserve_x, false),   // 2. Reserve X
+        AccountMeta::new(pair.reserve_y, false),   // 3. Reserve Y
+        AccountMeta::new(user_token_in, false),    // 4. User Token In
+        AccountMeta::new(user_token_out, false),   // 5. User Token Out
+        AccountMeta::new_readonly(pair.token_x_mint, false), // 6. Token X Mint
+        AccountMeta::new_readonly(pair.token_y_mint, false), // 7. Token Y Mint
+        AccountMeta::new(pair.oracle, false),      // 8. Oracle
+        AccountMeta::new_readonly(program, false), // 9. Host Fee In (None)
+        AccountMeta::new_readonly(*owner, true),   // 10. User (Signer)
+        AccountMeta::new_readonly(pair.token_x_program, false), // 11. Token X Program
+        AccountMeta::new_readonly(pair.token_y_program, false), // 12. Token Y Program
+        AccountMeta::new_readonly(derive_event_authority(), false), // 13. Event Authority
+        AccountMeta::new_readonly(program, false), // 14. Program
+    ];
+    accounts.extend(bin_arrays.iter().map(|ba| AccountMeta::new(*ba, false)));
+
+    Ok(Instruction {
+        program_id: program,
+        accounts,
+        data,
+    })
+}
+
+// ============================================================================
+// METEORA CLIENT - Misma interfaz que RaydiumClient
+// ============================================================================
+
+/// Bin arrays cargados para un swap concreto
+#[derive(Debug, Clone)]
+pub struct SwapBinArrays {
+    /// Direcciones existentes on-chain, en orden de recorrido
+    pub addresses: Vec<Pubkey>,
+    pub bins: Vec<DlmmBin>,
+}
+
+pub struct MeteoraClient {
+    rpc_client: RpcClient,
+    selected_pools: RwLock<HashMap<String, LbPairState>>, // Pool más profundo por par
+}
+
+impl MeteoraClient {
+    pub fn new(rpc_url: String) -> Result<Self> {
+        Ok(Self {
+            rpc_client: RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed()),
+            selected_pools: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Encuentra el LbPair más profundo para el par (cualquier bin_step)
+    pub async fn find_pool(&self, base_mint: &str, quote_mint: &str) -> Result<LbPairState> {
+        let key1 = format!("{}-{}", base_mint, quote_mint);
+        let key2 = format!("{}-{}", quote_mint, base_mint);
+
+        if let Ok(selected) = self.selected_pools.read() {
+            if let Some(pool) = selected.get(&key1).or_else(|| selected.get(&key2)) {
+                return Ok(pool.clone());
+            }
+        }
+
+        let base_pubkey = Pubkey::from_str(base_mint)?;
+        let quote_pubkey = Pubkey::from_str(quote_mint)?;
+
+        let mut candidates = self.discover_pools(&base_pubkey, &quote_pubkey)?;
+        if candidates.is_empty() {
+            anyhow::bail!("❌ Ningún pool DLMM para {}/{}", base_mint, quote_mint);
+        }
+
+        let reference_mint = if base_mint == WSOL_MINT {
+            base_pubkey
+        } else {
+            quote_pubkey
+        };
+        let vault_pairs: Vec<[Pubkey; 2]> = candidates
+            .iter()
+            .map(|p| [p.reserve_x, p.reserve_y])
+            .collect();
+        let depths = measure_vault_depths(&self.rpc_client, &vault_pairs, &reference_mint);
+
+        let (best_idx, best_depth) = deepest(&depths).unwrap_or((0, 0));
+        let best = candidates.swap_remove(best_idx);
+
+        println!(
+            "✅ Pool DLMM seleccionado: {} (bin_step {}, {} candidatos, profundidad {} raw)",
+            best.name(),
+            best.bin_step,
+            depths.len(),
+            best_depth
+        );
+
+        if let Ok(mut selected) = self.selected_pools.write() {
+            selected.insert(key1, best.clone());
+        }
+
+        Ok(best)
+    }
+
+    /// Busca LbPairs del par (ambos órdenes de mints) vía getProgramAccounts
+    fn discover_pools(&self, mint_a: &Pubkey, mint_b: &Pubkey) -> Result<Vec<LbPairState>> {
+        use solana_account_decoder::UiAccountEncoding;
+        use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+        use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+
+        let mut pools = Vec::new();
+
+        for (mint_x, mint_y) in [(mint_a, mint_b), (mint_b, mint_a)] {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(LB_PAIR_SIZE as u64),
+                    RpcFilterType::Memcmp(Memcmp::new(
+                        TOKEN_X_MINT_OFFSET,
+                        MemcmpEncodedBytes::Base58(mint_x.to_string()),
+                    )),
+                    RpcFilterType::Memcmp(Memcmp::new(
+                        TOKEN_Y_MINT_OFFSET,
+                        MemcmpEncodedBytes::Base58(mint_y.to_string()),
+                    )),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    ..Default::default()
+                },
+                with_context: Some(false),
+            };
+
+            for (address, account) in self
+                .rpc_client
+                .get_program_accounts_with_config(&program_id(), config)?
+            {
+                if let Ok(state) = LbPairState::from_account_data(address, &account.data) {
+                    // status != 0 → pool deshabilitado
+                    if state.status == 0 {
+                        pools.push(state);
+                    }
+                }
+            }
+        }
+
+        Ok(pools)
+    }
+
+    /// Re-lee el pool y carga los bin arrays del swap (una llamada RPC)
+    fn fetch_swap_state(
+        &self,
+        pair: &LbPairState,
+        swap_for_y: bool,
+    ) -> Result<(LbPairState, SwapBinArrays)> {
+        let account = self.rpc_client.get_account(&pair.address)?;
+        let state = LbPairState::from_account_data(pair.address, &account.data)?;
+
+        let candidates: Vec<Pubkey> = state
+            .swap_bin_array_indexes(swap_for_y)
+            .into_iter()
+            .map(|index| derive_bin_array(&state.address, index))
+            .collect();
+        let accounts = self.rpc_client.get_multiple_accounts(&candidates)?;
+
+        let mut addresses = Vec::new();
+        let mut bins = Vec::new();
+        for (address, account) in candidates.iter().zip(accounts) {
+            if let Some(account) = account {
+                bins.extend(parse_bin_array(&account.data)?);
+                addresses.push(*address);
+            }
+        }
+
+        if addresses.is_empty() {
+            anyhow::bail!("No hay bin arrays inicializados alrededor del bin activo");
+        }
+
+        Ok((state, SwapBinArrays { addresses, bins }))
+    }
+
+    /// Cotiza on-chain un swap exact-in (raw out)
+    pub fn quote_exact_in(
+        &self,
+        pair: &LbPairState,
+        input_mint: &Pubkey,
+        amount_in: u64,
+    ) -> Result<u64> {
+        let swap_for_y = pair
+            .is_token_x(input_mint)
+            .context("El mint de entrada no pertenece al pool DLMM")?;
+        let (state, bin_arrays) = self.fetch_swap_state(pair, swap_for_y)?;
+        let quote = quote_bins(
+            &bin_arrays.bins,
+            state.active_id,
+            state.bin_step,
+            amount_in,
+            swap_for_y,
+            state.fee_rate(),
+        );
+
+        if !quote.fully_filled {
+            anyhow::bail!(
+                "Liquidez DLMM insuficiente en los bin arrays cargados ({} de {} consumidos)",
+                quote.amount_in,
+                amount_in
+            );
+        }
+        Ok(quote.amount_out)
+    }
+
+    /// Atajo: busca el pool y cotiza
+    pub async fn quote(&self, input_mint: &str, output_mint: &str, amount_in: u64) -> Result<u64> {
+        let pair = self.find_pool(input_mint, output_mint).await?;
+        self.quote_exact_in(&pair, &Pubkey::from_str(input_mint)?, amount_in)
+    }
+
+    /// Calcula el min_amount_out basado en slippage
+    pub fn calculate_min_amount_out(&self, expected_out: u64, slippage_bps: u16) -> u64 {
+        let slippage_multiplier = 1.0 - (slippage_bps as f64 / 10000.0);
+        (expected_out as f64 * slippage_multiplier) as u64
+    }
+
+    /// Instrucciones completas del swap (ATAs + WSOL wrap/unwrap + swap)
+    pub fn build_pool_swap_instructions(
+        &self,
+        pair: &LbPairState,
+        input_mint: &Pubkey,
+        owner: &Pubkey,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<Vec<Instruction>> {
+        use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+        let swap_for_y = pair
+            .is_token_x(input_mint)
+            .context("El mint de entrada no pertenece al pool DLMM")?;
+        let (state, bin_arrays) = self.fetch_swap_state(pair, swap_for_y)?;
+
+        let (input_program, output_mint, output_program) = if swap_for_y {
+            (
+                state.token_x_program,
+                state.token_y_mint,
+                state.token_y_program,
+            )
+        } else {
+            (
+                state.token_y_program,
+                state.token_x_mint,
+                state.token_x_program,
+            )
+        };
+
+        let swap_ix = build_swap_instruction(
+            &state,
+            owner,
+            input_mint,
+            get_associated_token_address_with_program_id(owner, input_mint, &input_program),
+            get_associated_token_address_with_program_id(owner, &output_mint, &output_program),
+            &bin_arrays.addresses,
+            amount_in,
+            min_amount_out,
+        )?;
+
+        wrap_swap_instructions(
+            owner,
+            input_mint,
+            &output_mint,
+            &output_program,
+            amount_in,
+            swap_ix,
+        )
+    }
+
+    /// Ejecuta un swap completo (construcción + firma + envío)
+    pub async fn execute_swap(
+        &self,
+        base_mint: &str,
+        quote_mint: &str,
+        amount_in: u64,
+        min_amount_out: u64,
+        user_keypair: &Keypair,
+    ) -> Result<String> {
+        println!(
+            "🌀 [METEORA] Swap directo en DLMM: {} → {}",
+            base_mint, quote_mint
+        );
+
+        let pair = self.find_pool(base_mint, quote_mint).await?;
+        let ixs = self.build_pool_swap_instructions(
+            &pair,
+            &Pubkey::from_str(base_mint)?,
+            &user_keypair.pubkey(),
+            amount_in,
+            min_amount_out,
+        )?;
+
+        let signature = send_instructions(&self.rpc_client, &ixs, user_keypair)?;
+        println!("✅ [METEORA] Swap ejecutado: {}", signature);
+        Ok(signature)
+    }
+
+    /// Vende tokens por SOL directamente en el pool DLMM más profundo
+    pub async fn execute_sell(
+        &self,
+        token_mint: &str,
+        amount_in: u64,
+        min_sol_out: u64,
+        user_keypair: &Keypair,
+    ) -> Result<String> {
+        self.execute_swap(token_mint, WSOL_MINT, amount_in, min_sol_out, user_keypair)
+            .await
+    }
+
+    /// Venta directa empaquetada como bundle de Jito
+    pub async fn execute_sell_with_jito(
+        &self,
+        token_mint: &str,
+        amount_in: u64,
+        min_sol_out: u64,
+        jito_tip_lamports: u64,
+        user_keypair: &Keypair,
+    ) -> Result<String> {
+        println!("🌀 [METEORA SELL + JITO] Token: {}...", &token_mint[..8]);
+
+        let pair = self.find_pool(token_mint, WSOL_MINT).await?;
+        let ixs = self.build_pool_swap_instructions(
+            &pair,
+            &Pubkey::from_str(token_mint)?,
+            &user_keypair.pubkey(),
+            amount_in,
+            min_sol_out,
+        )?;
+
+        send_instructions_with_jito(&self.rpc_client, &ixs, jito_tip_lamports, user_keypair).await
+    }
+}
+
+#[async_trait]
+impl SwapVenue for MeteoraClient {
+    fn name(&self) -> &'static str {
+        "Meteora DLMM Direct"
+    }
+
+    fn supports(&self, mint: &str) -> bool {
+        Pubkey::from_str(mint).is_ok()
+    }
+
+    async fn quote(&self, request: &QuoteRequest) -> Result<VenueQuote> {
+        let amount_out = self
+            .quote(&request.input_mint, &request.output_mint, request.amount_in)
+            .await?;
+        Ok(VenueQuote::new(self.name(), request, amount_out))
+    }
+
+    async fn build_instructions(&self, quote: &VenueQuote, owner: &Pubkey) -> Result<VenuePayload> {
+        let pair = self
+            .find_pool(&quote.input_mint, &quote.output_mint)
+            .await?;
+        Ok(VenuePayload::Instructions(
+            self.build_pool_swap_instructions(
+                &pair,
+                &Pubkey::from_str(&quote.input_mint)?,
+                owner,
+                quote.amount_in,
+                quote.min_amount_out(),
+            )?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_pair() -> LbPairState {
+        let mut data = vec![0u8; LB_PAIR_SIZE];
+        data[8..10].copy_from_slice(&10_000u16.to_le_bytes()); // base_factor
+        data[76..80].copy_from_slice(&(-3i32).to_le_bytes()); // active_id
+        data[80..82].copy_from_slice(&25u16.to_le_bytes()); // bin_step
+        data[TOKEN_X_MINT_OFFSET..TOKEN_X_MINT_OFFSET + 32].copy_from_slice(&[1u8; 32]);
+        data[TOKEN_Y_MINT_OFFSET..TOKEN_Y_MINT_OFFSET + 32].copy_from_slice(&[2u8; 32]);
+        data[881] = 1; // token Y es Token-2022
+        LbPairState::from_account_data(Pubkey::new_unique(), &data).unwrap()
+    }
+
+    #[test]
+    fn test_parse_lb_pair() {
+        let pair = sample_pair();
+        assert_eq!(pair.active_id, -3);
+        assert_eq!(pair.bin_step, 25);
+        assert_eq!(pair.token_x_program, spl_token::id());
+        assert_eq!(pair.token_y_program, token_2022_program_id());
+        // base fee = 10_000 * 25 * 10 / 1e9 = 0.25%
+        assert!((pair.fee_rate() - 0.0025).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_bin_array_indexes() {
+        let pair = sample_pair();
+        assert_eq!(bin_array_index(-3), -1);
+        assert_eq!(bin_array_index(69), 0);
+        assert_eq!(pair.swap_bin_array_indexes(true), vec![-1, -2, -3]);
+        assert_eq!(pair.swap_bin_array_indexes(false), vec![-1, 0, 1]);
+    }
+
+    #[test]
+    fn test_quote_bins_walks_down_for_y() {
+        // bin_step 0 → precio 1 en todos los bins
+        let bins = vec![
+            DlmmBin {
+                bin_id: 0,
+                amount_x: 0,
+                amount_y: 100,
+            },
+            DlmmBin {
+                bin_id: -1,
+                amount_x: 0,
+                amount_y: 100,
+            },
+            DlmmBin {
+                bin_id: 1,
+                amount_x: 500,
+                amount_y: 0,
+            },
+        ];
+
+        let quote = quote_bins(&bins, 0, 0, 150, true, 0.0);
+        assert_eq!(quote.amount_out, 150);
+        assert_eq!(quote.bins_crossed, 1);
+        assert!(quote.fully_filled);
+
+        // Más entrada que liquidez Y disponible
+        let quote = quote_bins(&bins, 0, 0, 1_000, true, 0.0);
+        assert_eq!(quote.amount_out, 200);
+        assert!(!quote.fully_filled);
+
+        // Fee sobre la entrada
+        let quote = quote_bins(&bins, 0, 0, 100, false, 0.01);
+        assert_eq!(quote.amount_out, 99);
+    }
+
+    #[test]
+    fn test_parse_bin_array() {
+        let mut data = vec![0u8; BIN_ARRAY_BINS_OFFSET + 70 * BIN_LEN];
+        data[8..16].copy_from_slice(&(-1i64).to_le_bytes());
+        let base = BIN_ARRAY_BINS_OFFSET + 5 * BIN_LEN;
+        data[base..base + 8].copy_from_slice(&7u64.to_le_bytes());
+
+        let bins = parse_bin_array(&data).unwrap();
+        assert_eq!(
+            bins,
+            vec![DlmmBin {
+                bin_id: -65,
+                amount_x: 7,
+                amount_y: 0
+            }]
+        );
+    }
+}
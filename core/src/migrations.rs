@@ -0,0 +1,1002 @@
+//! # Migraciones de esquema versionadas
+//!
+//! Cada cambio de esquema de `trading_state.db` es una `Migration` numerada. La
+//! tabla `schema_version` guarda las aplicadas; al abrir la DB se ejecutan las
+//! pendientes en orden, cada una en su propia transacción.
+//!
+//! Las DBs creadas antes de este framework no tienen `schema_version`: se
+//! migran desde la v1 y cada paso es idempotente (`CREATE ... IF NOT EXISTS`,
+//! columnas añadidas solo si faltan), así que adoptar una DB existente no
+//! duplica ni pierde nada.
+//!
+//! Añadir una migración = añadir una entrada al final de `MIGRATIONS`. Nunca
+//! se edita ni se renumera una migración ya publicada.
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::state_manager::backfill_cost_basis;
+
+/// Paso de esquema numerado
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    up: fn(&Connection) -> Result<()>,
+}
+
+/// Todas las migraciones, en orden. Las versiones son consecutivas desde 1.
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        up: initial_schema,
+    },
+    Migration {
+        version: 2,
+        name: "take_profit_columns",
+        up: take_profit_columns,
+    },
+    Migration {
+        version: 3,
+        name: "trade_fees",
+        up: trade_fees,
+    },
+    Migration {
+        version: 4,
+        name: "circuit_breaker",
+        up: circuit_breaker,
+    },
+    Migration {
+        version: 5,
+        name: "order_journal",
+        up: order_journal,
+    },
+    Migration {
+        version: 6,
+        name: "position_balances",
+        up: position_balances,
+    },
+    Migration {
+        version: 7,
+        name: "cost_basis_lots",
+        up: cost_basis_lots,
+    },
+    Migration {
+        version: 8,
+        name: "tp_ladder",
+        up: tp_ladder,
+    },
+    Migration {
+        version: 9,
+        name: "exit_rules",
+        up: exit_rules,
+    },
+    Migration {
+        version: 10,
+        name: "entry_orders",
+        up: entry_orders,
+    },
+    Migration {
+        version: 11,
+        name: "breaker_capital",
+        up: breaker_capital,
+    },
+    Migration {
+        version: 12,
+        name: "entry_order_exits",
+        up: entry_order_exits,
+    },
+];
+
+/// Versión de esquema que espera este binario
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Fila de `schema_version`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub name: String,
+    pub applied_at: i64,
+}
+
+/// Estado del esquema de una DB (`the_chassis db status`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaStatus {
+    pub current_version: u32,
+    pub latest_version: u32,
+    pub applied: Vec<AppliedMigration>,
+    /// (versión, nombre) aún sin aplicar
+    pub pending: Vec<(u32, String)>,
+}
+
+impl SchemaStatus {
+    pub fn is_up_to_date(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+// ============================================================================
+// RUNNER
+// ============================================================================
+
+/// Estado del esquema sin modificar la DB
+pub fn status(conn: &Connection) -> Result<SchemaStatus> {
+    let applied = if has_version_table(conn)? {
+        applied_migrations(conn)?
+    } else {
+        Vec::new()
+    };
+    Ok(build_status(applied))
+}
+
+/// Aplica las migraciones pendientes y devuelve sus versiones.
+///
+/// Con `dry_run` todas se ejecutan dentro de una única transacción que se
+/// deshace al final: valida que aplicarían limpias sin tocar la DB.
+pub fn migrate(conn: &mut Connection, dry_run: bool) -> Result<Vec<u32>> {
+    if dry_run {
+        let tx = conn.transaction()?;
+        let applied = apply_pending(&tx)?;
+        tx.rollback()?;
+        return Ok(applied);
+    }
+
+    ensure_version_table(conn)?;
+    let current = current_version(conn)?;
+    check_not_newer(current)?;
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.transaction()?;
+        run_one(&tx, migration)?;
+        tx.commit()?;
+        applied.push(migration.version);
+    }
+    Ok(applied)
+}
+
+fn apply_pending(conn: &Connection) -> Result<Vec<u32>> {
+    ensure_version_table(conn)?;
+    let current = current_version(conn)?;
+    check_not_newer(current)?;
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        run_one(conn, migration)?;
+        applied.push(migration.version);
+    }
+    Ok(applied)
+}
+
+fn run_one(conn: &Connection, migration: &Migration) -> Result<()> {
+    (migration.up)(conn).with_context(|| {
+        format!(
+            "Migración v{} ({}) fallida",
+            migration.version, migration.name
+        )
+    })?;
+    conn.execute(
+        "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, ?2, ?3)",
+        params![migration.version, migration.name, Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+/// Una DB escrita por un binario más nuevo no se toca: podría perder columnas
+fn check_not_newer(current: u32) -> Result<()> {
+    if current > latest_version() {
+        bail!(
+            "La DB está en la versión de esquema {} pero este binario solo conoce hasta la {}",
+            current,
+            latest_version()
+        );
+    }
+    Ok(())
+}
+
+fn build_status(applied: Vec<AppliedMigration>) -> SchemaStatus {
+    let current_version = applied.iter().map(|m| m.version).max().unwrap_or(0);
+    let pending = MIGRATIONS
+        .iter()
+        .filter(|m| m.version > current_version)
+        .map(|m| (m.version, m.name.to_string()))
+        .collect();
+    SchemaStatus {
+        current_version,
+        latest_version: latest_version(),
+        applied,
+        pending,
+    }
+}
+
+fn ensure_version_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn has_version_table(conn: &Connection) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn current_version(conn: &Connection) -> Result<u32> {
+    let version: Option<u32> =
+        conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+            row.get(0)
+        })?;
+    Ok(version.unwrap_or(0))
+}
+
+fn applied_migrations(conn: &Connection) -> Result<Vec<AppliedMigration>> {
+    let mut stmt =
+        conn.prepare("SELECT version, name, applied_at FROM schema_version ORDER BY version")?;
+    let applied = stmt
+        .query_map([], |row| {
+            Ok(AppliedMigration {
+                version: row.get(0)?,
+                name: row.get(1)?,
+                applied_at: row.get(2)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(applied)
+}
+
+/// `ALTER TABLE ... ADD COLUMN` solo si la columna no existe (SQLite no tiene
+/// `ADD COLUMN IF NOT EXISTS`)
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists: i64 = conn.query_row(
+        &format!(
+            "SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = ?1",
+            table
+        ),
+        params![column],
+        |row| row.get(0),
+    )?;
+    if exists == 0 {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+/// `ALTER TABLE ... DROP COLUMN` solo si la columna existe
+fn drop_column(conn: &Connection, table: &str, column: &str) -> Result<()> {
+    let exists: i64 = conn.query_row(
+        &format!(
+            "SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = ?1",
+            table
+        ),
+        params![column],
+        |row| row.get(0),
+    )?;
+    if exists > 0 {
+        conn.execute(&format!("ALTER TABLE {} DROP COLUMN {}", table, column), [])?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// MIGRACIONES
+// ============================================================================
+
+fn initial_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS positions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_mint TEXT NOT NULL UNIQUE,
+            symbol TEXT NOT NULL,
+            entry_price REAL NOT NULL,
+            amount_sol REAL NOT NULL,
+            current_price REAL NOT NULL,
+            stop_loss_percent REAL NOT NULL,
+            trailing_enabled INTEGER NOT NULL,
+            trailing_distance_percent REAL NOT NULL,
+            trailing_activation_threshold REAL NOT NULL,
+            trailing_highest_price REAL,
+            trailing_current_sl REAL,
+            active INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS trades (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            signature TEXT NOT NULL UNIQUE,
+            token_mint TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            trade_type TEXT NOT NULL,
+            amount_sol REAL NOT NULL,
+            tokens_amount REAL NOT NULL,
+            price REAL NOT NULL,
+            pnl_sol REAL,
+            pnl_percent REAL,
+            route TEXT NOT NULL,
+            price_impact_pct REAL NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS config_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            config_json TEXT NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_positions_active ON positions(active);
+        CREATE INDEX IF NOT EXISTS idx_trades_timestamp ON trades(timestamp DESC);",
+    )?;
+    Ok(())
+}
+
+fn take_profit_columns(conn: &Connection) -> Result<()> {
+    add_column(conn, "positions", "tp_percent", "REAL")?;
+    add_column(conn, "positions", "tp_amount_percent", "REAL")?;
+    add_column(conn, "positions", "tp_triggered", "INTEGER DEFAULT 0")?;
+    add_column(conn, "positions", "tp2_percent", "REAL")?;
+    add_column(conn, "positions", "tp2_amount_percent", "REAL")?;
+    add_column(conn, "positions", "tp2_triggered", "INTEGER DEFAULT 0")?;
+    Ok(())
+}
+
+fn trade_fees(conn: &Connection) -> Result<()> {
+    add_column(conn, "trades", "fee_sol", "REAL NOT NULL DEFAULT 0.0")
+}
+
+fn circuit_breaker(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS circuit_breaker (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            tripped INTEGER NOT NULL DEFAULT 0,
+            reason TEXT,
+            tripped_at INTEGER,
+            reset_at INTEGER,
+            last_reset_at INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn order_journal(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS orders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_mint TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            command_type TEXT NOT NULL,
+            sell_pct INTEGER NOT NULL,
+            amount_invested REAL NOT NULL,
+            tokens_before INTEGER,
+            status TEXT NOT NULL,
+            signature TEXT,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_orders_status ON orders(status);",
+    )?;
+    Ok(())
+}
+
+fn position_balances(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS position_balances (
+            token_mint TEXT PRIMARY KEY,
+            token_amount INTEGER NOT NULL,
+            amount_sol REAL NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn cost_basis_lots(conn: &Connection) -> Result<()> {
+    add_column(conn, "positions", "token_amount", "REAL NOT NULL DEFAULT 0")?;
+    add_column(
+        conn,
+        "positions",
+        "realized_pnl_sol",
+        "REAL NOT NULL DEFAULT 0",
+    )?;
+    add_column(
+        conn,
+        "positions",
+        "realized_pnl_fifo_sol",
+        "REAL NOT NULL DEFAULT 0",
+    )?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS position_lots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_mint TEXT NOT NULL,
+            signature TEXT NOT NULL UNIQUE,
+            tokens REAL NOT NULL,
+            tokens_remaining REAL NOT NULL,
+            cost_sol REAL NOT NULL,
+            opened_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS position_fills (
+            signature TEXT PRIMARY KEY,
+            token_mint TEXT NOT NULL,
+            fraction REAL NOT NULL,
+            tokens REAL NOT NULL,
+            proceeds_sol REAL,
+            cost_wavg_sol REAL NOT NULL,
+            cost_fifo_sol REAL NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_lots_mint ON position_lots(token_mint, tokens_remaining);",
+    )?;
+
+    // Sin ningún lote: posiciones abiertas antes del cost basis, reconstruir desde `trades`
+    let lots: i64 = conn.query_row("SELECT COUNT(*) FROM position_lots", [], |row| row.get(0))?;
+    if lots == 0 {
+        backfill_cost_basis(conn)?;
+    }
+    Ok(())
+}
+
+/// TP1/TP2 fijos → escalera de N escalones en `position_tp_rungs`.
+///
+/// El TP2 vendía un % del balance que dejaba el TP1; en la escalera los % son
+/// sobre la posición original, así que se reescala. Los NULL toman los
+/// defaults que aplicaba la estrategia (TP1 a +100% vendiendo el 50%).
+fn tp_ladder(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS position_tp_rungs (
+            token_mint TEXT NOT NULL,
+            rung INTEGER NOT NULL,
+            target_percent REAL NOT NULL,
+            sell_percent REAL NOT NULL,
+            triggered INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (token_mint, rung)
+        )",
+        [],
+    )?;
+
+    let present: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('positions') WHERE name = 'tp_percent'",
+        [],
+        |row| row.get(0),
+    )?;
+
+    if present > 0 {
+        conn.execute_batch(
+            "INSERT OR IGNORE INTO position_tp_rungs (
+                token_mint, rung, target_percent, sell_percent, triggered
+            )
+            SELECT token_mint, 0,
+                   COALESCE(tp_percent, 100.0),
+                   COALESCE(tp_amount_percent, 50.0),
+                   COALESCE(tp_triggered, 0)
+            FROM positions;
+
+            INSERT OR IGNORE INTO position_tp_rungs (
+                token_mint, rung, target_percent, sell_percent, triggered
+            )
+            SELECT token_mint, 1,
+                   tp2_percent,
+                   COALESCE(tp2_amount_percent, 100.0)
+                       * (100.0 - COALESCE(tp_amount_percent, 50.0)) / 100.0,
+                   COALESCE(tp2_triggered, 0)
+            FROM positions
+            WHERE tp2_percent IS NOT NULL
+              AND COALESCE(tp_amount_percent, 50.0) < 100.0;",
+        )?;
+    }
+
+    for column in [
+        "tp_percent",
+        "tp_amount_percent",
+        "tp_triggered",
+        "tp2_percent",
+        "tp2_amount_percent",
+        "tp2_triggered",
+    ] {
+        drop_column(conn, "positions", column)?;
+    }
+    Ok(())
+}
+
+fn exit_rules(conn: &Connection) -> Result<()> {
+    add_column(conn, "positions", "max_hold_secs", "INTEGER")?;
+    add_column(conn, "positions", "break_even_after_percent", "REAL")?;
+    add_column(
+        conn,
+        "positions",
+        "break_even_armed",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column(conn, "positions", "stale_slope_threshold", "REAL")?;
+    add_column(conn, "positions", "stale_minutes", "INTEGER")?;
+    add_column(conn, "positions", "min_liquidity_usd", "REAL")?;
+    Ok(())
+}
+
+fn entry_orders(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS entry_orders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_mint TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            amount_sol REAL NOT NULL,
+            trigger_kind TEXT NOT NULL,
+            trigger_price REAL NOT NULL,
+            slippage_bps INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            signature TEXT,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_entry_orders_status ON entry_orders(status);",
+    )?;
+    Ok(())
+}
+
+fn breaker_capital(conn: &Connection) -> Result<()> {
+    add_column(conn, "circuit_breaker", "capital_sol", "REAL")
+}
+
+/// Las órdenes ya creadas se quedan con la salida que les aplicaba `/buy`
+fn entry_order_exits(conn: &Connection) -> Result<()> {
+    add_column(
+        conn,
+        "entry_orders",
+        "stop_loss_percent",
+        "REAL NOT NULL DEFAULT -40.0",
+    )?;
+    add_column(
+        conn,
+        "entry_orders",
+        "trailing_enabled",
+        "INTEGER NOT NULL DEFAULT 1",
+    )?;
+    add_column(
+        conn,
+        "entry_orders",
+        "trailing_distance_percent",
+        "REAL NOT NULL DEFAULT 15.0",
+    )?;
+    add_column(
+        conn,
+        "entry_orders",
+        "trailing_activation_threshold",
+        "REAL NOT NULL DEFAULT 10.0",
+    )?;
+    add_column(conn, "entry_orders", "tp_ladder_json", "TEXT")?;
+    Ok(())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Esquema tal y como lo dejaba `StateManager` antes de las migraciones
+    /// versionadas (sin `schema_version`), con una posición y sus trades.
+    const FIXTURE_UNVERSIONED: &str = "
+        CREATE TABLE positions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_mint TEXT NOT NULL UNIQUE,
+            symbol TEXT NOT NULL,
+            entry_price REAL NOT NULL,
+            amount_sol REAL NOT NULL,
+            current_price REAL NOT NULL,
+            stop_loss_percent REAL NOT NULL,
+            trailing_enabled INTEGER NOT NULL,
+            trailing_distance_percent REAL NOT NULL,
+            trailing_activation_threshold REAL NOT NULL,
+            trailing_highest_price REAL,
+            trailing_current_sl REAL,
+            tp_percent REAL,
+            tp_amount_percent REAL,
+            tp_triggered INTEGER DEFAULT 0,
+            tp2_percent REAL,
+            tp2_amount_percent REAL,
+            tp2_triggered INTEGER DEFAULT 0,
+            active INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            token_amount REAL NOT NULL DEFAULT 0,
+            realized_pnl_sol REAL NOT NULL DEFAULT 0,
+            realized_pnl_fifo_sol REAL NOT NULL DEFAULT 0
+        );
+        CREATE TABLE trades (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            signature TEXT NOT NULL UNIQUE,
+            token_mint TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            trade_type TEXT NOT NULL,
+            amount_sol REAL NOT NULL,
+            tokens_amount REAL NOT NULL,
+            price REAL NOT NULL,
+            pnl_sol REAL,
+            pnl_percent REAL,
+            route TEXT NOT NULL,
+            price_impact_pct REAL NOT NULL,
+            fee_sol REAL NOT NULL DEFAULT 0.0,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE TABLE config_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            config_json TEXT NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE TABLE circuit_breaker (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            tripped INTEGER NOT NULL DEFAULT 0,
+            reason TEXT,
+            tripped_at INTEGER,
+            reset_at INTEGER,
+            last_reset_at INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE orders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_mint TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            command_type TEXT NOT NULL,
+            sell_pct INTEGER NOT NULL,
+            amount_invested REAL NOT NULL,
+            tokens_before INTEGER,
+            status TEXT NOT NULL,
+            signature TEXT,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        CREATE TABLE position_balances (
+            token_mint TEXT PRIMARY KEY,
+            token_amount INTEGER NOT NULL,
+            amount_sol REAL NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        CREATE TABLE position_lots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_mint TEXT NOT NULL,
+            signature TEXT NOT NULL UNIQUE,
+            tokens REAL NOT NULL,
+            tokens_remaining REAL NOT NULL,
+            cost_sol REAL NOT NULL,
+            opened_at INTEGER NOT NULL
+        );
+        CREATE TABLE position_fills (
+            signature TEXT PRIMARY KEY,
+            token_mint TEXT NOT NULL,
+            fraction REAL NOT NULL,
+            tokens REAL NOT NULL,
+            proceeds_sol REAL,
+            cost_wavg_sol REAL NOT NULL,
+            cost_fifo_sol REAL NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE INDEX idx_positions_active ON positions(active);
+        CREATE INDEX idx_trades_timestamp ON trades(timestamp DESC);
+        CREATE INDEX idx_orders_status ON orders(status);
+        CREATE INDEX idx_lots_mint ON position_lots(token_mint, tokens_remaining);
+
+        INSERT INTO positions (
+            token_mint, symbol, entry_price, amount_sol, current_price,
+            stop_loss_percent, trailing_enabled, trailing_distance_percent,
+            trailing_activation_threshold, tp_percent, tp_amount_percent,
+            active, created_at, updated_at, token_amount
+        ) VALUES ('FIXTURE_MINT', 'FIX', 0.001, 1.0, 0.002, -40, 1, 15, 10, 50, 100, 1, 1, 1, 1000);
+        INSERT INTO position_lots (token_mint, signature, tokens, tokens_remaining, cost_sol, opened_at)
+        VALUES ('FIXTURE_MINT', 'FIXTURE_BUY', 1000, 1000, 1.0, 1);
+        INSERT INTO trades (
+            signature, token_mint, symbol, trade_type, amount_sol, tokens_amount,
+            price, route, price_impact_pct, fee_sol, timestamp
+        ) VALUES ('FIXTURE_BUY', 'FIXTURE_MINT', 'FIX', 'MANUAL_BUY', 1.0, 1000, 0.001, 'Jupiter', 0.1, 0.001, 1);
+    ";
+
+    /// Esquema del baseline (solo `positions` con TP1/TP2 fijos, `trades` y
+    /// `config_snapshots`): sin lotes, así que la v7 los reconstruye de `trades`.
+    /// DCA en dos compras + TP1 del 50% en una posición viva, y otra cerrada por SL.
+    const FIXTURE_BASELINE: &str = "
+        CREATE TABLE positions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_mint TEXT NOT NULL UNIQUE,
+            symbol TEXT NOT NULL,
+            entry_price REAL NOT NULL,
+            amount_sol REAL NOT NULL,
+            current_price REAL NOT NULL,
+            stop_loss_percent REAL NOT NULL,
+            trailing_enabled INTEGER NOT NULL,
+            trailing_distance_percent REAL NOT NULL,
+            trailing_activation_threshold REAL NOT NULL,
+            trailing_highest_price REAL,
+            trailing_current_sl REAL,
+            tp_percent REAL,
+            tp_amount_percent REAL,
+            tp_triggered INTEGER DEFAULT 0,
+            tp2_percent REAL,
+            tp2_amount_percent REAL,
+            tp2_triggered INTEGER DEFAULT 0,
+            active INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        CREATE TABLE trades (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            signature TEXT NOT NULL UNIQUE,
+            token_mint TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            trade_type TEXT NOT NULL,
+            amount_sol REAL NOT NULL,
+            tokens_amount REAL NOT NULL,
+            price REAL NOT NULL,
+            pnl_sol REAL,
+            pnl_percent REAL,
+            route TEXT NOT NULL,
+            price_impact_pct REAL NOT NULL,
+            fee_sol REAL NOT NULL DEFAULT 0.0,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE TABLE config_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            config_json TEXT NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE INDEX idx_positions_active ON positions(active);
+        CREATE INDEX idx_trades_timestamp ON trades(timestamp DESC);
+
+        INSERT INTO positions (
+            token_mint, symbol, entry_price, amount_sol, current_price,
+            stop_loss_percent, trailing_enabled, trailing_distance_percent,
+            trailing_activation_threshold, tp_percent, tp_amount_percent, tp_triggered,
+            active, created_at, updated_at
+        ) VALUES
+            ('DCA_MINT', 'DCA', 0.0012, 0.75, 0.0019, -40, 1, 15, 10, 50, 50, 1, 1, 1, 3),
+            ('SL_MINT', 'SLX', 0.001, 0.3, 0.0006, -40, 0, 15, 10, 50, 100, 0, 0, 1, 2);
+
+        INSERT INTO trades (
+            signature, token_mint, symbol, trade_type, amount_sol, tokens_amount,
+            price, pnl_sol, pnl_percent, route, price_impact_pct, fee_sol, timestamp
+        ) VALUES
+            ('BUY_1', 'DCA_MINT', 'DCA', 'MANUAL_BUY', 1.0, 1000, 0.001, NULL, NULL, 'Jupiter', 0.1, 0.001, 1),
+            ('BUY_2', 'DCA_MINT', 'DCA', 'MANUAL_BUY', 0.5, 250, 0.002, NULL, NULL, 'Jupiter', 0.1, 0.001, 2),
+            ('TP_1', 'DCA_MINT', 'DCA', 'AUTO_TP1', 1.2, 625, 0.00192, 0.45, 60.0, 'Jupiter', 0.2, 0.001, 3),
+            ('BUY_3', 'SL_MINT', 'SLX', 'MANUAL_BUY', 0.3, 300, 0.001, NULL, NULL, 'Jupiter', 0.1, 0.001, 1),
+            ('SL_1', 'SL_MINT', 'SLX', 'AUTO_SL', 0.18, 300, 0.0006, -0.12, -40.0, 'Jupiter', 0.3, 0.001, 2);
+    ";
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn
+            .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_versions_are_consecutive() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, i + 1, "{}", migration.name);
+        }
+    }
+
+    #[test]
+    fn test_upgrade_unversioned_fixture() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(FIXTURE_UNVERSIONED).unwrap();
+
+        let before = status(&conn).unwrap();
+        assert_eq!(before.current_version, 0);
+        assert_eq!(before.pending.len(), MIGRATIONS.len());
+
+        let applied = migrate(&mut conn, false).unwrap();
+        assert_eq!(applied.len(), MIGRATIONS.len());
+
+        let after = status(&conn).unwrap();
+        assert!(after.is_up_to_date());
+        assert_eq!(after.current_version, latest_version());
+
+        // Los datos sobreviven y el lote existente no se re-backfillea
+        let (amount, tokens): (f64, f64) = conn
+            .query_row(
+                "SELECT amount_sol, token_amount FROM positions WHERE token_mint = 'FIXTURE_MINT'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((amount, tokens), (1.0, 1000.0));
+        let lots: i64 = conn
+            .query_row("SELECT COUNT(*) FROM position_lots", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(lots, 1);
+
+        // TP fijo de la posición → primer escalón de la escalera
+        let rung: (f64, f64, i64) = conn
+            .query_row(
+                "SELECT target_percent, sell_percent, triggered FROM position_tp_rungs
+                 WHERE token_mint = 'FIXTURE_MINT' AND rung = 0",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(rung, (50.0, 100.0, 0));
+        assert!(!columns(&conn, "positions")
+            .iter()
+            .any(|c| c == "tp_percent"));
+
+        // Segunda pasada: nada pendiente
+        assert!(migrate(&mut conn, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_upgrade_baseline_backfills_cost_basis() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(FIXTURE_BASELINE).unwrap();
+
+        migrate(&mut conn, false).unwrap();
+        assert_eq!(status(&conn).unwrap().current_version, latest_version());
+
+        // TP1 liberó 0.75 de 1.5 SOL (recibido − PnL): 625 tokens, FIFO del primer lote
+        let lots: Vec<(String, f64, f64, f64)> = conn
+            .prepare(
+                "SELECT signature, tokens, tokens_remaining, cost_sol FROM position_lots
+                 WHERE token_mint = 'DCA_MINT' ORDER BY opened_at",
+            )
+            .unwrap()
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            lots,
+            vec![
+                ("BUY_1".to_string(), 1000.0, 375.0, 1.0),
+                ("BUY_2".to_string(), 250.0, 250.0, 0.5),
+            ]
+        );
+
+        let (amount, tokens, entry, realized, realized_fifo): (f64, f64, f64, f64, f64) = conn
+            .query_row(
+                "SELECT amount_sol, token_amount, entry_price, realized_pnl_sol, realized_pnl_fifo_sol
+                 FROM positions WHERE token_mint = 'DCA_MINT'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )
+            .unwrap();
+        assert!((amount - 0.75).abs() < 1e-9);
+        assert!((tokens - 625.0).abs() < 1e-9);
+        assert!((entry - 0.0012).abs() < 1e-12);
+        assert!((realized - 0.45).abs() < 1e-9);
+        assert!((realized_fifo - 0.575).abs() < 1e-9);
+
+        // La posición cerrada por SL no se backfillea
+        let closed_lots: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM position_lots WHERE token_mint = 'SL_MINT'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(closed_lots, 0);
+
+        // TP1 ya disparado → escalón 0 marcado
+        let triggered: i64 = conn
+            .query_row(
+                "SELECT triggered FROM position_tp_rungs WHERE token_mint = 'DCA_MINT' AND rung = 0",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(triggered, 1);
+    }
+
+    #[test]
+    fn test_upgrade_legacy_schema_adds_columns() {
+        // DB de antes de los TP y los fees: solo las tablas de la v1
+        let mut conn = Connection::open_in_memory().unwrap();
+        initial_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO positions (
+                token_mint, symbol, entry_price, amount_sol, current_price,
+                stop_loss_percent, trailing_enabled, trailing_distance_percent,
+                trailing_activation_threshold, active, created_at, updated_at
+            ) VALUES ('OLD', 'OLD', 0.001, 0.5, 0.001, -50, 0, 10, 10, 1, 1, 1)",
+            [],
+        )
+        .unwrap();
+
+        migrate(&mut conn, false).unwrap();
+
+        let positions = columns(&conn, "positions");
+        for column in ["token_amount", "realized_pnl_fifo_sol", "break_even_armed"] {
+            assert!(positions.iter().any(|c| c == column), "falta {}", column);
+        }
+        // Sin TP configurado: escalera con los defaults de la estrategia
+        let rungs: Vec<(f64, f64)> = conn
+            .prepare("SELECT target_percent, sell_percent FROM position_tp_rungs WHERE token_mint = 'OLD'")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(rungs, vec![(100.0, 50.0)]);
+        assert!(columns(&conn, "trades").iter().any(|c| c == "fee_sol"));
+        assert!(!columns(&conn, "orders").is_empty());
+    }
+
+    #[test]
+    fn test_tp2_rescaled_to_original_position() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(FIXTURE_UNVERSIONED).unwrap();
+        // TP1 ya ejecutado (50%), TP2 vendía el 100% de lo que quedaba
+        conn.execute(
+            "UPDATE positions SET tp_percent = 100, tp_amount_percent = 50, tp_triggered = 1,
+                    tp2_percent = 200, tp2_amount_percent = 100",
+            [],
+        )
+        .unwrap();
+
+        migrate(&mut conn, false).unwrap();
+
+        let rungs: Vec<(f64, f64, i64)> = conn
+            .prepare(
+                "SELECT target_percent, sell_percent, triggered FROM position_tp_rungs
+                 WHERE token_mint = 'FIXTURE_MINT' ORDER BY rung",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(rungs, vec![(100.0, 50.0, 1), (200.0, 50.0, 0)]);
+    }
+
+    #[test]
+    fn test_dry_run_leaves_db_untouched() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(FIXTURE_UNVERSIONED).unwrap();
+
+        let would_apply = migrate(&mut conn, true).unwrap();
+        assert_eq!(would_apply.len(), MIGRATIONS.len());
+        assert!(!has_version_table(&conn).unwrap());
+        assert_eq!(status(&conn).unwrap().current_version, 0);
+    }
+
+    #[test]
+    fn test_refuses_newer_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn, false).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, 'future', 0)",
+            params![latest_version() + 1],
+        )
+        .unwrap();
+
+        assert!(migrate(&mut conn, false).is_err());
+    }
+}
@@ -0,0 +1,692 @@
+//! # Orca Whirlpool Direct Swap
+//!
+//! Venue directo sobre Whirlpools (concentrated liquidity de Orca), con la misma
+//! interfaz que `RaydiumClient`: `find_pool` → `quote_exact_in` → `execute_swap`
+//! / `execute_sell` / `execute_sell_with_jito`.
+//!
+//! La cotización recorre los tick arrays en la dirección del swap con
+//! `amm_math::quote_concentrated` (misma matemática que Raydium CLMM).
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use crate::amm_math::{quote_concentrated, sqrt_price_from_x64, ClQuote, ClTick};
+use crate::direct_swap::{
+    deepest, measure_vault_depths, send_instructions, send_instructions_with_jito,
+    wrap_swap_instructions, WSOL_MINT,
+};
+use crate::token_2022::{associated_token_address, mint_token_program};
+use crate::venue::{QuoteRequest, SwapVenue, VenuePayload, VenueQuote};
+
+// ============================================================================
+// CONSTANTS - Orca Whirlpool Program
+// ============================================================================
+
+pub const WHIRLPOOL_PROGRAM_ID: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+
+/// Tamaño del account `Whirlpool` (discriminador Anchor incluido)
+pub const WHIRLPOOL_SIZE: usize = 653;
+
+/// Denominador de `fee_rate` (centésimas de bps: 3000 = 0.3%)
+pub const WHIRLPOOL_FEE_RATE_DENOMINATOR: u32 = 1_000_000;
+
+/// Ticks por `TickArray`
+pub const TICK_ARRAY_SIZE: i32 = 88;
+
+/// El swap de Whirlpool exige exactamente 3 tick arrays
+const TICK_ARRAYS_PER_SWAP: i32 = 3;
+
+/// Límites de sqrt_price (Q64.64) aceptados por el programa
+const MIN_SQRT_PRICE_X64: u128 = 4_295_048_016;
+const MAX_SQRT_PRICE_X64: u128 = 79_226_673_515_401_279_992_447_579_055;
+
+/// sha256("global:swap")[..8]
+const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+/// Offsets de los mints dentro de `Whirlpool` (para filtros memcmp)
+const MINT_A_OFFSET: usize = 101;
+const MINT_B_OFFSET: usize = 181;
+
+/// Layout de `TickArray`: [disc 8][start_tick 4][ticks 88 × 113][whirlpool 32]
+const TICK_ARRAY_TICKS_OFFSET: usize = 12;
+const TICK_LEN: usize = 113;
+
+// ============================================================================
+// WHIRLPOOL STATE
+// ============================================================================
+
+/// Estado on-chain de un Whirlpool (campos necesarios para swap/quote)
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhirlpoolState {
+    pub address: Pubkey,
+    pub tick_spacing: u16,
+    pub fee_rate: u16,
+    pub liquidity: u128,
+    pub sqrt_price_x64: u128,
+    pub tick_current_index: i32,
+    pub token_mint_a: Pubkey,
+    pub token_vault_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_vault_b: Pubkey,
+}
+
+impl WhirlpoolState {
+    /// Layout: [disc 8][config 32][bump 1][tick_spacing 2][seed 2][fee_rate 2]
+    /// [protocol_fee_rate 2][liquidity 16][sqrt_price 16][tick_current 4]
+    /// [proto_fee_a 8][proto_fee_b 8][mint_a 32][vault_a 32][fee_growth_a 16]
+    /// [mint_b 32][vault_b 32]...
+    pub fn from_account_data(address: Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() < 245 {
+            anyhow::bail!("Whirlpool demasiado corto: {} bytes", data.len());
+        }
+
+        let read_pubkey =
+            |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+        let read_u128 = |offset: usize| -> u128 {
+            u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+        };
+
+        Ok(Self {
+            address,
+            tick_spacing: u16::from_le_bytes(data[41..43].try_into().unwrap()),
+            fee_rate: u16::from_le_bytes(data[45..47].try_into().unwrap()),
+            liquidity: read_u128(49),
+            sqrt_price_x64: read_u128(65),
+            tick_current_index: i32::from_le_bytes(data[81..85].try_into().unwrap()),
+            token_mint_a: read_pubkey(MINT_A_OFFSET),
+            token_vault_a: read_pubkey(133),
+            token_mint_b: read_pubkey(MINT_B_OFFSET),
+            token_vault_b: read_pubkey(213),
+        })
+    }
+
+    /// `Some(true)` si `mint` es token A (swap a→b), `Some(false)` si es token B
+    pub fn is_token_a(&self, mint: &Pubkey) -> Option<bool> {
+        if *mint == self.token_mint_a {
+            Some(true)
+        } else if *mint == self.token_mint_b {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> String {
+        format!("Whirlpool/{}", &self.address.to_string()[..6])
+    }
+
+    pub fn tick_array_span(&self) -> i32 {
+        self.tick_spacing as i32 * TICK_ARRAY_SIZE
+    }
+
+    pub fn tick_array_start_index(&self, tick: i32) -> i32 {
+        let span = self.tick_array_span();
+        tick.div_euclid(span) * span
+    }
+
+    /// Start indexes de los 3 tick arrays del swap. En b→a el SDK de Orca
+    /// desplaza un tick_spacing para no quedarse en el borde del array actual.
+    pub fn swap_tick_array_starts(&self, a_to_b: bool) -> Vec<i32> {
+        let span = self.tick_array_span();
+        let shift = if a_to_b { 0 } else { self.tick_spacing as i32 };
+        let current = self.tick_array_start_index(self.tick_current_index + shift);
+        (0..TICK_ARRAYS_PER_SWAP)
+            .map(|i| {
+                if a_to_b {
+                    current - i * span
+                } else {
+                    current + i * span
+                }
+            })
+            .collect()
+    }
+}
+
+/// Extrae los ticks inicializados de un `TickArray` (el índice del tick no se
+/// guarda on-chain: se deriva de `start_tick_index + i * tick_spacing`)
+pub fn parse_tick_array(data: &[u8], tick_spacing: u16) -> Result<Vec<ClTick>> {
+    let end = TICK_ARRAY_TICKS_OFFSET + TICK_ARRAY_SIZE as usize * TICK_LEN;
+    if data.len() < end {
+        anyhow::bail!("TickArray demasiado corto: {} bytes", data.len());
+    }
+
+    let start = i32::from_le_bytes(data[8..12].try_into().unwrap());
+
+    let ticks = (0..TICK_ARRAY_SIZE as usize)
+        .filter_map(|i| {
+            let base = TICK_ARRAY_TICKS_OFFSET + i * TICK_LEN;
+            let initialized = data[base] != 0;
+            let liquidity_net = i128::from_le_bytes(data[base + 1..base + 17].try_into().unwrap());
+            initialized.then_some(ClTick {
+                tick: start + i as i32 * tick_spacing as i32,
+                liquidity_net,
+            })
+        })
+        .collect();
+
+    Ok(ticks)
+}
+
+/// Cotiza un swap exact-in con los ticks cargados
+pub fn quote_whirlpool(
+    pool: &WhirlpoolState,
+    ticks: &[ClTick],
+    amount_in: u64,
+    a_to_b: bool,
+) -> ClQuote {
+    let mut sorted = ticks.to_vec();
+    sorted.sort_by_key(|t| t.tick);
+    quote_concentrated(
+        sqrt_price_from_x64(pool.sqrt_price_x64),
+        pool.liquidity,
+        &sorted,
+        amount_in,
+        a_to_b,
+        pool.fee_rate as f64 / WHIRLPOOL_FEE_RATE_DENOMINATOR as f64,
+    )
+}
+
+// ============================================================================
+// PDAs & INSTRUCTIONS
+// ============================================================================
+
+pub fn program_id() -> Pubkey {
+    Pubkey::from_str(WHIRLPOOL_PROGRAM_ID).unwrap()
+}
+
+/// PDA del tick array (el start index va como string decimal en la seed)
+pub fn derive_tick_array(whirlpool: &Pubkey, start_index: i32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"tick_array",
+            whirlpool.as_ref(),
+            start_index.to_string().as_bytes(),
+        ],
+        &program_id(),
+    )
+    .0
+}
+
+pub fn derive_oracle(whirlpool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"oracle", whirlpool.as_ref()], &program_id()).0
+}
+
+/// Construye `swap` (exact-in). `tick_arrays` son los 3 arrays en orden de recorrido.
+#[allow(clippy::too_many_arguments)]
+pub fn build_swap_instruction(
+    pool: &WhirlpoolState,
+    owner: &Pubkey,
+    input_mint: &Pubkey,
+    owner_account_a: Pubkey,
+    owner_account_b: Pubkey,
+    tick_arrays: [Pubkey; 3],
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<Instruction> {
+    let a_to_b = pool
+        .is_token_a(input_mint)
+        .context("El mint de entrada no pertenece al Whirlpool")?;
+
+    let sqrt_price_limit = if a_to_b {
+        MIN_SQRT_PRICE_X64
+    } else {
+        MAX_SQRT_PRICE_X64
+    };
+
+    // [disc][amount][other_amount_threshold][sqrt_price_limit][amount_specified_is_input][a_to_b]
+    let mut data = Vec::with_capacity(42);
+    data.extend_from_slice(&SWAP_DISCRIMINATOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
+    data.extend_from_slice(&sqrt_price_limit.to_le_bytes());
+    data.push(1);
+    data.push(a_to_b as u8);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(spl_token::id(), false), // 0. Token Program
+        AccountMeta::new_readonly(*owner, true),           // 1. Token Authority (Signer)
+        AccountMeta::new(pool.address, false),             // 2. Whirlpool
+        AccountMeta::new(owner_account_a, false),          // 3. User Token Account A
+        AccountMeta::new(pool.token_vault_a, false),       // 4. Vault A
+        AccountMeta::new(owner_account_b, false),          // 5. User Token Account B
+        AccountMeta::new(pool.token_vault_b, false),       // 6. Vault B
+        AccountMeta::new(tick_arrays[0], false),           // 7. Tick Array 0
+        AccountMeta::new(tick_arrays[1], false),           // 8. Tick Array 1
+        AccountMeta::new(tick_arrays[2], false),           // 9. Tick Array 2
+        AccountMeta::new(derive_oracle(&pool.address), false), // 10. Oracle
+    ];
+
+    Ok(Instruction {
+        program_id: program_id(),
+        accounts,
+        data,
+    })
+}
+
+// ============================================================================
+// ORCA CLIENT - Misma interfaz que RaydiumClient
+// ============================================================================
+
+/// Tick arrays cargados para un swap concreto
+#[derive(Debug, Clone)]
+pub struct SwapTickArrays {
+    /// Siempre 3: si faltan arrays on-chain se repite el último existente
+    pub addresses: [Pubkey; 3],
+    pub ticks: Vec<ClTick>,
+}
+
+pub struct OrcaClient {
+    rpc_client: RpcClient,
+    selected_pools: RwLock<HashMap<String, WhirlpoolState>>, // Pool más profundo por par
+}
+
+impl OrcaClient {
+    pub fn new(rpc_url: String) -> Result<Self> {
+        Ok(Self {
+            rpc_client: RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed()),
+            selected_pools: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Encuentra el Whirlpool más profundo para el par (cualquier tick spacing)
+    pub async fn find_pool(&self, base_mint: &str, quote_mint: &str) -> Result<WhirlpoolState> {
+        let key1 = format!("{}-{}", base_mint, quote_mint);
+        let key2 = format!("{}-{}", quote_mint, base_mint);
+
+        if let Ok(selected) = self.selected_pools.read() {
+            if let Some(pool) = selected.get(&key1).or_else(|| selected.get(&key2)) {
+                return Ok(pool.clone());
+            }
+        }
+
+        let base_pubkey = Pubkey::from_str(base_mint)?;
+        let quote_pubkey = Pubkey::from_str(quote_mint)?;
+
+        let mut candidates = self.discover_pools(&base_pubkey, &quote_pubkey)?;
+        if candidates.is_empty() {
+            anyhow::bail!("❌ Ningún Whirlpool para {}/{}", base_mint, quote_mint);
+        }
+
+        let reference_mint = if base_mint == WSOL_MINT {
+            base_pubkey
+        } else {
+            quote_pubkey
+        };
+        let vault_pairs: Vec<[Pubkey; 2]> = candidates
+            .iter()
+            .map(|p| [p.token_vault_a, p.token_vault_b])
+            .collect();
+        let depths = measure_vault_depths(&self.rpc_client, &vault_pairs, &reference_mint);
+
+        let (best_idx, best_depth) = deepest(&depths).unwrap_or((0, 0));
+        let best = candidates.swap_remove(best_idx);
+
+        println!(
+            "✅ Whirlpool seleccionado: {} (spacing {}, {} candidatos, profundidad {} raw)",
+            best.name(),
+            best.tick_spacing,
+            depths.len(),
+            best_depth
+        );
+
+        if let Ok(mut selected) = self.selected_pools.write() {
+            selected.insert(key1, best.clone());
+        }
+
+        Ok(best)
+    }
+
+    /// Busca Whirlpools del par (ambos órdenes de mints) vía getProgramAccounts
+    fn discover_pools(&self, mint_a: &Pubkey, mint_b: &Pubkey) -> Result<Vec<WhirlpoolState>> {
+        use solana_account_decoder::UiAccountEncoding;
+        use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+        use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+
+        let mut pools = Vec::new();
+
+        for (first, second) in [(mint_a, mint_b), (mint_b, mint_a)] {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(WHIRLPOOL_SIZE as u64),
+                    RpcFilterType::Memcmp(Memcmp::new(
+                        MINT_A_OFFSET,
+                        MemcmpEncodedBytes::Base58(first.to_string()),
+                    )),
+                    RpcFilterType::Memcmp(Memcmp::new(
+                        MINT_B_OFFSET,
+                        MemcmpEncodedBytes::Base58(second.to_string()),
+                    )),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    ..Default::default()
+                },
+                with_context: Some(false),
+            };
+
+            for (address, account) in self
+                .rpc_client
+                .get_program_accounts_with_config(&program_id(), config)?
+            {
+                if let Ok(state) = WhirlpoolState::from_account_data(address, &account.data) {
+                    if state.liquidity > 0 {
+                        pools.push(state);
+                    }
+                }
+            }
+        }
+
+        Ok(pools)
+    }
+
+    /// Re-lee el pool y carga los 3 tick arrays del swap (una llamada RPC)
+    fn fetch_swap_state(
+        &self,
+        pool: &WhirlpoolState,
+        a_to_b: bool,
+    ) -> Result<(WhirlpoolState, SwapTickArrays)> {
+        let account = self.rpc_client.get_account(&pool.address)?;
+        let state = WhirlpoolState::from_account_data(pool.address, &account.data)?;
+
+        let candidates: Vec<Pubkey> = state
+            .swap_tick_array_starts(a_to_b)
+            .into_iter()
+            .map(|start| derive_tick_array(&state.address, start))
+            .collect();
+        let accounts = self.rpc_client.get_multiple_accounts(&candidates)?;
+
+        let mut existing = Vec::new();
+        let mut ticks = Vec::new();
+        for (address, account) in candidates.iter().zip(accounts) {
+            match account {
+                Some(account) => {
+                    ticks.extend(parse_tick_array(&account.data, state.tick_spacing)?);
+                    existing.push(*address);
+                }
+                // El recorrido no puede saltar un array sin inicializar
+                None => break,
+            }
+        }
+
+        let first = *existing
+            .first()
+            .context("Tick array actual del Whirlpool no inicializado")?;
+        let last = *existing.last().unwrap_or(&first);
+        let addresses = [
+            first,
+            existing.get(1).copied().unwrap_or(last),
+            existing.get(2).copied().unwrap_or(last),
+        ];
+
+        Ok((state, SwapTickArrays { addresses, ticks }))
+    }
+
+    /// Cotiza on-chain un swap exact-in (raw out)
+    pub fn quote_exact_in(
+        &self,
+        pool: &WhirlpoolState,
+        input_mint: &Pubkey,
+        amount_in: u64,
+    ) -> Result<u64> {
+        let a_to_b = pool
+            .is_token_a(input_mint)
+            .context("El mint de entrada no pertenece al Whirlpool")?;
+        let (state, tick_arrays) = self.fetch_swap_state(pool, a_to_b)?;
+        let quote = quote_whirlpool(&state, &tick_arrays.ticks, amount_in, a_to_b);
+
+        if !quote.fully_filled {
+            anyhow::bail!(
+                "Liquidez Whirlpool insuficiente en los tick arrays cargados ({} de {} consumidos)",
+                quote.amount_in,
+                amount_in
+            );
+        }
+        Ok(quote.amount_out)
+    }
+
+    /// Atajo: busca el pool y cotiza
+    pub async fn quote(&self, input_mint: &str, output_mint: &str, amount_in: u64) -> Result<u64> {
+        let pool = self.find_pool(input_mint, output_mint).await?;
+        self.quote_exact_in(&pool, &Pubkey::from_str(input_mint)?, amount_in)
+    }
+
+    /// Calcula el min_amount_out basado en slippage
+    pub fn calculate_min_amount_out(&self, expected_out: u64, slippage_bps: u16) -> u64 {
+        let slippage_multiplier = 1.0 - (slippage_bps as f64 / 10000.0);
+        (expected_out as f64 * slippage_multiplier) as u64
+    }
+
+    /// Instrucciones completas del swap (ATAs + WSOL wrap/unwrap + swap)
+    pub fn build_pool_swap_instructions(
+        &self,
+        pool: &WhirlpoolState,
+        input_mint: &Pubkey,
+        owner: &Pubkey,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<Vec<Instruction>> {
+        let a_to_b = pool
+            .is_token_a(input_mint)
+            .context("El mint de entrada no pertenece al Whirlpool")?;
+        let (state, tick_arrays) = self.fetch_swap_state(pool, a_to_b)?;
+        let output_mint = if a_to_b {
+            state.token_mint_b
+        } else {
+            state.token_mint_a
+        };
+
+        // ATAs bajo el token program de cada mint (SPL clásico o Token-2022)
+        let program_a = mint_token_program(&self.rpc_client, &state.token_mint_a);
+        let program_b = mint_token_program(&self.rpc_client, &state.token_mint_b);
+        let output_program = if a_to_b { program_b } else { program_a };
+
+        let swap_ix = build_swap_instruction(
+            &state,
+            owner,
+            input_mint,
+            associated_token_address(owner, &state.token_mint_a, &program_a),
+            associated_token_address(owner, &state.token_mint_b, &program_b),
+            tick_arrays.addresses,
+            amount_in,
+            min_amount_out,
+        )?;
+
+        wrap_swap_instructions(
+            owner,
+            input_mint,
+            &output_mint,
+            &output_program,
+            amount_in,
+            swap_ix,
+        )
+    }
+
+    /// Ejecuta un swap completo (construcción + firma + envío)
+    pub async fn execute_swap(
+        &self,
+        base_mint: &str,
+        quote_mint: &str,
+        amount_in: u64,
+        min_amount_out: u64,
+        user_keypair: &Keypair,
+    ) -> Result<String> {
+        println!(
+            "🌊 [ORCA] Swap directo en Whirlpool: {} → {}",
+            base_mint, quote_mint
+        );
+
+        let pool = self.find_pool(base_mint, quote_mint).await?;
+        let ixs = self.build_pool_swap_instructions(
+            &pool,
+            &Pubkey::from_str(base_mint)?,
+            &user_keypair.pubkey(),
+            amount_in,
+            min_amount_out,
+        )?;
+
+        let signature = send_instructions(&self.rpc_client, &ixs, user_keypair)?;
+        println!("✅ [ORCA] Swap ejecutado: {}", signature);
+        Ok(signature)
+    }
+
+    /// Vende tokens por SOL directamente en el Whirlpool más profundo
+    pub async fn execute_sell(
+        &self,
+        token_mint: &str,
+        amount_in: u64,
+        min_sol_out: u64,
+        user_keypair: &Keypair,
+    ) -> Result<String> {
+        self.execute_swap(token_mint, WSOL_MINT, amount_in, min_sol_out, user_keypair)
+            .await
+    }
+
+    /// Venta directa empaquetada como bundle de Jito
+    pub async fn execute_sell_with_jito(
+        &self,
+        token_mint: &str,
+        amount_in: u64,
+        min_sol_out: u64,
+        jito_tip_lamports: u64,
+        user_keypair: &Keypair,
+    ) -> Result<String> {
+        println!("🌊 [ORCA SELL + JITO] Token: {}...", &token_mint[..8]);
+
+        let pool = self.find_pool(token_mint, WSOL_MINT).await?;
+        let ixs = self.build_pool_swap_instructions(
+            &pool,
+            &Pubkey::from_str(token_mint)?,
+            &user_keypair.pubkey(),
+            amount_in,
+            min_sol_out,
+        )?;
+
+        send_instructions_with_jito(&self.rpc_client, &ixs, jito_tip_lamports, user_keypair).await
+    }
+}
+
+#[async_trait]
+impl SwapVenue for OrcaClient {
+    fn name(&self) -> &'static str {
+        "Orca Whirlpool Direct"
+    }
+
+    fn supports(&self, mint: &str) -> bool {
+        Pubkey::from_str(mint).is_ok()
+    }
+
+    async fn quote(&self, request: &QuoteRequest) -> Result<VenueQuote> {
+        let amount_out = self
+            .quote(&request.input_mint, &request.output_mint, request.amount_in)
+            .await?;
+        Ok(VenueQuote::new(self.name(), request, amount_out))
+    }
+
+    async fn build_instructions(&self, quote: &VenueQuote, owner: &Pubkey) -> Result<VenuePayload> {
+        let pool = self
+            .find_pool(&quote.input_mint, &quote.output_mint)
+            .await?;
+        Ok(VenuePayload::Instructions(
+            self.build_pool_swap_instructions(
+                &pool,
+                &Pubkey::from_str(&quote.input_mint)?,
+                owner,
+                quote.amount_in,
+                quote.min_amount_out(),
+            )?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_pool(tick_current: i32, tick_spacing: u16) -> WhirlpoolState {
+        let mut data = vec![0u8; WHIRLPOOL_SIZE];
+        data[41..43].copy_from_slice(&tick_spacing.to_le_bytes());
+        data[45..47].copy_from_slice(&3000u16.to_le_bytes());
+        data[49..65].copy_from_slice(&5_000_000u128.to_le_bytes());
+        data[65..81].copy_from_slice(&(1u128 << 64).to_le_bytes());
+        data[81..85].copy_from_slice(&tick_current.to_le_bytes());
+        data[MINT_A_OFFSET..MINT_A_OFFSET + 32].copy_from_slice(&[1u8; 32]);
+        data[133..165].copy_from_slice(&[3u8; 32]);
+        data[MINT_B_OFFSET..MINT_B_OFFSET + 32].copy_from_slice(&[2u8; 32]);
+        data[213..245].copy_from_slice(&[4u8; 32]);
+        WhirlpoolState::from_account_data(Pubkey::new_unique(), &data).unwrap()
+    }
+
+    #[test]
+    fn test_parse_whirlpool() {
+        let pool = sample_pool(-10, 64);
+        assert_eq!(pool.fee_rate, 3000);
+        assert_eq!(pool.liquidity, 5_000_000);
+        assert_eq!(pool.token_mint_a, Pubkey::new_from_array([1u8; 32]));
+        assert_eq!(pool.token_vault_b, Pubkey::new_from_array([4u8; 32]));
+        assert_eq!(pool.is_token_a(&pool.token_mint_b), Some(false));
+    }
+
+    #[test]
+    fn test_swap_tick_array_starts() {
+        // span = 64 * 88 = 5632
+        let pool = sample_pool(-10, 64);
+        assert_eq!(
+            pool.swap_tick_array_starts(true),
+            vec![-5632, -11264, -16896]
+        );
+        // b→a desplaza un tick_spacing: -10 + 64 = 54 → array 0
+        assert_eq!(pool.swap_tick_array_starts(false), vec![0, 5632, 11264]);
+    }
+
+    #[test]
+    fn test_parse_tick_array_derives_indexes() {
+        let mut data = vec![0u8; TICK_ARRAY_TICKS_OFFSET + 88 * TICK_LEN + 32];
+        data[8..12].copy_from_slice(&(-5632i32).to_le_bytes());
+        let base = TICK_ARRAY_TICKS_OFFSET + 2 * TICK_LEN;
+        data[base] = 1;
+        data[base + 1..base + 17].copy_from_slice(&42i128.to_le_bytes());
+
+        let ticks = parse_tick_array(&data, 64).unwrap();
+        assert_eq!(
+            ticks,
+            vec![ClTick {
+                tick: -5632 + 128,
+                liquidity_net: 42
+            }]
+        );
+    }
+
+    #[test]
+    fn test_swap_instruction_layout() {
+        let pool = sample_pool(0, 64);
+        let arrays = [Pubkey::new_unique(); 3];
+        let ix = build_swap_instruction(
+            &pool,
+            &Pubkey::new_unique(),
+            &pool.token_mint_b,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            arrays,
+            1_000,
+            990,
+        )
+        .unwrap();
+
+        assert_eq!(ix.accounts.len(), 11);
+        assert_eq!(ix.data.len(), 42);
+        // b→a: límite máximo y flag a_to_b = 0
+        assert_eq!(&ix.data[24..40], &MAX_SQRT_PRICE_X64.to_le_bytes());
+        assert_eq!(ix.data[41], 0);
+    }
+}
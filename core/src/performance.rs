@@ -0,0 +1,218 @@
+//! # Performance — Informe de rendimiento del trading real
+//!
+//! Agrupa el historial de `trades` en ciclos compra → ventas por mint (la
+//! misma clasificación `cost_basis::trade_step` que `replay_trades`) y los pasa al
+//! `PerformanceReport` de `intelligence_rs`, el mismo que genera el backtester.
+//!
+//! - El PnL de cada salida es neto: `pnl_sol` del trade menos su `fee_sol`; los
+//!   fees de las compras se descuentan del ciclo.
+//! - La razón de salida sale del `trade_type` (`AUTO_TP2` → `TAKE_PROFIT`,
+//!   `AUTO_EXIT_TIME_LIMIT` → `TIME_LIMIT`); el resto (`MANUAL_SELL`,
+//!   `GHOST_PURGE`...) se agrupa con su propio nombre.
+//! - Sin trayectoria de precio grabada no hay MAE/MFE.
+//! - Los ciclos aún abiertos no entran en el informe.
+
+use intelligence_rs::performance::{ExitLeg, PerformanceReport, TradeSummary};
+use std::collections::HashMap;
+
+use crate::cost_basis::{trade_step, LotBook, TradeStep};
+use crate::engine::commands::CommandType;
+use crate::state_manager::TradeRecord;
+
+/// Trades que lee el informe (`report` y `/stats`)
+pub const REPORT_HISTORY_LIMIT: usize = 10_000;
+
+/// Razón de salida de un `trade_type` de venta
+pub fn exit_reason(trade_type: &str) -> String {
+    match CommandType::from_trade_type(trade_type) {
+        Some(CommandType::TakeProfit(_)) => "TAKE_PROFIT".to_string(),
+        Some(CommandType::StopLoss) => "STOP_LOSS".to_string(),
+        Some(CommandType::Exit(reason)) => reason.as_str().to_string(),
+        Some(CommandType::Manual) | None => trade_type.to_string(),
+    }
+}
+
+/// Ciclo abierto de un mint
+#[derive(Default)]
+struct OpenCycle {
+    book: LotBook,
+    opened_at: i64,
+    invested_sol: f64,
+    pnl_sol: f64,
+    exits: Vec<ExitLeg>,
+}
+
+/// Agrupa el historial (en cualquier orden) en trades cerrados
+pub fn round_trips(history: &[TradeRecord]) -> Vec<TradeSummary> {
+    let mut trades: Vec<&TradeRecord> = history.iter().collect();
+    trades.sort_by_key(|t| (t.timestamp, t.id));
+
+    let mut open: HashMap<&str, OpenCycle> = HashMap::new();
+    let mut closed = Vec::new();
+
+    for trade in trades {
+        let cycle = open
+            .entry(trade.token_mint.as_str())
+            .or_insert_with(|| OpenCycle {
+                opened_at: trade.timestamp,
+                ..OpenCycle::default()
+            });
+
+        let (fraction, proceeds) = match trade_step(trade, &cycle.book) {
+            TradeStep::Buy(lot) => {
+                cycle.book.buy(lot);
+                cycle.invested_sol += trade.amount_sol.abs() + trade.fee_sol;
+                cycle.pnl_sol -= trade.fee_sol;
+                continue;
+            }
+            TradeStep::Sell {
+                fraction,
+                proceeds_sol,
+            } => (fraction, proceeds_sol),
+        };
+
+        // Sin SOL recibido (tokens que salieron fuera del bot) no hay PnL; una
+        // venta sin PnL registrado es salida total y libera todo el coste
+        let gross_pnl = match proceeds {
+            Some(proceeds) => trade.pnl_sol.unwrap_or(proceeds - cycle.book.wavg_cost_sol),
+            None => 0.0,
+        };
+
+        // Venta sin compra registrada (p.ej. /track): el coste es lo que liberó
+        if cycle.book.is_flat() && cycle.invested_sol <= 0.0 {
+            cycle.invested_sol = proceeds.unwrap_or_default() - gross_pnl;
+        }
+
+        cycle.book.sell(fraction, proceeds);
+        let leg_pnl = gross_pnl - trade.fee_sol;
+        cycle.pnl_sol += leg_pnl;
+        cycle.exits.push(ExitLeg {
+            reason: exit_reason(&trade.trade_type),
+            timestamp_ms: (trade.timestamp.max(0) as u64) * 1000,
+            pnl_sol: leg_pnl,
+        });
+
+        if cycle.book.is_flat() {
+            let Some(cycle) = open.remove(trade.token_mint.as_str()) else {
+                continue;
+            };
+            closed.push(TradeSummary {
+                token_mint: trade.token_mint.clone(),
+                opened_at_ms: (cycle.opened_at.max(0) as u64) * 1000,
+                closed_at_ms: (trade.timestamp.max(0) as u64) * 1000,
+                invested_sol: cycle.invested_sol,
+                pnl_sol: cycle.pnl_sol,
+                mae_percent: None,
+                mfe_percent: None,
+                exits: cycle.exits,
+            });
+        }
+    }
+
+    closed
+}
+
+/// Informe de rendimiento del historial real
+pub fn live_report(history: &[TradeRecord]) -> PerformanceReport {
+    PerformanceReport::from_trades(round_trips(history), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reconciler::RECONCILE_TRADE_TYPE;
+
+    fn trade(
+        mint: &str,
+        trade_type: &str,
+        amount_sol: f64,
+        pnl: Option<f64>,
+        ts: i64,
+    ) -> TradeRecord {
+        TradeRecord {
+            id: None,
+            signature: format!("SIG_{}_{}", mint, ts),
+            token_mint: mint.to_string(),
+            symbol: mint.to_string(),
+            trade_type: trade_type.to_string(),
+            amount_sol,
+            tokens_amount: if pnl.is_none() { 1_000.0 } else { 0.0 },
+            price: 0.0,
+            pnl_sol: pnl,
+            pnl_percent: None,
+            route: "test".to_string(),
+            price_impact_pct: 0.0,
+            fee_sol: 0.0001,
+            timestamp: ts,
+        }
+    }
+
+    #[test]
+    fn test_round_trips_from_history() {
+        // Historial en orden DESC, como lo devuelve get_trade_history
+        let mut history = vec![
+            trade("A", "MANUAL_BUY", 1.0, None, 100),
+            trade("B", "MANUAL_BUY", 0.5, None, 150),
+            trade("A", "AUTO_TP1", 1.0, Some(0.5), 200), // vende la mitad: coste 0.5
+            trade("B", "AUTO_EXIT_TIME_LIMIT", 0.3, Some(-0.2), 250),
+            trade("A", "AUTO_SL", 0.4, Some(-0.1), 300),
+            trade("C", "MANUAL_BUY", 0.2, None, 400), // sigue abierta
+        ];
+        history.reverse();
+
+        let trades = round_trips(&history);
+        assert_eq!(trades.len(), 2);
+
+        let b = &trades[0];
+        assert_eq!(b.token_mint, "B");
+        assert_eq!(b.exits[0].reason, "TIME_LIMIT");
+        assert!((b.pnl_sol - (-0.2 - 0.0002)).abs() < 1e-9);
+
+        let a = &trades[1];
+        assert_eq!((a.opened_at_ms, a.closed_at_ms), (100_000, 300_000));
+        assert!((a.invested_sol - 1.0001).abs() < 1e-9);
+        assert!((a.pnl_sol - (0.5 - 0.1 - 0.0003)).abs() < 1e-9);
+        let reasons: Vec<&str> = a.exits.iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(reasons, ["TAKE_PROFIT", "STOP_LOSS"]);
+
+        let report = live_report(&history);
+        assert_eq!(report.trades, 2);
+        assert_eq!(report.longest_losing_streak, 1);
+        assert_eq!(report.by_reason.len(), 3);
+        assert!(report.avg_mae_percent.is_none());
+    }
+
+    #[test]
+    fn test_sell_without_pnl_releases_remaining_cost() {
+        let history = vec![
+            trade("A", "MANUAL_BUY", 1.0, None, 100),
+            trade("A", "AUTO_TP1", 0.6, Some(0.1), 200), // libera 0.5
+            trade("A", "MANUAL_SELL", 0.8, None, 300),   // sin PnL: salida total
+        ];
+
+        let trades = round_trips(&history);
+        assert_eq!(trades.len(), 1);
+        let a = &trades[0];
+        assert!((a.exits[1].pnl_sol - (0.8 - 0.5 - 0.0001)).abs() < 1e-9);
+        assert!((a.pnl_sol - (0.1 + 0.3 - 0.0003)).abs() < 1e-9);
+        assert_eq!(a.exits[1].reason, "MANUAL_SELL");
+    }
+
+    #[test]
+    fn test_reconcile_out_transfer_shrinks_cycle_without_pnl() {
+        let history = vec![
+            trade("A", "MANUAL_BUY", 1.0, None, 100),
+            // Un cuarto de los tokens salió de la wallet fuera del bot
+            trade("A", RECONCILE_TRADE_TYPE, -0.25, None, 200),
+            trade("A", "AUTO_SL", 0.5, Some(-0.25), 300), // libera los 0.75 restantes
+        ];
+
+        let trades = round_trips(&history);
+        assert_eq!(trades.len(), 1);
+        let a = &trades[0];
+        assert_eq!(a.exits[0].reason, RECONCILE_TRADE_TYPE);
+        assert!((a.exits[0].pnl_sol + 0.0001).abs() < 1e-9);
+        assert!((a.pnl_sol - (-0.25 - 0.0003)).abs() < 1e-9);
+        assert_eq!(a.closed_at_ms, 300_000);
+    }
+}
@@ -10,12 +10,14 @@
 //!   [DexScreener]    ──pull──▶ └──────────┘
 //! ```
 
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock};
 
 use crate::geyser::{GeyserClient, GeyserConfig};
+use crate::quote_engine::LocalQuoteEngine;
 use crate::scanner::PriceScanner;
 use crate::telegram::TelegramNotifier;
 
@@ -43,7 +45,7 @@ pub struct PriceUpdate {
 }
 
 /// Fuente del dato de precio
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PriceSource {
     Geyser,
     WebSocket,
@@ -147,9 +149,12 @@ impl PriceFeed {
     /// Arranca el PriceFeed y devuelve:
     /// - Un `Receiver<PriceUpdate>` del cual el monitor principal consume
     /// - Un `PriceCache` con el último precio de cada token (para consultas rápidas)
+    ///
+    /// Geyser / WebSocket publican además las reservas de cada vault en `quotes`.
     pub fn start(
         config: PriceFeedConfig,
         tokens: Vec<MonitoredToken>,
+        quotes: Arc<LocalQuoteEngine>,
     ) -> (
         mpsc::Receiver<PriceUpdate>,
         PriceCache,
@@ -198,6 +203,7 @@ impl PriceFeed {
                 let geyser_tx = tx.clone();
                 let geyser_tokens = Arc::clone(&shared_tokens);
                 let geyser_cache = Arc::clone(&cache);
+                let geyser_quotes = Arc::clone(&quotes);
 
                 tokio::spawn(async move {
                     Self::geyser_stream_loop(
@@ -206,6 +212,7 @@ impl PriceFeed {
                         endpoint,
                         token,
                         geyser_cache,
+                        geyser_quotes,
                     )
                     .await;
                 });
@@ -228,9 +235,13 @@ impl PriceFeed {
                     let ws_tx = tx.clone();
                     let ws_tokens = tokens.clone();
                     let ws_cache = Arc::clone(&cache);
+                    let ws_quotes = Arc::clone(&quotes);
 
                     tokio::spawn(async move {
-                        crate::ws_feed::ws_price_loop(ws_tx, ws_tokens, ws_url, ws_cache).await;
+                        crate::ws_feed::ws_price_loop(
+                            ws_tx, ws_tokens, ws_url, ws_cache, ws_quotes,
+                        )
+                        .await;
                     });
 
                     println!("   🔌 WebSocket RPC: ACTIVADO (on-chain pricing GRATIS)");
@@ -330,6 +341,7 @@ impl PriceFeed {
         endpoint: String,
         api_token: String,
         cache: PriceCache,
+        quotes: Arc<LocalQuoteEngine>,
     ) {
         use crate::amm_math::{
             build_vault_tracker, new_sol_price_tracker, parse_spl_token_account_amount,
@@ -512,6 +524,7 @@ impl PriceFeed {
                                                             let mut tracker = vault_tracker.write().await;
                                                             if let Some(pair) = tracker.get_mut(token_mint) {
                                                                 pair.update_reserve(&vault_address, amount);
+                                                                quotes.publish(pair);
 
                                                                 // Intentar calcular precio
                                                                 if pair.is_ready() {
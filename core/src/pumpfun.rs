@@ -0,0 +1,744 @@
+//! # Pump.fun Bonding Curve Direct Execution
+//!
+//! Compra/venta directa contra la bonding curve de Pump.fun, sin esperar a que
+//! Jupiter indexe el token. Solo aplica a tokens pre-migración (`complete == false`):
+//! una vez la curva se completa, la liquidez migra a Raydium y se usa `RaydiumClient`.
+//!
+//! Pricing: producto constante sobre las reservas *virtuales* de la curva.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::{Transaction, VersionedTransaction},
+};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::direct_swap::WSOL_MINT;
+use crate::venue::{QuoteRequest, SwapVenue, VenuePayload, VenueQuote};
+
+// ============================================================================
+// CONSTANTS - Pump.fun Program
+// ============================================================================
+
+pub const PUMP_PROGRAM_ID: &str = "6EF8rrecthR5Dkzy5fG9VGA7zF5rR9WADwpupump";
+const PUMP_GLOBAL: &str = "4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf";
+const PUMP_FEE_RECIPIENT: &str = "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM";
+const PUMP_EVENT_AUTHORITY: &str = "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1";
+
+/// Discriminadores Anchor: sha256("global:buy")[..8] / sha256("global:sell")[..8]
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+/// Fee del protocolo (1%) cobrado sobre el lado SOL
+pub const PUMP_FEE_BPS: u64 = 100;
+/// Todos los tokens de Pump.fun se mintean con 6 decimales
+pub const PUMP_TOKEN_DECIMALS: u8 = 6;
+
+/// Vida en caché de una curva activa: sus reservas cambian con cada trade
+const ACTIVE_CURVE_TTL: Duration = Duration::from_secs(2);
+/// Vida en caché de "no está en Pump.fun" (curva completa o inexistente)
+const INACTIVE_CURVE_TTL: Duration = Duration::from_secs(300);
+
+const BONDING_CURVE_SEED: &[u8] = b"bonding-curve";
+const CREATOR_VAULT_SEED: &[u8] = b"creator-vault";
+
+// ============================================================================
+// BONDING CURVE STATE
+// ============================================================================
+
+/// Estado on-chain de la bonding curve (cuenta Anchor, 8 bytes de discriminador)
+#[derive(Debug, Clone, PartialEq)]
+pub struct BondingCurveState {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+    /// Presente desde la actualización de creator fees (cuentas nuevas)
+    pub creator: Option<Pubkey>,
+}
+
+impl BondingCurveState {
+    /// Layout: [disc 8][vt 8][vs 8][rt 8][rs 8][supply 8][complete 1][creator 32?]
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        if data.len() < 49 {
+            anyhow::bail!("Bonding curve demasiado corta: {} bytes", data.len());
+        }
+
+        let read_u64 = |offset: usize| -> u64 {
+            u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+        };
+
+        let creator = if data.len() >= 81 {
+            let key = Pubkey::new_from_array(data[49..81].try_into().unwrap());
+            (key != Pubkey::default()).then_some(key)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            virtual_token_reserves: read_u64(8),
+            virtual_sol_reserves: read_u64(16),
+            real_token_reserves: read_u64(24),
+            real_sol_reserves: read_u64(32),
+            token_total_supply: read_u64(40),
+            complete: data[48] != 0,
+            creator,
+        })
+    }
+
+    /// Precio spot en SOL por token (unidades UI)
+    pub fn spot_price_sol(&self) -> f64 {
+        if self.virtual_token_reserves == 0 {
+            return 0.0;
+        }
+        let sol = self.virtual_sol_reserves as f64 / 1e9;
+        let tokens = self.virtual_token_reserves as f64 / 10f64.powi(PUMP_TOKEN_DECIMALS as i32);
+        sol / tokens
+    }
+
+    /// Tokens (raw) recibidos por `sol_in` lamports, con el fee ya descontado
+    pub fn quote_buy(&self, sol_in: u64) -> u64 {
+        let net_sol = (sol_in as u128 * 10_000) / (10_000 + PUMP_FEE_BPS as u128);
+        let vs = self.virtual_sol_reserves as u128;
+        let vt = self.virtual_token_reserves as u128;
+        if vs + net_sol == 0 {
+            return 0;
+        }
+        let tokens_out = (vt * net_sol) / (vs + net_sol);
+        (tokens_out as u64).min(self.real_token_reserves)
+    }
+
+    /// Lamports recibidos por vender `tokens_in` (raw), netos de fee
+    pub fn quote_sell(&self, tokens_in: u64) -> u64 {
+        let vs = self.virtual_sol_reserves as u128;
+        let vt = self.virtual_token_reserves as u128;
+        let tokens_in = tokens_in as u128;
+        if vt + tokens_in == 0 {
+            return 0;
+        }
+        let gross = (vs * tokens_in) / (vt + tokens_in);
+        let net = gross * (10_000 - PUMP_FEE_BPS as u128) / 10_000;
+        (net as u64).min(self.real_sol_reserves)
+    }
+
+    /// Impacto de precio (%) de comprar con `sol_in` lamports
+    pub fn price_impact_buy(&self, sol_in: u64) -> f64 {
+        let tokens = self.quote_buy(sol_in);
+        let spot = self.spot_price_sol();
+        if tokens == 0 || spot <= 0.0 {
+            return 100.0;
+        }
+        let exec = (sol_in as f64 / 1e9) / (tokens as f64 / 10f64.powi(PUMP_TOKEN_DECIMALS as i32));
+        ((exec - spot) / spot * 100.0).max(0.0)
+    }
+
+    /// Impacto de precio (%) de vender `tokens_in` (raw)
+    pub fn price_impact_sell(&self, tokens_in: u64) -> f64 {
+        let sol_out = self.quote_sell(tokens_in);
+        let spot = self.spot_price_sol();
+        if tokens_in == 0 || spot <= 0.0 {
+            return 0.0;
+        }
+        let exec = (sol_out as f64 / 1e9) / (tokens_in as f64 / 10f64.powi(PUMP_TOKEN_DECIMALS as i32));
+        ((spot - exec) / spot * 100.0).max(0.0)
+    }
+}
+
+/// Aplica un cap de slippage hacia arriba (coste máximo aceptable)
+pub fn max_with_slippage(amount: u64, slippage_bps: u16) -> u64 {
+    (amount as u128 * (10_000 + slippage_bps as u128) / 10_000) as u64
+}
+
+/// Aplica un cap de slippage hacia abajo (salida mínima aceptable)
+pub fn min_with_slippage(amount: u64, slippage_bps: u16) -> u64 {
+    let bps = (slippage_bps as u128).min(10_000);
+    (amount as u128 * (10_000 - bps) / 10_000) as u64
+}
+
+/// Resultado de una operación en la curva
+#[derive(Debug, Clone)]
+pub struct PumpTradeResult {
+    pub signature: String,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub price_impact_pct: f64,
+}
+
+// ============================================================================
+// PUMP.FUN CLIENT
+// ============================================================================
+
+pub struct PumpFunClient {
+    rpc_client: Arc<RpcClient>,
+    program_id: Pubkey,
+    global: Pubkey,
+    fee_recipient: Pubkey,
+    event_authority: Pubkey,
+    curve_cache: CurveCache,
+}
+
+/// mint → (expira, curva activa o None): evita un `get_account` por trade
+#[derive(Default)]
+struct CurveCache {
+    entries: Mutex<HashMap<String, (Instant, Option<BondingCurveState>)>>,
+}
+
+impl CurveCache {
+    /// `Some(curva o None)` si hay entrada vigente
+    fn get(&self, mint: &str, now: Instant) -> Option<Option<BondingCurveState>> {
+        match self.entries.lock().unwrap().get(mint) {
+            Some((expires, curve)) if *expires > now => Some(curve.clone()),
+            _ => None,
+        }
+    }
+
+    fn insert(&self, mint: &str, curve: Option<BondingCurveState>, now: Instant) {
+        let ttl = if curve.is_some() {
+            ACTIVE_CURVE_TTL
+        } else {
+            INACTIVE_CURVE_TTL
+        };
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(mint.to_string(), (now + ttl, curve));
+    }
+
+    fn invalidate(&self, mint: &str) {
+        self.entries.lock().unwrap().remove(mint);
+    }
+}
+
+impl PumpFunClient {
+    pub fn new(rpc_url: String) -> Result<Self> {
+        Ok(Self {
+            rpc_client: Arc::new(RpcClient::new_with_commitment(
+                rpc_url,
+                CommitmentConfig::confirmed(),
+            )),
+            program_id: Pubkey::from_str(PUMP_PROGRAM_ID)?,
+            global: Pubkey::from_str(PUMP_GLOBAL)?,
+            fee_recipient: Pubkey::from_str(PUMP_FEE_RECIPIENT)?,
+            event_authority: Pubkey::from_str(PUMP_EVENT_AUTHORITY)?,
+            curve_cache: CurveCache::default(),
+        })
+    }
+
+    /// PDA de la bonding curve: seeds = ["bonding-curve", mint]
+    pub fn derive_bonding_curve(&self, mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[BONDING_CURVE_SEED, mint.as_ref()], &self.program_id).0
+    }
+
+    /// ATA de la bonding curve (donde viven los tokens no vendidos)
+    pub fn derive_associated_bonding_curve(&self, mint: &Pubkey) -> Pubkey {
+        let curve = self.derive_bonding_curve(mint);
+        spl_associated_token_account::get_associated_token_address(&curve, mint)
+    }
+
+    fn derive_creator_vault(&self, creator: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[CREATOR_VAULT_SEED, creator.as_ref()], &self.program_id).0
+    }
+
+    /// Lee el estado de la curva. Error si no existe (token no es de Pump.fun).
+    /// El `get_account` bloqueante corre fuera del runtime async.
+    pub async fn fetch_curve(&self, mint: &str) -> Result<BondingCurveState> {
+        let mint_pubkey = Pubkey::from_str(mint)?;
+        let curve = self.derive_bonding_curve(&mint_pubkey);
+        let rpc = Arc::clone(&self.rpc_client);
+        let account = tokio::task::spawn_blocking(move || {
+            rpc.get_account(&curve).map_err(anyhow::Error::from)
+        })
+        .await?
+        .context("Bonding curve no encontrada")?;
+
+        if account.owner != self.program_id {
+            anyhow::bail!("La cuenta {} no pertenece al programa de Pump.fun", curve);
+        }
+
+        BondingCurveState::from_account_data(&account.data)
+    }
+
+    /// Devuelve la curva solo si el token sigue operando en Pump.fun (pre-migración).
+    /// Cacheada por mint: `ACTIVE_CURVE_TTL` si está activa, `INACTIVE_CURVE_TTL`
+    /// si no. Los errores de red no se cachean.
+    pub async fn active_curve(&self, mint: &str) -> Option<BondingCurveState> {
+        if let Some(curve) = self.curve_cache.get(mint, Instant::now()) {
+            return curve;
+        }
+
+        let curve = match self.fetch_curve(mint).await {
+            Ok(curve) => (!curve.complete).then_some(curve),
+            Err(e) if is_missing_account(&e) => None,
+            Err(_) => return None,
+        };
+        self.curve_cache.insert(mint, curve.clone(), Instant::now());
+        curve
+    }
+
+    fn common_accounts(
+        &self,
+        mint: &Pubkey,
+        user: &Pubkey,
+    ) -> (Pubkey, Pubkey, Pubkey) {
+        let bonding_curve = self.derive_bonding_curve(mint);
+        let associated_bonding_curve = self.derive_associated_bonding_curve(mint);
+        let associated_user = spl_associated_token_account::get_associated_token_address(user, mint);
+        (bonding_curve, associated_bonding_curve, associated_user)
+    }
+
+    /// Instrucción `buy(amount, max_sol_cost)`: compra exactamente `token_amount`
+    /// pagando como máximo `max_sol_cost` lamports (fee incluido).
+    pub fn build_buy_instruction(
+        &self,
+        mint: &Pubkey,
+        user: &Pubkey,
+        curve: &BondingCurveState,
+        token_amount: u64,
+        max_sol_cost: u64,
+    ) -> Instruction {
+        let (bonding_curve, associated_bonding_curve, associated_user) =
+            self.common_accounts(mint, user);
+
+        let mut data = Vec::with_capacity(24);
+        data.extend_from_slice(&BUY_DISCRIMINATOR);
+        data.extend_from_slice(&token_amount.to_le_bytes());
+        data.extend_from_slice(&max_sol_cost.to_le_bytes());
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.global, false),         // 0. Global
+            AccountMeta::new(self.fee_recipient, false),           // 1. Fee Recipient
+            AccountMeta::new_readonly(*mint, false),               // 2. Mint
+            AccountMeta::new(bonding_curve, false),                // 3. Bonding Curve
+            AccountMeta::new(associated_bonding_curve, false),     // 4. Curve ATA
+            AccountMeta::new(associated_user, false),              // 5. User ATA
+            AccountMeta::new(*user, true),                         // 6. User (Signer)
+            AccountMeta::new_readonly(system_program::id(), false), // 7. System Program
+            AccountMeta::new_readonly(spl_token::id(), false),     // 8. Token Program
+        ];
+
+        match &curve.creator {
+            // Layout actual: creator_vault sustituye a Rent
+            Some(creator) => accounts.push(AccountMeta::new(self.derive_creator_vault(creator), false)),
+            // Layout legacy (curvas sin creator)
+            None => accounts.push(AccountMeta::new_readonly(solana_sdk::sysvar::rent::id(), false)),
+        }
+
+        accounts.push(AccountMeta::new_readonly(self.event_authority, false));
+        accounts.push(AccountMeta::new_readonly(self.program_id, false));
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    /// Instrucción `sell(amount, min_sol_output)`
+    pub fn build_sell_instruction(
+        &self,
+        mint: &Pubkey,
+        user: &Pubkey,
+        curve: &BondingCurveState,
+        token_amount: u64,
+        min_sol_output: u64,
+    ) -> Instruction {
+        let (bonding_curve, associated_bonding_curve, associated_user) =
+            self.common_accounts(mint, user);
+
+        let mut data = Vec::with_capacity(24);
+        data.extend_from_slice(&SELL_DISCRIMINATOR);
+        data.extend_from_slice(&token_amount.to_le_bytes());
+        data.extend_from_slice(&min_sol_output.to_le_bytes());
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.global, false),         // 0. Global
+            AccountMeta::new(self.fee_recipient, false),           // 1. Fee Recipient
+            AccountMeta::new_readonly(*mint, false),               // 2. Mint
+            AccountMeta::new(bonding_curve, false),                // 3. Bonding Curve
+            AccountMeta::new(associated_bonding_curve, false),     // 4. Curve ATA
+            AccountMeta::new(associated_user, false),              // 5. User ATA
+            AccountMeta::new(*user, true),                         // 6. User (Signer)
+            AccountMeta::new_readonly(system_program::id(), false), // 7. System Program
+        ];
+
+        match &curve.creator {
+            Some(creator) => {
+                accounts.push(AccountMeta::new(self.derive_creator_vault(creator), false));
+                accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+            }
+            None => {
+                accounts.push(AccountMeta::new_readonly(spl_associated_token_account::id(), false));
+                accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+            }
+        }
+
+        accounts.push(AccountMeta::new_readonly(self.event_authority, false));
+        accounts.push(AccountMeta::new_readonly(self.program_id, false));
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    /// Compra con `sol_amount` lamports. Slippage aplicado sobre el coste máximo.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute_buy(
+        &self,
+        token_mint: &str,
+        curve: &BondingCurveState,
+        sol_amount: u64,
+        slippage_bps: u16,
+        priority_fee_micro_lamports: u64,
+        jito_tip_lamports: u64,
+        user_keypair: &Keypair,
+    ) -> Result<PumpTradeResult> {
+        let mint = Pubkey::from_str(token_mint)?;
+        let user = user_keypair.pubkey();
+
+        let token_amount = curve.quote_buy(sol_amount);
+        if token_amount == 0 {
+            anyhow::bail!("Quote de Pump.fun inválido: 0 tokens");
+        }
+        let max_sol_cost = max_with_slippage(sol_amount, slippage_bps);
+        let price_impact_pct = curve.price_impact_buy(sol_amount);
+
+        println!("🎰 [PUMP.FUN BUY] {}...", &token_mint[..8]);
+        println!("   SOL In:       {:.6} SOL (max {:.6})", sol_amount as f64 / 1e9, max_sol_cost as f64 / 1e9);
+        println!("   Tokens Out:   {}", token_amount);
+        println!("   Impacto:      {:.2}%", price_impact_pct);
+
+        let instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_price(priority_fee_micro_lamports),
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &user,
+                &user,
+                &mint,
+                &spl_token::id(),
+            ),
+            self.build_buy_instruction(&mint, &user, curve, token_amount, max_sol_cost),
+        ];
+
+        let signature = self
+            .send_with_optional_jito(&instructions, jito_tip_lamports, user_keypair)
+            .await;
+        self.curve_cache.invalidate(token_mint);
+        let signature = signature?;
+
+        Ok(PumpTradeResult {
+            signature,
+            sol_amount,
+            token_amount,
+            price_impact_pct,
+        })
+    }
+
+    /// Vende `token_amount` (raw). Slippage aplicado sobre la salida mínima.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute_sell(
+        &self,
+        token_mint: &str,
+        curve: &BondingCurveState,
+        token_amount: u64,
+        slippage_bps: u16,
+        priority_fee_micro_lamports: u64,
+        jito_tip_lamports: u64,
+        user_keypair: &Keypair,
+    ) -> Result<PumpTradeResult> {
+        let mint = Pubkey::from_str(token_mint)?;
+        let user = user_keypair.pubkey();
+
+        let expected_sol = curve.quote_sell(token_amount);
+        let min_sol_output = min_with_slippage(expected_sol, slippage_bps);
+        let price_impact_pct = curve.price_impact_sell(token_amount);
+
+        println!("🎰 [PUMP.FUN SELL] {}...", &token_mint[..8]);
+        println!("   Tokens In:    {}", token_amount);
+        println!("   SOL Out:      {:.6} SOL (min {:.6})", expected_sol as f64 / 1e9, min_sol_output as f64 / 1e9);
+        println!("   Impacto:      {:.2}%", price_impact_pct);
+
+        let instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_price(priority_fee_micro_lamports),
+            self.build_sell_instruction(&mint, &user, curve, token_amount, min_sol_output),
+        ];
+
+        let signature = self
+            .send_with_optional_jito(&instructions, jito_tip_lamports, user_keypair)
+            .await;
+        self.curve_cache.invalidate(token_mint);
+        let signature = signature?;
+
+        Ok(PumpTradeResult {
+            signature,
+            sol_amount: expected_sol,
+            token_amount,
+            price_impact_pct,
+        })
+    }
+
+    /// Envía vía Jito Bundle (swap + tip) si hay tip; fallback a RPC estándar
+    async fn send_with_optional_jito(
+        &self,
+        instructions: &[Instruction],
+        jito_tip_lamports: u64,
+        user_keypair: &Keypair,
+    ) -> Result<String> {
+        let user = user_keypair.pubkey();
+        let recent_blockhash = self.rpc_client.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&user),
+            &[user_keypair],
+            recent_blockhash,
+        );
+
+        if jito_tip_lamports > 0 {
+            let tip_ix = crate::jito::JitoClient::create_tip_instruction(&user, jito_tip_lamports);
+            let tip_msg = solana_sdk::message::Message::new(&[tip_ix], Some(&user));
+            let mut tip_tx = Transaction::new_unsigned(tip_msg);
+            tip_tx.sign(&[user_keypair], recent_blockhash);
+
+            let versioned = VersionedTransaction::from(tx.clone());
+            let bundle = vec![versioned, VersionedTransaction::from(tip_tx)];
+
+            match crate::jito::JitoClient::new().send_bundle(bundle).await {
+                Ok(bundle_id) => {
+                    let sig = tx.signatures[0].to_string();
+                    println!("✅ [PUMP.FUN+JITO] Bundle enviado. ID: {}, Tx: {}", bundle_id, sig);
+                    return Ok(sig);
+                }
+                Err(e) => {
+                    eprintln!("⚠️ Jito falló: {}. Enviando directo via RPC...", e);
+                }
+            }
+        }
+
+        let signature = self.rpc_client.send_and_confirm_transaction(&tx)?;
+        println!("✅ [PUMP.FUN] Ejecutado: {}", signature);
+        println!("🔗 https://solscan.io/tx/{}", signature);
+        Ok(signature.to_string())
+    }
+}
+
+// ============================================================================
+// SWAP VENUE
+// ============================================================================
+
+/// Mint del token de un par contra SOL y si el par compra (SOL → token)
+fn curve_side<'a>(input_mint: &'a str, output_mint: &'a str) -> Result<(&'a str, bool)> {
+    if input_mint == WSOL_MINT {
+        Ok((output_mint, true))
+    } else if output_mint == WSOL_MINT {
+        Ok((input_mint, false))
+    } else {
+        anyhow::bail!("Pump.fun solo opera tokens contra SOL")
+    }
+}
+
+/// La curva como venue: cotiza sobre las reservas virtuales y paga/cobra SOL
+/// nativo (sin cuenta WSOL). Solo cotiza tokens pre-migración.
+#[async_trait]
+impl SwapVenue for PumpFunClient {
+    fn name(&self) -> &'static str {
+        "Pump.fun Curve"
+    }
+
+    fn supports(&self, mint: &str) -> bool {
+        Pubkey::from_str(mint).is_ok()
+    }
+
+    async fn quote(&self, request: &QuoteRequest) -> Result<VenueQuote> {
+        let (mint, buying) = curve_side(&request.input_mint, &request.output_mint)?;
+        let curve = self
+            .active_curve(mint)
+            .await
+            .with_context(|| format!("{} no está en una bonding curve activa", mint))?;
+
+        let (amount_out, price_impact_pct) = if buying {
+            (
+                curve.quote_buy(request.amount_in),
+                curve.price_impact_buy(request.amount_in),
+            )
+        } else {
+            (
+                curve.quote_sell(request.amount_in),
+                curve.price_impact_sell(request.amount_in),
+            )
+        };
+
+        let mut quote = VenueQuote::new(self.name(), request, amount_out);
+        quote.price_impact_pct = price_impact_pct;
+        quote.route = "Pump.fun Bonding Curve".to_string();
+        Ok(quote)
+    }
+
+    async fn build_instructions(&self, quote: &VenueQuote, owner: &Pubkey) -> Result<VenuePayload> {
+        let (mint_str, buying) = curve_side(&quote.input_mint, &quote.output_mint)?;
+        let mint = Pubkey::from_str(mint_str)?;
+        let curve = self
+            .active_curve(mint_str)
+            .await
+            .with_context(|| format!("{} no está en una bonding curve activa", mint_str))?;
+
+        let instructions = if buying {
+            // `buy` es exact-out: los tokens cotizados pagando como máximo el SOL con slippage
+            vec![
+                spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                    owner,
+                    owner,
+                    &mint,
+                    &spl_token::id(),
+                ),
+                self.build_buy_instruction(
+                    &mint,
+                    owner,
+                    &curve,
+                    quote.amount_out,
+                    max_with_slippage(quote.amount_in, quote.slippage_bps),
+                ),
+            ]
+        } else {
+            vec![self.build_sell_instruction(
+                &mint,
+                owner,
+                &curve,
+                quote.amount_in,
+                quote.min_amount_out(),
+            )]
+        };
+        Ok(VenuePayload::Instructions(instructions))
+    }
+}
+
+/// La cuenta de la curva no existe: el mint no se lanzó en Pump.fun
+fn is_missing_account(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| cause.to_string().contains("AccountNotFound"))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Estado inicial estándar de una curva recién creada
+    fn fresh_curve() -> BondingCurveState {
+        BondingCurveState {
+            virtual_token_reserves: 1_073_000_000_000_000,
+            virtual_sol_reserves: 30_000_000_000,
+            real_token_reserves: 793_100_000_000_000,
+            real_sol_reserves: 0,
+            token_total_supply: 1_000_000_000_000_000,
+            complete: false,
+            creator: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_bonding_curve() {
+        let mut data = vec![0u8; 81];
+        data[8..16].copy_from_slice(&1_073_000_000_000_000u64.to_le_bytes());
+        data[16..24].copy_from_slice(&30_000_000_000u64.to_le_bytes());
+        data[48] = 1;
+        data[49..81].copy_from_slice(&[7u8; 32]);
+
+        let curve = BondingCurveState::from_account_data(&data).unwrap();
+        assert_eq!(curve.virtual_sol_reserves, 30_000_000_000);
+        assert!(curve.complete);
+        assert_eq!(curve.creator, Some(Pubkey::new_from_array([7u8; 32])));
+
+        assert!(BondingCurveState::from_account_data(&data[..40]).is_err());
+    }
+
+    #[test]
+    fn test_spot_price_fresh_curve() {
+        // 30 SOL / 1.073B tokens ≈ 2.8e-8 SOL por token
+        let price = fresh_curve().spot_price_sol();
+        assert!((price - 2.796e-8).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_buy_sell_round_trip_loses_fees() {
+        let curve = fresh_curve();
+        let sol_in = 1_000_000_000; // 1 SOL
+
+        let tokens = curve.quote_buy(sol_in);
+        assert!(tokens > 0);
+
+        // Simular el estado tras la compra
+        let net_sol = sol_in * 10_000 / (10_000 + PUMP_FEE_BPS);
+        let after = BondingCurveState {
+            virtual_token_reserves: curve.virtual_token_reserves - tokens,
+            virtual_sol_reserves: curve.virtual_sol_reserves + net_sol,
+            real_token_reserves: curve.real_token_reserves - tokens,
+            real_sol_reserves: net_sol,
+            ..curve
+        };
+
+        let sol_back = after.quote_sell(tokens);
+        // Ida y vuelta: pagamos ~2% de fees, nunca se gana
+        assert!(sol_back < sol_in);
+        assert!(sol_back > sol_in * 97 / 100);
+    }
+
+    #[test]
+    fn test_slippage_caps() {
+        assert_eq!(max_with_slippage(1_000_000, 500), 1_050_000);
+        assert_eq!(min_with_slippage(1_000_000, 500), 950_000);
+        assert_eq!(min_with_slippage(1_000_000, 20_000), 0);
+    }
+
+    #[test]
+    fn test_pda_derivation_is_deterministic() {
+        let client = PumpFunClient::new("https://api.mainnet-beta.solana.com".to_string()).unwrap();
+        let mint = Pubkey::new_unique();
+        assert_eq!(client.derive_bonding_curve(&mint), client.derive_bonding_curve(&mint));
+        assert_ne!(client.derive_bonding_curve(&mint), client.derive_associated_bonding_curve(&mint));
+    }
+
+    #[test]
+    fn test_curve_side() {
+        let token = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        assert_eq!(curve_side(WSOL_MINT, token).unwrap(), (token, true));
+        assert_eq!(curve_side(token, WSOL_MINT).unwrap(), (token, false));
+        assert!(curve_side(token, token).is_err());
+    }
+
+    #[test]
+    fn test_curve_cache_ttl() {
+        let cache = CurveCache::default();
+        let now = Instant::now();
+        cache.insert("LIVE", Some(fresh_curve()), now);
+        cache.insert("MIGRATED", None, now);
+
+        assert_eq!(cache.get("LIVE", now), Some(Some(fresh_curve())));
+        assert_eq!(cache.get("MIGRATED", now), Some(None));
+        assert_eq!(cache.get("UNKNOWN", now), None);
+
+        // Curva activa: caduca enseguida; "no es de Pump.fun" dura más
+        let later = now + ACTIVE_CURVE_TTL;
+        assert_eq!(cache.get("LIVE", later), None);
+        assert_eq!(cache.get("MIGRATED", later), Some(None));
+
+        cache.insert("LIVE", Some(fresh_curve()), now);
+        cache.invalidate("LIVE");
+        assert_eq!(cache.get("LIVE", now), None);
+    }
+}
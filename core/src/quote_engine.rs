@@ -0,0 +1,294 @@
+//! # Local Quote Engine — Cotización sin HTTP desde reservas trackeadas
+//!
+//! Los feeds de Geyser / WebSocket publican aquí cada `VaultPair` que cambia.
+//! El executor cotiza las ventas con la matemática x·y=k sobre las últimas
+//! reservas y deriva `min_amount_out` sin preguntar a Jupiter ni al RPC.
+//!
+//! ```text
+//!   Geyser / WS  ──VaultPair──▶ LocalQuoteEngine ──LocalQuote──▶ TradeExecutor
+//!                                  (reservas + timestamp)        (min_amount_out)
+//! ```
+//!
+//! Si las reservas son más viejas que `max_age` no hay quote local y el
+//! executor vuelve al ranking de venues normal.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use intelligence_rs::strategy_engine::PoolReserves;
+
+use crate::amm_math::{constant_product_amount_out, max_sell_fraction_for_impact, VaultPair};
+use crate::direct_swap::WSOL_MINT;
+use crate::pumpfun::min_with_slippage;
+use crate::raydium::{AMM_V4_FEE_DENOMINATOR, AMM_V4_FEE_NUMERATOR};
+
+/// Antigüedad máxima de las reservas para cotizar sin I/O
+pub const DEFAULT_MAX_RESERVE_AGE: Duration = Duration::from_secs(15);
+
+/// Resultado de una cotización local (unidades raw)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Fee del pool cobrado sobre la entrada (unidades del mint de entrada)
+    pub fee_amount: u64,
+    /// Pérdida frente al precio spot (sin contar el fee), en %
+    pub price_impact_pct: f64,
+    /// Antigüedad de las reservas usadas
+    pub reserves_age: Duration,
+    /// Vaults (coin, pc) del pool cuyas reservas se usaron
+    pub vaults: [String; 2],
+}
+
+impl LocalQuote {
+    /// Salida mínima aceptada on-chain
+    pub fn min_amount_out(&self, slippage_bps: u16) -> u64 {
+        min_with_slippage(self.amount_out, slippage_bps)
+    }
+
+    /// `true` si `vaults` son los del pool cotizado (en cualquier orden).
+    /// Ejecutar en otro pool con este `min_amount_out` no tiene sentido.
+    pub fn priced_on(&self, vaults: &[String; 2]) -> bool {
+        let [a, b] = &self.vaults;
+        (vaults[0] == *a && vaults[1] == *b) || (vaults[0] == *b && vaults[1] == *a)
+    }
+}
+
+/// Últimas reservas conocidas de un pool token/SOL
+#[derive(Debug, Clone)]
+struct TrackedReserves {
+    token_reserve: u64,
+    sol_reserve: u64,
+    token_decimals: u8,
+    sol_decimals: u8,
+    coin_vault: String,
+    pc_vault: String,
+    updated_at: Instant,
+}
+
+/// Libro de reservas compartido entre los feeds y el executor
+pub struct LocalQuoteEngine {
+    reserves: RwLock<HashMap<String, TrackedReserves>>,
+    max_age: Duration,
+    fee_numerator: u64,
+    fee_denominator: u64,
+}
+
+impl Default for LocalQuoteEngine {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_RESERVE_AGE)
+    }
+}
+
+impl LocalQuoteEngine {
+    /// Los vaults que trackean los feeds son de pools Raydium AMM v4 (fee 0.25%)
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            reserves: RwLock::new(HashMap::new()),
+            max_age,
+            fee_numerator: AMM_V4_FEE_NUMERATOR,
+            fee_denominator: AMM_V4_FEE_DENOMINATOR,
+        }
+    }
+
+    /// Publica las reservas de un par (ignorado mientras falte alguna de las dos)
+    pub fn publish(&self, pair: &VaultPair) {
+        let (Some(token_reserve), Some(sol_reserve)) =
+            (pair.last_coin_reserve, pair.last_pc_reserve)
+        else {
+            return;
+        };
+
+        if let Ok(mut reserves) = self.reserves.write() {
+            reserves.insert(
+                pair.token_mint.clone(),
+                TrackedReserves {
+                    token_reserve,
+                    sol_reserve,
+                    token_decimals: pair.base_decimals,
+                    sol_decimals: pair.quote_decimals,
+                    coin_vault: pair.coin_vault.clone(),
+                    pc_vault: pair.pc_vault.clone(),
+                    updated_at: Instant::now(),
+                },
+            );
+        }
+    }
+
+    /// Cotiza token → SOL o SOL → token con las reservas trackeadas del token.
+    /// `None` si el par no es contra SOL, no hay reservas o están viejas.
+    pub fn quote_exact_in(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount_in: u64,
+    ) -> Option<LocalQuote> {
+        let (token_mint, token_is_input) = if output_mint == WSOL_MINT {
+            (input_mint, true)
+        } else if input_mint == WSOL_MINT {
+            (output_mint, false)
+        } else {
+            return None;
+        };
+
+        let reserves = self.fresh_reserves(token_mint)?;
+        let reserves_age = reserves.updated_at.elapsed();
+
+        let (reserve_in, reserve_out) = if token_is_input {
+            (reserves.token_reserve, reserves.sol_reserve)
+        } else {
+            (reserves.sol_reserve, reserves.token_reserve)
+        };
+
+        let amount_out = constant_product_amount_out(
+            reserve_in,
+            reserve_out,
+            amount_in,
+            self.fee_numerator,
+            self.fee_denominator,
+        );
+        if amount_out == 0 {
+            return None;
+        }
+
+        let fee_amount = (amount_in as u128 * self.fee_numerator as u128)
+            .div_ceil(self.fee_denominator as u128) as u64;
+        let spot_out = (amount_in - fee_amount) as f64 * reserve_out as f64 / reserve_in as f64;
+
+        Some(LocalQuote {
+            amount_in,
+            amount_out,
+            fee_amount,
+            price_impact_pct: (1.0 - amount_out as f64 / spot_out) * 100.0,
+            reserves_age,
+            vaults: [reserves.coin_vault, reserves.pc_vault],
+        })
+    }
+
+    /// Mayor venta (raw) del token que mueve su precio como mucho `max_impact_pct`.
+    /// `None` sin reservas frescas: el llamador no puede dimensionar la venta.
+    pub fn max_sell_for_impact(&self, token_mint: &str, max_impact_pct: f64) -> Option<u64> {
+        let reserves = self.fresh_reserves(token_mint)?;
+        let max_sell = reserves.token_reserve as f64 * max_sell_fraction_for_impact(max_impact_pct);
+        Some(max_sell.min(u64::MAX as f64) as u64)
+    }
+
+    /// Reservas frescas del pool en unidades UI (las que graba el `TickRecorder`)
+    pub fn pool_reserves(&self, token_mint: &str) -> Option<PoolReserves> {
+        let reserves = self.fresh_reserves(token_mint)?;
+        Some(PoolReserves {
+            sol: reserves.sol_reserve as f64 / 10f64.powi(reserves.sol_decimals as i32),
+            token: reserves.token_reserve as f64 / 10f64.powi(reserves.token_decimals as i32),
+        })
+    }
+
+    fn fresh_reserves(&self, token_mint: &str) -> Option<TrackedReserves> {
+        let reserves = self.reserves.read().ok()?.get(token_mint)?.clone();
+        if reserves.updated_at.elapsed() > self.max_age {
+            return None;
+        }
+        Some(reserves)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn pair(coin: Option<u64>, pc: Option<u64>) -> VaultPair {
+        VaultPair {
+            token_mint: TOKEN.to_string(),
+            symbol: "TEST".to_string(),
+            coin_vault: "coin".to_string(),
+            pc_vault: "pc".to_string(),
+            base_decimals: 6,
+            quote_decimals: 9,
+            last_coin_reserve: coin,
+            last_pc_reserve: pc,
+        }
+    }
+
+    #[test]
+    fn test_sell_and_buy_quotes() {
+        let engine = LocalQuoteEngine::default();
+        // 1M tokens (6 dec) contra 100 SOL → 1 token = 0.0001 SOL
+        engine.publish(&pair(Some(1_000_000_000_000), Some(100_000_000_000)));
+
+        let sell = engine
+            .quote_exact_in(TOKEN, WSOL_MINT, 10_000_000_000)
+            .unwrap();
+        assert_eq!(sell.fee_amount, 25_000_000);
+        assert_eq!(
+            sell.amount_out,
+            constant_product_amount_out(
+                1_000_000_000_000,
+                100_000_000_000,
+                10_000_000_000,
+                25,
+                10_000
+            )
+        );
+        // 1% del pool → ~1% de impacto
+        assert!(sell.price_impact_pct > 0.9 && sell.price_impact_pct < 1.1);
+        assert_eq!(
+            sell.min_amount_out(100),
+            min_with_slippage(sell.amount_out, 100)
+        );
+
+        let buy = engine
+            .quote_exact_in(WSOL_MINT, TOKEN, 1_000_000_000)
+            .unwrap();
+        assert!(buy.amount_out > 0 && buy.amount_out < 10_000_000_000);
+
+        // ~1% del pool mueve el precio ~2% (x·y=k)
+        let max_sell = engine.max_sell_for_impact(TOKEN, 2.0).unwrap();
+        assert!(max_sell > 10_000_000_000 && max_sell < 10_200_000_000);
+        assert!(engine.max_sell_for_impact("OTHER", 2.0).is_none());
+
+        let reserves = engine.pool_reserves(TOKEN).unwrap();
+        assert_eq!((reserves.sol, reserves.token), (100.0, 1_000_000.0));
+        assert!((reserves.price() - 0.0001).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_quote_is_tied_to_tracked_pool() {
+        let engine = LocalQuoteEngine::default();
+        engine.publish(&pair(Some(1_000_000_000_000), Some(100_000_000_000)));
+
+        let quote = engine.quote_exact_in(TOKEN, WSOL_MINT, 1_000_000).unwrap();
+        assert!(quote.priced_on(&["coin".to_string(), "pc".to_string()]));
+        assert!(quote.priced_on(&["pc".to_string(), "coin".to_string()]));
+        // Pool más profundo de otro programa: mismo par, otros vaults
+        assert!(!quote.priced_on(&["coin".to_string(), "other".to_string()]));
+    }
+
+    #[test]
+    fn test_no_quote_without_fresh_reserves() {
+        let engine = LocalQuoteEngine::default();
+
+        // Falta una reserva
+        engine.publish(&pair(Some(1_000), None));
+        assert!(engine.quote_exact_in(TOKEN, WSOL_MINT, 10).is_none());
+
+        // Par sin SOL
+        engine.publish(&pair(Some(1_000_000), Some(1_000_000)));
+        assert!(engine.quote_exact_in(TOKEN, TOKEN, 10).is_none());
+        assert!(engine.quote_exact_in(TOKEN, WSOL_MINT, 10_000).is_some());
+
+        // Reservas viejas
+        if let Some(old) = Instant::now().checked_sub(Duration::from_secs(60)) {
+            engine
+                .reserves
+                .write()
+                .unwrap()
+                .get_mut(TOKEN)
+                .unwrap()
+                .updated_at = old;
+            assert!(engine.quote_exact_in(TOKEN, WSOL_MINT, 10_000).is_none());
+            assert!(engine.pool_reserves(TOKEN).is_none());
+        }
+    }
+}
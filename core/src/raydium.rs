@@ -1,11 +1,16 @@
-//! # Raydium AMM v4 Direct Swap Implementation
+//! # Raydium Direct Swap Implementation (AMM v4 / CPMM / CLMM)
 //!
 //! Bypass de Jupiter para ejecución directa en Raydium Pools.
 //! Latencia ultra-baja: Solo RPC → Blockchain.
 //!
+//! `find_pool` busca el par en los tres programas de Raydium y elige el pool
+//! con más liquidez. El layout específico de CPMM y CLMM vive en
+//! `raydium_cpmm` y `raydium_clmm`.
+//!
 //! Estado: PRODUCTION READY (Pool Discovery + Swap Execution)
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
@@ -18,6 +23,16 @@ use solana_sdk::{
 use std::collections::HashMap;
 use std::fs;
 use std::str::FromStr;
+use std::sync::RwLock;
+
+use crate::amm_math::{
+    constant_product_amount_out, parse_spl_token_account_amount, parse_spl_token_account_mint,
+};
+use crate::direct_swap::{deepest, measure_vault_depths, wrap_swap_instructions, WSOL_MINT};
+use crate::raydium_clmm::{self, ClmmPoolState};
+use crate::raydium_cpmm::{self, CpmmPoolState};
+use crate::token_2022::{associated_token_address, mint_token_program};
+use crate::venue::{QuoteRequest, SwapVenue, VenuePayload, VenueQuote};
 
 // ============================================================================
 // CONSTANTS - Raydium & Serum Program IDs
@@ -29,6 +44,10 @@ const SERUM_PROGRAM_ID: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
 // Swap instruction discriminator
 const SWAP_BASE_IN_DISCRIMINATOR: u8 = 9;
 
+// Fee del AMM v4: 0.25%
+pub const AMM_V4_FEE_NUMERATOR: u64 = 25;
+pub const AMM_V4_FEE_DENOMINATOR: u64 = 10_000;
+
 // ============================================================================
 // DATA STRUCTURES - Pool Info & Cache
 // ============================================================================
@@ -97,6 +116,64 @@ pub struct PoolKeys {
     pub quote_mint: Pubkey,
 }
 
+/// Pool de Raydium de cualquiera de los tres programas
+#[derive(Debug, Clone)]
+pub enum RaydiumPool {
+    /// AMM v4 legacy (con cuentas de Serum/OpenBook)
+    AmmV4(PoolInfo),
+    /// Constant product sin OpenBook
+    Cpmm(CpmmPoolState),
+    /// Concentrated liquidity
+    Clmm(ClmmPoolState),
+}
+
+impl RaydiumPool {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RaydiumPool::AmmV4(_) => "AMM v4",
+            RaydiumPool::Cpmm(_) => "CPMM",
+            RaydiumPool::Clmm(_) => "CLMM",
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            RaydiumPool::AmmV4(pool) => pool.name.clone(),
+            _ => format!("{}/{}", self.kind(), &self.address()[..6]),
+        }
+    }
+
+    /// Address del account de estado del pool
+    pub fn address(&self) -> String {
+        match self {
+            RaydiumPool::AmmV4(pool) => pool.amm_id.clone(),
+            RaydiumPool::Cpmm(pool) => pool.address.to_string(),
+            RaydiumPool::Clmm(pool) => pool.address.to_string(),
+        }
+    }
+
+    /// Los dos vaults del pool (en el orden interno del programa)
+    pub fn vaults(&self) -> Result<[Pubkey; 2]> {
+        Ok(match self {
+            RaydiumPool::AmmV4(pool) => [
+                Pubkey::from_str(&pool.coin_vault)?,
+                Pubkey::from_str(&pool.pc_vault)?,
+            ],
+            RaydiumPool::Cpmm(pool) => [pool.token_0_vault, pool.token_1_vault],
+            RaydiumPool::Clmm(pool) => [pool.vault_0, pool.vault_1],
+        })
+    }
+
+    /// Token program de `mint` según el layout del pool (solo CPMM lo guarda)
+    pub fn token_program(&self, mint: &Pubkey) -> Option<Pubkey> {
+        match self {
+            RaydiumPool::Cpmm(pool) if *mint == pool.token_0_mint => Some(pool.token_0_program),
+            RaydiumPool::Cpmm(pool) if *mint == pool.token_1_mint => Some(pool.token_1_program),
+            _ => None,
+        }
+    }
+}
+
 /// Cache de pools cargado desde JSON
 #[derive(Debug, Deserialize, Serialize)]
 struct PoolsCache {
@@ -113,6 +190,7 @@ pub struct RaydiumClient {
     program_id: Pubkey,
     serum_program_id: Pubkey,
     pool_cache: HashMap<String, PoolInfo>, // Clave: "BASE_MINT-QUOTE_MINT"
+    selected_pools: RwLock<HashMap<String, RaydiumPool>>, // Pool más profundo por par
     scanner: crate::scanner::PriceScanner,
 }
 
@@ -136,6 +214,7 @@ impl RaydiumClient {
             program_id,
             serum_program_id,
             pool_cache,
+            selected_pools: RwLock::new(HashMap::new()),
             scanner: crate::scanner::PriceScanner::new(),
         })
     }
@@ -173,8 +252,91 @@ impl RaydiumClient {
         Ok(map)
     }
 
-    /// Encuentra un pool por par de mints (primero intenta cache, luego DexScreener, luego RPC)
-    pub async fn find_pool(&self, base_mint: &str, quote_mint: &str) -> Result<PoolInfo> {
+    /// Encuentra el pool con más liquidez para el par entre AMM v4, CPMM y CLMM.
+    ///
+    /// La profundidad se mide como el balance del vault de SOL (o del `quote_mint`
+    /// si el par no incluye SOL), que es comparable entre tipos de pool.
+    /// El resultado se cachea en memoria por par.
+    pub async fn find_pool(&self, base_mint: &str, quote_mint: &str) -> Result<RaydiumPool> {
+        let key1 = format!("{}-{}", base_mint, quote_mint);
+        let key2 = format!("{}-{}", quote_mint, base_mint);
+
+        if let Ok(selected) = self.selected_pools.read() {
+            if let Some(pool) = selected.get(&key1).or_else(|| selected.get(&key2)) {
+                return Ok(pool.clone());
+            }
+        }
+
+        let base_pubkey = Pubkey::from_str(base_mint)?;
+        let quote_pubkey = Pubkey::from_str(quote_mint)?;
+
+        // 1. Candidatos de los tres programas
+        let mut candidates = Vec::new();
+
+        match self.find_amm_v4_pool(base_mint, quote_mint).await {
+            Ok(pool) => candidates.push(RaydiumPool::AmmV4(pool)),
+            Err(e) => println!(
+                "   AMM v4: sin pool ({})",
+                e.to_string().lines().next().unwrap_or("")
+            ),
+        }
+
+        match raydium_cpmm::discover_pools(&self.rpc_client, &base_pubkey, &quote_pubkey) {
+            Ok(pools) => candidates.extend(pools.into_iter().map(RaydiumPool::Cpmm)),
+            Err(e) => eprintln!("⚠️  Búsqueda CPMM falló: {}", e),
+        }
+
+        match raydium_clmm::discover_pools(&self.rpc_client, &base_pubkey, &quote_pubkey) {
+            Ok(pools) => candidates.extend(pools.into_iter().map(RaydiumPool::Clmm)),
+            Err(e) => eprintln!("⚠️  Búsqueda CLMM falló: {}", e),
+        }
+
+        if candidates.is_empty() {
+            anyhow::bail!(
+                "❌ Ningún pool de Raydium (v4/CPMM/CLMM) para {}/{}",
+                base_mint,
+                quote_mint
+            );
+        }
+
+        // 2. Elegir el más profundo
+        let reference_mint = if base_mint == WSOL_MINT {
+            base_pubkey
+        } else {
+            quote_pubkey
+        };
+        let depths = self.measure_depths(&candidates, &reference_mint);
+
+        let (best_idx, best_depth) = deepest(&depths).unwrap_or((0, 0));
+        let best = candidates.swap_remove(best_idx);
+
+        println!(
+            "✅ Pool Raydium seleccionado: {} [{}] ({} candidatos, profundidad {} raw)",
+            best.name(),
+            best.kind(),
+            depths.len(),
+            best_depth
+        );
+
+        // 3. Cachear selección
+        if let Ok(mut selected) = self.selected_pools.write() {
+            selected.insert(key1, best.clone());
+        }
+
+        Ok(best)
+    }
+
+    /// Balance del vault de `reference_mint` en cada candidato (0 si no se pudo leer)
+    fn measure_depths(&self, candidates: &[RaydiumPool], reference_mint: &Pubkey) -> Vec<u64> {
+        let vault_pairs: Vec<[Pubkey; 2]> = candidates
+            .iter()
+            .map(|pool| pool.vaults().unwrap_or([Pubkey::default(); 2]))
+            .collect();
+        measure_vault_depths(&self.rpc_client, &vault_pairs, reference_mint)
+    }
+
+    /// Encuentra un pool AMM v4 por par de mints (primero intenta cache, luego DexScreener, luego RPC)
+    pub async fn find_amm_v4_pool(&self, base_mint: &str, quote_mint: &str) -> Result<PoolInfo> {
         // Intentar ambas direcciones (SOL/USDC y USDC/SOL)
         let key1 = format!("{}-{}", base_mint, quote_mint);
         let key2 = format!("{}-{}", quote_mint, base_mint);
@@ -205,8 +367,9 @@ impl RaydiumClient {
             if let Ok(amm_id) = Pubkey::from_str(&price_data.pair_address) {
                 if let Ok(account) = self.rpc_client.get_account(&amm_id) {
                     // Determinar si es reversed basado en los mints del account data
+                    // (el par de DexScreener puede ser CPMM/CLMM: solo nos sirve si es v4)
                     let data = &account.data;
-                    if data.len() >= 432 + 32 {
+                    if account.owner == self.program_id && data.len() >= 432 + 32 {
                         let coin_mint_on_chain =
                             Pubkey::new_from_array(data[400..432].try_into().unwrap()).to_string();
                         let is_reversed = coin_mint_on_chain != base_mint;
@@ -489,12 +652,189 @@ impl RaydiumClient {
         })
     }
 
+    /// Atajo: busca el pool más profundo y cotiza
+    pub async fn quote(&self, input_mint: &str, output_mint: &str, amount_in: u64) -> Result<u64> {
+        let pool = self.find_pool(input_mint, output_mint).await?;
+        self.quote_exact_in(&pool, &Pubkey::from_str(input_mint)?, amount_in)
+    }
+
     /// Calcula el min_amount_out basado en slippage
     pub fn calculate_min_amount_out(&self, expected_out: u64, slippage_bps: u16) -> u64 {
         let slippage_multiplier = 1.0 - (slippage_bps as f64 / 10000.0);
         (expected_out as f64 * slippage_multiplier) as u64
     }
 
+    /// Token program de `mint` en `pool`: el del layout (CPMM) o el dueño del mint
+    pub fn mint_program(&self, pool: &RaydiumPool, mint: &Pubkey) -> Pubkey {
+        pool.token_program(mint)
+            .unwrap_or_else(|| mint_token_program(&self.rpc_client, mint))
+    }
+
+    /// ATA de `owner` para `mint` bajo su token program (SPL clásico o Token-2022)
+    fn user_token_account(&self, pool: &RaydiumPool, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        associated_token_address(owner, mint, &self.mint_program(pool, mint))
+    }
+
+    /// Construye la instrucción de swap exact-in para cualquier tipo de pool.
+    /// La salida es el otro mint del pool; las cuentas del usuario son ATAs.
+    pub fn build_pool_swap_instruction(
+        &self,
+        pool: &RaydiumPool,
+        input_mint: &Pubkey,
+        owner: &Pubkey,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<Instruction> {
+        match pool {
+            RaydiumPool::AmmV4(info) => {
+                let output_mint = if info.base_mint == input_mint.to_string() {
+                    Pubkey::from_str(&info.quote_mint)?
+                } else {
+                    Pubkey::from_str(&info.base_mint)?
+                };
+                self.build_swap_instruction(
+                    &info.to_pubkeys()?,
+                    self.user_token_account(pool, owner, input_mint),
+                    self.user_token_account(pool, owner, &output_mint),
+                    *owner,
+                    amount_in,
+                    min_amount_out,
+                )
+            }
+            RaydiumPool::Cpmm(state) => {
+                let zero_for_one = state
+                    .is_token_0(input_mint)
+                    .context("El mint de entrada no pertenece al pool CPMM")?;
+                let (input_program, output_mint, output_program) = if zero_for_one {
+                    (
+                        state.token_0_program,
+                        state.token_1_mint,
+                        state.token_1_program,
+                    )
+                } else {
+                    (
+                        state.token_1_program,
+                        state.token_0_mint,
+                        state.token_0_program,
+                    )
+                };
+                raydium_cpmm::build_swap_base_input_instruction(
+                    state,
+                    owner,
+                    input_mint,
+                    associated_token_address(owner, input_mint, &input_program),
+                    associated_token_address(owner, &output_mint, &output_program),
+                    amount_in,
+                    min_amount_out,
+                )
+            }
+            RaydiumPool::Clmm(state) => {
+                // Estado fresco: los tick arrays dependen del tick actual
+                let state = raydium_clmm::fetch_pool(&self.rpc_client, &state.address)?;
+                let zero_for_one = state
+                    .is_token_0(input_mint)
+                    .context("El mint de entrada no pertenece al pool CLMM")?;
+                let output_mint = if zero_for_one {
+                    state.mint_1
+                } else {
+                    state.mint_0
+                };
+                let tick_arrays =
+                    raydium_clmm::fetch_swap_tick_arrays(&self.rpc_client, &state, zero_for_one)?;
+                raydium_clmm::build_swap_v2_instruction(
+                    &state,
+                    owner,
+                    input_mint,
+                    self.user_token_account(pool, owner, input_mint),
+                    self.user_token_account(pool, owner, &output_mint),
+                    &tick_arrays.addresses,
+                    amount_in,
+                    min_amount_out,
+                )
+            }
+        }
+    }
+
+    /// Cotiza on-chain un swap exact-in (raw out) sin pasar por Jupiter.
+    ///
+    /// - AMM v4 / CPMM: producto constante sobre los balances de los vaults.
+    /// - CLMM: recorrido de ticks sobre los tick arrays en la dirección del swap.
+    pub fn quote_exact_in(
+        &self,
+        pool: &RaydiumPool,
+        input_mint: &Pubkey,
+        amount_in: u64,
+    ) -> Result<u64> {
+        match pool {
+            RaydiumPool::AmmV4(_) => {
+                // Aproximación: ignora órdenes en OpenBook y PnL pendiente
+                let vaults = pool.vaults()?;
+                let accounts = self.rpc_client.get_multiple_accounts(&vaults)?;
+                let mut reserve_in = None;
+                let mut reserve_out = None;
+                for acc in accounts.iter().flatten() {
+                    let amount = parse_spl_token_account_amount(&acc.data);
+                    match parse_spl_token_account_mint(&acc.data) {
+                        Some(mint) if Pubkey::new_from_array(mint) == *input_mint => {
+                            reserve_in = amount
+                        }
+                        Some(_) => reserve_out = amount,
+                        None => {}
+                    }
+                }
+                Ok(constant_product_amount_out(
+                    reserve_in.context("Vault de entrada AMM v4 no disponible")?,
+                    reserve_out.context("Vault de salida AMM v4 no disponible")?,
+                    amount_in,
+                    AMM_V4_FEE_NUMERATOR,
+                    AMM_V4_FEE_DENOMINATOR,
+                ))
+            }
+            RaydiumPool::Cpmm(state) => {
+                let zero_for_one = state
+                    .is_token_0(input_mint)
+                    .context("El mint de entrada no pertenece al pool CPMM")?;
+                let (reserve_0, reserve_1) = raydium_cpmm::fetch_reserves(&self.rpc_client, state)?;
+                let fee_rate = raydium_cpmm::fetch_trade_fee_rate(&self.rpc_client, state)?;
+                let (reserve_in, reserve_out) = if zero_for_one {
+                    (reserve_0, reserve_1)
+                } else {
+                    (reserve_1, reserve_0)
+                };
+                Ok(raydium_cpmm::quote_exact_in(
+                    reserve_in,
+                    reserve_out,
+                    amount_in,
+                    fee_rate,
+                ))
+            }
+            RaydiumPool::Clmm(state) => {
+                let state = raydium_clmm::fetch_pool(&self.rpc_client, &state.address)?;
+                let zero_for_one = state
+                    .is_token_0(input_mint)
+                    .context("El mint de entrada no pertenece al pool CLMM")?;
+                let fee_rate = raydium_clmm::fetch_trade_fee_rate(&self.rpc_client, &state)?;
+                let tick_arrays =
+                    raydium_clmm::fetch_swap_tick_arrays(&self.rpc_client, &state, zero_for_one)?;
+                let quote = raydium_clmm::quote_exact_in(
+                    &state,
+                    &tick_arrays.ticks,
+                    amount_in,
+                    zero_for_one,
+                    fee_rate,
+                );
+                if !quote.fully_filled {
+                    anyhow::bail!(
+                        "Liquidez CLMM insuficiente en los tick arrays cargados ({} de {} consumidos)",
+                        quote.amount_in,
+                        amount_in
+                    );
+                }
+                Ok(quote.amount_out)
+            }
+        }
+    }
+
     /// Ejecuta un swap completo (construcción + firma + envío)
     pub async fn execute_swap(
         &self,
@@ -509,30 +849,16 @@ impl RaydiumClient {
         println!("   Amount In: {}", amount_in);
         println!("   Min Amount Out: {}", min_amount_out);
 
-        // 1. Encontrar pool
-        let pool_info = self.find_pool(base_mint, quote_mint).await?;
-        let pool_keys = pool_info.to_pubkeys()?;
+        // 1. Encontrar pool (el más profundo entre v4/CPMM/CLMM)
+        let pool = self.find_pool(base_mint, quote_mint).await?;
+        println!("   Pool: {} [{}]", pool.name(), pool.kind());
 
-        // 2. Derivar token accounts del usuario
+        // 2-3. Construir instrucción (token accounts del usuario = ATAs)
         let base_mint_pubkey = Pubkey::from_str(base_mint)?;
-        let quote_mint_pubkey = Pubkey::from_str(quote_mint)?;
-
-        let user_source = spl_associated_token_account::get_associated_token_address(
-            &user_keypair.pubkey(),
+        let swap_ix = self.build_pool_swap_instruction(
+            &pool,
             &base_mint_pubkey,
-        );
-
-        let user_dest = spl_associated_token_account::get_associated_token_address(
             &user_keypair.pubkey(),
-            &quote_mint_pubkey,
-        );
-
-        // 3. Construir instrucción
-        let swap_ix = self.build_swap_instruction(
-            &pool_keys,
-            user_source,
-            user_dest,
-            user_keypair.pubkey(),
             amount_in,
             min_amount_out,
         )?;
@@ -580,51 +906,20 @@ impl RaydiumClient {
         min_sol_out: u64,
         user_keypair: &Keypair,
     ) -> Result<String> {
-        const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
-
         println!("⚡ [RAYDIUM DIRECT SELL] Token → SOL");
         println!("   Token: {}...", &token_mint[..8]);
         println!("   Amount In: {} tokens", amount_in);
         println!("   Min SOL Out: {:.6} SOL", min_sol_out as f64 / 1e9);
 
-        // El swap es: token_mint (base) → SOL (quote)
-        // Raydium necesita que busquemos el pool en el orden correcto
-        // Intentamos token→SOL primero, luego SOL→token (reversed)
-        let pool_info = self.find_pool(token_mint, SOL_MINT).await?;
-        let pool_keys = pool_info.to_pubkeys()?;
+        // El swap es: token_mint → SOL (source = ATA del token, dest = ATA de WSOL)
+        let pool = self.find_pool(token_mint, WSOL_MINT).await?;
+        println!("   Pool: {} [{}]", pool.name(), pool.kind());
 
         let token_pubkey = Pubkey::from_str(token_mint)?;
-        let sol_pubkey = Pubkey::from_str(SOL_MINT)?;
-
-        // Source: token account del usuario
-        let user_token_account = spl_associated_token_account::get_associated_token_address(
-            &user_keypair.pubkey(),
+        let swap_ix = self.build_pool_swap_instruction(
+            &pool,
             &token_pubkey,
-        );
-
-        // Destination: WSOL account del usuario
-        let user_wsol_account = spl_associated_token_account::get_associated_token_address(
             &user_keypair.pubkey(),
-            &sol_pubkey,
-        );
-
-        // Determinar dirección: si el pool tiene token como base → normal
-        // Si el pool tiene SOL como base (reversed) → swap va SOL→token, no funciona
-        // Raydium usa la convención: coin_vault = base, pc_vault = quote
-        // En la instrucción: user_source = token, user_dest = WSOL
-        let (source_account, dest_account) = if pool_info.base_mint == token_mint {
-            (user_token_account, user_wsol_account)
-        } else {
-            // Pool reversed: base_mint es SOL, quote_mint es el token
-            // Para vender el token (quote), invertimos source/dest
-            (user_token_account, user_wsol_account)
-        };
-
-        let swap_ix = self.build_swap_instruction(
-            &pool_keys,
-            source_account,
-            dest_account,
-            user_keypair.pubkey(),
             amount_in,
             min_sol_out,
         )?;
@@ -638,9 +933,7 @@ impl RaydiumClient {
         );
 
         println!("📡 [RAYDIUM SELL] Enviando transacción directa...");
-        let signature = self
-            .rpc_client
-            .send_and_confirm_transaction(&transaction)?;
+        let signature = self.rpc_client.send_and_confirm_transaction(&transaction)?;
 
         println!("✅ [RAYDIUM SELL] Ejecutado: {}", signature);
         println!("🔗 https://solscan.io/tx/{}", signature);
@@ -662,32 +955,18 @@ impl RaydiumClient {
     ) -> Result<String> {
         use solana_sdk::transaction::VersionedTransaction;
 
-        const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
-
         println!("⚡ [RAYDIUM SELL + JITO] Bundle de alta prioridad");
         println!("   Token: {}...", &token_mint[..8]);
         println!("   Jito Tip: {:.6} SOL", jito_tip_lamports as f64 / 1e9);
 
-        let pool_info = self.find_pool(token_mint, SOL_MINT).await?;
-        let pool_keys = pool_info.to_pubkeys()?;
+        let pool = self.find_pool(token_mint, WSOL_MINT).await?;
+        println!("   Pool: {} [{}]", pool.name(), pool.kind());
 
         let token_pubkey = Pubkey::from_str(token_mint)?;
-        let sol_pubkey = Pubkey::from_str(SOL_MINT)?;
-
-        let user_token_account = spl_associated_token_account::get_associated_token_address(
-            &user_keypair.pubkey(),
+        let swap_ix = self.build_pool_swap_instruction(
+            &pool,
             &token_pubkey,
-        );
-        let user_wsol_account = spl_associated_token_account::get_associated_token_address(
             &user_keypair.pubkey(),
-            &sol_pubkey,
-        );
-
-        let swap_ix = self.build_swap_instruction(
-            &pool_keys,
-            user_token_account,
-            user_wsol_account,
-            user_keypair.pubkey(),
             amount_in,
             min_sol_out,
         )?;
@@ -696,7 +975,7 @@ impl RaydiumClient {
 
         // TX 1: El swap de Raydium
         let swap_tx = Transaction::new_signed_with_payer(
-            &[swap_ix],
+            std::slice::from_ref(&swap_ix),
             Some(&user_keypair.pubkey()),
             &[user_keypair],
             recent_blockhash,
@@ -708,8 +987,7 @@ impl RaydiumClient {
             &user_keypair.pubkey(),
             jito_tip_lamports,
         );
-        let tip_msg =
-            solana_sdk::message::Message::new(&[tip_ix], Some(&user_keypair.pubkey()));
+        let tip_msg = solana_sdk::message::Message::new(&[tip_ix], Some(&user_keypair.pubkey()));
         let mut tip_tx = Transaction::new_unsigned(tip_msg);
         tip_tx.sign(&[user_keypair], recent_blockhash);
         let versioned_tip = VersionedTransaction::from(tip_tx);
@@ -721,22 +999,17 @@ impl RaydiumClient {
         match jito_client.send_bundle(bundle).await {
             Ok(bundle_id) => {
                 let sig = versioned_swap.signatures[0].to_string();
-                println!("✅ [RAYDIUM+JITO] Bundle enviado. ID: {}, Tx: {}", bundle_id, sig);
+                println!(
+                    "✅ [RAYDIUM+JITO] Bundle enviado. ID: {}, Tx: {}",
+                    bundle_id, sig
+                );
                 Ok(sig)
             }
             Err(e) => {
                 eprintln!("⚠️ Jito falló: {}. Enviando directo via RPC...", e);
                 let recent_blockhash2 = self.rpc_client.get_latest_blockhash()?;
-                let swap_ix2 = self.build_swap_instruction(
-                    &pool_keys,
-                    user_token_account,
-                    user_wsol_account,
-                    user_keypair.pubkey(),
-                    amount_in,
-                    min_sol_out,
-                )?;
                 let fallback_tx = Transaction::new_signed_with_payer(
-                    &[swap_ix2],
+                    &[swap_ix],
                     Some(&user_keypair.pubkey()),
                     &[user_keypair],
                     recent_blockhash2,
@@ -760,6 +1033,57 @@ impl RaydiumClient {
     }
 }
 
+#[async_trait]
+impl SwapVenue for RaydiumClient {
+    fn name(&self) -> &'static str {
+        "Raydium Direct"
+    }
+
+    fn supports(&self, mint: &str) -> bool {
+        Pubkey::from_str(mint).is_ok()
+    }
+
+    async fn quote(&self, request: &QuoteRequest) -> Result<VenueQuote> {
+        let pool = self
+            .find_pool(&request.input_mint, &request.output_mint)
+            .await?;
+        let amount_out = self.quote_exact_in(
+            &pool,
+            &Pubkey::from_str(&request.input_mint)?,
+            request.amount_in,
+        )?;
+
+        let mut quote = VenueQuote::new(self.name(), request, amount_out);
+        quote.route = format!("{} {}", self.name(), pool.kind());
+        Ok(quote)
+    }
+
+    async fn build_instructions(&self, quote: &VenueQuote, owner: &Pubkey) -> Result<VenuePayload> {
+        let input_mint = Pubkey::from_str(&quote.input_mint)?;
+        let output_mint = Pubkey::from_str(&quote.output_mint)?;
+
+        let pool = self
+            .find_pool(&quote.input_mint, &quote.output_mint)
+            .await?;
+        let swap_ix = self.build_pool_swap_instruction(
+            &pool,
+            &input_mint,
+            owner,
+            quote.amount_in,
+            quote.min_amount_out(),
+        )?;
+
+        Ok(VenuePayload::Instructions(wrap_swap_instructions(
+            owner,
+            &input_mint,
+            &output_mint,
+            &self.mint_program(&pool, &output_mint),
+            quote.amount_in,
+            swap_ix,
+        )?))
+    }
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -784,7 +1108,7 @@ mod tests {
         let client = RaydiumClient::new("https://api.mainnet-beta.solana.com".to_string()).unwrap();
 
         let pool = client
-            .find_pool(
+            .find_amm_v4_pool(
                 "So11111111111111111111111111111111111111112",
                 "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
             )
@@ -794,6 +1118,31 @@ mod tests {
         assert_eq!(pool.unwrap().name, "SOL/USDC");
     }
 
+    #[test]
+    fn test_pool_variant_naming() {
+        let pool = RaydiumPool::AmmV4(PoolInfo {
+            name: "SOL/USDC".to_string(),
+            base_mint: String::new(),
+            quote_mint: String::new(),
+            amm_id: "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2".to_string(),
+            amm_authority: String::new(),
+            amm_open_orders: String::new(),
+            coin_vault: String::new(),
+            pc_vault: String::new(),
+            lp_mint: String::new(),
+            serum_market: String::new(),
+            serum_bids: String::new(),
+            serum_asks: String::new(),
+            serum_event_queue: String::new(),
+            serum_coin_vault: String::new(),
+            serum_pc_vault: String::new(),
+            serum_vault_signer: String::new(),
+        });
+        assert_eq!(pool.kind(), "AMM v4");
+        assert_eq!(pool.name(), "SOL/USDC");
+        assert!(pool.vaults().is_err());
+    }
+
     #[test]
     fn test_calculate_min_amount_out() {
         let client = RaydiumClient::new("https://api.mainnet-beta.solana.com".to_string()).unwrap();
@@ -0,0 +1,470 @@
+//! # Raydium CLMM (Concentrated Liquidity)
+//!
+//! Donde viven la mayoría de pares blue-chip. La liquidez se reparte en ticks;
+//! para cotizar con exactitud hay que recorrer los `TickArrayState` en la
+//! dirección del swap (la matemática está en `amm_math::quote_concentrated`).
+//!
+//! El swap (`swap_v2`) necesita como remaining accounts el bitmap extension
+//! y los tick arrays que va a atravesar, empezando por el del tick actual.
+
+use anyhow::{Context, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use std::str::FromStr;
+
+use crate::amm_math::{quote_concentrated, sqrt_price_from_x64, ClQuote, ClTick};
+use crate::token_2022::token_2022_program_id;
+
+// ============================================================================
+// CONSTANTS - Raydium CLMM Program
+// ============================================================================
+
+pub const RAYDIUM_CLMM_PROGRAM_ID: &str = "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK";
+
+/// Tamaño del account `PoolState` (discriminador Anchor incluido)
+pub const CLMM_POOL_STATE_SIZE: usize = 1544;
+
+/// Denominador de `AmmConfig.trade_fee_rate` (500 = 0.05%)
+pub const CLMM_FEE_RATE_DENOMINATOR: u32 = 1_000_000;
+
+/// Ticks por `TickArrayState`
+pub const TICK_ARRAY_SIZE: i32 = 60;
+
+/// Tick arrays que se cargan en la dirección del swap (incluido el actual)
+const TICK_ARRAYS_PER_SWAP: i32 = 3;
+
+/// sha256("global:swap_v2")[..8]
+const SWAP_V2_DISCRIMINATOR: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+const TICK_ARRAY_SEED: &[u8] = b"tick_array";
+const BITMAP_EXTENSION_SEED: &[u8] = b"pool_tick_array_bitmap_extension";
+
+/// Offsets de los mints dentro de `PoolState` (para filtros memcmp)
+const MINT_0_OFFSET: usize = 73;
+const MINT_1_OFFSET: usize = 105;
+
+/// Layout de `TickArrayState`: [disc 8][pool 32][start_tick 4][ticks 60 × 168]...
+const TICK_ARRAY_TICKS_OFFSET: usize = 44;
+const TICK_STATE_LEN: usize = 168;
+
+// ============================================================================
+// POOL STATE
+// ============================================================================
+
+/// Estado on-chain de un pool CLMM (solo los campos necesarios para swap/quote)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClmmPoolState {
+    pub address: Pubkey,
+    pub amm_config: Pubkey,
+    pub mint_0: Pubkey,
+    pub mint_1: Pubkey,
+    pub vault_0: Pubkey,
+    pub vault_1: Pubkey,
+    pub observation_key: Pubkey,
+    pub mint_0_decimals: u8,
+    pub mint_1_decimals: u8,
+    pub tick_spacing: u16,
+    pub liquidity: u128,
+    pub sqrt_price_x64: u128,
+    pub tick_current: i32,
+}
+
+impl ClmmPoolState {
+    /// Layout: [disc 8][bump 1][amm_config 32][owner 32][mint0 32][mint1 32][vault0 32]
+    /// [vault1 32][observation 32][dec0 1][dec1 1][tick_spacing 2][liquidity 16]
+    /// [sqrt_price_x64 16][tick_current 4]...
+    pub fn from_account_data(address: Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() < 273 {
+            anyhow::bail!("CLMM PoolState demasiado corto: {} bytes", data.len());
+        }
+
+        let read_pubkey =
+            |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+        let read_u128 = |offset: usize| -> u128 {
+            u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+        };
+
+        Ok(Self {
+            address,
+            amm_config: read_pubkey(9),
+            mint_0: read_pubkey(MINT_0_OFFSET),
+            mint_1: read_pubkey(MINT_1_OFFSET),
+            vault_0: read_pubkey(137),
+            vault_1: read_pubkey(169),
+            observation_key: read_pubkey(201),
+            mint_0_decimals: data[233],
+            mint_1_decimals: data[234],
+            tick_spacing: u16::from_le_bytes(data[235..237].try_into().unwrap()),
+            liquidity: read_u128(237),
+            sqrt_price_x64: read_u128(253),
+            tick_current: i32::from_le_bytes(data[269..273].try_into().unwrap()),
+        })
+    }
+
+    /// `Some(true)` si `mint` es token_0, `Some(false)` si es token_1
+    pub fn is_token_0(&self, mint: &Pubkey) -> Option<bool> {
+        if *mint == self.mint_0 {
+            Some(true)
+        } else if *mint == self.mint_1 {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Precio spot de token_0 expresado en token_1 (unidades UI)
+    pub fn spot_price(&self) -> f64 {
+        let sqrt_p = sqrt_price_from_x64(self.sqrt_price_x64);
+        sqrt_p * sqrt_p * 10f64.powi(self.mint_0_decimals as i32 - self.mint_1_decimals as i32)
+    }
+
+    /// Ticks cubiertos por un tick array
+    pub fn tick_array_span(&self) -> i32 {
+        self.tick_spacing as i32 * TICK_ARRAY_SIZE
+    }
+
+    /// Start index del tick array que contiene `tick` (división con floor)
+    pub fn tick_array_start_index(&self, tick: i32) -> i32 {
+        let span = self.tick_array_span();
+        tick.div_euclid(span) * span
+    }
+
+    /// Start indexes de los tick arrays a recorrer, en orden de swap
+    pub fn swap_tick_array_starts(&self, zero_for_one: bool) -> Vec<i32> {
+        let span = self.tick_array_span();
+        let current = self.tick_array_start_index(self.tick_current);
+        (0..TICK_ARRAYS_PER_SWAP)
+            .map(|i| {
+                if zero_for_one {
+                    current - i * span
+                } else {
+                    current + i * span
+                }
+            })
+            .collect()
+    }
+}
+
+/// Lee `trade_fee_rate` del account `AmmConfig`
+/// Layout: [disc 8][bump 1][index 2][owner 32][protocol_fee_rate 4][trade_fee_rate 4]...
+pub fn parse_trade_fee_rate(amm_config_data: &[u8]) -> Result<u32> {
+    if amm_config_data.len() < 51 {
+        anyhow::bail!("CLMM AmmConfig demasiado corto");
+    }
+    Ok(u32::from_le_bytes(
+        amm_config_data[47..51].try_into().unwrap(),
+    ))
+}
+
+/// Extrae los ticks inicializados (liquidity_gross > 0) de un `TickArrayState`
+pub fn parse_tick_array(data: &[u8]) -> Result<Vec<ClTick>> {
+    let end = TICK_ARRAY_TICKS_OFFSET + TICK_ARRAY_SIZE as usize * TICK_STATE_LEN;
+    if data.len() < end {
+        anyhow::bail!("TickArrayState demasiado corto: {} bytes", data.len());
+    }
+
+    let ticks = (0..TICK_ARRAY_SIZE as usize)
+        .filter_map(|i| {
+            let base = TICK_ARRAY_TICKS_OFFSET + i * TICK_STATE_LEN;
+            let tick = i32::from_le_bytes(data[base..base + 4].try_into().unwrap());
+            let liquidity_net = i128::from_le_bytes(data[base + 4..base + 20].try_into().unwrap());
+            let liquidity_gross =
+                u128::from_le_bytes(data[base + 20..base + 36].try_into().unwrap());
+            (liquidity_gross > 0).then_some(ClTick {
+                tick,
+                liquidity_net,
+            })
+        })
+        .collect();
+
+    Ok(ticks)
+}
+
+/// Cotiza un swap exact-in con los ticks cargados
+pub fn quote_exact_in(
+    pool: &ClmmPoolState,
+    ticks: &[ClTick],
+    amount_in: u64,
+    zero_for_one: bool,
+    trade_fee_rate: u32,
+) -> ClQuote {
+    let mut sorted = ticks.to_vec();
+    sorted.sort_by_key(|t| t.tick);
+    quote_concentrated(
+        sqrt_price_from_x64(pool.sqrt_price_x64),
+        pool.liquidity,
+        &sorted,
+        amount_in,
+        zero_for_one,
+        trade_fee_rate as f64 / CLMM_FEE_RATE_DENOMINATOR as f64,
+    )
+}
+
+// ============================================================================
+// PDAs & INSTRUCTIONS
+// ============================================================================
+
+pub fn program_id() -> Pubkey {
+    Pubkey::from_str(RAYDIUM_CLMM_PROGRAM_ID).unwrap()
+}
+
+pub fn derive_tick_array(pool: &Pubkey, start_index: i32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[TICK_ARRAY_SEED, pool.as_ref(), &start_index.to_be_bytes()],
+        &program_id(),
+    )
+    .0
+}
+
+pub fn derive_bitmap_extension(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[BITMAP_EXTENSION_SEED, pool.as_ref()], &program_id()).0
+}
+
+/// Construye `swap_v2` (exact-in). `tick_arrays` deben existir on-chain y
+/// estar en orden de recorrido, empezando por el que contiene el tick actual.
+#[allow(clippy::too_many_arguments)]
+pub fn build_swap_v2_instruction(
+    pool: &ClmmPoolState,
+    payer: &Pubkey,
+    input_mint: &Pubkey,
+    user_input_account: Pubkey,
+    user_output_account: Pubkey,
+    tick_arrays: &[Pubkey],
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<Instruction> {
+    let zero_for_one = pool
+        .is_token_0(input_mint)
+        .context("El mint de entrada no pertenece al pool CLMM")?;
+
+    let (input_vault, output_vault, output_mint) = if zero_for_one {
+        (pool.vault_0, pool.vault_1, pool.mint_1)
+    } else {
+        (pool.vault_1, pool.vault_0, pool.mint_0)
+    };
+
+    // [disc][amount][other_amount_threshold][sqrt_price_limit_x64 = 0 (sin límite)][is_base_input]
+    let mut data = Vec::with_capacity(41);
+    data.extend_from_slice(&SWAP_V2_DISCRIMINATOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
+    data.extend_from_slice(&0u128.to_le_bytes());
+    data.push(1);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*payer, true), // 0. Payer (Signer)
+        AccountMeta::new_readonly(pool.amm_config, false), // 1. AMM Config
+        AccountMeta::new(pool.address, false),   // 2. Pool State
+        AccountMeta::new(user_input_account, false), // 3. User Input Token Account
+        AccountMeta::new(user_output_account, false), // 4. User Output Token Account
+        AccountMeta::new(input_vault, false),    // 5. Input Vault
+        AccountMeta::new(output_vault, false),   // 6. Output Vault
+        AccountMeta::new(pool.observation_key, false), // 7. Observation State
+        AccountMeta::new_readonly(spl_token::id(), false), // 8. Token Program
+        AccountMeta::new_readonly(token_2022_program_id(), false), // 9. Token-2022
+        AccountMeta::new_readonly(spl_memo::id(), false), // 10. Memo Program
+        AccountMeta::new_readonly(*input_mint, false), // 11. Input Mint
+        AccountMeta::new_readonly(output_mint, false), // 12. Output Mint
+    ];
+
+    // Remaining accounts: bitmap extension + tick arrays
+    accounts.push(AccountMeta::new_readonly(
+        derive_bitmap_extension(&pool.address),
+        false,
+    ));
+    accounts.extend(tick_arrays.iter().map(|ta| AccountMeta::new(*ta, false)));
+
+    Ok(Instruction {
+        program_id: program_id(),
+        accounts,
+        data,
+    })
+}
+
+// ============================================================================
+// RPC HELPERS
+// ============================================================================
+
+/// Tick arrays cargados para un swap concreto
+#[derive(Debug, Clone)]
+pub struct SwapTickArrays {
+    /// Direcciones existentes on-chain, en orden de recorrido
+    pub addresses: Vec<Pubkey>,
+    /// Ticks inicializados de todos los arrays cargados
+    pub ticks: Vec<ClTick>,
+}
+
+/// Busca pools CLMM para el par (ambos órdenes de mints) vía getProgramAccounts
+pub fn discover_pools(
+    rpc: &RpcClient,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+) -> Result<Vec<ClmmPoolState>> {
+    use solana_account_decoder::UiAccountEncoding;
+    use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+    use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+    use solana_sdk::commitment_config::CommitmentConfig;
+
+    let mut pools = Vec::new();
+
+    for (mint_0, mint_1) in [(mint_a, mint_b), (mint_b, mint_a)] {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(CLMM_POOL_STATE_SIZE as u64),
+                RpcFilterType::Memcmp(Memcmp::new(
+                    MINT_0_OFFSET,
+                    MemcmpEncodedBytes::Base58(mint_0.to_string()),
+                )),
+                RpcFilterType::Memcmp(Memcmp::new(
+                    MINT_1_OFFSET,
+                    MemcmpEncodedBytes::Base58(mint_1.to_string()),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..Default::default()
+            },
+            with_context: Some(false),
+        };
+
+        for (address, account) in rpc.get_program_accounts_with_config(&program_id(), config)? {
+            if let Ok(state) = ClmmPoolState::from_account_data(address, &account.data) {
+                // Pools sin liquidez activa no sirven para swaps
+                if state.liquidity > 0 {
+                    pools.push(state);
+                }
+            }
+        }
+    }
+
+    Ok(pools)
+}
+
+/// Re-lee el estado del pool (sqrt_price/liquidity cambian en cada swap)
+pub fn fetch_pool(rpc: &RpcClient, address: &Pubkey) -> Result<ClmmPoolState> {
+    let account = rpc.get_account(address)?;
+    ClmmPoolState::from_account_data(*address, &account.data)
+}
+
+pub fn fetch_trade_fee_rate(rpc: &RpcClient, pool: &ClmmPoolState) -> Result<u32> {
+    let account = rpc.get_account(&pool.amm_config)?;
+    parse_trade_fee_rate(&account.data)
+}
+
+/// Carga los tick arrays en la dirección del swap (una sola llamada RPC)
+pub fn fetch_swap_tick_arrays(
+    rpc: &RpcClient,
+    pool: &ClmmPoolState,
+    zero_for_one: bool,
+) -> Result<SwapTickArrays> {
+    let candidates: Vec<Pubkey> = pool
+        .swap_tick_array_starts(zero_for_one)
+        .into_iter()
+        .map(|start| derive_tick_array(&pool.address, start))
+        .collect();
+
+    let accounts = rpc.get_multiple_accounts(&candidates)?;
+
+    let mut addresses = Vec::new();
+    let mut ticks = Vec::new();
+    for (address, account) in candidates.iter().zip(accounts) {
+        // Los arrays sin inicializar no existen on-chain: se omiten
+        if let Some(account) = account {
+            ticks.extend(parse_tick_array(&account.data)?);
+            addresses.push(*address);
+        }
+    }
+
+    if addresses.is_empty() {
+        anyhow::bail!("No hay tick arrays inicializados en el rango actual del pool CLMM");
+    }
+
+    Ok(SwapTickArrays { addresses, ticks })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_pool(tick_current: i32, tick_spacing: u16) -> ClmmPoolState {
+        let mut data = vec![0u8; CLMM_POOL_STATE_SIZE];
+        data[MINT_0_OFFSET..MINT_0_OFFSET + 32].copy_from_slice(&[1u8; 32]);
+        data[MINT_1_OFFSET..MINT_1_OFFSET + 32].copy_from_slice(&[2u8; 32]);
+        data[233] = 9;
+        data[234] = 6;
+        data[235..237].copy_from_slice(&tick_spacing.to_le_bytes());
+        data[237..253].copy_from_slice(&1_000_000u128.to_le_bytes());
+        data[253..269].copy_from_slice(&(1u128 << 64).to_le_bytes());
+        data[269..273].copy_from_slice(&tick_current.to_le_bytes());
+        ClmmPoolState::from_account_data(Pubkey::new_unique(), &data).unwrap()
+    }
+
+    #[test]
+    fn test_parse_pool_state() {
+        let pool = sample_pool(-5, 10);
+        assert_eq!(pool.mint_0, Pubkey::new_from_array([1u8; 32]));
+        assert_eq!(pool.tick_spacing, 10);
+        assert_eq!(pool.liquidity, 1_000_000);
+        assert_eq!(pool.tick_current, -5);
+        // sqrt_price = 1.0 → precio 1 raw → 1000 en UI (9 vs 6 decimales)
+        assert!((pool.spot_price() - 1000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tick_array_start_index_floors_negatives() {
+        let pool = sample_pool(-5, 10);
+        assert_eq!(pool.tick_array_start_index(-5), -600);
+        assert_eq!(pool.tick_array_start_index(0), 0);
+        assert_eq!(pool.tick_array_start_index(599), 0);
+        assert_eq!(pool.swap_tick_array_starts(true), vec![-600, -1200, -1800]);
+        assert_eq!(pool.swap_tick_array_starts(false), vec![-600, 0, 600]);
+    }
+
+    #[test]
+    fn test_parse_tick_array_only_initialized() {
+        let mut data = vec![0u8; TICK_ARRAY_TICKS_OFFSET + 60 * TICK_STATE_LEN];
+        let base = TICK_ARRAY_TICKS_OFFSET + 3 * TICK_STATE_LEN;
+        data[base..base + 4].copy_from_slice(&30i32.to_le_bytes());
+        data[base + 4..base + 20].copy_from_slice(&(-500i128).to_le_bytes());
+        data[base + 20..base + 36].copy_from_slice(&500u128.to_le_bytes());
+
+        let ticks = parse_tick_array(&data).unwrap();
+        assert_eq!(
+            ticks,
+            vec![ClTick {
+                tick: 30,
+                liquidity_net: -500
+            }]
+        );
+    }
+
+    #[test]
+    fn test_swap_v2_remaining_accounts() {
+        let pool = sample_pool(0, 10);
+        let arrays = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let ix = build_swap_v2_instruction(
+            &pool,
+            &Pubkey::new_unique(),
+            &pool.mint_0,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            &arrays,
+            1_000,
+            900,
+        )
+        .unwrap();
+
+        assert_eq!(ix.accounts.len(), 13 + 1 + arrays.len());
+        assert_eq!(
+            ix.accounts[13].pubkey,
+            derive_bitmap_extension(&pool.address)
+        );
+        assert_eq!(ix.accounts[14].pubkey, arrays[0]);
+        assert_eq!(ix.data.len(), 41);
+        assert_eq!(*ix.data.last().unwrap(), 1);
+    }
+}
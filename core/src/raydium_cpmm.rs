@@ -0,0 +1,354 @@
+//! # Raydium CPMM (Constant Product, sin OpenBook)
+//!
+//! Programa al que migran la mayoría de launches nuevos (incluido Pump.fun
+//! post-graduación vía LaunchLab). Producto constante x·y=k como el AMM v4,
+//! pero sin cuentas de Serum/OpenBook y con soporte nativo de Token-2022.
+//!
+//! Reservas efectivas = balance del vault − protocol fees − fund fees pendientes.
+
+use anyhow::{Context, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use std::str::FromStr;
+
+use crate::amm_math::{constant_product_amount_out, parse_spl_token_account_amount};
+
+// ============================================================================
+// CONSTANTS - Raydium CPMM Program
+// ============================================================================
+
+pub const RAYDIUM_CPMM_PROGRAM_ID: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+
+/// Tamaño del account `PoolState` (discriminador Anchor incluido)
+pub const CPMM_POOL_STATE_SIZE: usize = 637;
+
+/// Denominador de `AmmConfig.trade_fee_rate` (2500 = 0.25%)
+pub const CPMM_FEE_RATE_DENOMINATOR: u64 = 1_000_000;
+
+/// sha256("global:swap_base_input")[..8]
+const SWAP_BASE_INPUT_DISCRIMINATOR: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+
+const AUTH_SEED: &[u8] = b"vault_and_lp_mint_auth_seed";
+
+/// Offsets de los mints dentro de `PoolState` (para filtros memcmp)
+const TOKEN_0_MINT_OFFSET: usize = 168;
+const TOKEN_1_MINT_OFFSET: usize = 200;
+
+/// Bit de `status` que deshabilita swaps
+const STATUS_SWAP_DISABLED: u8 = 1 << 2;
+
+// ============================================================================
+// POOL STATE
+// ============================================================================
+
+/// Estado on-chain de un pool CPMM
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpmmPoolState {
+    pub address: Pubkey,
+    pub amm_config: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub token_0_program: Pubkey,
+    pub token_1_program: Pubkey,
+    pub observation_key: Pubkey,
+    pub status: u8,
+    pub mint_0_decimals: u8,
+    pub mint_1_decimals: u8,
+    pub protocol_fees_token_0: u64,
+    pub protocol_fees_token_1: u64,
+    pub fund_fees_token_0: u64,
+    pub fund_fees_token_1: u64,
+    pub open_time: u64,
+}
+
+impl CpmmPoolState {
+    /// Layout: [disc 8][amm_config 32][creator 32][vault0 32][vault1 32][lp_mint 32]
+    /// [mint0 32][mint1 32][prog0 32][prog1 32][observation 32][bump 1][status 1]
+    /// [lp_dec 1][dec0 1][dec1 1][lp_supply 8][proto0 8][proto1 8][fund0 8][fund1 8][open_time 8]
+    pub fn from_account_data(address: Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() < 381 {
+            anyhow::bail!("CPMM PoolState demasiado corto: {} bytes", data.len());
+        }
+
+        let read_pubkey =
+            |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+        let read_u64 = |offset: usize| -> u64 {
+            u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+        };
+
+        Ok(Self {
+            address,
+            amm_config: read_pubkey(8),
+            token_0_vault: read_pubkey(72),
+            token_1_vault: read_pubkey(104),
+            lp_mint: read_pubkey(136),
+            token_0_mint: read_pubkey(TOKEN_0_MINT_OFFSET),
+            token_1_mint: read_pubkey(TOKEN_1_MINT_OFFSET),
+            token_0_program: read_pubkey(232),
+            token_1_program: read_pubkey(264),
+            observation_key: read_pubkey(296),
+            status: data[329],
+            mint_0_decimals: data[331],
+            mint_1_decimals: data[332],
+            protocol_fees_token_0: read_u64(341),
+            protocol_fees_token_1: read_u64(349),
+            fund_fees_token_0: read_u64(357),
+            fund_fees_token_1: read_u64(365),
+            open_time: read_u64(373),
+        })
+    }
+
+    pub fn swap_enabled(&self) -> bool {
+        self.status & STATUS_SWAP_DISABLED == 0
+    }
+
+    /// `Some(true)` si `mint` es token_0, `Some(false)` si es token_1
+    pub fn is_token_0(&self, mint: &Pubkey) -> Option<bool> {
+        if *mint == self.token_0_mint {
+            Some(true)
+        } else if *mint == self.token_1_mint {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Reservas efectivas a partir de los balances brutos de los vaults
+    pub fn net_reserves(&self, vault_0_amount: u64, vault_1_amount: u64) -> (u64, u64) {
+        (
+            vault_0_amount
+                .saturating_sub(self.protocol_fees_token_0)
+                .saturating_sub(self.fund_fees_token_0),
+            vault_1_amount
+                .saturating_sub(self.protocol_fees_token_1)
+                .saturating_sub(self.fund_fees_token_1),
+        )
+    }
+}
+
+/// Lee `trade_fee_rate` del account `AmmConfig`
+/// Layout: [disc 8][bump 1][disable_create 1][index 2][trade_fee_rate 8]...
+pub fn parse_trade_fee_rate(amm_config_data: &[u8]) -> Result<u64> {
+    if amm_config_data.len() < 20 {
+        anyhow::bail!("CPMM AmmConfig demasiado corto");
+    }
+    Ok(u64::from_le_bytes(
+        amm_config_data[12..20].try_into().unwrap(),
+    ))
+}
+
+/// Salida (raw) de un swap exact-in dadas las reservas efectivas
+pub fn quote_exact_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    trade_fee_rate: u64,
+) -> u64 {
+    constant_product_amount_out(
+        reserve_in,
+        reserve_out,
+        amount_in,
+        trade_fee_rate,
+        CPMM_FEE_RATE_DENOMINATOR,
+    )
+}
+
+// ============================================================================
+// PDAs & INSTRUCTIONS
+// ============================================================================
+
+pub fn program_id() -> Pubkey {
+    Pubkey::from_str(RAYDIUM_CPMM_PROGRAM_ID).unwrap()
+}
+
+pub fn derive_authority() -> Pubkey {
+    Pubkey::find_program_address(&[AUTH_SEED], &program_id()).0
+}
+
+/// Construye `swap_base_input` (exact-in) para el pool
+pub fn build_swap_base_input_instruction(
+    pool: &CpmmPoolState,
+    payer: &Pubkey,
+    input_mint: &Pubkey,
+    user_input_account: Pubkey,
+    user_output_account: Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<Instruction> {
+    let zero_for_one = pool
+        .is_token_0(input_mint)
+        .context("El mint de entrada no pertenece al pool CPMM")?;
+
+    let (input_vault, output_vault, input_program, output_program, output_mint) = if zero_for_one {
+        (
+            pool.token_0_vault,
+            pool.token_1_vault,
+            pool.token_0_program,
+            pool.token_1_program,
+            pool.token_1_mint,
+        )
+    } else {
+        (
+            pool.token_1_vault,
+            pool.token_0_vault,
+            pool.token_1_program,
+            pool.token_0_program,
+            pool.token_0_mint,
+        )
+    };
+
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&SWAP_BASE_INPUT_DISCRIMINATOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*payer, true), // 0. Payer (Signer)
+        AccountMeta::new_readonly(derive_authority(), false), // 1. Authority
+        AccountMeta::new_readonly(pool.amm_config, false), // 2. AMM Config
+        AccountMeta::new(pool.address, false),   // 3. Pool State
+        AccountMeta::new(user_input_account, false), // 4. User Input Token Account
+        AccountMeta::new(user_output_account, false), // 5. User Output Token Account
+        AccountMeta::new(input_vault, false),    // 6. Input Vault
+        AccountMeta::new(output_vault, false),   // 7. Output Vault
+        AccountMeta::new_readonly(input_program, false), // 8. Input Token Program
+        AccountMeta::new_readonly(output_program, false), // 9. Output Token Program
+        AccountMeta::new_readonly(*input_mint, false), // 10. Input Mint
+        AccountMeta::new_readonly(output_mint, false), // 11. Output Mint
+        AccountMeta::new(pool.observation_key, false), // 12. Observation State
+    ];
+
+    Ok(Instruction {
+        program_id: program_id(),
+        accounts,
+        data,
+    })
+}
+
+// ============================================================================
+// RPC HELPERS
+// ============================================================================
+
+/// Busca pools CPMM para el par (ambos órdenes de mints) vía getProgramAccounts
+pub fn discover_pools(
+    rpc: &RpcClient,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+) -> Result<Vec<CpmmPoolState>> {
+    use solana_account_decoder::UiAccountEncoding;
+    use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+    use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+    use solana_sdk::commitment_config::CommitmentConfig;
+
+    let mut pools = Vec::new();
+
+    for (mint_0, mint_1) in [(mint_a, mint_b), (mint_b, mint_a)] {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(CPMM_POOL_STATE_SIZE as u64),
+                RpcFilterType::Memcmp(Memcmp::new(
+                    TOKEN_0_MINT_OFFSET,
+                    MemcmpEncodedBytes::Base58(mint_0.to_string()),
+                )),
+                RpcFilterType::Memcmp(Memcmp::new(
+                    TOKEN_1_MINT_OFFSET,
+                    MemcmpEncodedBytes::Base58(mint_1.to_string()),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..Default::default()
+            },
+            with_context: Some(false),
+        };
+
+        for (address, account) in rpc.get_program_accounts_with_config(&program_id(), config)? {
+            if let Ok(state) = CpmmPoolState::from_account_data(address, &account.data) {
+                if state.swap_enabled() {
+                    pools.push(state);
+                }
+            }
+        }
+    }
+
+    Ok(pools)
+}
+
+/// Reservas efectivas actuales (token_0, token_1)
+pub fn fetch_reserves(rpc: &RpcClient, pool: &CpmmPoolState) -> Result<(u64, u64)> {
+    let accounts = rpc.get_multiple_accounts(&[pool.token_0_vault, pool.token_1_vault])?;
+    let amount = |idx: usize| -> Result<u64> {
+        accounts[idx]
+            .as_ref()
+            .and_then(|acc| parse_spl_token_account_amount(&acc.data))
+            .context("Vault CPMM no disponible")
+    };
+    Ok(pool.net_reserves(amount(0)?, amount(1)?))
+}
+
+pub fn fetch_trade_fee_rate(rpc: &RpcClient, pool: &CpmmPoolState) -> Result<u64> {
+    let account = rpc.get_account(&pool.amm_config)?;
+    parse_trade_fee_rate(&account.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_pool_data() -> Vec<u8> {
+        let mut data = vec![0u8; CPMM_POOL_STATE_SIZE];
+        data[72..104].copy_from_slice(&[3u8; 32]);
+        data[104..136].copy_from_slice(&[4u8; 32]);
+        data[TOKEN_0_MINT_OFFSET..TOKEN_0_MINT_OFFSET + 32].copy_from_slice(&[1u8; 32]);
+        data[TOKEN_1_MINT_OFFSET..TOKEN_1_MINT_OFFSET + 32].copy_from_slice(&[2u8; 32]);
+        data[331] = 9;
+        data[332] = 6;
+        data[341..349].copy_from_slice(&100u64.to_le_bytes());
+        data[357..365].copy_from_slice(&50u64.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_parse_pool_state() {
+        let pool =
+            CpmmPoolState::from_account_data(Pubkey::new_unique(), &sample_pool_data()).unwrap();
+        assert_eq!(pool.token_0_mint, Pubkey::new_from_array([1u8; 32]));
+        assert_eq!(pool.token_1_mint, Pubkey::new_from_array([2u8; 32]));
+        assert_eq!(pool.mint_0_decimals, 9);
+        assert_eq!(pool.mint_1_decimals, 6);
+        assert!(pool.swap_enabled());
+
+        // Las fees pendientes no son liquidez
+        assert_eq!(pool.net_reserves(1_000, 1_000), (850, 1_000));
+    }
+
+    #[test]
+    fn test_swap_instruction_direction() {
+        let pool =
+            CpmmPoolState::from_account_data(Pubkey::new_unique(), &sample_pool_data()).unwrap();
+        let payer = Pubkey::new_unique();
+        let (src, dst) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let ix =
+            build_swap_base_input_instruction(&pool, &payer, &pool.token_1_mint, src, dst, 10, 9)
+                .unwrap();
+        assert_eq!(ix.accounts.len(), 13);
+        assert_eq!(ix.accounts[6].pubkey, pool.token_1_vault);
+        assert_eq!(ix.accounts[7].pubkey, pool.token_0_vault);
+        assert_eq!(ix.accounts[10].pubkey, pool.token_1_mint);
+        assert_eq!(ix.accounts[11].pubkey, pool.token_0_mint);
+        assert_eq!(&ix.data[..8], &SWAP_BASE_INPUT_DISCRIMINATOR);
+
+        let foreign = Pubkey::new_unique();
+        assert!(
+            build_swap_base_input_instruction(&pool, &payer, &foreign, src, dst, 10, 9).is_err()
+        );
+    }
+}
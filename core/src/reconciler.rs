@@ -0,0 +1,674 @@
+//! # Reconciliador periódico de posiciones (DB ↔ wallet)
+//!
+//! La purga de ghost positions del arranque solo mira los ATAs una vez. Entre
+//! reinicios, una venta manual desde Phantom, un fill parcial o un airdrop dejan
+//! `PositionState.amount_sol` desalineado con lo que hay realmente en la wallet.
+//!
+//! Cada `interval_sec` este loop compara las posiciones activas con las token
+//! accounts de la wallet:
+//! - Sin tokens on-chain → cierra la posición.
+//! - Tokens distintos a los esperados → redimensiona `amount_sol` en proporción.
+//! - Tokens que la DB no conoce → con `adopt_untracked` (opt-in), los adopta como
+//!   posición con el SL/TP por defecto. Stablecoins y majors nunca se adoptan.
+//!
+//! "Esperados" se mide contra el último `BalanceSnapshot` conciliado: si el bot
+//! vendió un 50% (amount_sol a la mitad) y la wallet tiene la mitad de tokens, no
+//! hay deriva. Cada ajuste queda registrado como `TradeRecord` de tipo `RECONCILE`.
+
+use anyhow::Result;
+use chrono::Utc;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::config::ReconcilerSettings;
+use crate::price_feed::{FeedCommand, MonitoredToken};
+use crate::state_manager::{
+    BalanceSnapshot, ExitRules, PositionState, StateManager, TpRung, TradeRecord,
+};
+use crate::telegram::TelegramNotifier;
+use crate::token_2022::{fetch_wallet_holdings, TokenHolding};
+
+/// `trade_type` de los ajustes. `amount_sol` y `tokens_amount` van con signo:
+/// negativo = salió de la posición, positivo = entró.
+pub const RECONCILE_TRADE_TYPE: &str = "RECONCILE";
+
+/// Wrapped SOL: es saldo operativo, nunca una posición
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Decimales asumidos cuando la cuenta ya no existe (estándar pump.fun / Raydium)
+const DEFAULT_DECIMALS: u8 = 6;
+
+// ============================================================================
+// DETECCIÓN DE DERIVA
+// ============================================================================
+
+/// Qué hacer con una posición tras comparar con la wallet
+#[derive(Debug, Clone, PartialEq)]
+pub enum Drift {
+    /// Cambio explicado por el propio bot (o primer avistamiento): solo re-snapshot
+    Rebase,
+    /// La wallet ya no tiene el token
+    Close,
+    /// La wallet tiene más o menos tokens de los esperados
+    Resize { new_amount_sol: f64 },
+}
+
+/// Compara la posición con el balance actual de la wallet.
+///
+/// Los tokens esperados son los del snapshot escalados por cuánto cambió
+/// `amount_sol` desde entonces (las ventas del bot reducen ambos a la vez).
+pub fn detect_drift(
+    position: &PositionState,
+    snapshot: Option<&BalanceSnapshot>,
+    tokens_now: u64,
+    tolerance_pct: f64,
+) -> Option<Drift> {
+    if tokens_now == 0 {
+        return Some(Drift::Close);
+    }
+
+    let Some(snapshot) = snapshot else {
+        return Some(Drift::Rebase);
+    };
+    if snapshot.token_amount == 0 || snapshot.amount_sol <= 0.0 || position.amount_sol <= 0.0 {
+        return Some(Drift::Rebase);
+    }
+
+    let expected = snapshot.token_amount as f64 * position.amount_sol / snapshot.amount_sol;
+    let drift_pct = (tokens_now as f64 - expected) / expected * 100.0;
+
+    if drift_pct.abs() > tolerance_pct {
+        return Some(Drift::Resize {
+            new_amount_sol: position.amount_sol * tokens_now as f64 / expected,
+        });
+    }
+
+    let unchanged = tokens_now == snapshot.token_amount
+        && (position.amount_sol - snapshot.amount_sol).abs() < f64::EPSILON;
+    if unchanged {
+        None
+    } else {
+        Some(Drift::Rebase)
+    }
+}
+
+// ============================================================================
+// ADOPCIÓN DE TOKENS NO TRACKEADOS
+// ============================================================================
+
+/// Memoria entre pasadas para no adoptar a destiempo ni re-evaluar basura
+#[derive(Debug, Default)]
+pub struct AdoptionTracker {
+    /// Vistos sin posición en la pasada anterior
+    seen: HashSet<String>,
+    /// Descartados (polvo, sin precio, ya tradeados) con el balance de entonces
+    rejected: HashMap<String, u64>,
+}
+
+impl AdoptionTracker {
+    /// Tokens a adoptar en esta pasada.
+    ///
+    /// Un token tiene que aparecer sin posición en DOS pasadas seguidas: una compra
+    /// del bot que acaba de aterrizar aún no ha escrito su posición en la primera.
+    pub fn candidates(
+        &mut self,
+        holdings: &[TokenHolding],
+        tracked: &HashSet<String>,
+        settings: &ReconcilerSettings,
+    ) -> Vec<TokenHolding> {
+        let untracked: Vec<&TokenHolding> = holdings
+            .iter()
+            .filter(|h| !tracked.contains(&h.mint))
+            .filter(|h| h.mint != WSOL_MINT && !settings.ignore_mints.contains(&h.mint))
+            // Un descarte se re-evalúa solo si el balance cambió
+            .filter(|h| self.rejected.get(&h.mint) != Some(&h.amount))
+            .collect();
+
+        let ready = untracked
+            .iter()
+            .filter(|h| self.seen.contains(&h.mint))
+            .map(|h| (*h).clone())
+            .collect();
+
+        self.seen = untracked.iter().map(|h| h.mint.clone()).collect();
+        ready
+    }
+
+    pub fn reject(&mut self, holding: &TokenHolding) {
+        self.rejected.insert(holding.mint.clone(), holding.amount);
+    }
+}
+
+// ============================================================================
+// RECONCILIADOR
+// ============================================================================
+
+/// Resumen de una pasada
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReconcileReport {
+    pub resized: usize,
+    pub closed: usize,
+    pub adopted: usize,
+}
+
+pub struct PositionReconciler {
+    state_manager: Arc<StateManager>,
+    telegram: Arc<TelegramNotifier>,
+    rpc_url: String,
+    wallet: Pubkey,
+    settings: ReconcilerSettings,
+    feed_tx: Option<mpsc::Sender<FeedCommand>>,
+    adoption: Mutex<AdoptionTracker>,
+}
+
+impl PositionReconciler {
+    pub fn new(
+        state_manager: Arc<StateManager>,
+        telegram: Arc<TelegramNotifier>,
+        rpc_url: String,
+        wallet: Pubkey,
+        settings: ReconcilerSettings,
+    ) -> Self {
+        Self {
+            state_manager,
+            telegram,
+            rpc_url,
+            wallet,
+            settings,
+            feed_tx: None,
+            adoption: Mutex::new(AdoptionTracker::default()),
+        }
+    }
+
+    /// Suscribe al PriceFeed los tokens adoptados
+    pub fn with_feed(mut self, feed_tx: mpsc::Sender<FeedCommand>) -> Self {
+        self.feed_tx = Some(feed_tx);
+        self
+    }
+
+    /// Una pasada completa de reconciliación
+    pub async fn reconcile_once(&self) -> Result<ReconcileReport> {
+        let mut report = ReconcileReport::default();
+
+        let positions_before = self.state_manager.get_active_positions().await?;
+        // RpcClient es bloqueante: fuera del runtime
+        let (rpc_url, wallet) = (self.rpc_url.clone(), self.wallet);
+        let holdings = tokio::task::spawn_blocking(move || {
+            fetch_wallet_holdings(&RpcClient::new(rpc_url), &wallet)
+        })
+        .await??;
+        let positions = self.state_manager.get_active_positions().await?;
+
+        // Salidas en vuelo: el router aplicará el fill, no lo tocamos
+        let in_flight: HashSet<String> = self
+            .state_manager
+            .get_open_orders()
+            .await?
+            .into_iter()
+            .map(|o| o.token_mint)
+            .collect();
+
+        let snapshots: HashMap<String, BalanceSnapshot> = self
+            .state_manager
+            .get_balance_snapshots()
+            .await?
+            .into_iter()
+            .map(|s| (s.token_mint.clone(), s))
+            .collect();
+        let by_mint: HashMap<&str, &TokenHolding> =
+            holdings.iter().map(|h| (h.mint.as_str(), h)).collect();
+
+        for position in &positions {
+            // Si la posición cambió mientras leíamos la wallet, lo veremos en la próxima pasada
+            let stable = positions_before.iter().any(|p| {
+                p.token_mint == position.token_mint && p.amount_sol == position.amount_sol
+            });
+            if !stable || in_flight.contains(&position.token_mint) {
+                continue;
+            }
+
+            let holding = by_mint.get(position.token_mint.as_str()).copied();
+            let tokens_now = holding.map(|h| h.amount).unwrap_or(0);
+            let decimals = holding.map(|h| h.decimals).unwrap_or(DEFAULT_DECIMALS);
+            let snapshot = snapshots.get(&position.token_mint);
+
+            match detect_drift(
+                position,
+                snapshot,
+                tokens_now,
+                self.settings.drift_tolerance_pct,
+            ) {
+                None => {}
+                Some(Drift::Rebase) => {
+                    self.save_snapshot(&position.token_mint, tokens_now, position.amount_sol)
+                        .await?;
+                }
+                Some(Drift::Close) => {
+                    let tokens_before = snapshot.map(|s| s.token_amount).unwrap_or(0);
+                    println!(
+                        "🧮 [RECONCILER] {} sin balance on-chain. Cerrando posición ({:.4} SOL).",
+                        position.symbol, position.amount_sol
+                    );
+                    let signature = adjustment_signature(&position.token_mint);
+                    self.state_manager
+                        .record_sell_fill(&position.token_mint, &signature, 1.0, None)
+                        .await?;
+                    self.state_manager
+                        .close_position(&position.token_mint)
+                        .await?;
+                    self.state_manager
+                        .delete_balance_snapshot(&position.token_mint)
+                        .await?;
+                    self.record_adjustment(
+                        position,
+                        &signature,
+                        -position.amount_sol,
+                        -ui_amount(tokens_before, decimals),
+                    )
+                    .await;
+                    self.notify(&format!(
+                        "🧮 <b>RECONCILE</b> {}: sin tokens en la wallet. Posición cerrada.",
+                        position.symbol
+                    ))
+                    .await;
+                    report.closed += 1;
+                }
+                Some(Drift::Resize { new_amount_sol }) => {
+                    let tokens_before = snapshot.map(|s| s.token_amount).unwrap_or(0);
+                    println!(
+                        "🧮 [RECONCILER] {} desalineado: {} → {} tokens. amount_sol {:.4} → {:.4}",
+                        position.symbol,
+                        tokens_before,
+                        tokens_now,
+                        position.amount_sol,
+                        new_amount_sol
+                    );
+                    let signature = adjustment_signature(&position.token_mint);
+                    if new_amount_sol < position.amount_sol {
+                        // Tokens que salieron fuera del bot: venta sin SOL conocido
+                        let fraction = 1.0 - new_amount_sol / position.amount_sol;
+                        self.state_manager
+                            .record_sell_fill(&position.token_mint, &signature, fraction, None)
+                            .await?;
+                    } else {
+                        // Tokens que entraron fuera del bot: lote nuevo al coste prorrateado
+                        let delta_tokens =
+                            ui_amount(tokens_now, decimals) - ui_amount(tokens_before, decimals);
+                        self.state_manager
+                            .record_buy_fill(
+                                position.clone(),
+                                &signature,
+                                delta_tokens.max(0.0),
+                                new_amount_sol - position.amount_sol,
+                            )
+                            .await?;
+                    }
+                    self.save_snapshot(&position.token_mint, tokens_now, new_amount_sol)
+                        .await?;
+                    self.record_adjustment(
+                        position,
+                        &signature,
+                        new_amount_sol - position.amount_sol,
+                        ui_amount(tokens_now, decimals) - ui_amount(tokens_before, decimals),
+                    )
+                    .await;
+                    self.notify(&format!(
+                        "🧮 <b>RECONCILE</b> {}: balance on-chain distinto al esperado.\n\
+                        <b>⬢ Invertido:</b> <code>{:.4} → {:.4} SOL</code>",
+                        position.symbol, position.amount_sol, new_amount_sol
+                    ))
+                    .await;
+                    report.resized += 1;
+                }
+            }
+        }
+
+        if self.settings.adopt_untracked {
+            let tracked: HashSet<String> = positions_before
+                .iter()
+                .chain(positions.iter())
+                .map(|p| p.token_mint.clone())
+                .collect();
+            let candidates =
+                self.adoption
+                    .lock()
+                    .unwrap()
+                    .candidates(&holdings, &tracked, &self.settings);
+
+            for holding in candidates {
+                if self.adopt(&holding).await? {
+                    report.adopted += 1;
+                } else {
+                    self.adoption.lock().unwrap().reject(&holding);
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Crea una posición para un token que la DB no conoce. `false` = descartado.
+    async fn adopt(&self, holding: &TokenHolding) -> Result<bool> {
+        // Un mint con historial (cerrado o /untrack) no se re-adopta solo
+        if self
+            .state_manager
+            .get_position(&holding.mint)
+            .await?
+            .is_some()
+        {
+            return Ok(false);
+        }
+
+        let scanner = crate::scanner::PriceScanner::new();
+        let price = match scanner.get_token_price(&holding.mint).await {
+            Ok(price) if price.price_native > 0.0 => price,
+            _ => return Ok(false),
+        };
+
+        let value_sol = holding.ui_amount() * price.price_native;
+        if value_sol < self.settings.min_adopt_value_sol {
+            return Ok(false);
+        }
+
+        let now = Utc::now().timestamp();
+        let sl = self.settings.default_sl_percent;
+        let position = PositionState {
+            id: None,
+            token_mint: holding.mint.clone(),
+            symbol: price.symbol.clone(),
+            // Coste real desconocido: se adopta a precio de mercado
+            entry_price: price.price_native,
+            current_price: price.price_native,
+            amount_sol: value_sol,
+            stop_loss_percent: sl,
+            trailing_enabled: true,
+            trailing_distance_percent: self.settings.default_trailing_distance_percent,
+            trailing_activation_threshold: self.settings.default_trailing_activation_threshold,
+            trailing_highest_price: Some(price.price_native),
+            trailing_current_sl: Some(sl),
+            tp_ladder: vec![
+                TpRung::new(
+                    self.settings.default_tp_percent,
+                    self.settings.default_tp_sell_percent,
+                ),
+                TpRung::new(
+                    self.settings.default_tp2_percent,
+                    self.settings.default_tp_sell_percent,
+                ),
+            ],
+            active: true,
+            created_at: now,
+            updated_at: now,
+            token_amount: 0.0,
+            realized_pnl_sol: 0.0,
+            exit_rules: ExitRules::default(),
+        };
+
+        println!(
+            "🧮 [RECONCILER] Adoptando {} ({:.4} SOL) con SL {}% / TP {}%",
+            position.symbol, value_sol, sl, self.settings.default_tp_percent
+        );
+        let signature = adjustment_signature(&holding.mint);
+        self.state_manager
+            .record_buy_fill(position.clone(), &signature, holding.ui_amount(), value_sol)
+            .await?;
+        self.save_snapshot(&holding.mint, holding.amount, value_sol)
+            .await?;
+        self.record_adjustment(&position, &signature, value_sol, holding.ui_amount())
+            .await;
+
+        if let Some(feed_tx) = &self.feed_tx {
+            let _ = feed_tx
+                .send(FeedCommand::Subscribe(MonitoredToken {
+                    mint: holding.mint.clone(),
+                    symbol: position.symbol.clone(),
+                    pool_account: None,
+                    coin_vault: None,
+                    pc_vault: None,
+                    token_decimals: holding.decimals,
+                }))
+                .await;
+        }
+
+        self.notify(&format!(
+            "🧮 <b>RECONCILE</b> {}: token sin trackear en la wallet. Adoptado.\n\
+            <b>⬢ Valor:</b> <code>{:.4} SOL</code>\n\
+            <b>⬢ SL / TP:</b> <code>{}% / {}%</code>",
+            position.symbol, value_sol, sl, self.settings.default_tp_percent
+        ))
+        .await;
+
+        Ok(true)
+    }
+
+    async fn save_snapshot(
+        &self,
+        token_mint: &str,
+        token_amount: u64,
+        amount_sol: f64,
+    ) -> Result<()> {
+        self.state_manager
+            .save_balance_snapshot(BalanceSnapshot {
+                token_mint: token_mint.to_string(),
+                token_amount,
+                amount_sol,
+                updated_at: Utc::now().timestamp(),
+            })
+            .await
+    }
+
+    /// Trade RECONCILE del ajuste; `signature` es la misma del fill de lotes
+    async fn record_adjustment(
+        &self,
+        position: &PositionState,
+        signature: &str,
+        delta_sol: f64,
+        delta_tokens: f64,
+    ) {
+        let now = Utc::now().timestamp();
+        let trade = TradeRecord {
+            id: None,
+            signature: signature.to_string(),
+            token_mint: position.token_mint.clone(),
+            symbol: position.symbol.clone(),
+            trade_type: RECONCILE_TRADE_TYPE.to_string(),
+            amount_sol: delta_sol,
+            tokens_amount: delta_tokens,
+            price: position.current_price,
+            // No sabemos a qué precio se vendió fuera del bot: sin PnL realizado
+            pnl_sol: None,
+            pnl_percent: None,
+            route: "Reconciler".to_string(),
+            price_impact_pct: 0.0,
+            fee_sol: 0.0,
+            timestamp: now,
+        };
+        if let Err(e) = self.state_manager.record_trade(trade).await {
+            eprintln!(
+                "❌ DB ERROR registrando ajuste RECONCILE para {}: {}",
+                position.symbol, e
+            );
+        }
+    }
+
+    async fn notify(&self, message: &str) {
+        let _ = self.telegram.send_message(message, true).await;
+    }
+
+    /// Loop de reconciliación en background
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        loop {
+            tokio::time::sleep(interval).await;
+            match self.reconcile_once().await {
+                Ok(report) if report != ReconcileReport::default() => println!(
+                    "🧮 [RECONCILER] Ajustes: {} redimensionadas, {} cerradas, {} adoptadas",
+                    report.resized, report.closed, report.adopted
+                ),
+                Ok(_) => {}
+                Err(e) => eprintln!("⚠️ [RECONCILER] Error reconciliando posiciones: {}", e),
+            }
+        }
+    }
+}
+
+fn ui_amount(raw: u64, decimals: u8) -> f64 {
+    raw as f64 / 10f64.powi(decimals as i32)
+}
+
+/// Firma sintética de un ajuste: el trade y el fill de lotes comparten clave
+fn adjustment_signature(token_mint: &str) -> String {
+    let now = Utc::now().timestamp();
+    format!("{}_{}_{}", RECONCILE_TRADE_TYPE, token_mint, now)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(amount_sol: f64) -> PositionState {
+        PositionState {
+            id: Some(1),
+            token_mint: "MINT".to_string(),
+            symbol: "TST".to_string(),
+            entry_price: 0.001,
+            amount_sol,
+            current_price: 0.001,
+            stop_loss_percent: -50.0,
+            trailing_enabled: false,
+            trailing_distance_percent: 25.0,
+            trailing_activation_threshold: 20.0,
+            trailing_highest_price: None,
+            trailing_current_sl: None,
+            tp_ladder: vec![TpRung::new(100.0, 50.0)],
+            active: true,
+            created_at: 0,
+            updated_at: 0,
+            token_amount: 0.0,
+            realized_pnl_sol: 0.0,
+            exit_rules: ExitRules::default(),
+        }
+    }
+
+    fn snapshot(token_amount: u64, amount_sol: f64) -> BalanceSnapshot {
+        BalanceSnapshot {
+            token_mint: "MINT".to_string(),
+            token_amount,
+            amount_sol,
+            updated_at: 0,
+        }
+    }
+
+    fn holding(mint: &str, amount: u64) -> TokenHolding {
+        TokenHolding {
+            mint: mint.to_string(),
+            amount,
+            decimals: 6,
+        }
+    }
+
+    #[test]
+    fn test_bot_sells_are_not_drift() {
+        let snap = snapshot(1_000_000, 1.0);
+
+        // Primer avistamiento y balance intacto
+        assert_eq!(
+            detect_drift(&position(1.0), None, 1_000_000, 2.0),
+            Some(Drift::Rebase)
+        );
+        assert_eq!(
+            detect_drift(&position(1.0), Some(&snap), 1_000_000, 2.0),
+            None
+        );
+
+        // TP1 del bot: 50% de tokens y amount_sol a la mitad → solo re-snapshot
+        assert_eq!(
+            detect_drift(&position(0.5), Some(&snap), 500_000, 2.0),
+            Some(Drift::Rebase)
+        );
+    }
+
+    #[test]
+    fn test_manual_sell_resizes_or_closes() {
+        let snap = snapshot(1_000_000, 1.0);
+
+        // Venta manual del 40% desde Phantom
+        match detect_drift(&position(1.0), Some(&snap), 600_000, 2.0) {
+            Some(Drift::Resize { new_amount_sol }) => assert!((new_amount_sol - 0.6).abs() < 1e-9),
+            other => panic!("esperado Resize, obtenido {:?}", other),
+        }
+
+        // Fill parcial del TP1: el bot asumió 50% pero solo salió el 30%
+        match detect_drift(&position(0.5), Some(&snap), 700_000, 2.0) {
+            Some(Drift::Resize { new_amount_sol }) => assert!((new_amount_sol - 0.7).abs() < 1e-9),
+            other => panic!("esperado Resize, obtenido {:?}", other),
+        }
+
+        // Dentro de la tolerancia (transfer fees, redondeos)
+        assert_eq!(
+            detect_drift(&position(1.0), Some(&snap), 990_000, 2.0),
+            Some(Drift::Rebase)
+        );
+
+        assert_eq!(
+            detect_drift(&position(1.0), Some(&snap), 0, 2.0),
+            Some(Drift::Close)
+        );
+    }
+
+    #[test]
+    fn test_adoption_needs_two_sightings() {
+        let settings = ReconcilerSettings {
+            ignore_mints: vec!["IGNORED".to_string()],
+            ..ReconcilerSettings::default()
+        };
+        let tracked: HashSet<String> = ["TRACKED".to_string()].into_iter().collect();
+        let holdings = vec![
+            holding("NEW", 1_000),
+            holding("TRACKED", 1_000),
+            holding("IGNORED", 1_000),
+            holding(WSOL_MINT, 1_000),
+        ];
+
+        let mut tracker = AdoptionTracker::default();
+        assert!(tracker
+            .candidates(&holdings, &tracked, &settings)
+            .is_empty());
+        let ready = tracker.candidates(&holdings, &tracked, &settings);
+        assert_eq!(ready, vec![holding("NEW", 1_000)]);
+
+        // Descartado (polvo): no se re-evalúa hasta que cambie el balance
+        tracker.reject(&ready[0]);
+        assert!(tracker
+            .candidates(&holdings, &tracked, &settings)
+            .is_empty());
+        let grown = vec![holding("NEW", 50_000)];
+        assert!(tracker.candidates(&grown, &tracked, &settings).is_empty());
+        assert_eq!(
+            tracker.candidates(&grown, &tracked, &settings),
+            vec![holding("NEW", 50_000)]
+        );
+    }
+
+    #[test]
+    fn test_default_settings_never_adopt_majors() {
+        let settings = ReconcilerSettings::default();
+        assert!(!settings.adopt_untracked);
+
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let holdings = vec![holding(usdc, 5_000_000), holding("NEW", 1_000)];
+        let mut tracker = AdoptionTracker::default();
+        tracker.candidates(&holdings, &HashSet::new(), &settings);
+        assert_eq!(
+            tracker.candidates(&holdings, &HashSet::new(), &settings),
+            vec![holding("NEW", 1_000)]
+        );
+    }
+}
@@ -9,12 +9,16 @@
 use anyhow::{Context, Result};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
-use solana_sdk::program_pack::Pack;
 use solana_sdk::pubkey::Pubkey;
-use spl_token::state::Mint;
 use std::str::FromStr;
 use std::sync::Arc;
 
+use crate::raydium_cpmm::RAYDIUM_CPMM_PROGRAM_ID;
+use crate::token_2022::parse_mint;
+
+/// Programa de Raydium AMM v4
+const RAYDIUM_V4_PROGRAM_ID: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+
 /// Datos de seguridad extraídos on-chain (v1 — básicos)
 #[derive(Debug, Clone)]
 pub struct OnChainSecurityData {
@@ -24,6 +28,13 @@ pub struct OnChainSecurityData {
     pub mint_authority: Option<String>,
     pub freeze_authority: Option<String>,
     pub is_initialized: bool,
+    /// SPL Token clásico o Token-2022
+    pub token_program: String,
+    /// Transfer fee de Token-2022 en bps (0 = sin fee)
+    pub transfer_fee_bps: u16,
+    pub permanent_delegate: Option<String>,
+    pub transfer_hook_program: Option<String>,
+    pub non_transferable: bool,
 }
 
 /// Datos extendidos de análisis on-chain (v2 — métricas avanzadas)
@@ -34,10 +45,10 @@ pub struct OnChainAnalysis {
     /// Edad estimada del token en minutos
     /// Basado en el campo `block_time` del primer token holder encontrado
     pub estimated_age_minutes: u64,
-    /// Porcentaje del supply en el Top 10 Holders  
+    /// Porcentaje del supply en el Top 10 Holders (sin pools ni bonding curve)
     /// Riesgo: > 20% = concentración alta → posible rug
     pub top_10_holders_pct: f64,
-    /// Porcentaje del supply controlado por el deployer/dev wallet
+    /// Porcentaje del supply de la mayor wallet (proxy del deployer/dev wallet)
     pub dev_wallet_pct: f64,
     /// Ratio de wallets únicas entre el total de token accounts
     /// Proxy para detectar wash trading (bajo ratio = sospechoso)
@@ -96,29 +107,22 @@ impl HeliusSensor {
             .get_account(&pubkey)
             .context(format!("Failed to fetch account info for {}", mint_address))?;
 
-        if account.owner != spl_token::id() {
-            anyhow::bail!("Account is not owned by SPL Token Program");
-        }
-
-        let mint_data = Mint::unpack(&account.data).context("Failed to unpack Mint data")?;
-
-        let mint_authority: Option<String> = {
-            let opt_pubkey: Option<Pubkey> = mint_data.mint_authority.into();
-            opt_pubkey.map(|p| p.to_string())
-        };
-
-        let freeze_authority: Option<String> = {
-            let opt_pubkey: Option<Pubkey> = mint_data.freeze_authority.into();
-            opt_pubkey.map(|p| p.to_string())
-        };
+        // SPL Token clásico o Token-2022 (con extensiones)
+        let mint_data =
+            parse_mint(&account.owner, &account.data).context("Failed to unpack Mint data")?;
 
         Ok(OnChainSecurityData {
             mint: mint_address.to_string(),
             decimals: mint_data.decimals,
             supply: mint_data.supply,
-            mint_authority,
-            freeze_authority,
+            mint_authority: mint_data.mint_authority.map(|p| p.to_string()),
+            freeze_authority: mint_data.freeze_authority.map(|p| p.to_string()),
             is_initialized: mint_data.is_initialized,
+            token_program: mint_data.program_id.to_string(),
+            transfer_fee_bps: mint_data.transfer_fee_bps(),
+            permanent_delegate: mint_data.permanent_delegate.map(|p| p.to_string()),
+            transfer_hook_program: mint_data.transfer_hook_program.map(|p| p.to_string()),
+            non_transferable: mint_data.non_transferable,
         })
     }
 
@@ -148,9 +152,8 @@ impl HeliusSensor {
 
         let total_holders = token_accounts.len() as u32;
 
-        // ── 3. Calcular Top Holders y concentración ──
         // Extraemos balances de los token accounts
-        let mut balances: Vec<u64> = token_accounts
+        let balances: Vec<u64> = token_accounts
             .iter()
             .filter_map(|acc| {
                 // acc es RpcKeyedAccount — el data es UiAccount
@@ -170,24 +173,14 @@ impl HeliusSensor {
             })
             .collect();
 
-        // Ordenar descendente (mayor balance primero)
-        balances.sort_unstable_by(|a, b| b.cmp(a));
-
-        // Top 10 holders concentration
-        let top_10_balance: u64 = balances.iter().take(10).sum();
-        let top_10_holders_pct = if total_supply > 0.0 {
-            (top_10_balance as f64 / total_supply) * 100.0
-        } else {
-            0.0
-        };
-
-        // Dev wallet: asumimos que el mayor holder es el dev (heurística conservadora)
-        let dev_balance = balances.first().copied().unwrap_or(0);
-        let dev_wallet_pct = if total_supply > 0.0 {
-            (dev_balance as f64 / total_supply) * 100.0
-        } else {
-            0.0
-        };
+        // ── 3. Concentración: top holders sin pools ni bonding curve ──
+        // Los vaults de los pools y la bonding curve son los mayores holders de
+        // casi cualquier memecoin; su dueño es un PDA y no puede vender a voluntad
+        let rpc = Arc::clone(&self.rpc_client);
+        let largest = tokio::task::spawn_blocking(move || fetch_largest_holders(&rpc, &pubkey))
+            .await?
+            .context("No se pudieron leer los mayores holders")?;
+        let (top_10_holders_pct, dev_wallet_pct) = holder_concentration(&largest, security.supply);
 
         // ── 4. Unique wallets ratio ──
         // Ratio = holders únicos / total_holders
@@ -220,6 +213,42 @@ impl HeliusSensor {
         })
     }
 
+    /// Retraso del nodo RPC: último slot con shreds recibidos vs slot procesado.
+    /// Un lag alto = red congestionada o nodo atrasado (nuestras TXs llegarán tarde).
+    pub async fn measure_slot_lag(&self) -> Result<u64> {
+        let rpc = Arc::clone(&self.rpc_client);
+        tokio::task::spawn_blocking(move || {
+            let tip = rpc.get_max_shred_insert_slot()?;
+            let processed = rpc.get_slot_with_commitment(CommitmentConfig::processed())?;
+            Ok(tip.saturating_sub(processed))
+        })
+        .await?
+    }
+
+    /// % del LP quemado en el pool principal del token (`pair_address` de DexScreener).
+    ///
+    /// - Raydium AMM v4 / CPMM: LP emitido según el pool vs supply vivo del LP mint.
+    /// - Bonding curve de Pump.fun: no hay LP, la liquidez la custodia el programa.
+    ///
+    /// Otros pools (CLMM, Orca, Meteora...) no tienen LP fungible: `Err`.
+    pub async fn lp_burned_pct(&self, dex_id: &str, pair_address: &str) -> Result<f64> {
+        if dex_id == "pumpfun" {
+            return Ok(100.0);
+        }
+
+        let pool = Pubkey::from_str(pair_address).context("Invalid pool address")?;
+        let rpc = Arc::clone(&self.rpc_client);
+        tokio::task::spawn_blocking(move || {
+            let account = rpc.get_account(&pool)?;
+            let (lp_mint, lp_issued) = pool_lp_state(&account.owner, &account.data)
+                .with_context(|| format!("Pool sin LP fungible ({})", account.owner))?;
+            let mint = rpc.get_account(&lp_mint)?;
+            let lp_supply = parse_mint(&mint.owner, &mint.data)?.supply;
+            Ok(burned_pct(lp_issued, lp_supply))
+        })
+        .await?
+    }
+
     /// Estima la edad del token buscando las primeras transacciones de su mint account
     async fn estimate_token_age(&self, mint_address: &str) -> Result<u64> {
         let pubkey = Pubkey::from_str(mint_address)?;
@@ -277,3 +306,128 @@ impl HeliusSensor {
         })
     }
 }
+
+// ============================================================================
+// HOLDERS & LP
+// ============================================================================
+
+/// Mayores token accounts del mint (hasta 20) como (dueño, balance raw)
+fn fetch_largest_holders(rpc: &RpcClient, mint: &Pubkey) -> Result<Vec<(Pubkey, u64)>> {
+    let addresses: Vec<Pubkey> = rpc
+        .get_token_largest_accounts(mint)?
+        .iter()
+        .filter_map(|account| Pubkey::from_str(&account.address).ok())
+        .collect();
+
+    // Layout común a SPL Token y Token-2022: [mint 32][owner 32][amount 8]
+    Ok(rpc
+        .get_multiple_accounts(&addresses)?
+        .into_iter()
+        .flatten()
+        .filter_map(|account| {
+            let data = account.data.get(..72)?;
+            let owner = Pubkey::new_from_array(data[32..64].try_into().ok()?);
+            let amount = u64::from_le_bytes(data[64..72].try_into().ok()?);
+            Some((owner, amount))
+        })
+        .collect())
+}
+
+/// (% del Top 10, % de la mayor wallet) sobre `supply`. Se ignoran las cuentas
+/// cuyo dueño es un PDA (fuera de la curva ed25519): vaults de pools, bonding
+/// curves y lockers, que no pueden vender a voluntad.
+pub fn holder_concentration(holders: &[(Pubkey, u64)], supply: u64) -> (f64, f64) {
+    if supply == 0 {
+        return (0.0, 0.0);
+    }
+
+    let mut balances: Vec<u64> = holders
+        .iter()
+        .filter(|(owner, _)| owner.is_on_curve())
+        .map(|(_, amount)| *amount)
+        .collect();
+    balances.sort_unstable_by(|a, b| b.cmp(a));
+
+    let pct = |amount: u64| amount as f64 / supply as f64 * 100.0;
+    let top_10: u64 = balances.iter().take(10).sum();
+    (pct(top_10), pct(balances.first().copied().unwrap_or(0)))
+}
+
+/// LP mint y LP emitido según el estado del pool (Raydium AMM v4 o CPMM)
+pub fn pool_lp_state(program: &Pubkey, data: &[u8]) -> Option<(Pubkey, u64)> {
+    let read_pubkey = |offset: usize| -> Option<Pubkey> {
+        Some(Pubkey::new_from_array(
+            data.get(offset..offset + 32)?.try_into().ok()?,
+        ))
+    };
+    let read_u64 = |offset: usize| -> Option<u64> {
+        Some(u64::from_le_bytes(
+            data.get(offset..offset + 8)?.try_into().ok()?,
+        ))
+    };
+
+    match program.to_string().as_str() {
+        // LIQUIDITY_STATE_LAYOUT_V4: lp_mint @464, lp_reserve @720
+        RAYDIUM_V4_PROGRAM_ID => Some((read_pubkey(464)?, read_u64(720)?)),
+        // PoolState CPMM: lp_mint @136, lp_supply @333
+        RAYDIUM_CPMM_PROGRAM_ID => Some((read_pubkey(136)?, read_u64(333)?)),
+        _ => None,
+    }
+}
+
+/// % del LP emitido que ya no circula (quemado). Sin LP emitido = 0%.
+pub fn burned_pct(lp_issued: u64, lp_supply: u64) -> f64 {
+    if lp_issued == 0 {
+        return 0.0;
+    }
+    lp_issued.saturating_sub(lp_supply) as f64 / lp_issued as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_holder_concentration_skips_pool_accounts() {
+        use solana_sdk::signature::{Keypair, Signer};
+
+        let curve = Pubkey::find_program_address(&[b"bonding-curve"], &Pubkey::new_unique()).0;
+        let dev = Keypair::new();
+        let whale = Keypair::new();
+
+        let holders = vec![
+            (curve, 800_000),
+            (dev.pubkey(), 40_000),
+            (whale.pubkey(), 10_000),
+        ];
+        let (top_10, dev_pct) = holder_concentration(&holders, 1_000_000);
+        assert!((top_10 - 5.0).abs() < 1e-9);
+        assert!((dev_pct - 4.0).abs() < 1e-9);
+
+        assert_eq!(holder_concentration(&holders, 0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_lp_burned_from_pool_state() {
+        let lp_mint = Pubkey::new_unique();
+        let mut v4 = vec![0u8; 752];
+        v4[464..496].copy_from_slice(lp_mint.as_ref());
+        v4[720..728].copy_from_slice(&1_000_000u64.to_le_bytes());
+        let v4_program = Pubkey::from_str(RAYDIUM_V4_PROGRAM_ID).unwrap();
+        assert_eq!(pool_lp_state(&v4_program, &v4), Some((lp_mint, 1_000_000)));
+
+        let mut cpmm = vec![0u8; 637];
+        cpmm[136..168].copy_from_slice(lp_mint.as_ref());
+        cpmm[333..341].copy_from_slice(&500u64.to_le_bytes());
+        let cpmm_program = Pubkey::from_str(RAYDIUM_CPMM_PROGRAM_ID).unwrap();
+        assert_eq!(pool_lp_state(&cpmm_program, &cpmm), Some((lp_mint, 500)));
+
+        // CLMM / otros programas: sin LP fungible
+        assert_eq!(pool_lp_state(&Pubkey::new_unique(), &v4), None);
+        assert_eq!(pool_lp_state(&v4_program, &v4[..700]), None);
+
+        assert_eq!(burned_pct(1_000_000, 0), 100.0);
+        assert_eq!(burned_pct(1_000_000, 250_000), 75.0);
+        assert_eq!(burned_pct(0, 10), 0.0);
+    }
+}
@@ -0,0 +1,203 @@
+//! # Sliced Exit — Ventas troceadas (TWAP) para pools finos
+//!
+//! `execute_sell_with_retry` vende todo el porcentaje en un solo swap; en un
+//! pool de memecoin con poca liquidez eso hunde el precio. Con el modo troceado
+//! activo, el executor parte la venta en trozos que mueven el precio como mucho
+//! `max_impact_pct` (x·y=k sobre las reservas trackeadas, ver
+//! `RaydiumPoolState::max_sell_for_impact`) y los espacia `interval_ms`.
+//!
+//! ```text
+//!   objetivo = balance · pct ──▶ trozo ≤ venta máx. por impacto ──▶ espera ──▶ ...
+//!                                   │
+//!                                   └─ SL y el fill cae > escalate_drop_pct ──▶ resto de golpe
+//! ```
+//!
+//! Sin reservas frescas no se puede dimensionar el trozo y se vende lo que
+//! quede en uno. Aquí solo vive la planificación (pura); el bucle que envía los
+//! trozos es `TradeExecutor::execute_sliced_sell`.
+
+use crate::jupiter::SwapResult;
+
+/// Porcentaje del balance actual a vender en el siguiente trozo.
+///
+/// - `balance`: tokens (raw) en la wallet ahora.
+/// - `remaining`: tokens (raw) que faltan para completar la venta.
+/// - `max_chunk`: venta máxima por impacto (`None` = sin reservas, todo de golpe).
+/// - `last_slice`: tope de trozos alcanzado, se vende lo que falte.
+///
+/// Redondea hacia abajo para no pasarse del impacto, con un mínimo del 1%.
+pub fn next_slice_percent(
+    balance: u64,
+    remaining: u64,
+    max_chunk: Option<u64>,
+    last_slice: bool,
+) -> u8 {
+    if balance == 0 || remaining == 0 {
+        return 0;
+    }
+    let chunk = match max_chunk {
+        Some(max_chunk) if !last_slice => remaining.min(max_chunk),
+        _ => remaining,
+    };
+    if chunk >= balance {
+        return 100;
+    }
+    // El trozo que cierra la venta se redondea al más cercano: truncar dejaría polvo
+    let pct = if chunk == remaining {
+        (chunk as f64 * 100.0 / balance as f64).round()
+    } else {
+        (chunk as f64 * 100.0 / balance as f64).floor()
+    };
+    pct.clamp(1.0, 100.0) as u8
+}
+
+/// ¿El precio de fill ha caído lo bastante desde el primer trozo como para
+/// dejar de trocear y vender el resto de una vez? (solo stop-loss)
+pub fn should_escalate(
+    first_fill_price: f64,
+    last_fill_price: f64,
+    escalate_drop_pct: f64,
+) -> bool {
+    if first_fill_price <= 0.0 || escalate_drop_pct <= 0.0 {
+        return false;
+    }
+    last_fill_price <= first_fill_price * (1.0 - escalate_drop_pct / 100.0)
+}
+
+/// Precio de fill (SOL por token raw) de un trozo
+pub fn fill_price(fill: &SwapResult) -> f64 {
+    if fill.input_amount > 0.0 {
+        fill.output_amount / fill.input_amount
+    } else {
+        0.0
+    }
+}
+
+/// Une los trozos en un único fill para el journal y la posición. La firma es
+/// la del último trozo; el impacto, el peor de todos.
+pub fn merge_fills(fills: &[SwapResult]) -> Option<SwapResult> {
+    let last = fills.last()?;
+    let route = if fills.len() == 1 {
+        last.route.clone()
+    } else {
+        format!("TWAP {}× {}", fills.len(), last.route)
+    };
+
+    Some(SwapResult {
+        signature: last.signature.clone(),
+        input_amount: fills.iter().map(|f| f.input_amount).sum(),
+        output_amount: fills.iter().map(|f| f.output_amount).sum(),
+        route,
+        price_impact_pct: fills.iter().map(|f| f.price_impact_pct).fold(0.0, f64::max),
+        fee_sol: fills.iter().map(|f| f.fee_sol).sum(),
+    })
+}
+
+/// La venta troceada falló después de ejecutar algún trozo. El router no debe
+/// reintentarla entera: vendería de más. `filled` une lo ya vendido.
+#[derive(Debug)]
+pub struct PartialSellError {
+    pub filled: SwapResult,
+    /// Balance raw antes del primer trozo: `filled.input_amount / balance_before`
+    /// es la fracción de la posición que ya salió
+    pub balance_before: u64,
+    pub cause: anyhow::Error,
+}
+
+impl std::fmt::Display for PartialSellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "venta troceada interrumpida tras vender {:.0} tokens ({}): {}",
+            self.filled.input_amount, self.filled.signature, self.cause
+        )
+    }
+}
+
+impl std::error::Error for PartialSellError {}
+
+/// ¿Puede el router repetir una venta fallida? Solo si no vendió nada.
+pub fn can_retry_sell(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<PartialSellError>().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(signature: &str, tokens: f64, sol: f64, impact: f64) -> SwapResult {
+        SwapResult {
+            signature: signature.to_string(),
+            input_amount: tokens,
+            output_amount: sol,
+            route: "Raydium + Jito".to_string(),
+            price_impact_pct: impact,
+            fee_sol: 0.0001,
+        }
+    }
+
+    #[test]
+    fn test_slices_stay_under_max_chunk() {
+        // Vender todo (1000) con trozos de 300 como máximo
+        assert_eq!(next_slice_percent(1_000, 1_000, Some(300), false), 30);
+        assert_eq!(next_slice_percent(700, 700, Some(300), false), 42);
+        // Último trozo: lo que quede, aunque supere el máximo
+        assert_eq!(next_slice_percent(406, 406, Some(300), true), 100);
+        // Sin reservas: todo de golpe
+        assert_eq!(next_slice_percent(1_000, 1_000, None, false), 100);
+    }
+
+    #[test]
+    fn test_partial_sell_only_sells_its_target() {
+        // TP del 25% sobre 1000 tokens: objetivo 250, trozos de 100
+        assert_eq!(next_slice_percent(1_000, 250, Some(100), false), 10);
+        assert_eq!(next_slice_percent(900, 150, Some(100), false), 11);
+        // El trozo que cierra el objetivo redondea al más cercano
+        assert_eq!(next_slice_percent(801, 51, Some(100), false), 6);
+        // Trozo mínimo del 1% aunque la venta máxima sea diminuta
+        assert_eq!(next_slice_percent(1_000, 1_000, Some(1), false), 1);
+        assert_eq!(next_slice_percent(1_000, 0, Some(100), false), 0);
+    }
+
+    #[test]
+    fn test_escalation_on_falling_fills() {
+        assert!(!should_escalate(1.0, 0.95, 10.0));
+        assert!(should_escalate(1.0, 0.9, 10.0));
+        assert!(should_escalate(1.0, 0.5, 10.0));
+        // Desactivado o sin precio de referencia
+        assert!(!should_escalate(1.0, 0.5, 0.0));
+        assert!(!should_escalate(0.0, 0.5, 10.0));
+    }
+
+    #[test]
+    fn test_merge_fills() {
+        assert!(merge_fills(&[]).is_none());
+
+        let merged = merge_fills(&[
+            fill("SIG_1", 300.0, 0.3, 1.8),
+            fill("SIG_2", 300.0, 0.28, 2.0),
+            fill("SIG_3", 400.0, 0.35, 1.5),
+        ])
+        .unwrap();
+        assert_eq!(merged.signature, "SIG_3");
+        assert_eq!(merged.input_amount, 1_000.0);
+        assert!((merged.output_amount - 0.93).abs() < 1e-12);
+        assert_eq!(merged.price_impact_pct, 2.0);
+        assert_eq!(merged.route, "TWAP 3× Raydium + Jito");
+        assert!((fill_price(&merged) - 0.00093).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_partial_sale_blocks_router_retry() {
+        assert!(can_retry_sell(&anyhow::anyhow!("quote timeout")));
+
+        let partial: anyhow::Error = PartialSellError {
+            filled: fill("SIG_1", 1_000.0, 0.1, 1.0),
+            balance_before: 4_000,
+            cause: anyhow::anyhow!("blockhash expirado"),
+        }
+        .into();
+        assert!(!can_retry_sell(&partial));
+        assert!(partial.to_string().contains("SIG_1"));
+    }
+}
@@ -6,9 +6,16 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
 use deadpool_sqlite::{Config, Pool, Runtime};
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use crate::cost_basis::{replay_trades, Lot, LotBook, SellCost};
+use crate::migrations::{self, SchemaStatus};
+
+/// Ruta de la DB del bot (relativa al directorio de trabajo)
+pub const DEFAULT_DB_PATH: &str = "trading_state.db";
 
 // ============================================================================
 // DATA STRUCTURES
@@ -29,15 +36,69 @@ pub struct PositionState {
     pub trailing_activation_threshold: f64,
     pub trailing_highest_price: Option<f64>,
     pub trailing_current_sl: Option<f64>,
-    pub tp_percent: Option<f64>,        // Take Profit Target %
-    pub tp_amount_percent: Option<f64>, // % of stack to sell
-    pub tp_triggered: bool,
-    pub tp2_percent: Option<f64>,        // Moonbag TP Target %
-    pub tp2_amount_percent: Option<f64>, // % of stack to sell for TP2
-    pub tp2_triggered: bool,
+    /// Escalera de take profit en orden de disparo (tabla `position_tp_rungs`)
+    #[serde(default)]
+    pub tp_ladder: Vec<TpRung>,
     pub active: bool,
     pub created_at: i64,
     pub updated_at: i64,
+    /// Tokens en cartera según los lotes de compra (0 = desconocido, posición legacy)
+    #[serde(default)]
+    pub token_amount: f64,
+    /// PnL realizado por media ponderada en las ventas parciales de esta posición
+    #[serde(default)]
+    pub realized_pnl_sol: f64,
+    /// Reglas de salida adicionales (tiempo, break-even, momentum, liquidez)
+    #[serde(default)]
+    pub exit_rules: ExitRules,
+}
+
+/// Escalón de la escalera de take profit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TpRung {
+    /// Ganancia (%) sobre la entrada que dispara el escalón
+    pub target_percent: f64,
+    /// % de la posición ORIGINAL que vende (no del balance que quede)
+    pub sell_percent: f64,
+    pub triggered: bool,
+}
+
+impl TpRung {
+    pub fn new(target_percent: f64, sell_percent: f64) -> Self {
+        Self {
+            target_percent,
+            sell_percent,
+            triggered: false,
+        }
+    }
+}
+
+/// Reglas de salida por posición además de SL/TP/trailing. Todas opcionales:
+/// `None` desactiva la regla.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExitRules {
+    /// Tiempo máximo en cartera (segundos desde `created_at`)
+    pub max_hold_secs: Option<i64>,
+    /// Ganancia (%) a partir de la cual el stop sube a la entrada
+    pub break_even_after_percent: Option<f64>,
+    /// El stop ya está en break-even (persiste aunque la ganancia retroceda)
+    #[serde(default)]
+    pub break_even_armed: bool,
+    /// Pendiente mínima de la ganancia (%/min, ver `MomentumSensor::slope`)
+    pub stale_slope_threshold: Option<f64>,
+    /// Minutos seguidos por debajo de `stale_slope_threshold` antes de salir
+    pub stale_minutes: Option<u32>,
+    /// Liquidez mínima del pool (USD)
+    pub min_liquidity_usd: Option<f64>,
+}
+
+/// Cambio de una posición ya persistido en SQLite (write-through hacia el `PositionBook`)
+#[derive(Debug, Clone)]
+pub enum PositionEvent {
+    /// Estado completo de la posición tal y como quedó en la DB
+    Upserted(Box<PositionState>),
+    /// Posición cerrada (active = 0)
+    Closed(String),
 }
 
 /// Registro de un trade ejecutado
@@ -70,6 +131,194 @@ pub struct FeeStats {
     pub net_pnl_sol: f64, // PnL bruto - fees totales
 }
 
+/// Estado persistido del Circuit Breaker de portfolio (fila única)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BreakerState {
+    pub tripped: bool,
+    pub reason: Option<String>,
+    pub tripped_at: Option<i64>,
+    /// Momento programado para el rearme automático
+    pub reset_at: Option<i64>,
+    /// Último rearme: los contadores (rachas, drawdown) empiezan aquí
+    pub last_reset_at: i64,
+    /// Capital de referencia fijado en el primer arranque (None = aún sin fijar)
+    pub capital_sol: Option<f64>,
+}
+
+/// Ciclo de vida de una orden de salida: PENDING → SUBMITTED(firma) → CONFIRMED | FAILED
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    /// Registrada antes de enviar nada a la red
+    Pending,
+    /// El executor devolvió firma; falta aplicar el fill a la posición
+    Submitted,
+    /// Fill aplicado a la posición (terminal)
+    Confirmed,
+    /// No aterrizó o revirtió (terminal)
+    Failed,
+}
+
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Pending => "PENDING",
+            OrderStatus::Submitted => "SUBMITTED",
+            OrderStatus::Confirmed => "CONFIRMED",
+            OrderStatus::Failed => "FAILED",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "PENDING" => Some(OrderStatus::Pending),
+            "SUBMITTED" => Some(OrderStatus::Submitted),
+            "CONFIRMED" => Some(OrderStatus::Confirmed),
+            "FAILED" => Some(OrderStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Orden del journal de ejecución (tabla `orders`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderRecord {
+    pub id: Option<i64>,
+    pub token_mint: String,
+    pub symbol: String,
+    /// "AUTO_SL" | "AUTO_TP1" | "AUTO_TP2"
+    pub command_type: String,
+    pub sell_pct: u8,
+    pub amount_invested: f64,
+    /// Balance raw del token antes de enviar (para reconciliar sin firma)
+    pub tokens_before: Option<u64>,
+    pub status: OrderStatus,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Último balance on-chain conciliado de una posición (tabla `position_balances`).
+/// El reconciliador compara contra él para detectar ventas/compras fuera del bot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceSnapshot {
+    pub token_mint: String,
+    /// Balance raw del token en la wallet
+    pub token_amount: u64,
+    /// `amount_sol` de la posición en ese momento
+    pub amount_sol: f64,
+    pub updated_at: i64,
+}
+
+/// Disparo de una orden de entrada
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntryTrigger {
+    /// Compra límite: precio <= trigger
+    Below,
+    /// Ruptura: precio >= trigger con momentum positivo
+    Above,
+}
+
+impl EntryTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryTrigger::Below => "BELOW",
+            EntryTrigger::Above => "ABOVE",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "BELOW" => Some(EntryTrigger::Below),
+            "ABOVE" => Some(EntryTrigger::Above),
+            _ => None,
+        }
+    }
+}
+
+/// Ciclo de vida de una orden de entrada:
+/// OPEN → TRIGGERED → FILLED | FAILED, o bien OPEN → CANCELLED | EXPIRED
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntryOrderStatus {
+    /// En reposo, evaluándose contra el PriceFeed
+    Open,
+    /// Reclamada para ejecutar (ya no se puede cancelar)
+    Triggered,
+    Filled,
+    Failed,
+    Cancelled,
+    Expired,
+}
+
+impl EntryOrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryOrderStatus::Open => "OPEN",
+            EntryOrderStatus::Triggered => "TRIGGERED",
+            EntryOrderStatus::Filled => "FILLED",
+            EntryOrderStatus::Failed => "FAILED",
+            EntryOrderStatus::Cancelled => "CANCELLED",
+            EntryOrderStatus::Expired => "EXPIRED",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "OPEN" => Some(EntryOrderStatus::Open),
+            "TRIGGERED" => Some(EntryOrderStatus::Triggered),
+            "FILLED" => Some(EntryOrderStatus::Filled),
+            "FAILED" => Some(EntryOrderStatus::Failed),
+            "CANCELLED" => Some(EntryOrderStatus::Cancelled),
+            "EXPIRED" => Some(EntryOrderStatus::Expired),
+            _ => None,
+        }
+    }
+}
+
+/// SL/trailing/TP con los que se arma la posición de una orden de entrada llenada
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryExit {
+    pub stop_loss_percent: f64,
+    pub trailing_enabled: bool,
+    pub trailing_distance_percent: f64,
+    pub trailing_activation_threshold: f64,
+    pub tp_ladder: Vec<TpRung>,
+}
+
+impl Default for EntryExit {
+    /// La misma salida que arma `/buy`
+    fn default() -> Self {
+        Self {
+            stop_loss_percent: -40.0,
+            trailing_enabled: true,
+            trailing_distance_percent: 15.0,
+            trailing_activation_threshold: 10.0,
+            tp_ladder: vec![TpRung::new(50.0, 100.0)],
+        }
+    }
+}
+
+/// Compra en reposo (tabla `entry_orders`, ver `entry_orders::EntryOrderManager`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryOrder {
+    pub id: Option<i64>,
+    pub token_mint: String,
+    pub symbol: String,
+    pub amount_sol: f64,
+    pub trigger: EntryTrigger,
+    /// Precio de disparo en SOL (mismas unidades que `PriceUpdate::price_native`)
+    pub trigger_price: f64,
+    pub slippage_bps: u16,
+    /// Salida de la posición que abre al llenarse
+    pub exit: EntryExit,
+    pub status: EntryOrderStatus,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub updated_at: i64,
+}
+
 /// Snapshot de configuración
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigSnapshot {
@@ -84,11 +333,27 @@ pub struct ConfigSnapshot {
 
 pub struct StateManager {
     pool: Arc<Pool>,
+    /// Canal hacia el PositionBook del StrategyEngine (None = sin suscriptor)
+    position_events: Option<mpsc::UnboundedSender<PositionEvent>>,
 }
 
 impl StateManager {
-    /// Inicializa el State Manager y crea las tablas si no existen
+    /// Inicializa el State Manager y migra el esquema a la última versión
     pub async fn new(db_path: &str) -> Result<Self> {
+        let manager = Self::open(db_path).await?;
+
+        let applied = manager.migrate(false).await?;
+        if !applied.is_empty() {
+            println!("🗄️  Migraciones de esquema aplicadas: {:?}", applied);
+        }
+
+        println!("✅ State Manager inicializado: {}", db_path);
+
+        Ok(manager)
+    }
+
+    /// Abre la DB sin migrar (CLI `db status` / `db migrate`)
+    pub async fn open(db_path: &str) -> Result<Self> {
         let cfg = Config::new(db_path);
         let pool = cfg
             .create_pool(Runtime::Tokio1)
@@ -96,6 +361,7 @@ impl StateManager {
 
         let manager = Self {
             pool: Arc::new(pool),
+            position_events: None,
         };
 
         // Enable WAL mode
@@ -107,134 +373,47 @@ impl StateManager {
             .await
             .map_err(|e| anyhow::anyhow!("tokio join error: {}", e))??;
 
-        manager.initialize_schema().await?;
-        manager.run_migrations().await?;
-
-        println!("✅ State Manager inicializado: {}", db_path);
-
         Ok(manager)
     }
 
-    /// Crea las tablas necesarias
-    async fn initialize_schema(&self) -> Result<()> {
-        let conn = self.pool.get().await?;
-
-        conn.interact(|conn| -> Result<()> {
-            // Tabla de posiciones activas
-            conn.execute(
-                "CREATE TABLE IF NOT EXISTS positions (
-                    id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    token_mint TEXT NOT NULL UNIQUE,
-                    symbol TEXT NOT NULL,
-                    entry_price REAL NOT NULL,
-                    amount_sol REAL NOT NULL,
-                    current_price REAL NOT NULL,
-                    stop_loss_percent REAL NOT NULL,
-                    trailing_enabled INTEGER NOT NULL,
-                    trailing_distance_percent REAL NOT NULL,
-                    trailing_activation_threshold REAL NOT NULL,
-                    trailing_highest_price REAL,
-                    trailing_current_sl REAL,
-                    tp_percent REAL,
-                    tp_amount_percent REAL,
-                    tp_triggered INTEGER DEFAULT 0,
-                    tp2_percent REAL,
-                    tp2_amount_percent REAL,
-                    tp2_triggered INTEGER DEFAULT 0,
-                    active INTEGER NOT NULL,
-                    created_at INTEGER NOT NULL,
-                    updated_at INTEGER NOT NULL
-                )",
-                [],
-            )?;
-
-            // Tabla de historial de trades
-            conn.execute(
-                "CREATE TABLE IF NOT EXISTS trades (
-                    id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    signature TEXT NOT NULL UNIQUE,
-                    token_mint TEXT NOT NULL,
-                    symbol TEXT NOT NULL,
-                    trade_type TEXT NOT NULL,
-                    amount_sol REAL NOT NULL,
-                    tokens_amount REAL NOT NULL,
-                    price REAL NOT NULL,
-                    pnl_sol REAL,
-                    pnl_percent REAL,
-                    route TEXT NOT NULL,
-                    price_impact_pct REAL NOT NULL,
-                    fee_sol REAL NOT NULL DEFAULT 0.0,
-                    timestamp INTEGER NOT NULL
-                )",
-                [],
-            )?;
-
-            // Tabla de snapshots de configuración
-            conn.execute(
-                "CREATE TABLE IF NOT EXISTS config_snapshots (
-                    id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    config_json TEXT NOT NULL,
-                    timestamp INTEGER NOT NULL
-                )",
-                [],
-            )?;
-
-            // Índices para búsquedas rápidas
-            conn.execute(
-                "CREATE INDEX IF NOT EXISTS idx_positions_active ON positions(active)",
-                [],
-            )?;
-
-            conn.execute(
-                "CREATE INDEX IF NOT EXISTS idx_trades_timestamp ON trades(timestamp DESC)",
-                [],
-            )?;
+    /// Publica cada escritura de posiciones en `tx` (después de persistirla)
+    pub fn with_position_events(mut self, tx: mpsc::UnboundedSender<PositionEvent>) -> Self {
+        self.position_events = Some(tx);
+        self
+    }
 
-            Ok(())
-        })
-        .await
-        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
+    /// Relee la posición recién escrita y la publica hacia el PositionBook
+    async fn publish_position(&self, token_mint: &str) {
+        let Some(tx) = &self.position_events else {
+            return;
+        };
 
-        Ok(())
+        match self.get_position(token_mint).await {
+            Ok(Some(pos)) => {
+                let _ = tx.send(PositionEvent::Upserted(Box::new(pos)));
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("⚠️ PositionBook desincronizado para {}: {}", token_mint, e),
+        }
     }
 
-    /// Ejecuta migraciones de esquema para actualizar DBs existentes
-    async fn run_migrations(&self) -> Result<()> {
+    /// Aplica las migraciones pendientes (ver `migrations`). Con `dry_run` solo
+    /// comprueba que aplicarían, sin modificar la DB.
+    pub async fn migrate(&self, dry_run: bool) -> Result<Vec<u32>> {
         let conn = self.pool.get().await?;
 
-        conn.interact(|conn| -> Result<()> {
-            // Migration: Add TP columns if they don't exist
-            // Note: SQLite doesn't support IF NOT EXISTS for ADD COLUMN, so we catch errors
-            let _ = conn.execute("ALTER TABLE positions ADD COLUMN tp_percent REAL", []);
-            let _ = conn.execute(
-                "ALTER TABLE positions ADD COLUMN tp_amount_percent REAL",
-                [],
-            );
-            let _ = conn.execute(
-                "ALTER TABLE positions ADD COLUMN tp_triggered INTEGER DEFAULT 0",
-                [],
-            );
-            let _ = conn.execute("ALTER TABLE positions ADD COLUMN tp2_percent REAL", []);
-            let _ = conn.execute(
-                "ALTER TABLE positions ADD COLUMN tp2_amount_percent REAL",
-                [],
-            );
-            let _ = conn.execute(
-                "ALTER TABLE positions ADD COLUMN tp2_triggered INTEGER DEFAULT 0",
-                [],
-            );
-            // Migration v2: Add fee tracking column
-            let _ = conn.execute(
-                "ALTER TABLE trades ADD COLUMN fee_sol REAL NOT NULL DEFAULT 0.0",
-                [],
-            );
+        conn.interact(move |conn| migrations::migrate(conn, dry_run))
+            .await
+            .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
+    }
 
-            Ok(())
-        })
-        .await
-        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
+    /// Versión de esquema aplicada y migraciones pendientes
+    pub async fn schema_status(&self) -> Result<SchemaStatus> {
+        let conn = self.pool.get().await?;
 
-        Ok(())
+        conn.interact(|conn| migrations::status(conn))
+            .await
+            .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
     }
 
     // ========================================================================
@@ -244,62 +423,19 @@ impl StateManager {
     /// Guarda o actualiza una posición
     pub async fn upsert_position(&self, position: PositionState) -> Result<()> {
         let conn = self.pool.get().await?;
+        let position_mint = position.token_mint.clone();
 
         let now = Utc::now().timestamp();
 
         conn.interact(move |conn| -> Result<()> {
-            conn.execute(
-                "INSERT INTO positions (
-                    token_mint, symbol, entry_price, amount_sol, current_price,
-                    stop_loss_percent, trailing_enabled, trailing_distance_percent,
-                    trailing_activation_threshold, trailing_highest_price,
-                    trailing_current_sl, tp_percent, tp_amount_percent, tp_triggered,
-                    tp2_percent, tp2_amount_percent, tp2_triggered,
-                    active, created_at, updated_at
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)
-                ON CONFLICT(token_mint) DO UPDATE SET
-                    entry_price = excluded.entry_price,
-                    amount_sol = excluded.amount_sol,
-                    current_price = excluded.current_price,
-                    stop_loss_percent = excluded.stop_loss_percent,
-                    trailing_highest_price = excluded.trailing_highest_price,
-                    trailing_current_sl = excluded.trailing_current_sl,
-                    tp_percent = excluded.tp_percent,
-                    tp_amount_percent = excluded.tp_amount_percent,
-                    tp_triggered = excluded.tp_triggered,
-                    tp2_percent = excluded.tp2_percent,
-                    tp2_amount_percent = excluded.tp2_amount_percent,
-                    tp2_triggered = excluded.tp2_triggered,
-                    active = excluded.active,
-                    updated_at = excluded.updated_at",
-                params![
-                    position.token_mint,
-                    position.symbol,
-                    position.entry_price,
-                    position.amount_sol,
-                    position.current_price,
-                    position.stop_loss_percent,
-                    position.trailing_enabled as i32,
-                    position.trailing_distance_percent,
-                    position.trailing_activation_threshold,
-                    position.trailing_highest_price,
-                    position.trailing_current_sl,
-                    position.tp_percent,
-                    position.tp_amount_percent,
-                    position.tp_triggered as i32,
-                    position.tp2_percent,
-                    position.tp2_amount_percent,
-                    position.tp2_triggered as i32,
-                    position.active as i32,
-                    position.created_at,
-                    now,
-                ],
-            )?;
+            upsert_position_row(conn, &position, now)?;
             Ok(())
         })
         .await
         .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
 
+        self.publish_position(&position_mint).await;
+
         Ok(())
     }
 
@@ -308,45 +444,17 @@ impl StateManager {
         let conn = self.pool.get().await?;
 
         conn.interact(|conn| -> Result<Vec<PositionState>> {
-            let mut stmt = conn.prepare(
-                "SELECT id, token_mint, symbol, entry_price, amount_sol, current_price,
-                        stop_loss_percent, trailing_enabled, trailing_distance_percent,
-                        trailing_activation_threshold, trailing_highest_price,
-                        trailing_current_sl, tp_percent, tp_amount_percent, tp_triggered,
-                        tp2_percent, tp2_amount_percent, tp2_triggered,
-                        active, created_at, updated_at
-                 FROM positions
-                 WHERE active = 1
-                 ORDER BY created_at DESC",
-            )?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM positions WHERE active = 1 ORDER BY created_at DESC",
+                POSITION_COLUMNS
+            ))?;
 
-            let positions = stmt
-                .query_map([], |row| {
-                    Ok(PositionState {
-                        id: Some(row.get(0)?),
-                        token_mint: row.get(1)?,
-                        symbol: row.get(2)?,
-                        entry_price: row.get(3)?,
-                        amount_sol: row.get(4)?,
-                        current_price: row.get(5)?,
-                        stop_loss_percent: row.get(6)?,
-                        trailing_enabled: row.get::<_, i32>(7)? != 0,
-                        trailing_distance_percent: row.get(8)?,
-                        trailing_activation_threshold: row.get(9)?,
-                        trailing_highest_price: row.get(10)?,
-                        trailing_current_sl: row.get(11)?,
-                        tp_percent: row.get(12).ok(),
-                        tp_amount_percent: row.get(13).ok(),
-                        tp_triggered: row.get::<_, i32>(14).unwrap_or(0) != 0,
-                        tp2_percent: row.get(15).ok(),
-                        tp2_amount_percent: row.get(16).ok(),
-                        tp2_triggered: row.get::<_, i32>(17).unwrap_or(0) != 0,
-                        active: row.get::<_, i32>(18)? != 0,
-                        created_at: row.get(19)?,
-                        updated_at: row.get(20)?,
-                    })
-                })?
+            let mut positions = stmt
+                .query_map([], position_from_row)?
                 .collect::<std::result::Result<Vec<_>, _>>()?;
+            for position in positions.iter_mut() {
+                position.tp_ladder = load_tp_ladder(conn, &position.token_mint)?;
+            }
 
             Ok(positions)
         })
@@ -360,77 +468,41 @@ impl StateManager {
         let tm = token_mint.to_string();
 
         conn.interact(move |conn| -> Result<Option<PositionState>> {
-            let mut stmt = conn.prepare(
-                "SELECT id, token_mint, symbol, entry_price, amount_sol, current_price,
-                        stop_loss_percent, trailing_enabled, trailing_distance_percent,
-                        trailing_activation_threshold, trailing_highest_price,
-                        trailing_current_sl, tp_percent, tp_amount_percent, tp_triggered,
-                        tp2_percent, tp2_amount_percent, tp2_triggered,
-                        active, created_at, updated_at
-                 FROM positions
-                 WHERE token_mint = ?1",
-            )?;
-
-            let mut rows = stmt.query(params![tm])?;
-
-            if let Some(row) = rows.next()? {
-                Ok(Some(PositionState {
-                    id: Some(row.get(0)?),
-                    token_mint: row.get(1)?,
-                    symbol: row.get(2)?,
-                    entry_price: row.get(3)?,
-                    amount_sol: row.get(4)?,
-                    current_price: row.get(5)?,
-                    stop_loss_percent: row.get(6)?,
-                    trailing_enabled: row.get::<_, i32>(7)? != 0,
-                    trailing_distance_percent: row.get(8)?,
-                    trailing_activation_threshold: row.get(9)?,
-                    trailing_highest_price: row.get(10)?,
-                    trailing_current_sl: row.get(11)?,
-                    tp_percent: row.get(12).ok(),
-                    tp_amount_percent: row.get(13).ok(),
-                    tp_triggered: row.get::<_, i32>(14).unwrap_or(0) != 0,
-                    tp2_percent: row.get(15).ok(),
-                    tp2_amount_percent: row.get(16).ok(),
-                    tp2_triggered: row.get::<_, i32>(17).unwrap_or(0) != 0,
-                    active: row.get::<_, i32>(18)? != 0,
-                    created_at: row.get(19)?,
-                    updated_at: row.get(20)?,
-                }))
-            } else {
-                Ok(None)
+            let position = conn
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM positions WHERE token_mint = ?1",
+                        POSITION_COLUMNS
+                    ),
+                    params![tm],
+                    position_from_row,
+                )
+                .optional()?;
+
+            match position {
+                Some(mut position) => {
+                    position.tp_ladder = load_tp_ladder(conn, &position.token_mint)?;
+                    Ok(Some(position))
+                }
+                None => Ok(None),
             }
         })
         .await
         .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
     }
 
-    /// Marca el TP como disparado
-    pub async fn mark_tp_triggered(&self, token_mint: &str) -> Result<()> {
+    /// Marca como ejecutado el escalón `rung` (índice) de la escalera de TP
+    pub async fn mark_rung_triggered(&self, token_mint: &str, rung: u8) -> Result<()> {
         let conn = self.pool.get().await?;
         let tm = token_mint.to_string();
 
         conn.interact(move |conn| -> Result<()> {
             conn.execute(
-                "UPDATE positions SET tp_triggered = 1, updated_at = ?1 WHERE token_mint = ?2",
-                params![Utc::now().timestamp(), tm],
+                "UPDATE position_tp_rungs SET triggered = 1 WHERE token_mint = ?1 AND rung = ?2",
+                params![tm, rung],
             )?;
-            Ok(())
-        })
-        .await
-        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
-
-        Ok(())
-    }
-
-    /// Marca el Take Profit 2 (Moonbag) como ejecutado
-    pub async fn mark_tp2_triggered(&self, token_mint: &str) -> Result<()> {
-        let conn = self.pool.get().await?;
-        let tm = token_mint.to_string();
-
-        conn.interact(move |conn| -> Result<()> {
             conn.execute(
-                "UPDATE positions SET tp2_triggered = 1, updated_at = ?1 WHERE token_mint = ?2",
+                "UPDATE positions SET updated_at = ?1 WHERE token_mint = ?2",
                 params![Utc::now().timestamp(), tm],
             )?;
             Ok(())
@@ -438,6 +510,8 @@ impl StateManager {
         .await
         .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
 
+        self.publish_position(token_mint).await;
+
         Ok(())
     }
 
@@ -460,6 +534,8 @@ impl StateManager {
         .await
         .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
 
+        self.publish_position(token_mint).await;
+
         Ok(())
     }
 
@@ -478,6 +554,10 @@ impl StateManager {
         .await
         .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
 
+        if let Some(tx) = &self.position_events {
+            let _ = tx.send(PositionEvent::Closed(token_mint.to_string()));
+        }
+
         Ok(())
     }
 
@@ -526,6 +606,28 @@ impl StateManager {
         Ok(())
     }
 
+    /// Sube el stop a break-even de forma permanente (lo escribe el engine,
+    /// que ya lo reflejó en su `PositionBook`)
+    pub async fn arm_break_even(&self, token_mint: &str) -> Result<()> {
+        let conn = self.pool.get().await?;
+        let tm = token_mint.to_string();
+
+        conn.interact(move |conn| -> Result<()> {
+            conn.execute(
+                "UPDATE positions
+                 SET break_even_armed = 1,
+                     updated_at = ?1
+                 WHERE token_mint = ?2",
+                params![Utc::now().timestamp(), tm],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
+
+        Ok(())
+    }
+
     // ========================================================================
     // TRADE HISTORY OPERATIONS
     // ========================================================================
@@ -580,24 +682,7 @@ impl StateManager {
             )?;
 
             let trades = stmt
-                .query_map(params![limit], |row| {
-                    Ok(TradeRecord {
-                        id: Some(row.get(0)?),
-                        signature: row.get(1)?,
-                        token_mint: row.get(2)?,
-                        symbol: row.get(3)?,
-                        trade_type: row.get(4)?,
-                        amount_sol: row.get(5)?,
-                        tokens_amount: row.get(6)?,
-                        price: row.get(7)?,
-                        pnl_sol: row.get(8)?,
-                        pnl_percent: row.get(9)?,
-                        route: row.get(10)?,
-                        price_impact_pct: row.get(11)?,
-                        fee_sol: row.get(12)?,
-                        timestamp: row.get(13)?,
-                    })
-                })?
+                .query_map(params![limit], trade_from_row)?
                 .collect::<std::result::Result<Vec<_>, _>>()?;
 
             Ok(trades)
@@ -662,72 +747,630 @@ impl StateManager {
         .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
     }
 
-    // ========================================================================
-    // CONFIG SNAPSHOT OPERATIONS
-    // ========================================================================
-
-    /// Guarda un snapshot de la configuración actual
-    pub async fn save_config_snapshot(&self, config_json: &str) -> Result<()> {
+    /// PnL realizado desde `since_timestamp`, en orden cronológico: (timestamp, pnl_sol)
+    pub async fn get_realized_pnl_since(&self, since_timestamp: i64) -> Result<Vec<(i64, f64)>> {
         let conn = self.pool.get().await?;
-        let cj = config_json.to_string();
 
-        conn.interact(move |conn| -> Result<()> {
-            conn.execute(
-                "INSERT INTO config_snapshots (config_json, timestamp) VALUES (?1, ?2)",
-                params![cj, Utc::now().timestamp()],
+        conn.interact(move |conn| -> Result<Vec<(i64, f64)>> {
+            let mut stmt = conn.prepare(
+                "SELECT timestamp, pnl_sol
+                 FROM trades
+                 WHERE pnl_sol IS NOT NULL AND timestamp >= ?1
+                 ORDER BY timestamp ASC, id ASC",
             )?;
-            Ok(())
+
+            let pnl = stmt
+                .query_map(params![since_timestamp], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            Ok(pnl)
         })
         .await
-        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
-
-        Ok(())
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
     }
 
-    /// Obtiene el último snapshot de configuración
-    pub async fn get_latest_config_snapshot(&self) -> Result<Option<ConfigSnapshot>> {
+    // ========================================================================
+    // CIRCUIT BREAKER OPERATIONS
+    // ========================================================================
+
+    /// Obtiene el estado persistido del Circuit Breaker (default = armado, sin disparos)
+    pub async fn get_breaker_state(&self) -> Result<BreakerState> {
         let conn = self.pool.get().await?;
 
-        conn.interact(|conn| -> Result<Option<ConfigSnapshot>> {
+        conn.interact(|conn| -> Result<BreakerState> {
             let mut stmt = conn.prepare(
-                "SELECT id, config_json, timestamp 
-                 FROM config_snapshots 
-                 ORDER BY timestamp DESC 
-                 LIMIT 1",
+                "SELECT tripped, reason, tripped_at, reset_at, last_reset_at, capital_sol
+                 FROM circuit_breaker
+                 WHERE id = 1",
             )?;
 
             let mut rows = stmt.query([])?;
 
             if let Some(row) = rows.next()? {
-                Ok(Some(ConfigSnapshot {
-                    id: Some(row.get(0)?),
-                    config_json: row.get(1)?,
-                    timestamp: row.get(2)?,
-                }))
+                Ok(BreakerState {
+                    tripped: row.get::<_, i32>(0)? != 0,
+                    reason: row.get(1)?,
+                    tripped_at: row.get(2)?,
+                    reset_at: row.get(3)?,
+                    last_reset_at: row.get(4)?,
+                    capital_sol: row.get(5)?,
+                })
             } else {
-                Ok(None)
+                Ok(BreakerState::default())
             }
         })
         .await
         .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
     }
 
+    /// Persiste el estado del Circuit Breaker
+    pub async fn save_breaker_state(&self, state: BreakerState) -> Result<()> {
+        let conn = self.pool.get().await?;
+
+        conn.interact(move |conn| -> Result<()> {
+            conn.execute(
+                "INSERT INTO circuit_breaker (id, tripped, reason, tripped_at, reset_at, last_reset_at, capital_sol)
+                 VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT(id) DO UPDATE SET
+                    tripped = excluded.tripped,
+                    reason = excluded.reason,
+                    tripped_at = excluded.tripped_at,
+                    reset_at = excluded.reset_at,
+                    last_reset_at = excluded.last_reset_at,
+                    capital_sol = excluded.capital_sol",
+                params![
+                    state.tripped as i32,
+                    state.reason,
+                    state.tripped_at,
+                    state.reset_at,
+                    state.last_reset_at,
+                    state.capital_sol,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
+
+        Ok(())
+    }
+
     // ========================================================================
-    // UTILITY OPERATIONS
+    // ORDER JOURNAL OPERATIONS
     // ========================================================================
 
-    /// Obtiene estadísticas del estado actual
-    pub async fn get_stats(&self) -> Result<StateStats> {
+    /// Registra una orden nueva (normalmente PENDING) y devuelve su id
+    pub async fn create_order(&self, order: OrderRecord) -> Result<i64> {
         let conn = self.pool.get().await?;
 
-        conn.interact(|conn| -> Result<StateStats> {
-            let active_positions: i64 = conn.query_row(
-                "SELECT COUNT(*) FROM positions WHERE active = 1",
-                [],
-                |row| row.get(0),
+        conn.interact(move |conn| -> Result<i64> {
+            conn.execute(
+                "INSERT INTO orders (
+                    token_mint, symbol, command_type, sell_pct, amount_invested,
+                    tokens_before, status, signature, error, created_at, updated_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    order.token_mint,
+                    order.symbol,
+                    order.command_type,
+                    order.sell_pct,
+                    order.amount_invested,
+                    order.tokens_before.map(|t| t as i64),
+                    order.status.as_str(),
+                    order.signature,
+                    order.error,
+                    order.created_at,
+                    order.updated_at,
+                ],
             )?;
+            Ok(conn.last_insert_rowid())
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
+    }
 
-            let total_trades: i64 =
+    /// Avanza el estado de una orden. `signature`/`error` solo se sobrescriben si son Some.
+    pub async fn update_order_status(
+        &self,
+        order_id: i64,
+        status: OrderStatus,
+        signature: Option<String>,
+        error: Option<String>,
+    ) -> Result<()> {
+        let conn = self.pool.get().await?;
+
+        conn.interact(move |conn| -> Result<()> {
+            conn.execute(
+                "UPDATE orders SET
+                    status = ?1,
+                    signature = COALESCE(?2, signature),
+                    error = COALESCE(?3, error),
+                    updated_at = ?4
+                 WHERE id = ?5",
+                params![
+                    status.as_str(),
+                    signature,
+                    error,
+                    Utc::now().timestamp(),
+                    order_id
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
+
+        Ok(())
+    }
+
+    /// Órdenes sin estado terminal (PENDING/SUBMITTED), de la más antigua a la más reciente
+    pub async fn get_open_orders(&self) -> Result<Vec<OrderRecord>> {
+        let conn = self.pool.get().await?;
+
+        conn.interact(|conn| -> Result<Vec<OrderRecord>> {
+            let mut stmt = conn.prepare(
+                "SELECT id, token_mint, symbol, command_type, sell_pct, amount_invested,
+                        tokens_before, status, signature, error, created_at, updated_at
+                 FROM orders
+                 WHERE status IN ('PENDING', 'SUBMITTED')
+                 ORDER BY created_at ASC, id ASC",
+            )?;
+
+            let orders = stmt
+                .query_map([], |row| {
+                    let status: String = row.get(7)?;
+                    Ok(OrderRecord {
+                        id: Some(row.get(0)?),
+                        token_mint: row.get(1)?,
+                        symbol: row.get(2)?,
+                        command_type: row.get(3)?,
+                        sell_pct: row.get(4)?,
+                        amount_invested: row.get(5)?,
+                        tokens_before: row.get::<_, Option<i64>>(6)?.map(|t| t as u64),
+                        status: OrderStatus::parse(&status).unwrap_or(OrderStatus::Pending),
+                        signature: row.get(8)?,
+                        error: row.get(9)?,
+                        created_at: row.get(10)?,
+                        updated_at: row.get(11)?,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            Ok(orders)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
+    }
+
+    // ========================================================================
+    // ENTRY ORDER OPERATIONS
+    // ========================================================================
+
+    /// Registra una orden de entrada y devuelve su id
+    pub async fn create_entry_order(&self, order: EntryOrder) -> Result<i64> {
+        let conn = self.pool.get().await?;
+
+        conn.interact(move |conn| -> Result<i64> {
+            conn.execute(
+                "INSERT INTO entry_orders (
+                    token_mint, symbol, amount_sol, trigger_kind, trigger_price, slippage_bps,
+                    status, signature, error, created_at, expires_at, updated_at,
+                    stop_loss_percent, trailing_enabled, trailing_distance_percent,
+                    trailing_activation_threshold, tp_ladder_json
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+                params![
+                    order.token_mint,
+                    order.symbol,
+                    order.amount_sol,
+                    order.trigger.as_str(),
+                    order.trigger_price,
+                    order.slippage_bps,
+                    order.status.as_str(),
+                    order.signature,
+                    order.error,
+                    order.created_at,
+                    order.expires_at,
+                    order.updated_at,
+                    order.exit.stop_loss_percent,
+                    order.exit.trailing_enabled as i32,
+                    order.exit.trailing_distance_percent,
+                    order.exit.trailing_activation_threshold,
+                    serde_json::to_string(&order.exit.tp_ladder)?,
+                ],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
+    }
+
+    /// Órdenes de entrada en un estado, de la más antigua a la más reciente
+    pub async fn get_entry_orders(&self, status: EntryOrderStatus) -> Result<Vec<EntryOrder>> {
+        let conn = self.pool.get().await?;
+
+        conn.interact(move |conn| -> Result<Vec<EntryOrder>> {
+            let mut stmt = conn.prepare(
+                "SELECT id, token_mint, symbol, amount_sol, trigger_kind, trigger_price, slippage_bps,
+                        status, signature, error, created_at, expires_at, updated_at,
+                        stop_loss_percent, trailing_enabled, trailing_distance_percent,
+                        trailing_activation_threshold, tp_ladder_json
+                 FROM entry_orders
+                 WHERE status = ?1
+                 ORDER BY created_at ASC, id ASC",
+            )?;
+            let orders = stmt
+                .query_map(params![status.as_str()], entry_order_from_row)?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok(orders)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
+    }
+
+    /// Pasa una orden OPEN al estado indicado. Devuelve false si ya no estaba
+    /// abierta: es el cerrojo entre `/cancel`, la expiración y el disparo.
+    pub async fn transition_open_entry_order(
+        &self,
+        order_id: i64,
+        status: EntryOrderStatus,
+    ) -> Result<bool> {
+        let conn = self.pool.get().await?;
+
+        conn.interact(move |conn| -> Result<bool> {
+            let changed = conn.execute(
+                "UPDATE entry_orders SET status = ?1, updated_at = ?2
+                 WHERE id = ?3 AND status = 'OPEN'",
+                params![status.as_str(), Utc::now().timestamp(), order_id],
+            )?;
+            Ok(changed > 0)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
+    }
+
+    /// Cierra una orden ya disparada (FILLED/FAILED) con su firma o error
+    pub async fn finish_entry_order(
+        &self,
+        order_id: i64,
+        status: EntryOrderStatus,
+        signature: Option<String>,
+        error: Option<String>,
+    ) -> Result<()> {
+        let conn = self.pool.get().await?;
+
+        conn.interact(move |conn| -> Result<()> {
+            conn.execute(
+                "UPDATE entry_orders SET
+                    status = ?1,
+                    signature = COALESCE(?2, signature),
+                    error = COALESCE(?3, error),
+                    updated_at = ?4
+                 WHERE id = ?5",
+                params![
+                    status.as_str(),
+                    signature,
+                    error,
+                    Utc::now().timestamp(),
+                    order_id
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // COST BASIS OPERATIONS
+    // ========================================================================
+
+    /// Registra una compra ejecutada como lote. Si el mint no tiene posición activa
+    /// se abre con `position` (SL/TP incluidos); si ya la tiene (DCA) se añade el
+    /// lote y se recalcula el precio medio, conservando su configuración de riesgo.
+    ///
+    /// Idempotente por `signature`: `false` si la firma ya tenía lote y no se aplicó nada.
+    pub async fn record_buy_fill(
+        &self,
+        position: PositionState,
+        signature: &str,
+        tokens: f64,
+        cost_sol: f64,
+    ) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        let position_mint = position.token_mint.clone();
+        let lot = Lot::new(
+            &position.token_mint,
+            signature,
+            tokens,
+            cost_sol,
+            Utc::now().timestamp(),
+        );
+
+        let inserted = conn
+            .interact(move |conn| -> Result<bool> {
+                let tx = conn.transaction()?;
+
+                if !insert_lot(&tx, &lot)? {
+                    return Ok(false);
+                }
+
+                let active = tx
+                    .query_row(
+                        "SELECT active FROM positions WHERE token_mint = ?1",
+                        params![lot.token_mint],
+                        |row| row.get::<_, i32>(0),
+                    )
+                    .optional()?
+                    .is_some_and(|a| a != 0);
+
+                if !active {
+                    // Ciclo nuevo: lo que quedara de posiciones anteriores ya no cuenta
+                    tx.execute(
+                        "UPDATE position_lots SET tokens_remaining = 0
+                     WHERE token_mint = ?1 AND signature != ?2",
+                        params![lot.token_mint, lot.signature],
+                    )?;
+                    let fresh = PositionState {
+                        amount_sol: 0.0,
+                        token_amount: 0.0,
+                        realized_pnl_sol: 0.0,
+                        created_at: lot.opened_at,
+                        ..position
+                    };
+                    upsert_position_row(&tx, &fresh, lot.opened_at)?;
+                    tx.execute(
+                        "UPDATE positions SET created_at = ?1, realized_pnl_fifo_sol = 0
+                     WHERE token_mint = ?2",
+                        params![lot.opened_at, lot.token_mint],
+                    )?;
+                }
+
+                // El lote ya está en la tabla: solo falta sumar su coste al WAVG
+                if let Some(mut book) = load_lot_book(&tx, &lot.token_mint)? {
+                    book.wavg_cost_sol += lot.cost_sol;
+                    save_lot_book(&tx, &lot.token_mint, &book)?;
+                }
+
+                tx.commit()?;
+                Ok(true)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
+
+        if !inserted {
+            eprintln!(
+                "⚠️ [COST BASIS] Compra {} de {} ya registrada: lote ignorado",
+                signature, position_mint
+            );
+            return Ok(false);
+        }
+        self.publish_position(&position_mint).await;
+
+        Ok(true)
+    }
+
+    /// Aplica una venta de `fraction` (0..=1) de la posición a sus lotes y devuelve
+    /// el coste liberado (WAVG y FIFO). `proceeds_sol` = SOL recibido, si se conoce.
+    ///
+    /// Idempotente por `signature`: `None` si ya se aplicó o no hay posición.
+    pub async fn record_sell_fill(
+        &self,
+        token_mint: &str,
+        signature: &str,
+        fraction: f64,
+        proceeds_sol: Option<f64>,
+    ) -> Result<Option<SellCost>> {
+        let conn = self.pool.get().await?;
+        let tm = token_mint.to_string();
+        let sig = signature.to_string();
+
+        let cost = conn
+            .interact(move |conn| -> Result<Option<SellCost>> {
+                let tx = conn.transaction()?;
+
+                let applied = tx
+                    .query_row(
+                        "SELECT 1 FROM position_fills WHERE signature = ?1",
+                        params![sig],
+                        |_| Ok(()),
+                    )
+                    .optional()?
+                    .is_some();
+                if applied {
+                    return Ok(None);
+                }
+
+                let Some(mut book) = load_lot_book(&tx, &tm)? else {
+                    return Ok(None);
+                };
+                let cost = book.sell(fraction, proceeds_sol);
+                save_lot_book(&tx, &tm, &book)?;
+
+                tx.execute(
+                    "INSERT INTO position_fills (
+                        signature, token_mint, fraction, tokens, proceeds_sol,
+                        cost_wavg_sol, cost_fifo_sol, timestamp
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        sig,
+                        tm,
+                        fraction,
+                        cost.tokens,
+                        cost.proceeds_sol,
+                        cost.cost_wavg_sol,
+                        cost.cost_fifo_sol,
+                        Utc::now().timestamp(),
+                    ],
+                )?;
+
+                tx.commit()?;
+                Ok(Some(cost))
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
+
+        if cost.is_some() {
+            self.publish_position(token_mint).await;
+        }
+
+        Ok(cost)
+    }
+
+    /// Cost basis de la posición: lotes abiertos, coste WAVG/FIFO y realizados
+    pub async fn get_lot_book(&self, token_mint: &str) -> Result<Option<LotBook>> {
+        let conn = self.pool.get().await?;
+        let tm = token_mint.to_string();
+
+        conn.interact(move |conn| -> Result<Option<LotBook>> { load_lot_book(conn, &tm) })
+            .await
+            .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
+    }
+
+    // ========================================================================
+    // BALANCE SNAPSHOT OPERATIONS
+    // ========================================================================
+
+    /// Balances conciliados de todas las posiciones
+    pub async fn get_balance_snapshots(&self) -> Result<Vec<BalanceSnapshot>> {
+        let conn = self.pool.get().await?;
+
+        conn.interact(|conn| -> Result<Vec<BalanceSnapshot>> {
+            let mut stmt = conn.prepare(
+                "SELECT token_mint, token_amount, amount_sol, updated_at FROM position_balances",
+            )?;
+
+            let snapshots = stmt
+                .query_map([], |row| {
+                    Ok(BalanceSnapshot {
+                        token_mint: row.get(0)?,
+                        token_amount: row.get::<_, i64>(1)? as u64,
+                        amount_sol: row.get(2)?,
+                        updated_at: row.get(3)?,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            Ok(snapshots)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
+    }
+
+    /// Guarda (o reemplaza) el balance conciliado de una posición
+    pub async fn save_balance_snapshot(&self, snapshot: BalanceSnapshot) -> Result<()> {
+        let conn = self.pool.get().await?;
+
+        conn.interact(move |conn| -> Result<()> {
+            conn.execute(
+                "INSERT INTO position_balances (token_mint, token_amount, amount_sol, updated_at)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(token_mint) DO UPDATE SET
+                    token_amount = excluded.token_amount,
+                    amount_sol = excluded.amount_sol,
+                    updated_at = excluded.updated_at",
+                params![
+                    snapshot.token_mint,
+                    snapshot.token_amount as i64,
+                    snapshot.amount_sol,
+                    snapshot.updated_at,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
+
+        Ok(())
+    }
+
+    /// Olvida el balance conciliado (posición cerrada)
+    pub async fn delete_balance_snapshot(&self, token_mint: &str) -> Result<()> {
+        let conn = self.pool.get().await?;
+        let tm = token_mint.to_string();
+
+        conn.interact(move |conn| -> Result<()> {
+            conn.execute(
+                "DELETE FROM position_balances WHERE token_mint = ?1",
+                params![tm],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // CONFIG SNAPSHOT OPERATIONS
+    // ========================================================================
+
+    /// Guarda un snapshot de la configuración actual
+    pub async fn save_config_snapshot(&self, config_json: &str) -> Result<()> {
+        let conn = self.pool.get().await?;
+        let cj = config_json.to_string();
+
+        conn.interact(move |conn| -> Result<()> {
+            conn.execute(
+                "INSERT INTO config_snapshots (config_json, timestamp) VALUES (?1, ?2)",
+                params![cj, Utc::now().timestamp()],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))??;
+
+        Ok(())
+    }
+
+    /// Obtiene el último snapshot de configuración
+    pub async fn get_latest_config_snapshot(&self) -> Result<Option<ConfigSnapshot>> {
+        let conn = self.pool.get().await?;
+
+        conn.interact(|conn| -> Result<Option<ConfigSnapshot>> {
+            let mut stmt = conn.prepare(
+                "SELECT id, config_json, timestamp 
+                 FROM config_snapshots 
+                 ORDER BY timestamp DESC 
+                 LIMIT 1",
+            )?;
+
+            let mut rows = stmt.query([])?;
+
+            if let Some(row) = rows.next()? {
+                Ok(Some(ConfigSnapshot {
+                    id: Some(row.get(0)?),
+                    config_json: row.get(1)?,
+                    timestamp: row.get(2)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interact error: {}", e))?
+    }
+
+    // ========================================================================
+    // UTILITY OPERATIONS
+    // ========================================================================
+
+    /// Obtiene estadísticas del estado actual
+    pub async fn get_stats(&self) -> Result<StateStats> {
+        let conn = self.pool.get().await?;
+
+        conn.interact(|conn| -> Result<StateStats> {
+            let active_positions: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM positions WHERE active = 1",
+                [],
+                |row| row.get(0),
+            )?;
+
+            let total_trades: i64 =
                 conn.query_row("SELECT COUNT(*) FROM trades", [], |row| row.get(0))?;
 
             let mut stmt = conn.prepare(
@@ -754,6 +1397,348 @@ pub struct StateStats {
     pub total_pnl_sol: f64,
 }
 
+// ============================================================================
+// SQL HELPERS (síncronos, dentro de `interact`)
+// ============================================================================
+
+/// Columnas de `positions` en el orden que espera `position_from_row`
+const POSITION_COLUMNS: &str = "id, token_mint, symbol, entry_price, amount_sol, current_price,
+    stop_loss_percent, trailing_enabled, trailing_distance_percent,
+    trailing_activation_threshold, trailing_highest_price, trailing_current_sl,
+    active, created_at, updated_at, token_amount, realized_pnl_sol,
+    max_hold_secs, break_even_after_percent, break_even_armed,
+    stale_slope_threshold, stale_minutes, min_liquidity_usd";
+
+/// Fila de `positions` sin la escalera de TP (ver `load_tp_ladder`)
+fn position_from_row(row: &rusqlite::Row) -> rusqlite::Result<PositionState> {
+    Ok(PositionState {
+        id: Some(row.get(0)?),
+        token_mint: row.get(1)?,
+        symbol: row.get(2)?,
+        entry_price: row.get(3)?,
+        amount_sol: row.get(4)?,
+        current_price: row.get(5)?,
+        stop_loss_percent: row.get(6)?,
+        trailing_enabled: row.get::<_, i32>(7)? != 0,
+        trailing_distance_percent: row.get(8)?,
+        trailing_activation_threshold: row.get(9)?,
+        trailing_highest_price: row.get(10)?,
+        trailing_current_sl: row.get(11)?,
+        tp_ladder: Vec::new(),
+        active: row.get::<_, i32>(12)? != 0,
+        created_at: row.get(13)?,
+        updated_at: row.get(14)?,
+        token_amount: row.get(15)?,
+        realized_pnl_sol: row.get(16)?,
+        exit_rules: ExitRules {
+            max_hold_secs: row.get(17)?,
+            break_even_after_percent: row.get(18)?,
+            break_even_armed: row.get::<_, i32>(19)? != 0,
+            stale_slope_threshold: row.get(20)?,
+            stale_minutes: row.get(21)?,
+            min_liquidity_usd: row.get(22)?,
+        },
+    })
+}
+
+fn load_tp_ladder(conn: &Connection, token_mint: &str) -> rusqlite::Result<Vec<TpRung>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT target_percent, sell_percent, triggered
+         FROM position_tp_rungs
+         WHERE token_mint = ?1
+         ORDER BY rung ASC",
+    )?;
+    let ladder = stmt
+        .query_map(params![token_mint], |row| {
+            Ok(TpRung {
+                target_percent: row.get(0)?,
+                sell_percent: row.get(1)?,
+                triggered: row.get::<_, i32>(2)? != 0,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(ladder)
+}
+
+/// Reemplaza la escalera completa del mint (el índice en el Vec es el nº de escalón)
+fn save_tp_ladder(conn: &Connection, token_mint: &str, ladder: &[TpRung]) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM position_tp_rungs WHERE token_mint = ?1",
+        params![token_mint],
+    )?;
+    for (rung, step) in ladder.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO position_tp_rungs (
+                token_mint, rung, target_percent, sell_percent, triggered
+            ) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                token_mint,
+                rung as i64,
+                step.target_percent,
+                step.sell_percent,
+                step.triggered as i32,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+fn upsert_position_row(
+    conn: &Connection,
+    position: &PositionState,
+    now: i64,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO positions (
+            token_mint, symbol, entry_price, amount_sol, current_price,
+            stop_loss_percent, trailing_enabled, trailing_distance_percent,
+            trailing_activation_threshold, trailing_highest_price,
+            trailing_current_sl, active, created_at, updated_at, token_amount,
+            realized_pnl_sol, max_hold_secs, break_even_after_percent,
+            break_even_armed, stale_slope_threshold, stale_minutes, min_liquidity_usd
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                  ?17, ?18, ?19, ?20, ?21, ?22)
+        ON CONFLICT(token_mint) DO UPDATE SET
+            entry_price = excluded.entry_price,
+            amount_sol = excluded.amount_sol,
+            current_price = excluded.current_price,
+            stop_loss_percent = excluded.stop_loss_percent,
+            trailing_highest_price = excluded.trailing_highest_price,
+            trailing_current_sl = excluded.trailing_current_sl,
+            active = excluded.active,
+            -- Reabrir una posición cerrada empieza un ciclo nuevo (max_hold_secs)
+            created_at = CASE WHEN positions.active = 0
+                              THEN excluded.created_at ELSE positions.created_at END,
+            updated_at = excluded.updated_at,
+            token_amount = excluded.token_amount,
+            realized_pnl_sol = excluded.realized_pnl_sol,
+            max_hold_secs = excluded.max_hold_secs,
+            break_even_after_percent = excluded.break_even_after_percent,
+            break_even_armed = excluded.break_even_armed,
+            stale_slope_threshold = excluded.stale_slope_threshold,
+            stale_minutes = excluded.stale_minutes,
+            min_liquidity_usd = excluded.min_liquidity_usd",
+        params![
+            position.token_mint,
+            position.symbol,
+            position.entry_price,
+            position.amount_sol,
+            position.current_price,
+            position.stop_loss_percent,
+            position.trailing_enabled as i32,
+            position.trailing_distance_percent,
+            position.trailing_activation_threshold,
+            position.trailing_highest_price,
+            position.trailing_current_sl,
+            position.active as i32,
+            position.created_at,
+            now,
+            position.token_amount,
+            position.realized_pnl_sol,
+            position.exit_rules.max_hold_secs,
+            position.exit_rules.break_even_after_percent,
+            position.exit_rules.break_even_armed as i32,
+            position.exit_rules.stale_slope_threshold,
+            position.exit_rules.stale_minutes,
+            position.exit_rules.min_liquidity_usd,
+        ],
+    )?;
+    save_tp_ladder(conn, &position.token_mint, &position.tp_ladder)
+}
+
+fn entry_order_from_row(row: &rusqlite::Row) -> rusqlite::Result<EntryOrder> {
+    let trigger: String = row.get(4)?;
+    let status: String = row.get(7)?;
+    // Sin escalera guardada (órdenes anteriores a la v12): la de `/buy`
+    let tp_ladder = row
+        .get::<_, Option<String>>(17)?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(|| EntryExit::default().tp_ladder);
+    Ok(EntryOrder {
+        id: Some(row.get(0)?),
+        token_mint: row.get(1)?,
+        symbol: row.get(2)?,
+        amount_sol: row.get(3)?,
+        trigger: EntryTrigger::parse(&trigger).unwrap_or(EntryTrigger::Below),
+        trigger_price: row.get(5)?,
+        slippage_bps: row.get(6)?,
+        exit: EntryExit {
+            stop_loss_percent: row.get(13)?,
+            trailing_enabled: row.get::<_, i32>(14)? != 0,
+            trailing_distance_percent: row.get(15)?,
+            trailing_activation_threshold: row.get(16)?,
+            tp_ladder,
+        },
+        status: EntryOrderStatus::parse(&status).unwrap_or(EntryOrderStatus::Failed),
+        signature: row.get(8)?,
+        error: row.get(9)?,
+        created_at: row.get(10)?,
+        expires_at: row.get(11)?,
+        updated_at: row.get(12)?,
+    })
+}
+
+fn trade_from_row(row: &rusqlite::Row) -> rusqlite::Result<TradeRecord> {
+    Ok(TradeRecord {
+        id: Some(row.get(0)?),
+        signature: row.get(1)?,
+        token_mint: row.get(2)?,
+        symbol: row.get(3)?,
+        trade_type: row.get(4)?,
+        amount_sol: row.get(5)?,
+        tokens_amount: row.get(6)?,
+        price: row.get(7)?,
+        pnl_sol: row.get(8)?,
+        pnl_percent: row.get(9)?,
+        route: row.get(10)?,
+        price_impact_pct: row.get(11)?,
+        fee_sol: row.get(12)?,
+        timestamp: row.get(13)?,
+    })
+}
+
+/// `false` si el lote ya existía (firma repetida)
+fn insert_lot(conn: &Connection, lot: &Lot) -> Result<bool> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO position_lots (
+            token_mint, signature, tokens, tokens_remaining, cost_sol, opened_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            lot.token_mint,
+            lot.signature,
+            lot.tokens,
+            lot.tokens_remaining,
+            lot.cost_sol,
+            lot.opened_at,
+        ],
+    )?;
+    Ok(inserted > 0)
+}
+
+/// Lotes abiertos + contadores de la posición (None si el mint no tiene posición)
+fn load_lot_book(conn: &Connection, token_mint: &str) -> Result<Option<LotBook>> {
+    let Some((wavg_cost_sol, realized_wavg_sol, realized_fifo_sol)) = conn
+        .query_row(
+            "SELECT amount_sol, realized_pnl_sol, realized_pnl_fifo_sol
+             FROM positions WHERE token_mint = ?1",
+            params![token_mint],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?
+    else {
+        return Ok(None);
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT id, token_mint, signature, tokens, tokens_remaining, cost_sol, opened_at
+         FROM position_lots
+         WHERE token_mint = ?1 AND tokens_remaining > 0
+         ORDER BY opened_at ASC, id ASC",
+    )?;
+    let lots = stmt
+        .query_map(params![token_mint], |row| {
+            Ok(Lot {
+                id: Some(row.get(0)?),
+                token_mint: row.get(1)?,
+                signature: row.get(2)?,
+                tokens: row.get(3)?,
+                tokens_remaining: row.get(4)?,
+                cost_sol: row.get(5)?,
+                opened_at: row.get(6)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(Some(LotBook {
+        lots,
+        wavg_cost_sol,
+        realized_wavg_sol,
+        realized_fifo_sol,
+    }))
+}
+
+/// Persiste el `LotBook`: tokens restantes por lote y contadores de la posición
+fn save_lot_book(conn: &Connection, token_mint: &str, book: &LotBook) -> Result<()> {
+    for lot in &book.lots {
+        if let Some(id) = lot.id {
+            conn.execute(
+                "UPDATE position_lots SET tokens_remaining = ?1 WHERE id = ?2",
+                params![lot.tokens_remaining.max(0.0), id],
+            )?;
+        }
+    }
+
+    conn.execute(
+        "UPDATE positions SET
+            amount_sol = ?1,
+            token_amount = ?2,
+            entry_price = COALESCE(?3, entry_price),
+            realized_pnl_sol = ?4,
+            realized_pnl_fifo_sol = ?5,
+            updated_at = ?6
+         WHERE token_mint = ?7",
+        params![
+            book.wavg_cost_sol,
+            book.tokens_held(),
+            book.avg_entry_price(),
+            book.realized_wavg_sol,
+            book.realized_fifo_sol,
+            Utc::now().timestamp(),
+            token_mint,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Reconstruye lotes y realizados de las posiciones activas a partir de `trades`.
+/// Corre dentro de la transacción de la migración que crea `position_lots`.
+pub(crate) fn backfill_cost_basis(conn: &Connection) -> Result<usize> {
+    let mints: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT token_mint FROM positions WHERE active = 1")?;
+        let mints = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        mints
+    };
+
+    let mut backfilled = 0;
+    for mint in mints {
+        let trades = {
+            let mut stmt = conn.prepare(
+                "SELECT id, signature, token_mint, symbol, trade_type, amount_sol,
+                        tokens_amount, price, pnl_sol, pnl_percent, route,
+                        price_impact_pct, COALESCE(fee_sol, 0.0), timestamp
+                 FROM trades
+                 WHERE token_mint = ?1
+                 ORDER BY timestamp ASC, id ASC",
+            )?;
+            let trades = stmt
+                .query_map(params![mint], trade_from_row)?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            trades
+        };
+
+        let mut book = replay_trades(&trades);
+        // Posición sin compras registradas (p.ej. /track): se queda como está
+        if book.lots.is_empty() {
+            continue;
+        }
+
+        for lot in book.lots.iter_mut() {
+            insert_lot(conn, lot)?;
+            lot.id = Some(conn.query_row(
+                "SELECT id FROM position_lots WHERE signature = ?1",
+                params![lot.signature],
+                |row| row.get(0),
+            )?);
+        }
+        save_lot_book(conn, &mint, &book)?;
+        backfilled += 1;
+    }
+
+    Ok(backfilled)
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -792,15 +1777,17 @@ mod tests {
             trailing_activation_threshold: 10.0,
             trailing_highest_price: None,
             trailing_current_sl: None,
-            tp_percent: None,
-            tp_amount_percent: None,
-            tp_triggered: false,
-            tp2_percent: None,
-            tp2_amount_percent: None,
-            tp2_triggered: false,
+            tp_ladder: Vec::new(),
             active: true,
             created_at: Utc::now().timestamp(),
             updated_at: Utc::now().timestamp(),
+            token_amount: 0.0,
+            realized_pnl_sol: 0.0,
+            exit_rules: ExitRules {
+                max_hold_secs: Some(3_600),
+                break_even_after_percent: Some(30.0),
+                ..ExitRules::default()
+            },
         };
 
         manager.upsert_position(position).await.unwrap();
@@ -809,6 +1796,16 @@ mod tests {
         assert!(retrieved.is_some());
         assert_eq!(retrieved.unwrap().symbol, "TEST");
 
+        manager.arm_break_even("TEST_MINT").await.unwrap();
+        let rules = manager
+            .get_position("TEST_MINT")
+            .await
+            .unwrap()
+            .unwrap()
+            .exit_rules;
+        assert_eq!(rules.max_hold_secs, Some(3_600));
+        assert!(rules.break_even_armed);
+
         manager.close_position("TEST_MINT").await.unwrap();
 
         let stats = manager.get_stats().await.unwrap();
@@ -851,4 +1848,448 @@ mod tests {
         // comparing floating point directly can be flaky, but should be fine for this exact value
         assert!((fee_stats.net_pnl_sol - 0.09).abs() < f64::EPSILON);
     }
+
+    #[tokio::test]
+    async fn test_breaker_state_persistence() {
+        let db_path = "file:test_breaker_state?mode=memory&cache=shared";
+        let manager = StateManager::new(db_path).await.unwrap();
+
+        let initial = manager.get_breaker_state().await.unwrap();
+        assert!(!initial.tripped);
+
+        let tripped = BreakerState {
+            tripped: true,
+            reason: Some("DailyLoss".to_string()),
+            tripped_at: Some(1_000),
+            reset_at: Some(87_400),
+            last_reset_at: 0,
+            capital_sol: Some(2.5),
+        };
+        manager.save_breaker_state(tripped).await.unwrap();
+
+        let loaded = manager.get_breaker_state().await.unwrap();
+        assert!(loaded.tripped);
+        assert_eq!(loaded.reset_at, Some(87_400));
+        assert_eq!(loaded.reason.as_deref(), Some("DailyLoss"));
+        assert_eq!(loaded.capital_sol, Some(2.5));
+    }
+
+    #[tokio::test]
+    async fn test_position_events_write_through() {
+        let db_path = "file:test_position_events?mode=memory&cache=shared";
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let manager = StateManager::new(db_path)
+            .await
+            .unwrap()
+            .with_position_events(tx);
+
+        let position = PositionState {
+            id: None,
+            token_mint: "EVENT_MINT".to_string(),
+            symbol: "EVT".to_string(),
+            entry_price: 0.001,
+            amount_sol: 1.0,
+            current_price: 0.001,
+            stop_loss_percent: -20.0,
+            trailing_enabled: false,
+            trailing_distance_percent: 5.0,
+            trailing_activation_threshold: 10.0,
+            trailing_highest_price: None,
+            trailing_current_sl: None,
+            tp_ladder: vec![TpRung::new(100.0, 50.0)],
+            active: true,
+            created_at: Utc::now().timestamp(),
+            updated_at: Utc::now().timestamp(),
+            token_amount: 0.0,
+            realized_pnl_sol: 0.0,
+            exit_rules: ExitRules::default(),
+        };
+        manager.upsert_position(position).await.unwrap();
+        assert!(matches!(rx.try_recv(), Ok(PositionEvent::Upserted(p)) if p.active));
+
+        manager.mark_rung_triggered("EVENT_MINT", 0).await.unwrap();
+        assert!(
+            matches!(rx.try_recv(), Ok(PositionEvent::Upserted(p)) if p.tp_ladder[0].triggered)
+        );
+
+        manager.close_position("EVENT_MINT").await.unwrap();
+        assert!(matches!(rx.try_recv(), Ok(PositionEvent::Closed(m)) if m == "EVENT_MINT"));
+    }
+
+    #[tokio::test]
+    async fn test_order_journal_lifecycle() {
+        let db_path = "file:test_order_journal?mode=memory&cache=shared";
+        let manager = StateManager::new(db_path).await.unwrap();
+        let now = Utc::now().timestamp();
+
+        let order = OrderRecord {
+            id: None,
+            token_mint: "ORDER_MINT".to_string(),
+            symbol: "ORD".to_string(),
+            command_type: "AUTO_SL".to_string(),
+            sell_pct: 100,
+            amount_invested: 0.5,
+            tokens_before: Some(1_000_000),
+            status: OrderStatus::Pending,
+            signature: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = manager.create_order(order).await.unwrap();
+
+        let open = manager.get_open_orders().await.unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].tokens_before, Some(1_000_000));
+
+        manager
+            .update_order_status(
+                id,
+                OrderStatus::Submitted,
+                Some("SIG_ORDER".to_string()),
+                None,
+            )
+            .await
+            .unwrap();
+        let open = manager.get_open_orders().await.unwrap();
+        assert_eq!(open[0].status, OrderStatus::Submitted);
+        assert_eq!(open[0].signature.as_deref(), Some("SIG_ORDER"));
+
+        manager
+            .update_order_status(id, OrderStatus::Confirmed, None, None)
+            .await
+            .unwrap();
+        assert!(manager.get_open_orders().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_entry_order_lifecycle() {
+        let db_path = "file:test_entry_orders?mode=memory&cache=shared";
+        let manager = StateManager::new(db_path).await.unwrap();
+        let now = Utc::now().timestamp();
+
+        let order = EntryOrder {
+            id: None,
+            token_mint: "ENTRY_MINT".to_string(),
+            symbol: "ENT".to_string(),
+            amount_sol: 0.1,
+            trigger: EntryTrigger::Above,
+            trigger_price: 0.002,
+            slippage_bps: 150,
+            exit: EntryExit {
+                stop_loss_percent: -25.0,
+                trailing_enabled: false,
+                tp_ladder: vec![TpRung::new(80.0, 50.0), TpRung::new(200.0, 50.0)],
+                ..EntryExit::default()
+            },
+            status: EntryOrderStatus::Open,
+            signature: None,
+            error: None,
+            created_at: now,
+            expires_at: now + 3_600,
+            updated_at: now,
+        };
+        let filled = manager.create_entry_order(order.clone()).await.unwrap();
+        let cancelled = manager.create_entry_order(order).await.unwrap();
+
+        let open = manager
+            .get_entry_orders(EntryOrderStatus::Open)
+            .await
+            .unwrap();
+        assert_eq!(open.len(), 2);
+        assert_eq!(open[0].trigger, EntryTrigger::Above);
+        assert_eq!(open[0].slippage_bps, 150);
+        assert_eq!(open[0].exit.stop_loss_percent, -25.0);
+        assert!(!open[0].exit.trailing_enabled);
+        assert_eq!(open[0].exit.tp_ladder[1], TpRung::new(200.0, 50.0));
+
+        // Disparo y cancelación compiten por la misma transición: gana uno
+        assert!(manager
+            .transition_open_entry_order(filled, EntryOrderStatus::Triggered)
+            .await
+            .unwrap());
+        assert!(!manager
+            .transition_open_entry_order(filled, EntryOrderStatus::Cancelled)
+            .await
+            .unwrap());
+        assert!(manager
+            .transition_open_entry_order(cancelled, EntryOrderStatus::Cancelled)
+            .await
+            .unwrap());
+
+        manager
+            .finish_entry_order(
+                filled,
+                EntryOrderStatus::Filled,
+                Some("SIG_ENTRY".to_string()),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(manager
+            .get_entry_orders(EntryOrderStatus::Open)
+            .await
+            .unwrap()
+            .is_empty());
+        let done = manager
+            .get_entry_orders(EntryOrderStatus::Filled)
+            .await
+            .unwrap();
+        assert_eq!(done[0].signature.as_deref(), Some("SIG_ENTRY"));
+    }
+
+    #[tokio::test]
+    async fn test_entry_order_trigger_loses_to_cancel_and_expiry() {
+        let db_path = "file:test_entry_order_races?mode=memory&cache=shared";
+        let manager = StateManager::new(db_path).await.unwrap();
+        let now = Utc::now().timestamp();
+
+        let order = EntryOrder {
+            id: None,
+            token_mint: "RACE_MINT".to_string(),
+            symbol: "RACE".to_string(),
+            amount_sol: 0.1,
+            trigger: EntryTrigger::Below,
+            trigger_price: 0.001,
+            slippage_bps: 100,
+            exit: EntryExit::default(),
+            status: EntryOrderStatus::Open,
+            signature: None,
+            error: None,
+            created_at: now,
+            expires_at: now + 60,
+            updated_at: now,
+        };
+
+        // /cancel llega antes que el tick: el disparo ya no compra
+        let cancelled = manager.create_entry_order(order.clone()).await.unwrap();
+        assert!(manager
+            .transition_open_entry_order(cancelled, EntryOrderStatus::Cancelled)
+            .await
+            .unwrap());
+        assert!(!manager
+            .transition_open_entry_order(cancelled, EntryOrderStatus::Triggered)
+            .await
+            .unwrap());
+
+        // La expiración del refresh gana igual que /cancel
+        let expired = manager.create_entry_order(order.clone()).await.unwrap();
+        assert!(manager
+            .transition_open_entry_order(expired, EntryOrderStatus::Expired)
+            .await
+            .unwrap());
+        assert!(!manager
+            .transition_open_entry_order(expired, EntryOrderStatus::Triggered)
+            .await
+            .unwrap());
+
+        // Disparada primero: ni /cancel ni la expiración la tocan
+        let triggered = manager.create_entry_order(order).await.unwrap();
+        assert!(manager
+            .transition_open_entry_order(triggered, EntryOrderStatus::Triggered)
+            .await
+            .unwrap());
+        for late in [EntryOrderStatus::Cancelled, EntryOrderStatus::Expired] {
+            assert!(!manager
+                .transition_open_entry_order(triggered, late)
+                .await
+                .unwrap());
+        }
+
+        let still_triggered = manager
+            .get_entry_orders(EntryOrderStatus::Triggered)
+            .await
+            .unwrap();
+        assert_eq!(still_triggered.len(), 1);
+        assert_eq!(still_triggered[0].id, Some(triggered));
+        assert_eq!(still_triggered[0].exit, EntryExit::default());
+    }
+
+    #[tokio::test]
+    async fn test_balance_snapshots() {
+        let db_path = "file:test_balance_snapshots?mode=memory&cache=shared";
+        let manager = StateManager::new(db_path).await.unwrap();
+
+        let mut snapshot = BalanceSnapshot {
+            token_mint: "SNAP_MINT".to_string(),
+            token_amount: 1_000_000,
+            amount_sol: 0.5,
+            updated_at: 1,
+        };
+        manager
+            .save_balance_snapshot(snapshot.clone())
+            .await
+            .unwrap();
+
+        snapshot.token_amount = 400_000;
+        snapshot.amount_sol = 0.2;
+        manager
+            .save_balance_snapshot(snapshot.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            manager.get_balance_snapshots().await.unwrap(),
+            vec![snapshot]
+        );
+
+        manager.delete_balance_snapshot("SNAP_MINT").await.unwrap();
+        assert!(manager.get_balance_snapshots().await.unwrap().is_empty());
+    }
+
+    fn lot_position(mint: &str) -> PositionState {
+        PositionState {
+            id: None,
+            token_mint: mint.to_string(),
+            symbol: "LOT".to_string(),
+            entry_price: 0.0,
+            amount_sol: 0.0,
+            current_price: 0.0,
+            stop_loss_percent: -30.0,
+            trailing_enabled: false,
+            trailing_distance_percent: 5.0,
+            trailing_activation_threshold: 10.0,
+            trailing_highest_price: None,
+            trailing_current_sl: None,
+            tp_ladder: vec![TpRung::new(100.0, 50.0)],
+            active: true,
+            created_at: 1,
+            updated_at: 1,
+            token_amount: 0.0,
+            realized_pnl_sol: 0.0,
+            exit_rules: ExitRules::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cost_basis_lots() {
+        let db_path = "file:test_cost_basis_lots?mode=memory&cache=shared";
+        let manager = StateManager::new(db_path).await.unwrap();
+
+        // Compra + DCA a mitad de precio
+        let pos = lot_position("LOT_MINT");
+        manager
+            .record_buy_fill(pos.clone(), "BUY_1", 1_000.0, 1.0)
+            .await
+            .unwrap();
+        assert!(manager
+            .record_buy_fill(pos.clone(), "BUY_2", 2_000.0, 1.0)
+            .await
+            .unwrap());
+        // Firma repetida: no duplica el lote
+        assert!(!manager
+            .record_buy_fill(pos, "BUY_2", 2_000.0, 1.0)
+            .await
+            .unwrap());
+
+        let position = manager.get_position("LOT_MINT").await.unwrap().unwrap();
+        assert!((position.amount_sol - 2.0).abs() < 1e-9);
+        assert!((position.token_amount - 3_000.0).abs() < 1e-9);
+        assert!((position.entry_price - 2.0 / 3_000.0).abs() < 1e-12);
+        assert_eq!(position.stop_loss_percent, -30.0);
+
+        let cost = manager
+            .record_sell_fill("LOT_MINT", "SELL_1", 0.5, Some(1.5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!((cost.cost_wavg_sol - 1.0).abs() < 1e-9);
+        assert!((cost.cost_fifo_sol - 1.25).abs() < 1e-9);
+        // Re-aplicar la misma venta (reconciliación de arranque) no hace nada
+        assert!(manager
+            .record_sell_fill("LOT_MINT", "SELL_1", 0.5, Some(1.5))
+            .await
+            .unwrap()
+            .is_none());
+
+        let book = manager.get_lot_book("LOT_MINT").await.unwrap().unwrap();
+        assert_eq!(book.lots.len(), 1);
+        assert!((book.tokens_held() - 1_500.0).abs() < 1e-9);
+        assert!((book.realized_wavg_sol - 0.5).abs() < 1e-9);
+        assert!((book.realized_fifo_sol - 0.25).abs() < 1e-9);
+
+        let position = manager.get_position("LOT_MINT").await.unwrap().unwrap();
+        assert!((position.amount_sol - 1.0).abs() < 1e-9);
+        assert!((position.realized_pnl_sol - 0.5).abs() < 1e-9);
+
+        // Cierre y recompra: ciclo nuevo sin lotes ni realizados anteriores
+        manager
+            .record_sell_fill("LOT_MINT", "SELL_2", 1.0, Some(1.0))
+            .await
+            .unwrap();
+        manager.close_position("LOT_MINT").await.unwrap();
+        manager
+            .record_buy_fill(lot_position("LOT_MINT"), "BUY_3", 500.0, 0.25)
+            .await
+            .unwrap();
+
+        let position = manager.get_position("LOT_MINT").await.unwrap().unwrap();
+        assert!(position.active);
+        assert!((position.amount_sol - 0.25).abs() < 1e-9);
+        assert!((position.token_amount - 500.0).abs() < 1e-9);
+        assert_eq!(position.realized_pnl_sol, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_cost_basis_backfill_from_trades() {
+        let db_path = "file:test_cost_basis_backfill?mode=memory&cache=shared";
+        let manager = StateManager::new(db_path).await.unwrap();
+
+        // Posición anterior a los lotes: solo la fila y su historial de trades
+        manager
+            .upsert_position(PositionState {
+                amount_sol: 1.0,
+                entry_price: 0.001,
+                ..lot_position("OLD_MINT")
+            })
+            .await
+            .unwrap();
+        for (sig, trade_type, amount_sol, tokens, pnl) in [
+            ("OLD_BUY_1", "MANUAL_BUY", 1.0, 1_000.0, None),
+            ("OLD_BUY_2", "MANUAL_BUY", 1.0, 2_000.0, None),
+            ("OLD_TP1", "AUTO_TP1", 1.5, 1_500_000_000.0, Some(0.5)),
+        ] {
+            manager
+                .record_trade(TradeRecord {
+                    id: None,
+                    signature: sig.to_string(),
+                    token_mint: "OLD_MINT".to_string(),
+                    symbol: "OLD".to_string(),
+                    trade_type: trade_type.to_string(),
+                    amount_sol,
+                    tokens_amount: tokens,
+                    price: 0.0,
+                    pnl_sol: pnl,
+                    pnl_percent: None,
+                    route: "test".to_string(),
+                    price_impact_pct: 0.0,
+                    fee_sol: 0.0,
+                    timestamp: 1,
+                })
+                .await
+                .unwrap();
+        }
+        let legacy = manager.get_lot_book("OLD_MINT").await.unwrap().unwrap();
+        assert!(legacy.lots.is_empty());
+
+        // Simular una DB anterior al cost basis: al reabrir se re-ejecuta desde la v7
+        manager
+            .pool
+            .get()
+            .await
+            .unwrap()
+            .interact(|conn| conn.execute("DELETE FROM schema_version WHERE version >= 7", []))
+            .await
+            .unwrap()
+            .unwrap();
+        let reopened = StateManager::new(db_path).await.unwrap();
+        let book = reopened.get_lot_book("OLD_MINT").await.unwrap().unwrap();
+        assert_eq!(book.lots.len(), 1);
+        assert!((book.tokens_held() - 1_500.0).abs() < 1e-9);
+        assert!((book.realized_fifo_sol - 0.25).abs() < 1e-9);
+
+        let position = reopened.get_position("OLD_MINT").await.unwrap().unwrap();
+        assert!((position.amount_sol - 1.0).abs() < 1e-9);
+        assert!((position.token_amount - 1_500.0).abs() < 1e-9);
+        assert!((position.realized_pnl_sol - 0.5).abs() < 1e-9);
+    }
 }
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use std::sync::Arc;
 use crate::executor_v2::TradeExecutor;
-use crate::state_manager::StateManager;
+use crate::state_manager::{ExitRules, StateManager, TpRung};
 
 
 
@@ -17,6 +17,11 @@ use crate::state_manager::StateManager;
                 .await?;
             return Ok(());
         }
+        if crate::circuit_breaker::buys_halted() {
+            handler.send_message("⛔ CIRCUIT BREAKER activo: compras bloqueadas (las ventas siguen operativas).")
+                .await?;
+            return Ok(());
+        }
         let parts: Vec<&str> = command.split_whitespace().collect();
         if parts.len() < 3 {
             handler.send_message(
@@ -65,17 +70,18 @@ use crate::state_manager::StateManager;
                                 trailing_activation_threshold: 15.0,
                                 trailing_highest_price: Some(res.price_per_token),
                                 trailing_current_sl: Some(-50.0),
-                                tp_percent: Some(100.0),
-                                tp_amount_percent: Some(50.0),
-                                tp_triggered: false,
-                                tp2_percent: Some(200.0),
-                                tp2_amount_percent: Some(100.0),
-                                tp2_triggered: false,
+                                tp_ladder: vec![TpRung::new(100.0, 50.0), TpRung::new(200.0, 50.0)],
                                 active: true,
                                 created_at: chrono::Utc::now().timestamp(),
                                 updated_at: chrono::Utc::now().timestamp(),
+                                token_amount: 0.0,
+                                realized_pnl_sol: 0.0,
+                                exit_rules: ExitRules::default(),
                             };
-                            if let Err(e) = state_manager.upsert_position(pos).await {
+                            if let Err(e) = state_manager
+                                .record_buy_fill(pos, &res.signature, res.tokens_received, res.sol_spent)
+                                .await
+                            {
                                 handler.send_message(&format!("⚠️ <b>DB Error guardando posición:</b> {}", e)).await?;
                             }
 
@@ -145,6 +151,11 @@ use crate::state_manager::StateManager;
                 .await?;
             return Ok(());
         }
+        if crate::circuit_breaker::buys_halted() {
+            handler.send_message("⛔ CIRCUIT BREAKER activo: compras bloqueadas (las ventas siguen operativas).")
+                .await?;
+            return Ok(());
+        }
         let parts: Vec<&str> = command.split_whitespace().collect();
         if parts.len() < 3 {
             handler.send_message(
@@ -227,18 +238,19 @@ use crate::state_manager::StateManager;
                         trailing_activation_threshold: 10.0,
                         trailing_highest_price: Some(price),
                         trailing_current_sl: Some(-40.0),
-                        tp_percent: Some(50.0),
-                        tp_amount_percent: Some(100.0),
-                        tp_triggered: false,
-                        tp2_percent: None,
-                        tp2_amount_percent: None,
-                        tp2_triggered: false,
+                        tp_ladder: vec![TpRung::new(50.0, 100.0)],
                         active: true,
                         created_at: chrono::Utc::now().timestamp(),
                         updated_at: chrono::Utc::now().timestamp(),
+                        token_amount: 0.0,
+                        realized_pnl_sol: 0.0,
+                        exit_rules: ExitRules::default(),
                     };
 
-                    if let Err(e) = state_manager.upsert_position(pos).await {
+                    if let Err(e) = state_manager
+                        .record_buy_fill(pos, &res.signature, res.output_amount, amount)
+                        .await
+                    {
                         handler.send_message(&format!(
                             "⚠️ <b>DB Error:</b> {}\nTx: {}",
                             e, res.signature
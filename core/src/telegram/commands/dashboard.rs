@@ -139,7 +139,9 @@ use crate::config::AppConfig;
                         target.symbol,
                         &target.token_mint[..8],
                         target.stop_loss_percent,
-                        target.tp_percent.unwrap_or(100.0),
+                        crate::engine::tp_ladder::next_rung(&target.tp_ladder)
+                            .map(|(_, rung)| rung.target_percent)
+                            .unwrap_or(100.0),
                         target.amount_sol,
                         status
                     ));
@@ -292,6 +294,32 @@ use crate::config::AppConfig;
                     "🔴"
                 };
 
+                // Informe completo sobre los ciclos cerrados (mismo que `the_chassis report`)
+                let report = state_manager
+                    .get_trade_history(crate::performance::REPORT_HISTORY_LIMIT)
+                    .await
+                    .map(|history| crate::performance::live_report(&history))
+                    .ok();
+                let report_section = match report {
+                    Some(r) if r.trades > 0 => format!(
+                        "<b>⋄ Win Rate:</b>  <code>{:.1}% ({}W/{}L)</code>\n\
+                         <b>⋄ Profit F.:</b> <code>{}</code>\n\
+                         <b>⋄ Expect.:</b>  <code>{:+.4} SOL</code>\n\
+                         <b>⋄ Sharpe:</b>   <code>{}</code> | <b>Sortino:</b> <code>{}</code>\n\
+                         <b>⋄ Avg Hold:</b> <code>{:.0} min</code> | <b>L-Streak:</b> <code>{}</code>\n\n",
+                        r.win_rate,
+                        r.wins,
+                        r.losses,
+                        fmt_ratio(r.profit_factor),
+                        r.expectancy_sol,
+                        fmt_ratio(r.sharpe),
+                        fmt_ratio(r.sortino),
+                        r.avg_hold_secs / 60.0,
+                        r.longest_losing_streak
+                    ),
+                    _ => String::new(),
+                };
+
                 let response = format!(
                     "<b>📈 PERFORMANCE METRICS</b>\n\
                     <b>━━━━━━━━━━━━━━━━━━━━━━</b>\n\n\
@@ -299,6 +327,7 @@ use crate::config::AppConfig;
                     <b>⋄ Scalps:</b>    <code>{}</code>\n\
                     <b>⋄ Active:</b>    <code>{}</code>\n\
                     <b>⋄ Avg/Pos:</b>   <code>{}{:.4} SOL</code>\n\n\
+                    {}\
                     <b>━━━━━━━━━━━━━━━━━━━━━━</b>",
                     status_emoji,
                     if stats.total_pnl_sol > 0.0 { "+" } else { "" },
@@ -306,7 +335,8 @@ use crate::config::AppConfig;
                     stats.total_trades,
                     stats.active_positions,
                     if avg_pnl > 0.0 { "+" } else { "" },
-                    avg_pnl
+                    avg_pnl,
+                    report_section
                 );
 
                 handler.send_message(&response).await?;
@@ -319,3 +349,8 @@ use crate::config::AppConfig;
         Ok(())
     }
 
+fn fmt_ratio(value: Option<f64>) -> String {
+    value
+        .map(|v| format!("{:.2}", v))
+        .unwrap_or_else(|| "—".to_string())
+}
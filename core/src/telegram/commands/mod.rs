@@ -21,6 +21,7 @@ pub static HIBERNATION_MODE: AtomicBool = AtomicBool::new(false);
 pub mod system;
 pub mod buy;
 pub mod monitor;
+pub mod orders;
 pub mod sell;
 pub mod dashboard;
 
@@ -202,6 +203,9 @@ impl CommandHandler {
                     <b>⬢ TRADING</b>\n\
                     ⬡ <code>/buy &lt;MINT&gt; &lt;SOL&gt;</code>\n\
                     ⬡ <code>/rbuy &lt;MINT&gt; &lt;SOL&gt;</code>\n\
+                    ⬡ <code>/limit &lt;MINT&gt; &lt;SOL&gt; below|above &lt;PRICE&gt; [MIN]</code>\n\
+                    ⬡ /orders - Resting Entries\n\
+                    ⬡ <code>/cancel &lt;ID&gt;</code>\n\
                     ⬡ <code>/panic &lt;MINT&gt;</code>\n\
                     ⬡ /panic_all - Liquidate All\n\n\
                     <b>⬢ MONITORING</b>\n\
@@ -329,6 +333,9 @@ impl CommandHandler {
                     <b>⬢ TRADING</b>\n\
                     ⬡ <code>/buy &lt;MINT&gt; &lt;SOL&gt;</code>\n\
                     ⬡ <code>/rbuy &lt;MINT&gt; &lt;SOL&gt;</code>\n\
+                    ⬡ <code>/limit &lt;MINT&gt; &lt;SOL&gt; below|above &lt;PRICE&gt; [MIN]</code>\n\
+                    ⬡ /orders - Resting Entries\n\
+                    ⬡ <code>/cancel &lt;ID&gt;</code>\n\
                     ⬡ <code>/panic &lt;MINT&gt;</code>\n\
                     ⬡ /panic_all - Liquidate All\n\n\
                     <b>⬢ MONITORING</b>\n\
@@ -340,6 +347,8 @@ impl CommandHandler {
                     <b>⬢ MANAGEMENT</b>\n\
                     ⬡ <code>/track &lt;MINT&gt; &lt;SYM&gt; &lt;SOL&gt; &lt;SL&gt;</code>\n\
                     ⬡ <code>/update &lt;MINT&gt; sl=-X tp=Y</code>\n\
+                    ⬡ <code>/update &lt;MINT&gt; tp=50:25,100:25,300:25</code>\n\
+                    ⬡ <code>/update &lt;MINT&gt; hold=120 be=30 stale=0.5:10 liq=5000</code>\n\
                     ⬡ <code>/untrack &lt;MINT&gt;</code>\n\n\
                     <b>⬢ ENGINE</b>\n\
                     ⬡ /hibernate - Halt Ops\n\
@@ -357,6 +366,18 @@ impl CommandHandler {
                 self.cmd_rbuy(cmd, executor, state_manager, feed_tx).await?;
             }
 
+            cmd if cmd.starts_with("/limit ") => {
+                self.cmd_limit(cmd, Arc::clone(&state_manager)).await?;
+            }
+
+            "/orders" => {
+                self.cmd_orders(Arc::clone(&state_manager)).await?;
+            }
+
+            cmd if cmd.starts_with("/cancel ") => {
+                self.cmd_cancel(cmd, Arc::clone(&state_manager)).await?;
+            }
+
             cmd if cmd.starts_with("/track ") => {
                 self.cmd_track(cmd, Arc::clone(&state_manager)).await?;
             }
@@ -437,6 +458,18 @@ impl CommandHandler {
         crate::telegram::commands::monitor::cmd_update(self, command, state_manager).await
     }
 
+    async fn cmd_limit(&self, command: &str, state_manager: Arc<StateManager>) -> Result<()> {
+        crate::telegram::commands::orders::cmd_limit(self, command, state_manager).await
+    }
+
+    async fn cmd_orders(&self, state_manager: Arc<StateManager>) -> Result<()> {
+        crate::telegram::commands::orders::cmd_orders(self, state_manager).await
+    }
+
+    async fn cmd_cancel(&self, command: &str, state_manager: Arc<StateManager>) -> Result<()> {
+        crate::telegram::commands::orders::cmd_cancel(self, command, state_manager).await
+    }
+
         async fn cmd_panic(
         &self,
         command: &str,
@@ -568,11 +601,24 @@ impl CommandHandler {
                     } else {
                         "🔴"
                     };
-                    let tokens_held = pos.amount_sol / pos.entry_price;
+                    // Tokens según los lotes; posiciones legacy sin lotes: estimado por coste
+                    let tokens_held = if pos.token_amount > 0.0 {
+                        pos.token_amount
+                    } else {
+                        pos.amount_sol / pos.entry_price
+                    };
                     let current_value_sol = tokens_held * pos.current_price;
                     let pnl = current_value_sol - pos.amount_sol;
+                    let pnl_fifo = match state_manager.get_lot_book(&pos.token_mint).await {
+                        Ok(Some(book)) if !book.lots.is_empty() => {
+                            book.unrealized_fifo(pos.current_price)
+                        }
+                        _ => pnl,
+                    };
 
-                    let tp_safe = pos.tp_percent.unwrap_or(100.0);
+                    let tp_safe = crate::engine::tp_ladder::next_rung(&pos.tp_ladder)
+                        .map(|(_, rung)| rung.target_percent)
+                        .unwrap_or(100.0);
                     let sl_safe = pos.stop_loss_percent;
                     let mut pct = (dd - sl_safe) / (tp_safe - sl_safe).max(0.1);
                     pct = pct.clamp(0.0, 1.0);
@@ -595,6 +641,8 @@ impl CommandHandler {
                         <b>⋄ Entry:</b>   <code>{:.8} SOL</code>\n\
                         <b>⋄ Price:</b>   <code>{:.8} SOL</code>\n\
                         <b>⋄ PnL:</b>     <b>{}{:.2}%</b> <i>({}{:.3} SOL)</i>\n\
+                        <b>⋄ FIFO:</b>    <i>{}{:.3} SOL</i> | <b>Realized:</b> <i>{}{:.3} SOL</i>\n\
+                        <b>⋄ TP:</b>      <code>{}</code>\n\
                         <b>⋄ Status:</b>  {}\n",
                         status_emoji,
                         pos.symbol,
@@ -604,6 +652,11 @@ impl CommandHandler {
                         dd,
                         if pnl > 0.0 { "+" } else { "" },
                         pnl,
+                        if pnl_fifo > 0.0 { "+" } else { "" },
+                        pnl_fifo,
+                        if pos.realized_pnl_sol > 0.0 { "+" } else { "" },
+                        pos.realized_pnl_sol,
+                        crate::engine::tp_ladder::format_ladder(&pos.tp_ladder),
                         visual_bar
                     );
 
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use std::sync::Arc;
-use crate::state_manager::StateManager;
+use crate::engine::{exit_rules, tp_ladder};
+use crate::state_manager::{ExitRules, StateManager, TpRung};
 
 /// Comando /track - Añade un token manualmente al DB para monitoreo
     pub async fn cmd_track(handler: &super::CommandHandler, command: &str, state_manager: Arc<StateManager>) -> Result<()> {
@@ -51,18 +52,26 @@ use crate::state_manager::StateManager;
                     trailing_activation_threshold: 20.0,
                     trailing_highest_price: Some(price_data.price_native),
                     trailing_current_sl: Some(sl),
-                    tp_percent: Some(tp),
-                    tp_amount_percent: Some(50.0), // Default sell 50%
-                    tp_triggered: false,
-                    tp2_percent: Some(200.0),        // TP2 Moonbag default
-                    tp2_amount_percent: Some(100.0), // Sell remaining
-                    tp2_triggered: false,
+                    // 50% en el TP elegido, el resto en el moonbag de +200%
+                    tp_ladder: vec![TpRung::new(tp, 50.0), TpRung::new(200.0, 50.0)],
                     active: true,
                     created_at: chrono::Utc::now().timestamp(),
                     updated_at: chrono::Utc::now().timestamp(),
+                    token_amount: 0.0,
+                    realized_pnl_sol: 0.0,
+                    exit_rules: ExitRules::default(),
                 };
 
-                state_manager.upsert_position(pos).await?;
+                // Lote estimado al precio actual: no hay compra on-chain que leer
+                let tokens = if price_data.price_native > 0.0 {
+                    sol / price_data.price_native
+                } else {
+                    0.0
+                };
+                let signature = format!("TRACK_{}_{}", valid_mint, pos.created_at);
+                state_manager
+                    .record_buy_fill(pos, &signature, tokens, sol)
+                    .await?;
 
                 handler.send_message(&format!(
                     "<b>✅ ASSET TRACKED SUCCESSFULLY</b>\n\
@@ -112,7 +121,9 @@ use crate::state_manager::StateManager;
         let parts: Vec<&str> = command.split_whitespace().collect();
         if parts.len() < 3 {
             handler.send_message(
-                "❌ <b>Syntax Error:</b> <code>/update &lt;MINT&gt; sl=-X tp=Y</code>",
+                "❌ <b>Syntax Error:</b> <code>/update &lt;MINT&gt; sl=-X tp=Y</code>\n\
+                 Ladder: <code>tp=50:25,100:25,300:25</code> (target%:sell%)\n\
+                 Exits: <code>hold=MIN be=GAIN% stale=%/MIN:MIN liq=USD</code> (<code>off</code> to clear)",
             )
             .await?;
             return Ok(());
@@ -125,6 +136,7 @@ use crate::state_manager::StateManager;
             Ok(Some(mut pos)) => {
                 let mut updated_sl = false;
                 let mut updated_tp = false;
+                let mut updated_exits = false;
 
                 for param in &parts[2..] {
                     if let Some(stripped) = param.strip_prefix("sl=") {
@@ -133,10 +145,32 @@ use crate::state_manager::StateManager;
                             updated_sl = true;
                         }
                     } else if let Some(stripped) = param.strip_prefix("tp=") {
-                        if let Ok(val) = stripped.parse::<f64>() {
-                            pos.tp_percent = Some(val);
+                        if stripped.contains(':') {
+                            // Escalera completa: sustituye los escalones pendientes
+                            match tp_ladder::parse_ladder(stripped)
+                                .and_then(|rungs| tp_ladder::replace_pending(&pos.tp_ladder, rungs))
+                            {
+                                Ok(ladder) => {
+                                    pos.tp_ladder = ladder;
+                                    updated_tp = true;
+                                }
+                                Err(e) => {
+                                    handler.send_message(&format!("❌ <b>Invalid Ladder:</b> {}", e))
+                                        .await?;
+                                    return Ok(());
+                                }
+                            }
+                        } else if let Ok(val) = stripped.parse::<f64>() {
+                            tp_ladder::retarget_next(&mut pos.tp_ladder, val);
                             updated_tp = true;
                         }
+                    } else if let Some(result) = exit_rules::apply_update(&mut pos.exit_rules, param) {
+                        if let Err(e) = result {
+                            handler.send_message(&format!("❌ <b>Invalid Exit Rule:</b> {:#}", e))
+                                .await?;
+                            return Ok(());
+                        }
+                        updated_exits = true;
                     }
                 }
 
@@ -151,6 +185,7 @@ use crate::state_manager::StateManager;
                      <b>━━━━━━━━━━━━━━━━━━━━━━</b>\n\
                      ⬡ <b>SL:</b> {}\n\
                      ⬡ <b>TP:</b> {}\n\
+                     ⬡ <b>Exits:</b> {}\n\
                      <i>Execution engine updated without reboot.</i>",
                     pos.symbol,
                     if updated_sl {
@@ -159,7 +194,12 @@ use crate::state_manager::StateManager;
                         "Unchanged".to_string()
                     },
                     if updated_tp {
-                        format!("<code>{:.1}%</code> ✅", pos.tp_percent.unwrap_or(0.0))
+                        format!("<code>{}</code> ✅", tp_ladder::format_ladder(&pos.tp_ladder))
+                    } else {
+                        "Unchanged".to_string()
+                    },
+                    if updated_exits {
+                        format!("<code>{}</code> ✅", exit_rules::format_rules(&pos.exit_rules))
                     } else {
                         "Unchanged".to_string()
                     }
@@ -0,0 +1,252 @@
+use crate::engine::tp_ladder;
+use crate::state_manager::{EntryExit, EntryOrder, EntryOrderStatus, EntryTrigger, StateManager};
+use anyhow::Result;
+use std::sync::Arc;
+
+/// Caducidad por defecto de una orden de entrada (minutos)
+const DEFAULT_EXPIRY_MIN: i64 = 24 * 60;
+
+/// Comando /limit - Registra una orden de entrada en reposo
+pub async fn cmd_limit(
+    handler: &super::CommandHandler,
+    command: &str,
+    state_manager: Arc<StateManager>,
+) -> Result<()> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    if parts.len() < 5 {
+        handler.send_message(
+            "❌ <b>Syntax:</b> <code>/limit &lt;MINT&gt; &lt;SOL&gt; &lt;below|above&gt; &lt;PRICE_SOL&gt; [EXPIRY_MIN] [SLIPPAGE_BPS] [sl=-40] [tp=50:100] [trail=15]</code>\n\
+             Example: <code>/limit 3GEz... 0.1 below 0.0000012 720 sl=-30 tp=50:50,150:50</code>",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let valid_mint =
+        match crate::validation::FinancialValidator::validate_mint(parts[1], "/limit command") {
+            Ok(m) => m,
+            Err(e) => {
+                handler
+                    .send_message(&format!("❌ <b>MINT VALIDATION ERROR:</b> {}", e))
+                    .await?;
+                return Ok(());
+            }
+        };
+
+    let amount_sol: f64 = parts[2].parse().unwrap_or(0.0);
+    let trigger = match parts[3].to_lowercase().as_str() {
+        "below" => EntryTrigger::Below,
+        "above" => EntryTrigger::Above,
+        _ => {
+            handler.send_message("❌ <b>Trigger:</b> use <code>below</code> (limit) or <code>above</code> (breakout)")
+                .await?;
+            return Ok(());
+        }
+    };
+    let trigger_price: f64 = parts[4].parse().unwrap_or(0.0);
+
+    // Opcionales: posicionales (caducidad, slippage) y clave=valor (salida)
+    let (exit_params, positional): (Vec<&str>, Vec<&str>) =
+        parts[5..].iter().copied().partition(|p| p.contains('='));
+    let expiry_min: i64 = positional
+        .first()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_EXPIRY_MIN);
+    let slippage_bps: u16 = positional
+        .get(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(100);
+    let exit = match parse_exit(&exit_params) {
+        Ok(exit) => exit,
+        Err(e) => {
+            handler
+                .send_message(&format!("❌ <b>Invalid Exit:</b> {}", e))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if amount_sol <= 0.0 || trigger_price <= 0.0 || expiry_min <= 0 {
+        handler
+            .send_message("❌ <b>Invalid Order:</b> SOL, price and expiry must be positive")
+            .await?;
+        return Ok(());
+    }
+
+    let scanner = crate::scanner::PriceScanner::new();
+    let symbol = match scanner.get_token_price(&valid_mint).await {
+        Ok(data) => data.symbol,
+        Err(_) => "TOKEN".to_string(),
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let order = EntryOrder {
+        id: None,
+        token_mint: valid_mint.to_string(),
+        symbol: symbol.clone(),
+        amount_sol,
+        trigger,
+        trigger_price,
+        slippage_bps,
+        exit: exit.clone(),
+        status: EntryOrderStatus::Open,
+        signature: None,
+        error: None,
+        created_at: now,
+        expires_at: now + expiry_min * 60,
+        updated_at: now,
+    };
+
+    match state_manager.create_entry_order(order).await {
+        Ok(id) => {
+            handler
+                .send_message(&format!(
+                    "<b>📌 ENTRY ORDER #{} ARMED</b>\n\
+                     <b>━━━━━━━━━━━━━━━━━━━━━━</b>\n\
+                     <b>⬢ Asset:</b> <code>{}</code>\n\
+                     <b>⬢ Size:</b> <code>{} SOL</code>\n\
+                     <b>⬢ Trigger:</b> <code>{} {:.10} SOL</code>\n\
+                     <b>⬢ Expires:</b> <code>{} min</code>\n\
+                     <b>⬢ Exit:</b> <code>SL {}% | TP {}</code>\n\
+                     <b>━━━━━━━━━━━━━━━━━━━━━━</b>\n\
+                     <i>Cancel with /cancel {}</i>",
+                    id,
+                    symbol,
+                    amount_sol,
+                    trigger_label(trigger),
+                    trigger_price,
+                    expiry_min,
+                    exit.stop_loss_percent,
+                    tp_ladder::format_ladder(&exit.tp_ladder),
+                    id
+                ))
+                .await?;
+        }
+        Err(e) => {
+            handler
+                .send_message(&format!("❌ <b>DB Fault:</b> {}", e))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Comando /orders - Lista las órdenes de entrada abiertas
+pub async fn cmd_orders(
+    handler: &super::CommandHandler,
+    state_manager: Arc<StateManager>,
+) -> Result<()> {
+    let orders = match state_manager.get_entry_orders(EntryOrderStatus::Open).await {
+        Ok(orders) => orders,
+        Err(e) => {
+            handler
+                .send_message(&format!("❌ <b>DB Fault:</b> {}", e))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if orders.is_empty() {
+        handler
+            .send_message("📭 <b>No open entry orders.</b>")
+            .await?;
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut msg = String::from("<b>📌 OPEN ENTRY ORDERS</b>\n<b>━━━━━━━━━━━━━━━━━━━━━━</b>\n");
+    for order in &orders {
+        msg.push_str(&format!(
+            "<b>#{}</b> {} | <code>{} SOL</code>\n\
+             ⬡ {} <code>{:.10}</code> | ⌛ {}m\n",
+            order.id.unwrap_or_default(),
+            order.symbol,
+            order.amount_sol,
+            trigger_label(order.trigger),
+            order.trigger_price,
+            (order.expires_at - now).max(0) / 60
+        ));
+    }
+    handler.send_message(&msg).await?;
+    Ok(())
+}
+
+/// Comando /cancel - Cancela una orden abierta (si aún no se disparó)
+pub async fn cmd_cancel(
+    handler: &super::CommandHandler,
+    command: &str,
+    state_manager: Arc<StateManager>,
+) -> Result<()> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    let Some(id) = parts
+        .get(1)
+        .and_then(|s| s.trim_start_matches('#').parse::<i64>().ok())
+    else {
+        handler
+            .send_message("❌ <b>Syntax:</b> <code>/cancel &lt;ORDER_ID&gt;</code>")
+            .await?;
+        return Ok(());
+    };
+
+    match state_manager
+        .transition_open_entry_order(id, EntryOrderStatus::Cancelled)
+        .await
+    {
+        Ok(true) => {
+            handler
+                .send_message(&format!("🗑️ <b>ENTRY ORDER #{} CANCELLED</b>", id))
+                .await?;
+        }
+        Ok(false) => {
+            handler
+                .send_message(&format!(
+                    "⚠️ <b>Order #{} is not open</b> (already triggered, expired or unknown)",
+                    id
+                ))
+                .await?;
+        }
+        Err(e) => {
+            handler
+                .send_message(&format!("❌ <b>DB Fault:</b> {}", e))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// `sl=-30 tp=50:50,150:50 trail=15` sobre la salida por defecto (la de `/buy`).
+/// `trail=0` desactiva el trailing.
+fn parse_exit(params: &[&str]) -> Result<EntryExit> {
+    let mut exit = EntryExit::default();
+    for param in params {
+        let Some((key, value)) = param.split_once('=') else {
+            anyhow::bail!("parámetro '{}' sin valor", param);
+        };
+        match key {
+            "sl" => {
+                let sl: f64 = value.parse()?;
+                if sl >= 0.0 {
+                    anyhow::bail!("el SL debe ser negativo ({})", value);
+                }
+                exit.stop_loss_percent = sl;
+            }
+            "tp" => exit.tp_ladder = tp_ladder::parse_ladder(value)?,
+            "trail" => {
+                let distance: f64 = value.parse()?;
+                exit.trailing_enabled = distance > 0.0;
+                if distance > 0.0 {
+                    exit.trailing_distance_percent = distance;
+                }
+            }
+            _ => anyhow::bail!("parámetro desconocido '{}'", param),
+        }
+    }
+    Ok(exit)
+}
+
+fn trigger_label(trigger: EntryTrigger) -> &'static str {
+    match trigger {
+        EntryTrigger::Below => "≤ LIMIT",
+        EntryTrigger::Above => "≥ BREAKOUT",
+    }
+}
@@ -39,6 +39,10 @@ use crate::wallet::load_keypair_from_env;
             .await
         {
             Ok(res) => {
+                let cost = state_manager
+                    .record_sell_fill(mint, &res.signature, 1.0, Some(res.output_amount))
+                    .await
+                    .unwrap_or_default();
                 if let Err(e) = state_manager.close_position(mint).await {
                     handler.send_message(&format!("⚠️ <b>DB ERROR cerrando posición:</b> {}\nLa posición puede seguir activa en DB.", e)).await?;
                 }
@@ -52,8 +56,8 @@ use crate::wallet::load_keypair_from_env;
                     amount_sol: res.output_amount,
                     tokens_amount: 0.0,
                     price: 0.0,
-                    pnl_sol: None,
-                    pnl_percent: None,
+                    pnl_sol: cost.and_then(|c| c.pnl_wavg()),
+                    pnl_percent: cost.and_then(|c| c.pnl_wavg_pct()),
                     route: "Telegram Override".to_string(),
                     price_impact_pct: res.price_impact_pct,
                     fee_sol: res.fee_sol,
@@ -132,6 +136,10 @@ use crate::wallet::load_keypair_from_env;
 
                     // Marcar como inactivas en DB
                     for (mint, res) in mints.into_iter().zip(results) {
+                        let cost = state_manager
+                            .record_sell_fill(&mint, &res.signature, 1.0, Some(res.output_amount))
+                            .await
+                            .unwrap_or_default();
                         if let Err(e) = state_manager.close_position(&mint).await {
                             eprintln!("❌ DB ERROR cerrando posición {} en panic_all: {}", mint, e);
                         }
@@ -145,8 +153,8 @@ use crate::wallet::load_keypair_from_env;
                             amount_sol: res.output_amount,
                             tokens_amount: 0.0,
                             price: 0.0,
-                            pnl_sol: None,
-                            pnl_percent: None,
+                            pnl_sol: cost.and_then(|c| c.pnl_wavg()),
+                            pnl_percent: cost.and_then(|c| c.pnl_wavg_pct()),
                             route: "Telegram Override Bundle".to_string(),
                             price_impact_pct: res.price_impact_pct,
                             fee_sol: res.fee_sol,
@@ -164,6 +164,52 @@ impl TelegramNotifier {
         self.send_message(&message, true).await
     }
 
+    /// Envía una alerta de disparo/rearme del Circuit Breaker de portfolio
+    pub async fn send_circuit_breaker_alert(
+        &self,
+        tripped: bool,
+        reason: &str,
+        reset_at: Option<i64>,
+    ) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let reset_text = reset_at
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+            .map(|dt| dt.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_else(|| "—".to_string());
+
+        let message = if tripped {
+            format!(
+                "<b>⛔ CIRCUIT BREAKER TRIPPED</b>\n\
+                <b>━━━━━━━━━━━━━━━━━━━━━━</b>\n\
+                <b>⬢ Motivo:</b> {}\n\
+                <b>⬡ Compras:</b> 🔴 BLOQUEADAS\n\
+                <b>⬡ Salidas:</b> 🟢 Activas (SL/TP)\n\
+                <b>⬡ Rearme:</b> <code>{}</code>\n\
+                <b>━━━━━━━━━━━━━━━━━━━━━━</b>\n\
+                <i>🕰 {}</i>",
+                reason,
+                reset_text,
+                chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+            )
+        } else {
+            format!(
+                "<b>✅ CIRCUIT BREAKER RESET</b>\n\
+                <b>━━━━━━━━━━━━━━━━━━━━━━</b>\n\
+                <b>⬢ Disparo previo:</b> {}\n\
+                <b>⬡ Compras:</b> 🟢 Habilitadas\n\
+                <b>━━━━━━━━━━━━━━━━━━━━━━</b>\n\
+                <i>🕰 {}</i>",
+                reason,
+                chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+            )
+        };
+
+        self.send_message(&message, true).await
+    }
+
     /// Envía un alerta de error crítico
     pub async fn send_error_alert(&self, error: &str) -> Result<()> {
         if !self.enabled {
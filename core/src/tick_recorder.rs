@@ -0,0 +1,269 @@
+//! # Tick Recorder — Grabación del PriceFeed para replay
+//!
+//! Escucha el broadcast de precios del `EventBus` y añade cada `PriceUpdate` a
+//! un fichero JSONL (una línea por tick, solo append). La grabación de un día de
+//! mercado real se puede reproducir después contra el `StrategyEngine` con
+//! `the_chassis replay` (ver `engine::replay`).
+//!
+//! Con el `LocalQuoteEngine` enganchado cada tick guarda además las reservas
+//! del pool en ese instante, y el backtester simula los fills con x·y=k.
+//!
+//! Se activa en modo monitor con `CHASSIS_RECORD_TICKS=<ruta>`.
+
+use anyhow::{Context, Result};
+use intelligence_rs::strategy_engine::{MarketData, PoolReserves};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use crate::price_feed::{PriceSource, PriceUpdate};
+use crate::quote_engine::LocalQuoteEngine;
+
+/// Variable de entorno con la ruta de grabación
+pub const RECORD_TICKS_ENV: &str = "CHASSIS_RECORD_TICKS";
+
+/// Cada cuánto se vuelca el buffer a disco
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Un tick tal como se grabó
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedTick {
+    /// Hora de pared (ms Unix) a la que se grabó: el reloj del replay
+    pub recorded_at_ms: i64,
+    /// Latencia entre la recepción en el feed y la grabación (µs)
+    pub feed_latency_us: u64,
+    pub source: PriceSource,
+    pub token_mint: String,
+    pub symbol: String,
+    pub price_native: f64,
+    pub price_usd: f64,
+    pub liquidity_usd: f64,
+    pub volume_24h: f64,
+    pub price_change_24h: f64,
+    /// Reservas SOL/token del pool al grabar (grabaciones antiguas: ninguna)
+    #[serde(default)]
+    pub pool_reserves: Option<PoolReserves>,
+}
+
+impl RecordedTick {
+    pub fn from_update(
+        update: &PriceUpdate,
+        recorded_at_ms: i64,
+        pool_reserves: Option<PoolReserves>,
+    ) -> Self {
+        Self {
+            recorded_at_ms,
+            feed_latency_us: update.received_at.elapsed().as_micros() as u64,
+            source: update.source.clone(),
+            token_mint: update.token_mint.clone(),
+            symbol: update.symbol.clone(),
+            price_native: update.price_native,
+            price_usd: update.price_usd,
+            liquidity_usd: update.liquidity_usd,
+            volume_24h: update.volume_24h,
+            price_change_24h: update.price_change_24h,
+            pool_reserves,
+        }
+    }
+
+    /// Reconstruye el `PriceUpdate` con el instante del reloj del replay
+    pub fn to_update(&self, received_at: Instant) -> PriceUpdate {
+        PriceUpdate {
+            token_mint: self.token_mint.clone(),
+            symbol: self.symbol.clone(),
+            price_usd: self.price_usd,
+            price_native: self.price_native,
+            liquidity_usd: self.liquidity_usd,
+            volume_24h: self.volume_24h,
+            price_change_24h: self.price_change_24h,
+            source: self.source.clone(),
+            received_at,
+        }
+    }
+
+    /// Tick para el backtester de `intelligence_rs` (precio en SOL)
+    pub fn to_market_data(&self) -> MarketData {
+        MarketData {
+            token_mint: self.token_mint.clone(),
+            timestamp_ms: self.recorded_at_ms.max(0) as u64,
+            price: self.price_native,
+            volume_24h: self.volume_24h,
+            liquidity: self.liquidity_usd,
+            pool_reserves: self.pool_reserves,
+        }
+    }
+}
+
+/// Grabador append-only de ticks
+pub struct TickRecorder {
+    writer: BufWriter<File>,
+    recorded: u64,
+    /// Libro de reservas del que se graban las del pool de cada tick
+    quotes: Option<Arc<LocalQuoteEngine>>,
+}
+
+impl TickRecorder {
+    /// Abre (o crea) el fichero en modo append
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("No se pudo abrir la grabación {}", path.display()))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            recorded: 0,
+            quotes: None,
+        })
+    }
+
+    /// Graba con cada tick las reservas que trackea el quote engine
+    pub fn with_reserves(mut self, quotes: Arc<LocalQuoteEngine>) -> Self {
+        self.quotes = Some(quotes);
+        self
+    }
+
+    pub fn record(&mut self, tick: &RecordedTick) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, tick)?;
+        self.writer.write_all(b"\n")?;
+        self.recorded += 1;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Graba hasta que se cierre el broadcast de precios
+    pub async fn run(mut self, mut prices: broadcast::Receiver<PriceUpdate>) {
+        println!("⏺️ [RECORDER] Grabando ticks del PriceFeed...");
+        let mut last_flush = Instant::now();
+
+        loop {
+            let update = match prices.recv().await {
+                Ok(update) => update,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("⚠️ [RECORDER] {} ticks perdidos (grabación lenta)", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            let reserves = self
+                .quotes
+                .as_ref()
+                .and_then(|quotes| quotes.pool_reserves(&update.token_mint));
+            let tick =
+                RecordedTick::from_update(&update, chrono::Utc::now().timestamp_millis(), reserves);
+            if let Err(e) = self.record(&tick) {
+                eprintln!("❌ [RECORDER] Error grabando tick: {}", e);
+            }
+            if last_flush.elapsed() >= FLUSH_INTERVAL {
+                let _ = self.flush();
+                last_flush = Instant::now();
+            }
+        }
+
+        let _ = self.flush();
+        println!("⏹️ [RECORDER] Grabación cerrada ({} ticks).", self.recorded);
+    }
+}
+
+/// Lee una grabación completa. Una última línea cortada (el proceso cayó a
+/// mitad de escritura) se descarta; cualquier otra línea inválida es un error.
+pub fn read_recording(path: impl AsRef<Path>) -> Result<Vec<RecordedTick>> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("No se pudo abrir {}", path.display()))?;
+
+    let lines: Vec<String> = BufReader::new(file)
+        .lines()
+        .collect::<std::io::Result<_>>()?;
+    let last = lines.len().saturating_sub(1);
+
+    let mut ticks = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(tick) => ticks.push(tick),
+            Err(_) if i == last => {
+                eprintln!("⚠️ [REPLAY] Última línea incompleta descartada");
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("{}:{} inválida", path.display(), i + 1))
+            }
+        }
+    }
+    Ok(ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(ms: i64, price: f64) -> RecordedTick {
+        RecordedTick {
+            recorded_at_ms: ms,
+            feed_latency_us: 120,
+            source: PriceSource::Geyser,
+            token_mint: "MINT".to_string(),
+            symbol: "TEST".to_string(),
+            price_native: price,
+            price_usd: price * 150.0,
+            liquidity_usd: 25_000.0,
+            volume_24h: 0.0,
+            price_change_24h: 0.0,
+            pool_reserves: Some(PoolReserves {
+                sol: 100.0,
+                token: 100.0 / price,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_record_and_read_back() {
+        let path = std::env::temp_dir().join(format!("ticks_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut recorder = TickRecorder::open(&path).unwrap();
+        recorder.record(&tick(1_000, 0.001)).unwrap();
+        recorder.flush().unwrap();
+        drop(recorder);
+
+        // Append: una segunda sesión continúa el mismo fichero
+        let mut recorder = TickRecorder::open(&path).unwrap();
+        recorder.record(&tick(2_000, 0.002)).unwrap();
+        recorder.flush().unwrap();
+        drop(recorder);
+
+        // Corte a mitad de línea al caer el proceso
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"recorded_at_ms\":30").unwrap();
+
+        let ticks = read_recording(&path).unwrap();
+        assert_eq!(ticks, vec![tick(1_000, 0.001), tick(2_000, 0.002)]);
+
+        let update = ticks[1].to_update(Instant::now());
+        assert_eq!(update.source, PriceSource::Geyser);
+        assert_eq!(update.price_native, 0.002);
+
+        let market = ticks[1].to_market_data();
+        assert_eq!(market.timestamp_ms, 2_000);
+        assert_eq!(market.price, 0.002);
+        assert_eq!(market.pool_reserves, ticks[1].pool_reserves);
+
+        // Grabaciones previas a las reservas siguen leyéndose
+        let legacy = r#"{"recorded_at_ms":1,"feed_latency_us":0,"source":"Geyser","token_mint":"MINT","symbol":"TEST","price_native":0.1,"price_usd":15.0,"liquidity_usd":0.0,"volume_24h":0.0,"price_change_24h":0.0}"#;
+        let legacy: RecordedTick = serde_json::from_str(legacy).unwrap();
+        assert!(legacy.to_market_data().pool_reserves.is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,358 @@
+//! # Token-2022 — Mints y cuentas de ambos token programs
+//!
+//! Los memecoins nuevos se lanzan cada vez más con Token-2022. Este módulo
+//! unifica el acceso a mints y token accounts de SPL Token clásico y
+//! Token-2022:
+//!
+//! - Detecta el token program dueño del mint (para derivar el ATA correcto).
+//! - Parsea mint / cuentas con extensiones.
+//! - Expone el transfer fee y las extensiones de riesgo (permanent delegate,
+//!   transfer hook, non-transferable) para los filtros del engine.
+
+use anyhow::{Context, Result};
+use solana_account_decoder::UiAccountData;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::TokenAccountsFilter;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::extension::{
+    non_transferable::NonTransferable, permanent_delegate::PermanentDelegate,
+    transfer_fee::TransferFeeConfig, transfer_hook::TransferHook, BaseStateWithExtensions,
+    StateWithExtensions,
+};
+use spl_token_2022::state::{Account, Mint};
+
+/// Program ID de Token-2022
+pub fn token_2022_program_id() -> Pubkey {
+    spl_token_2022::id()
+}
+
+/// `true` si `program_id` es uno de los dos token programs
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == spl_token::id() || *program_id == spl_token_2022::id()
+}
+
+/// Transfer fee de un mint Token-2022 (se cobra en tokens sobre cada transfer)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFee {
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+}
+
+impl TransferFee {
+    /// Fee retenido al transferir `amount` (redondeo hacia arriba, con tope)
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        if self.basis_points == 0 || amount == 0 {
+            return 0;
+        }
+        let fee = (amount as u128 * self.basis_points as u128).div_ceil(10_000) as u64;
+        fee.min(self.maximum_fee)
+    }
+}
+
+/// Balance de un mint en la wallet (suma de todas sus token accounts)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenHolding {
+    pub mint: String,
+    /// Balance raw
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl TokenHolding {
+    pub fn ui_amount(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(self.decimals as i32)
+    }
+}
+
+/// Mint parseado (clásico o Token-2022) con las extensiones relevantes
+#[derive(Debug, Clone)]
+pub struct MintInfo {
+    /// Token program dueño del mint
+    pub program_id: Pubkey,
+    pub decimals: u8,
+    pub supply: u64,
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+    pub is_initialized: bool,
+    pub transfer_fee: Option<TransferFee>,
+    /// Puede mover o quemar tokens de cualquier holder
+    pub permanent_delegate: Option<Pubkey>,
+    /// Programa que autoriza (o bloquea) cada transfer
+    pub transfer_hook_program: Option<Pubkey>,
+    /// Los tokens no se pueden transferir (ni vender)
+    pub non_transferable: bool,
+}
+
+impl MintInfo {
+    pub fn is_token_2022(&self) -> bool {
+        self.program_id == spl_token_2022::id()
+    }
+
+    /// Basis points del transfer fee (0 si no tiene)
+    pub fn transfer_fee_bps(&self) -> u16 {
+        self.transfer_fee.map(|f| f.basis_points).unwrap_or(0)
+    }
+
+    /// Cantidad que llega al destino tras transferir `amount`
+    pub fn amount_after_transfer_fee(&self, amount: u64) -> u64 {
+        let fee = self.transfer_fee.map(|f| f.fee_for(amount)).unwrap_or(0);
+        amount.saturating_sub(fee)
+    }
+}
+
+/// Parsea un mint de cualquiera de los dos token programs.
+/// `owner` es el dueño de la cuenta (define el program del mint).
+pub fn parse_mint(owner: &Pubkey, data: &[u8]) -> Result<MintInfo> {
+    if !is_token_program(owner) {
+        anyhow::bail!(
+            "La cuenta no pertenece a SPL Token ni a Token-2022 ({})",
+            owner
+        );
+    }
+
+    // El layout base de Token-2022 es idéntico al clásico: un solo parser
+    let state = StateWithExtensions::<Mint>::unpack(data)
+        .map_err(|e| anyhow::anyhow!("Mint inválido: {}", e))?;
+
+    // Fee más reciente: la que rige ya o a partir del próximo epoch (conservador)
+    let transfer_fee = state
+        .get_extension::<TransferFeeConfig>()
+        .ok()
+        .map(|config| {
+            let fee = &config.newer_transfer_fee;
+            TransferFee {
+                basis_points: u16::from(fee.transfer_fee_basis_points),
+                maximum_fee: u64::from(fee.maximum_fee),
+            }
+        });
+    let permanent_delegate = state
+        .get_extension::<PermanentDelegate>()
+        .ok()
+        .and_then(|ext| Option::<Pubkey>::from(ext.delegate));
+    let transfer_hook_program = state
+        .get_extension::<TransferHook>()
+        .ok()
+        .and_then(|ext| Option::<Pubkey>::from(ext.program_id));
+    let non_transferable = state.get_extension::<NonTransferable>().is_ok();
+
+    Ok(MintInfo {
+        program_id: *owner,
+        decimals: state.base.decimals,
+        supply: state.base.supply,
+        mint_authority: state.base.mint_authority.into(),
+        freeze_authority: state.base.freeze_authority.into(),
+        is_initialized: state.base.is_initialized,
+        transfer_fee,
+        permanent_delegate,
+        transfer_hook_program,
+        non_transferable,
+    })
+}
+
+/// Balance raw de un token account (clásico o con extensiones)
+pub fn parse_token_account_amount(owner: &Pubkey, data: &[u8]) -> Result<u64> {
+    if !is_token_program(owner) {
+        anyhow::bail!("La cuenta no es un token account ({})", owner);
+    }
+    let state = StateWithExtensions::<Account>::unpack(data)
+        .map_err(|e| anyhow::anyhow!("Token account inválido: {}", e))?;
+    Ok(state.base.amount)
+}
+
+/// Lee y parsea un mint on-chain
+pub fn fetch_mint(rpc: &RpcClient, mint: &Pubkey) -> Result<MintInfo> {
+    let account = rpc
+        .get_account(mint)
+        .with_context(|| format!("No se pudo leer el mint {}", mint))?;
+    parse_mint(&account.owner, &account.data)
+}
+
+/// Token program del mint. Si no se puede leer se asume SPL Token clásico.
+pub fn mint_token_program(rpc: &RpcClient, mint: &Pubkey) -> Pubkey {
+    match rpc.get_account(mint) {
+        Ok(account) if is_token_program(&account.owner) => account.owner,
+        _ => spl_token::id(),
+    }
+}
+
+/// ATA de `wallet` para `mint` bajo el token program correcto
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address_with_program_id(
+        wallet, mint, program_id,
+    )
+}
+
+/// Balance raw de `wallet` para `mint`. Un ATA inexistente (o cerrado tras vender) = 0.
+pub fn fetch_token_balance(rpc: &RpcClient, wallet: &Pubkey, mint: &Pubkey) -> Result<u64> {
+    let token_program = mint_token_program(rpc, mint);
+    let ata = associated_token_address(wallet, mint, &token_program);
+    match rpc
+        .get_account_with_commitment(&ata, rpc.commitment())
+        .with_context(|| format!("No se pudo leer el ATA {}", ata))?
+        .value
+    {
+        Some(account) => parse_token_account_amount(&account.owner, &account.data),
+        None => Ok(0),
+    }
+}
+
+/// Extrae mint y balance de un token account en formato `jsonParsed`
+pub fn parse_holding(parsed: &serde_json::Value) -> Option<TokenHolding> {
+    let info = parsed.get("info")?;
+    let token_amount = info.get("tokenAmount")?;
+    Some(TokenHolding {
+        mint: info.get("mint")?.as_str()?.to_string(),
+        amount: token_amount.get("amount")?.as_str()?.parse().ok()?,
+        decimals: token_amount.get("decimals")?.as_u64()? as u8,
+    })
+}
+
+/// Todos los tokens con balance > 0 de la wallet, en ambos token programs
+pub fn fetch_wallet_holdings(rpc: &RpcClient, wallet: &Pubkey) -> Result<Vec<TokenHolding>> {
+    let mut by_mint: std::collections::HashMap<String, TokenHolding> =
+        std::collections::HashMap::new();
+
+    for program_id in [spl_token::id(), spl_token_2022::id()] {
+        let accounts = rpc
+            .get_token_accounts_by_owner(wallet, TokenAccountsFilter::ProgramId(program_id))
+            .with_context(|| format!("No se pudieron listar las cuentas de {}", program_id))?;
+
+        for keyed in accounts {
+            let UiAccountData::Json(parsed) = &keyed.account.data else {
+                continue;
+            };
+            let Some(holding) = parse_holding(&parsed.parsed) else {
+                continue;
+            };
+            by_mint
+                .entry(holding.mint.clone())
+                .and_modify(|h| h.amount = h.amount.saturating_add(holding.amount))
+                .or_insert(holding);
+        }
+    }
+
+    Ok(by_mint.into_values().filter(|h| h.amount > 0).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::program_option::COption;
+    use solana_sdk::program_pack::Pack;
+    use spl_token_2022::extension::{ExtensionType, StateWithExtensionsMut};
+
+    fn classic_mint_data() -> Vec<u8> {
+        let mint = spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 1_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        let mut data = vec![0u8; spl_token::state::Mint::LEN];
+        mint.pack_into_slice(&mut data);
+        data
+    }
+
+    fn token_2022_mint_data(delegate: Pubkey) -> Vec<u8> {
+        let extensions = [
+            ExtensionType::TransferFeeConfig,
+            ExtensionType::PermanentDelegate,
+            ExtensionType::NonTransferable,
+        ];
+        let len = ExtensionType::try_calculate_account_len::<Mint>(&extensions).unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+
+        let fee = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        fee.newer_transfer_fee.transfer_fee_basis_points = 250.into();
+        fee.newer_transfer_fee.maximum_fee = 1_000.into();
+        state
+            .init_extension::<PermanentDelegate>(true)
+            .unwrap()
+            .delegate = Some(delegate).try_into().unwrap();
+        state.init_extension::<NonTransferable>(true).unwrap();
+
+        state.base = Mint {
+            supply: 5_000,
+            decimals: 9,
+            is_initialized: true,
+            ..Default::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_parse_classic_mint() {
+        let info = parse_mint(&spl_token::id(), &classic_mint_data()).unwrap();
+        assert!(!info.is_token_2022());
+        assert_eq!(info.decimals, 6);
+        assert_eq!(info.transfer_fee, None);
+        assert_eq!(info.amount_after_transfer_fee(1_000), 1_000);
+
+        assert!(parse_mint(&Pubkey::new_unique(), &classic_mint_data()).is_err());
+    }
+
+    #[test]
+    fn test_parse_token_2022_extensions() {
+        let delegate = Pubkey::new_unique();
+        let info = parse_mint(&spl_token_2022::id(), &token_2022_mint_data(delegate)).unwrap();
+
+        assert!(info.is_token_2022());
+        assert_eq!(info.decimals, 9);
+        assert_eq!(info.supply, 5_000);
+        assert_eq!(info.permanent_delegate, Some(delegate));
+        assert_eq!(info.transfer_hook_program, None);
+        assert!(info.non_transferable);
+        assert_eq!(info.transfer_fee_bps(), 250);
+
+        // 2.5% con tope de 1_000
+        assert_eq!(info.amount_after_transfer_fee(10_000), 9_750);
+        assert_eq!(info.amount_after_transfer_fee(1_000_000), 999_000);
+    }
+
+    #[test]
+    fn test_transfer_fee_rounds_up() {
+        let fee = TransferFee {
+            basis_points: 100,
+            maximum_fee: u64::MAX,
+        };
+        assert_eq!(fee.fee_for(0), 0);
+        assert_eq!(fee.fee_for(1), 1);
+        assert_eq!(fee.fee_for(10_000), 100);
+    }
+
+    #[test]
+    fn test_ata_depends_on_program() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        assert_eq!(
+            associated_token_address(&wallet, &mint, &spl_token::id()),
+            spl_associated_token_account::get_associated_token_address(&wallet, &mint)
+        );
+        assert_ne!(
+            associated_token_address(&wallet, &mint, &spl_token::id()),
+            associated_token_address(&wallet, &mint, &token_2022_program_id())
+        );
+    }
+
+    #[test]
+    fn test_parse_json_holding() {
+        let parsed = serde_json::json!({
+            "type": "account",
+            "info": {
+                "mint": "So11111111111111111111111111111111111111112",
+                "owner": "11111111111111111111111111111111",
+                "tokenAmount": { "amount": "2500000", "decimals": 6, "uiAmount": 2.5 }
+            }
+        });
+        let holding = parse_holding(&parsed).unwrap();
+        assert_eq!(holding.amount, 2_500_000);
+        assert_eq!(holding.decimals, 6);
+        assert!((holding.ui_amount() - 2.5).abs() < 1e-9);
+
+        assert!(parse_holding(&serde_json::json!({ "info": {} })).is_none());
+    }
+}
@@ -0,0 +1,421 @@
+//! # Swap Venues
+//!
+//! Abstracción común de los venues de ejecución (Jupiter, Raydium, Orca,
+//! Meteora, y sus variantes empaquetadas con Jito).
+//!
+//! `TradeExecutor` cotiza en todos los venues registrados, los ordena por
+//! salida neta de fees y ejecuta en el mejor, cayendo al siguiente si falla.
+//! Añadir un DEX = implementar `SwapVenue` en su módulo y registrarlo en
+//! `TradeExecutor::new`.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, transaction::VersionedTransaction};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::direct_swap::WSOL_MINT;
+use crate::jupiter::QuoteResponse;
+use crate::pumpfun::min_with_slippage;
+
+/// Fee base de red por firma (lamports)
+pub const BASE_TX_FEE_LAMPORTS: u64 = 5_000;
+
+/// Tiempo máximo de espera por el quote de cada venue al rankear
+pub const DEFAULT_QUOTE_TIMEOUT: Duration = Duration::from_millis(400);
+
+// ============================================================================
+// QUOTES
+// ============================================================================
+
+/// Parámetros de una cotización exact-in
+#[derive(Debug, Clone)]
+pub struct QuoteRequest {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount_in: u64,
+    pub slippage_bps: u16,
+    /// Tip disponible para venues que envían como bundle de Jito (0 = sin Jito)
+    pub jito_tip: u64,
+}
+
+/// Cotización de un venue concreto
+#[derive(Debug, Clone)]
+pub struct VenueQuote {
+    pub venue: &'static str,
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount_in: u64,
+    /// Salida raw esperada (las fees del pool ya están descontadas)
+    pub amount_out: u64,
+    pub slippage_bps: u16,
+    /// Costes fuera del swap en lamports: fees de red + tip de Jito
+    pub fee_lamports: u64,
+    /// Tip de Jito con el que se debe enviar (0 = RPC estándar)
+    pub jito_tip: u64,
+    pub price_impact_pct: f64,
+    pub route: String,
+    /// Quote original de Jupiter (su API lo exige para construir la TX)
+    pub jupiter_quote: Option<QuoteResponse>,
+}
+
+impl VenueQuote {
+    /// Quote de un venue on-chain: una firma, sin Jito
+    pub fn new(venue: &'static str, request: &QuoteRequest, amount_out: u64) -> Self {
+        Self {
+            venue,
+            input_mint: request.input_mint.clone(),
+            output_mint: request.output_mint.clone(),
+            amount_in: request.amount_in,
+            amount_out,
+            slippage_bps: request.slippage_bps,
+            fee_lamports: BASE_TX_FEE_LAMPORTS,
+            jito_tip: 0,
+            price_impact_pct: 0.0,
+            route: venue.to_string(),
+            jupiter_quote: None,
+        }
+    }
+
+    /// Salida mínima aceptada on-chain
+    pub fn min_amount_out(&self) -> u64 {
+        min_with_slippage(self.amount_out, self.slippage_bps)
+    }
+
+    /// Salida descontando `fee_lamports`, en unidades del mint de salida.
+    /// Si ningún lado es SOL las fees no se pueden convertir y se ignoran.
+    pub fn net_amount_out(&self) -> u64 {
+        if self.output_mint == WSOL_MINT {
+            self.amount_out.saturating_sub(self.fee_lamports)
+        } else if self.input_mint == WSOL_MINT && self.amount_in > 0 {
+            // Fee en SOL → tokens al precio implícito del propio quote
+            let fee_out =
+                self.amount_out as u128 * self.fee_lamports as u128 / self.amount_in as u128;
+            self.amount_out.saturating_sub(fee_out as u64)
+        } else {
+            self.amount_out
+        }
+    }
+}
+
+// ============================================================================
+// VENUE TRAIT
+// ============================================================================
+
+/// Lo que un venue entrega para ejecutar un quote
+pub enum VenuePayload {
+    /// Instrucciones a firmar en una TX legacy (venues on-chain)
+    Instructions(Vec<Instruction>),
+    /// TX pre-armada (Jupiter): se refresca el blockhash y se firma
+    Transaction(VersionedTransaction),
+}
+
+/// Trait que todo venue de ejecución debe implementar
+#[async_trait]
+pub trait SwapVenue: Send + Sync {
+    /// Nombre del venue para logs y `route`
+    fn name(&self) -> &'static str;
+
+    /// Filtro barato (sin RPC) de mints que el venue puede operar
+    fn supports(&self, mint: &str) -> bool;
+
+    /// Cotiza un swap exact-in
+    async fn quote(&self, request: &QuoteRequest) -> Result<VenueQuote>;
+
+    /// Quote con una salida calculada fuera del venue (p.ej. reservas locales),
+    /// con los costes de envío propios del venue
+    fn quote_with_output(&self, request: &QuoteRequest, amount_out: u64) -> VenueQuote {
+        VenueQuote::new(self.name(), request, amount_out)
+    }
+
+    /// Construye la ejecución de `quote` con `owner` como firmante
+    async fn build_instructions(&self, quote: &VenueQuote, owner: &Pubkey) -> Result<VenuePayload>;
+}
+
+/// Envuelve un venue on-chain para enviarlo como bundle de Jito (swap + tip)
+pub struct JitoBundled<V> {
+    inner: Arc<V>,
+    name: &'static str,
+}
+
+impl<V: SwapVenue> JitoBundled<V> {
+    pub fn new(inner: Arc<V>, name: &'static str) -> Self {
+        Self { inner, name }
+    }
+
+    /// Renombra el quote del venue interno y le suma la TX del tip + el tip
+    fn bundled(&self, mut quote: VenueQuote, request: &QuoteRequest) -> VenueQuote {
+        quote.venue = self.name;
+        quote.route = format!("{} + Jito", quote.route);
+        quote.jito_tip = request.jito_tip;
+        quote.fee_lamports += BASE_TX_FEE_LAMPORTS + request.jito_tip;
+        quote
+    }
+}
+
+#[async_trait]
+impl<V: SwapVenue> SwapVenue for JitoBundled<V> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn supports(&self, mint: &str) -> bool {
+        self.inner.supports(mint)
+    }
+
+    async fn quote(&self, request: &QuoteRequest) -> Result<VenueQuote> {
+        let quote = self.inner.quote(request).await?;
+        Ok(self.bundled(quote, request))
+    }
+
+    fn quote_with_output(&self, request: &QuoteRequest, amount_out: u64) -> VenueQuote {
+        self.bundled(self.inner.quote_with_output(request, amount_out), request)
+    }
+
+    async fn build_instructions(&self, quote: &VenueQuote, owner: &Pubkey) -> Result<VenuePayload> {
+        self.inner.build_instructions(quote, owner).await
+    }
+}
+
+// ============================================================================
+// RANKING
+// ============================================================================
+
+/// Cotiza en paralelo en todos los venues que soportan el par y los ordena
+/// por salida neta (mejor primero). Los venues sin quote, con salida 0 o que
+/// no responden en `timeout` quedan fuera: una venta no espera al más lento.
+pub async fn rank_venues(
+    venues: &[Arc<dyn SwapVenue>],
+    request: &QuoteRequest,
+    timeout: Duration,
+) -> Vec<(Arc<dyn SwapVenue>, VenueQuote)> {
+    let candidates: Vec<&Arc<dyn SwapVenue>> = venues
+        .iter()
+        .filter(|v| v.supports(&request.input_mint) && v.supports(&request.output_mint))
+        .collect();
+
+    // Los venues on-chain cotizan con el `RpcClient` bloqueante: cada quote
+    // corre en su propio hilo para que uno colgado no frene a los demás ni
+    // impida que salte el timeout (el hilo termina en segundo plano)
+    let handles: Vec<_> = candidates
+        .iter()
+        .map(|venue| {
+            let venue = Arc::clone(venue);
+            let request = request.clone();
+            let runtime = tokio::runtime::Handle::current();
+            tokio::task::spawn_blocking(move || runtime.block_on(venue.quote(&request)))
+        })
+        .collect();
+
+    let quotes = join_all(handles.into_iter().map(|handle| async move {
+        match tokio::time::timeout(timeout, handle).await {
+            Ok(Ok(quote)) => quote,
+            Ok(Err(e)) => Err(anyhow::anyhow!("quote abortado ({})", e)),
+            Err(_) => Err(anyhow::anyhow!("timeout tras {} ms", timeout.as_millis())),
+        }
+    }))
+    .await;
+
+    let mut ranked: Vec<(Arc<dyn SwapVenue>, VenueQuote)> = candidates
+        .into_iter()
+        .zip(quotes)
+        .filter_map(|(venue, quote)| match quote {
+            Ok(q) if q.amount_out > 0 => Some((Arc::clone(venue), q)),
+            Ok(_) => None,
+            Err(e) => {
+                println!("   {}: sin quote ({})", venue.name(), e);
+                None
+            }
+        })
+        .collect();
+
+    ranked.sort_by_key(|(_, q)| std::cmp::Reverse(q.net_amount_out()));
+    for (_, q) in &ranked {
+        println!(
+            "   📊 {:<22} → {} raw (neto {})",
+            q.venue,
+            q.amount_out,
+            q.net_amount_out()
+        );
+    }
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    struct StubVenue {
+        name: &'static str,
+        amount_out: Option<u64>,
+        fee_lamports: u64,
+        delay: Duration,
+        /// Simula un `RpcClient` bloqueante: duerme el hilo en vez de ceder
+        blocks_thread: bool,
+    }
+
+    #[async_trait]
+    impl SwapVenue for StubVenue {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn supports(&self, mint: &str) -> bool {
+            mint != "unsupported"
+        }
+
+        async fn quote(&self, request: &QuoteRequest) -> Result<VenueQuote> {
+            if self.blocks_thread {
+                std::thread::sleep(self.delay);
+            } else {
+                tokio::time::sleep(self.delay).await;
+            }
+            let out = self.amount_out.ok_or_else(|| anyhow::anyhow!("sin pool"))?;
+            let mut quote = VenueQuote::new(self.name, request, out);
+            quote.fee_lamports = self.fee_lamports;
+            Ok(quote)
+        }
+
+        async fn build_instructions(
+            &self,
+            _quote: &VenueQuote,
+            _owner: &Pubkey,
+        ) -> Result<VenuePayload> {
+            Ok(VenuePayload::Instructions(vec![]))
+        }
+    }
+
+    fn request(input: &str, output: &str) -> QuoteRequest {
+        QuoteRequest {
+            input_mint: input.to_string(),
+            output_mint: output.to_string(),
+            amount_in: 1_000_000_000,
+            slippage_bps: 100,
+            jito_tip: 100_000,
+        }
+    }
+
+    fn stub(name: &'static str, amount_out: Option<u64>, fee: u64) -> Arc<dyn SwapVenue> {
+        Arc::new(StubVenue {
+            name,
+            amount_out,
+            fee_lamports: fee,
+            delay: Duration::ZERO,
+            blocks_thread: false,
+        })
+    }
+
+    #[test]
+    fn test_net_amount_out() {
+        // Venta: la fee se descuenta directamente del SOL recibido
+        let sell = VenueQuote {
+            fee_lamports: 10_000,
+            ..VenueQuote::new("a", &request(TOKEN, WSOL_MINT), 1_000_000)
+        };
+        assert_eq!(sell.net_amount_out(), 990_000);
+        assert_eq!(sell.min_amount_out(), 990_000);
+
+        // Compra: 1% de fee sobre 1 SOL = 1% menos de tokens
+        let buy = VenueQuote {
+            fee_lamports: 10_000_000,
+            ..VenueQuote::new("a", &request(WSOL_MINT, TOKEN), 5_000_000)
+        };
+        assert_eq!(buy.net_amount_out(), 4_950_000);
+    }
+
+    #[tokio::test]
+    async fn test_rank_by_net_output() {
+        let venues = vec![
+            stub("gross-best", Some(1_000_000), 200_000),
+            stub("net-best", Some(950_000), 5_000),
+            stub("no-pool", None, 0),
+            stub("empty", Some(0), 0),
+        ];
+
+        let ranked = rank_venues(&venues, &request(TOKEN, WSOL_MINT), DEFAULT_QUOTE_TIMEOUT).await;
+        let names: Vec<&str> = ranked.iter().map(|(v, _)| v.name()).collect();
+        assert_eq!(names, vec!["net-best", "gross-best"]);
+
+        let unsupported = rank_venues(
+            &venues,
+            &request("unsupported", WSOL_MINT),
+            DEFAULT_QUOTE_TIMEOUT,
+        )
+        .await;
+        assert!(unsupported.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rank_skips_slow_venues() {
+        let slow: Arc<dyn SwapVenue> = Arc::new(StubVenue {
+            name: "slow",
+            amount_out: Some(2_000_000),
+            fee_lamports: 0,
+            delay: Duration::from_secs(5),
+            blocks_thread: false,
+        });
+        let venues = vec![slow, stub("fast", Some(1_000_000), 5_000)];
+
+        let started = std::time::Instant::now();
+        let ranked = rank_venues(
+            &venues,
+            &request(TOKEN, WSOL_MINT),
+            Duration::from_millis(50),
+        )
+        .await;
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        let names: Vec<&str> = ranked.iter().map(|(v, _)| v.name()).collect();
+        assert_eq!(names, vec!["fast"]);
+    }
+
+    #[tokio::test]
+    async fn test_rank_skips_venues_blocking_their_thread() {
+        // Runtime de un solo hilo: un quote bloqueante sin aislar congelaría
+        // también el timer del timeout
+        let blocking: Arc<dyn SwapVenue> = Arc::new(StubVenue {
+            name: "blocking",
+            amount_out: Some(2_000_000),
+            fee_lamports: 0,
+            delay: Duration::from_millis(1_500),
+            blocks_thread: true,
+        });
+        let venues = vec![blocking, stub("fast", Some(1_000_000), 5_000)];
+
+        let started = std::time::Instant::now();
+        let ranked = rank_venues(
+            &venues,
+            &request(TOKEN, WSOL_MINT),
+            Duration::from_millis(50),
+        )
+        .await;
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        let names: Vec<&str> = ranked.iter().map(|(v, _)| v.name()).collect();
+        assert_eq!(names, vec!["fast"]);
+    }
+
+    #[tokio::test]
+    async fn test_jito_bundled_adds_tip_cost() {
+        let inner = Arc::new(StubVenue {
+            name: "inner",
+            amount_out: Some(1_000_000),
+            fee_lamports: BASE_TX_FEE_LAMPORTS,
+            delay: Duration::ZERO,
+            blocks_thread: false,
+        });
+        let bundled = JitoBundled::new(inner, "inner + Jito");
+
+        let quote = bundled.quote(&request(TOKEN, WSOL_MINT)).await.unwrap();
+        assert_eq!(quote.venue, "inner + Jito");
+        assert_eq!(quote.jito_tip, 100_000);
+        assert_eq!(quote.fee_lamports, 2 * BASE_TX_FEE_LAMPORTS + 100_000);
+
+        let local = bundled.quote_with_output(&request(TOKEN, WSOL_MINT), 900_000);
+        assert_eq!(local.amount_out, 900_000);
+        assert_eq!(local.fee_lamports, quote.fee_lamports);
+    }
+}
@@ -9,6 +9,8 @@ use solana_sdk::signature::Keypair;
 use std::env;
 use std::str::FromStr;
 
+use crate::token_2022::{associated_token_address, mint_token_program};
+
 pub struct WalletMonitor {
     rpc_url: String,
     pubkey: Pubkey,
@@ -27,15 +29,17 @@ impl WalletMonitor {
         Ok(lamports as f64 / 1_000_000_000.0)
     }
 
-    /// Obtiene el balance de un token específico (SPL Token)
-    /// Para simplificar esta versión, usamos directamente el mint pubkey
+    /// Obtiene el balance de un token específico (SPL Token o Token-2022)
+    /// leyendo el ATA de la wallet bajo el token program del mint
     pub fn get_token_balance(&self, mint_addr: &str) -> Result<f64> {
         let mint_pubkey = Pubkey::from_str(mint_addr)?;
         let client = RpcClient::new(&self.rpc_url);
 
-        // En esta versión simplificada, intentamos obtener el balance directamente.
+        let token_program = mint_token_program(&client, &mint_pubkey);
+        let ata = associated_token_address(&self.pubkey, &mint_pubkey, &token_program);
+
         // En una versión final por gRPC, recibiríamos account updates.
-        match client.get_token_account_balance(&mint_pubkey) {
+        match client.get_token_account_balance(&ata) {
             Ok(balance) => Ok(balance.ui_amount.unwrap_or(0.0)),
             Err(_) => Ok(0.0),
         }
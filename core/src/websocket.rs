@@ -11,8 +11,7 @@ use serde_json::json;
 use std::time::Duration;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
-/// Program ID de Pump.fun para monitorear eventos
-const PUMP_PROGRAM_ID: &str = "6EF8rrecthR5Dkzy5fG9VGA7zF5rR9WADwpupump";
+use crate::pumpfun::PUMP_PROGRAM_ID;
 
 /// Número máximo de reconexiones antes de pausar
 const MAX_RETRIES: u32 = 5;
@@ -30,6 +30,7 @@ use crate::amm_math::{
     new_sol_price_tracker, parse_spl_token_account_amount, SolPriceUsd, VaultPair,
 };
 use crate::price_feed::{MonitoredToken, PriceCache, PriceSource, PriceUpdate};
+use crate::quote_engine::LocalQuoteEngine;
 use crate::telegram::TelegramNotifier;
 
 /// Ejecuta el loop de WebSocket que monitorea vault accounts
@@ -39,6 +40,7 @@ pub async fn ws_price_loop(
     tokens: Vec<MonitoredToken>,
     rpc_ws_url: String,
     cache: PriceCache,
+    quotes: Arc<LocalQuoteEngine>,
 ) {
     let mut reconnect_delay = Duration::from_secs(2);
     let max_reconnect_delay = Duration::from_secs(60);
@@ -272,6 +274,7 @@ pub async fn ws_price_loop(
                                                                         &vault_addr,
                                                                         amount,
                                                                     );
+                                                                    quotes.publish(pair);
 
                                                                     if pair.is_ready() {
                                                                         pair.calculate_price_in_quote().map(|price_sol| {
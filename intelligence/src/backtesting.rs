@@ -1,24 +1,391 @@
-use crate::strategy_engine::{Strategy, MarketData, TradeAction};
+//! # Backtesting — Simulador de mercado orientado a eventos
+//!
+//! Reproduce un histórico de `MarketData` (uno o varios mints intercalados) en
+//! orden temporal. Cada tick es un evento que se procesa igual que en vivo:
+//!
+//! ```text
+//!   tick ──▶ marcar precio ──▶ órdenes de salida (trailing, TP ladder, SL) ──▶ estrategia ──▶ equity
+//! ```
+//!
+//! Las órdenes de salida siguen la semántica del `StrategyEngine` del core:
+//! el trailing solo sube el stop una vez superada la activación, el stop
+//! efectivo es el mayor entre el trailing y el SL base, los escalones del TP
+//! venden un % de la posición ORIGINAL y se disparan de uno en uno por tick, y
+//! el SL vende todo. Las ventas de la estrategia pueden ser parciales.
+//!
+//! Todas las ejecuciones (incluido el cierre forzado al final) pagan coste de
+//! ejecución. Si el tick trae las reservas del pool, el fill se calcula con
+//! x·y=k y el fee de Raydium (0.25%) igual que `amm_math` en el core, y
+//! nuestras propias ventas mueven el pool hasta el siguiente tick; si no, se
+//! aplica el `slippage_taker` fijo. Cada swap paga además las firmas, el tip de
+//! Jito y la priority fee por compute unit (`docs/FEE_SYSTEM.md`). La equity se
+//! marca a mercado en cada tick, así el
+//! drawdown refleja también las pérdidas no realizadas.
+
+use crate::performance::{ExitLeg, PerformanceReport, TradeSummary};
+use crate::strategy_engine::{MarketData, PoolReserves, SellReason, Strategy, TradeAction};
 use anyhow::Result;
+use std::collections::HashMap;
 use std::time::Instant;
 
+/// Tokens por debajo de los cuales una posición se considera cerrada
+const DUST_TOKENS: f64 = 1e-12;
+
+/// Cash mínimo para abrir una posición
+const MIN_ORDER_SOL: f64 = 0.01;
+
+/// Escalón de take-profit: vende `sell_percent` de la posición ORIGINAL al
+/// alcanzar `target_percent` de ganancia (igual que `TpRung` en el core)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TpStep {
+    pub target_percent: f64,
+    pub sell_percent: f64,
+}
+
+impl TpStep {
+    pub fn new(target_percent: f64, sell_percent: f64) -> Self {
+        Self {
+            target_percent,
+            sell_percent,
+        }
+    }
+}
+
+/// Órdenes de salida que se arman en cada entrada
+#[derive(Debug, Clone)]
+pub struct ExitPlan {
+    /// SL base (porcentaje negativo). El `stop_loss` de la señal lo sustituye si es válido.
+    pub stop_loss_percent: f64,
+    pub trailing_enabled: bool,
+    /// Retroceso permitido desde el pico una vez activo
+    pub trailing_distance_percent: f64,
+    /// Ganancia mínima para activar el trailing
+    pub trailing_activation_threshold: f64,
+    /// Escalera ordenada por objetivo. Vacía: se usa el `target_price` de la señal.
+    pub tp_ladder: Vec<TpStep>,
+}
+
+impl Default for ExitPlan {
+    /// Los mismos valores con los que `/buy` arma una posición
+    fn default() -> Self {
+        Self {
+            stop_loss_percent: -50.0,
+            trailing_enabled: true,
+            trailing_distance_percent: 25.0,
+            trailing_activation_threshold: 15.0,
+            tp_ladder: vec![TpStep::new(100.0, 50.0), TpStep::new(200.0, 50.0)],
+        }
+    }
+}
+
+/// Fee base de red por firma (lamports), igual que `venue::BASE_TX_FEE_LAMPORTS`
+pub const BASE_TX_FEE_LAMPORTS: u64 = 5_000;
+
+/// Costes de ejecución de cada swap
+#[derive(Debug, Clone)]
+pub struct ExecutionCosts {
+    /// Fee del pool sobre la entrada (Raydium AMM v4: 25/10000)
+    pub pool_fee_rate: f64,
+    /// Priority fee (µL por compute unit). 100k es el fallback del executor sin Helius.
+    pub priority_fee_microlamports: u64,
+    /// Compute units que consume un swap (límite por defecto de Solana sin
+    /// `set_compute_unit_limit`)
+    pub compute_units: u64,
+    /// Tip de Jito por swap (lamports), el `jito_tip_lamports` de settings.json
+    pub jito_tip_lamports: u64,
+}
+
+impl Default for ExecutionCosts {
+    fn default() -> Self {
+        Self {
+            pool_fee_rate: 0.0025,
+            priority_fee_microlamports: 100_000,
+            compute_units: 200_000,
+            jito_tip_lamports: 100_000,
+        }
+    }
+}
+
+impl ExecutionCosts {
+    /// Coste de red por swap: con tip, las 2 firmas (swap + tip) + el tip como el
+    /// quote de `JitoBundled`; sin tip, una sola firma. Más la priority fee,
+    /// `µL × CU / 1e6` lamports redondeado hacia arriba como el runtime.
+    pub fn network_fee_sol(&self) -> f64 {
+        let signatures = if self.jito_tip_lamports > 0 { 2 } else { 1 };
+        let priority_lamports =
+            (self.priority_fee_microlamports * self.compute_units).div_ceil(1_000_000);
+        (signatures * BASE_TX_FEE_LAMPORTS + self.jito_tip_lamports + priority_lamports) as f64
+            / 1_000_000_000.0
+    }
+}
+
+/// Salida de un swap x·y=k con el fee cobrado sobre la entrada
+/// (versión f64 de `amm_math::constant_product_amount_out`)
+pub fn constant_product_amount_out(
+    reserve_in: f64,
+    reserve_out: f64,
+    amount_in: f64,
+    fee_rate: f64,
+) -> f64 {
+    if reserve_in <= 0.0 || reserve_out <= 0.0 || amount_in <= 0.0 {
+        return 0.0;
+    }
+    let amount_in_net = amount_in * (1.0 - fee_rate);
+    reserve_out * amount_in_net / (reserve_in + amount_in_net)
+}
+
+/// Tipo de ejecución simulada
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillKind {
+    Buy,
+    Sell(SellReason),
+    /// Cierre de lo que quede abierto al acabar el histórico
+    ForcedClose,
+}
+
+impl FillKind {
+    /// Razón de salida para el informe de rendimiento
+    pub fn label(&self) -> &'static str {
+        match self {
+            FillKind::Buy => "BUY",
+            FillKind::Sell(reason) => reason.as_str(),
+            FillKind::ForcedClose => "END_OF_DATA",
+        }
+    }
+}
+
+/// Una ejecución simulada
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub timestamp_ms: u64,
+    pub token_mint: String,
+    pub kind: FillKind,
+    /// Precio de ejecución (con slippage)
+    pub price: f64,
+    pub tokens: f64,
+    /// SOL gastados (compra) o recibidos (venta), sin fee de red
+    pub sol: f64,
+    /// Priority fee + tip de Jito (comparable con `TradeRecord.fee_sol`)
+    pub fee: f64,
+    /// Fee del pool valorado en SOL (ya incluido en `price`)
+    pub pool_fee_sol: f64,
+    /// PnL realizado de esta venta sobre su parte del coste (0 en compras)
+    pub realized_pnl: f64,
+}
+
+/// Punto de la curva de equity (uno por tick)
+#[derive(Debug, Clone)]
+pub struct EquityPoint {
+    pub timestamp_ms: u64,
+    pub cash: f64,
+    /// Cash + posiciones marcadas a mercado
+    pub equity: f64,
+    pub open_positions: usize,
+}
+
 /// Resultados detallados de una sesión de backtesting
 #[derive(Debug)]
 pub struct BacktestResult {
     pub strategy_name: String,
+    /// Posiciones cerradas (una entrada con todas sus ventas)
     pub total_trades: u32,
+    /// % de posiciones cerradas con PnL realizado positivo
     pub win_rate: f64,
     pub final_balance: f64,
+    /// Máximo drawdown de la equity marcada a mercado (%)
     pub max_drawdown: f64,
+    /// Fees de red (priority + Jito)
     pub total_fees_paid: f64,
+    /// Fees del pool (ya descontados en los precios de fill)
+    pub total_pool_fees: f64,
+    pub fills: Vec<Fill>,
+    pub equity_curve: Vec<EquityPoint>,
+    /// Posiciones cerradas con su recorrido (MAE/MFE) y salidas
+    pub trades: Vec<TradeSummary>,
+}
+
+impl BacktestResult {
+    /// Informe de rendimiento (mismo formato que el del trading real)
+    pub fn report(&self) -> PerformanceReport {
+        let period = match (self.equity_curve.first(), self.equity_curve.last()) {
+            (Some(first), Some(last)) => Some((first.timestamp_ms, last.timestamp_ms)),
+            _ => None,
+        };
+        PerformanceReport::from_trades(self.trades.clone(), period)
+    }
 }
 
+// ============================================================================
+// POSICIÓN SIMULADA
+// ============================================================================
+
+/// Trailing stop con la semántica de `TrailingStopLoss` del core
+#[derive(Debug)]
+struct TrailingState {
+    peak_price: f64,
+    current_sl_percent: f64,
+    enabled: bool,
+    distance_percent: f64,
+    activation_percent: f64,
+}
+
+impl TrailingState {
+    fn update(&mut self, entry_price: f64, price: f64) {
+        self.peak_price = self.peak_price.max(price);
+
+        let gain = (price - entry_price) / entry_price * 100.0;
+        if !self.enabled && gain >= self.activation_percent {
+            self.enabled = true;
+        }
+
+        if self.enabled {
+            let sl_price = self.peak_price * (1.0 - self.distance_percent / 100.0);
+            let sl_percent = (sl_price - entry_price) / entry_price * 100.0;
+            if sl_percent > self.current_sl_percent {
+                self.current_sl_percent = sl_percent;
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SimPosition {
+    opened_at_ms: u64,
+    /// SOL invertidos en la entrada (fee incluida)
+    invested_sol: f64,
+    entry_price: f64,
+    /// Mínimo y máximo marcados desde la entrada (MAE/MFE)
+    low_price: f64,
+    high_price: f64,
+    exits: Vec<ExitLeg>,
+    tokens: f64,
+    /// Coste (SOL, fee de compra incluida) de los tokens que siguen en cartera
+    cost_basis: f64,
+    realized_pnl: f64,
+    last_price: f64,
+    stop_loss_percent: f64,
+    trailing: Option<TrailingState>,
+    tp_ladder: Vec<TpStep>,
+    /// Escalones ya ejecutados (en orden)
+    tp_triggered: usize,
+}
+
+impl SimPosition {
+    fn gain_percent(&self, price: f64) -> f64 {
+        (price - self.entry_price) / self.entry_price * 100.0
+    }
+
+    fn mark(&mut self, price: f64) {
+        self.last_price = price;
+        self.low_price = self.low_price.min(price);
+        self.high_price = self.high_price.max(price);
+    }
+
+    fn summary(&self, token_mint: &str, closed_at_ms: u64) -> TradeSummary {
+        TradeSummary {
+            token_mint: token_mint.to_string(),
+            opened_at_ms: self.opened_at_ms,
+            closed_at_ms,
+            invested_sol: self.invested_sol,
+            pnl_sol: self.realized_pnl,
+            mae_percent: Some(self.gain_percent(self.low_price).min(0.0)),
+            mfe_percent: Some(self.gain_percent(self.high_price).max(0.0)),
+            exits: self.exits.clone(),
+        }
+    }
+
+    /// Actualiza el trailing con el tick y devuelve el stop efectivo (%)
+    fn update_stop(&mut self, price: f64) -> f64 {
+        match self.trailing.as_mut() {
+            Some(trailing) => {
+                trailing.update(self.entry_price, price);
+                trailing.current_sl_percent.max(self.stop_loss_percent)
+            }
+            None => self.stop_loss_percent,
+        }
+    }
+
+    /// % del balance actual que vende el siguiente escalón, si ya se alcanzó
+    fn due_tp_percent(&self, price: f64) -> Option<u8> {
+        let step = self.tp_ladder.get(self.tp_triggered)?;
+        if self.gain_percent(price) < step.target_percent {
+            return None;
+        }
+
+        let sold: f64 = self.tp_ladder[..self.tp_triggered]
+            .iter()
+            .map(|step| step.sell_percent)
+            .sum();
+        let remaining = (100.0 - sold).max(0.0);
+        if remaining <= 0.0 {
+            return Some(100);
+        }
+        Some(
+            (step.sell_percent / remaining * 100.0)
+                .round()
+                .clamp(1.0, 100.0) as u8,
+        )
+    }
+}
+
+// ============================================================================
+// SIMULADOR
+// ============================================================================
+
 /// Simulador de mercado para backtesting de alta fidelidad
 pub struct MarketSimulator {
     pub initial_balance: f64,
     pub slippage_maker: f64, // 0.1%
-    pub slippage_taker: f64, // 0.3%
-    pub fee_per_trade: f64,  // 0.000005 SOL
+    pub slippage_taker: f64, // 0.3% (solo sin reservas del pool)
+    pub costs: ExecutionCosts,
+    /// % de la equity asignado a cada entrada (limitado por el cash libre)
+    pub position_size_pct: f64,
+    pub exit_plan: ExitPlan,
+    /// Log por fill (desactivar en barridos de parámetros)
+    pub verbose: bool,
+}
+
+/// Estado mutable de una sesión
+struct Session {
+    cash: f64,
+    positions: HashMap<String, SimPosition>,
+    /// Reservas vigentes por mint (las del último tick, movidas por nuestros fills)
+    pools: HashMap<String, PoolReserves>,
+    fills: Vec<Fill>,
+    equity_curve: Vec<EquityPoint>,
+    trades: Vec<TradeSummary>,
+    fees_paid: f64,
+    pool_fees: f64,
+    closed_trades: u32,
+    wins: u32,
+    peak_equity: f64,
+    max_drawdown: f64,
+}
+
+impl Session {
+    fn equity(&self) -> f64 {
+        self.cash
+            + self
+                .positions
+                .values()
+                .map(|p| p.tokens * p.last_price)
+                .sum::<f64>()
+    }
+
+    fn mark(&mut self, timestamp_ms: u64) {
+        let equity = self.equity();
+        self.peak_equity = self.peak_equity.max(equity);
+        if self.peak_equity > 0.0 {
+            let dd = (self.peak_equity - equity) / self.peak_equity * 100.0;
+            self.max_drawdown = self.max_drawdown.max(dd);
+        }
+        self.equity_curve.push(EquityPoint {
+            timestamp_ms,
+            cash: self.cash,
+            equity,
+            open_positions: self.positions.len(),
+        });
+    }
 }
 
 impl MarketSimulator {
@@ -27,93 +394,701 @@ impl MarketSimulator {
             initial_balance,
             slippage_maker: 0.001,
             slippage_taker: 0.003,
-            fee_per_trade: 0.000005,
+            costs: ExecutionCosts::default(),
+            position_size_pct: 100.0,
+            exit_plan: ExitPlan::default(),
+            verbose: true,
+        }
+    }
+
+    /// % de la equity por entrada (por debajo de 100 permite varias posiciones a la vez)
+    pub fn with_position_size(mut self, pct: f64) -> Self {
+        self.position_size_pct = pct.clamp(0.0, 100.0);
+        self
+    }
+
+    pub fn with_exit_plan(mut self, plan: ExitPlan) -> Self {
+        self.exit_plan = plan;
+        self
+    }
+
+    pub fn with_costs(mut self, costs: ExecutionCosts) -> Self {
+        self.costs = costs;
+        self
+    }
+
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Compra con `sol_in`: (tokens recibidos, fee del pool en SOL)
+    fn quote_buy(&self, pool: Option<&mut PoolReserves>, price: f64, sol_in: f64) -> (f64, f64) {
+        match pool {
+            Some(pool) => {
+                let fee_rate = self.costs.pool_fee_rate;
+                let tokens = constant_product_amount_out(pool.sol, pool.token, sol_in, fee_rate);
+                pool.sol += sol_in;
+                pool.token -= tokens;
+                (tokens, sol_in * fee_rate)
+            }
+            None => (sol_in / (price * (1.0 + self.slippage_taker)), 0.0),
         }
     }
 
-    /// Ejecuta una estrategia sobre un dataset histórico
-    pub fn run<S: Strategy>(&self, strategy: &mut S, data: &[MarketData]) -> Result<BacktestResult> {
+    /// Venta de `tokens_in`: (SOL recibidos, fee del pool en SOL)
+    fn quote_sell(
+        &self,
+        pool: Option<&mut PoolReserves>,
+        price: f64,
+        tokens_in: f64,
+    ) -> (f64, f64) {
+        match pool {
+            Some(pool) => {
+                let fee_rate = self.costs.pool_fee_rate;
+                let pool_fee_sol = tokens_in * fee_rate * pool.price();
+                let sol = constant_product_amount_out(pool.token, pool.sol, tokens_in, fee_rate);
+                pool.token += tokens_in;
+                pool.sol -= sol;
+                (sol, pool_fee_sol)
+            }
+            None => (tokens_in * price * (1.0 - self.slippage_taker), 0.0),
+        }
+    }
+
+    /// Ejecuta una estrategia sobre un dataset histórico (uno o varios mints)
+    pub fn run<S: Strategy>(
+        &self,
+        strategy: &mut S,
+        data: &[MarketData],
+    ) -> Result<BacktestResult> {
         let start_time = Instant::now();
-        println!("🧪 Iniciando backtesting para '{}' con {} puntos de datos...", strategy.name(), data.len());
-        
+        if self.verbose {
+            println!(
+                "🧪 Iniciando backtesting para '{}' con {} puntos de datos...",
+                strategy.name(),
+                data.len()
+            );
+        }
+
         strategy.initialize()?;
-        
-        let mut balance = self.initial_balance;
-        let mut position: Option<(f64, f64)> = None; // (amount, entry_price)
-        let mut trades = 0;
-        let mut wins = 0;
-        let mut max_balance = balance;
-        let mut max_drawdown = 0.0;
-        let mut fees_paid = 0.0;
-
-        for tick in data {
-            let action = strategy.on_price_update(tick)?;
-            
-            match action {
-                TradeAction::Buy { confidence: _, .. } => {
-                    if position.is_none() && balance > 0.01 {
-                        // Simular compra con slippage de taker
-                        let execution_price = tick.price * (1.0 + self.slippage_taker);
-                        let amount = (balance - self.fee_per_trade) / execution_price;
-                        
-                        fees_paid += self.fee_per_trade;
-                        balance -= amount * execution_price + self.fee_per_trade;
-                        position = Some((amount, execution_price));
-                        
-                        println!("   🟢 BUY @ {:.6} (Amt: {:.4})", execution_price, amount);
-                    }
-                },
-                TradeAction::Sell { amount_percent, .. } => {
-                    if let Some((amount, entry)) = position {
-                        // Simular venta
-                        let execution_price = tick.price * (1.0 - self.slippage_taker);
-                        let sell_amount = amount * (amount_percent as f64 / 100.0);
-                        
-                        let revenue = sell_amount * execution_price - self.fee_per_trade;
-                        balance += revenue;
-                        fees_paid += self.fee_per_trade;
-                        
-                        // Check Win
-                        trades += 1;
-                        if execution_price > entry {
-                            wins += 1;
-                        }
-
-                        // Calcular Drawdown
-                        if balance > max_balance {
-                            max_balance = balance;
-                        }
-                        let dd = (max_balance - balance) / max_balance * 100.0;
-                        if dd > max_drawdown {
-                            max_drawdown = dd;
-                        }
-
-                        position = None; // Asumimos venta total por simplicidad
-                        println!("   🔴 SELL @ {:.6} (P/L: {:.2}%)", execution_price, (execution_price/entry - 1.0)*100.0);
-                    }
-                },
+
+        // Cola de eventos en orden temporal (estable: mismo ms conserva el orden del dataset)
+        let mut events: Vec<&MarketData> = data.iter().collect();
+        events.sort_by_key(|tick| tick.timestamp_ms);
+
+        let mut session = Session {
+            cash: self.initial_balance,
+            positions: HashMap::new(),
+            pools: HashMap::new(),
+            fills: Vec::new(),
+            equity_curve: Vec::with_capacity(events.len()),
+            trades: Vec::new(),
+            fees_paid: 0.0,
+            pool_fees: 0.0,
+            closed_trades: 0,
+            wins: 0,
+            peak_equity: self.initial_balance,
+            max_drawdown: 0.0,
+        };
+
+        for tick in events {
+            if let Some(position) = session.positions.get_mut(&tick.token_mint) {
+                position.mark(tick.price);
+            }
+            match tick.pool_reserves {
+                Some(reserves) => {
+                    session.pools.insert(tick.token_mint.clone(), reserves);
+                }
+                None => {
+                    session.pools.remove(&tick.token_mint);
+                }
+            }
+
+            self.process_exit_orders(&mut session, tick);
+
+            match strategy.on_price_update(tick)? {
+                TradeAction::Buy {
+                    target_price,
+                    stop_loss,
+                    ..
+                } => self.buy(&mut session, tick, target_price, stop_loss),
+                TradeAction::Sell {
+                    reason,
+                    amount_percent,
+                } => self.sell(&mut session, tick, amount_percent, FillKind::Sell(reason)),
                 TradeAction::Hold => {}
             }
+
+            session.mark(tick.timestamp_ms);
         }
 
-        // Cerrar posición al final si existe
-        if let Some((amount, _entry)) = position {
-            let last_price = data.last().map(|d| d.price).unwrap_or(0.0);
-            let revenue = amount * last_price;
-            balance += revenue;
-            println!("   ⚠️  Cierre forzado al final @ {:.6}", last_price);
+        // Cerrar lo que quede al final, al último precio de cada mint
+        let mut open: Vec<(String, f64)> = session
+            .positions
+            .iter()
+            .map(|(mint, p)| (mint.clone(), p.last_price))
+            .collect();
+        open.sort_by(|a, b| a.0.cmp(&b.0));
+        let last_ts = data
+            .iter()
+            .map(|d| d.timestamp_ms)
+            .max()
+            .unwrap_or_default();
+        let forced_close = !open.is_empty();
+        for (mint, last_price) in open {
+            let tick = MarketData {
+                token_mint: mint,
+                timestamp_ms: last_ts,
+                price: last_price,
+                volume_24h: 0.0,
+                liquidity: 0.0,
+                pool_reserves: None,
+            };
+            if self.verbose {
+                println!(
+                    "   ⚠️  Cierre forzado al final de {} @ {:.6}",
+                    tick.token_mint, last_price
+                );
+            }
+            self.sell(&mut session, &tick, 100, FillKind::ForcedClose);
+        }
+        if forced_close || session.equity_curve.is_empty() {
+            session.mark(last_ts);
         }
 
         let duration = start_time.elapsed();
-        println!("✅ Backtesting completado en {:?}", duration);
+        if self.verbose {
+            println!("✅ Backtesting completado en {:?}", duration);
+        }
 
+        let trades = session.closed_trades;
         Ok(BacktestResult {
             strategy_name: strategy.name().to_string(),
             total_trades: trades,
-            win_rate: if trades > 0 { (wins as f64 / trades as f64) * 100.0 } else { 0.0 },
-            final_balance: balance,
-            max_drawdown,
-            total_fees_paid: fees_paid,
+            win_rate: if trades > 0 {
+                (session.wins as f64 / trades as f64) * 100.0
+            } else {
+                0.0
+            },
+            final_balance: session.cash,
+            max_drawdown: session.max_drawdown,
+            total_fees_paid: session.fees_paid,
+            total_pool_fees: session.pool_fees,
+            fills: session.fills,
+            equity_curve: session.equity_curve,
+            trades: session.trades,
         })
     }
+
+    /// Trailing, escalera de TP y SL de la posición del mint del tick
+    fn process_exit_orders(&self, session: &mut Session, tick: &MarketData) {
+        let Some(position) = session.positions.get_mut(&tick.token_mint) else {
+            return;
+        };
+
+        let effective_sl = position.update_stop(tick.price);
+        let gain = position.gain_percent(tick.price);
+
+        // Solo el siguiente escalón pendiente por tick, como el StrategyEngine
+        if let Some(pct) = position.due_tp_percent(tick.price) {
+            position.tp_triggered += 1;
+            self.sell(session, tick, pct, FillKind::Sell(SellReason::TakeProfit));
+        }
+
+        if gain <= effective_sl && session.positions.contains_key(&tick.token_mint) {
+            self.sell(session, tick, 100, FillKind::Sell(SellReason::StopLoss));
+        }
+    }
+
+    fn buy(
+        &self,
+        session: &mut Session,
+        tick: &MarketData,
+        target_price: Option<f64>,
+        stop_loss: f64,
+    ) {
+        if session.positions.contains_key(&tick.token_mint) || tick.price <= 0.0 {
+            return;
+        }
+
+        let fee = self.costs.network_fee_sol();
+        let budget = session.equity() * self.position_size_pct / 100.0;
+        let size = budget.min(session.cash - fee);
+        if size < MIN_ORDER_SOL {
+            return;
+        }
+
+        let (tokens, pool_fee_sol) =
+            self.quote_buy(session.pools.get_mut(&tick.token_mint), tick.price, size);
+        if tokens <= 0.0 {
+            return;
+        }
+        let execution_price = size / tokens;
+        session.cash -= size + fee;
+        session.fees_paid += fee;
+        session.pool_fees += pool_fee_sol;
+
+        let plan = &self.exit_plan;
+        let stop_loss_percent = if stop_loss > 0.0 && stop_loss < execution_price {
+            (stop_loss / execution_price - 1.0) * 100.0
+        } else {
+            plan.stop_loss_percent
+        };
+        let tp_ladder = match target_price {
+            Some(target) if plan.tp_ladder.is_empty() && target > execution_price => {
+                vec![TpStep::new((target / execution_price - 1.0) * 100.0, 100.0)]
+            }
+            _ => plan.tp_ladder.clone(),
+        };
+        let trailing = plan.trailing_enabled.then_some(TrailingState {
+            peak_price: execution_price,
+            current_sl_percent: stop_loss_percent,
+            enabled: false,
+            distance_percent: plan.trailing_distance_percent,
+            activation_percent: plan.trailing_activation_threshold,
+        });
+
+        session.positions.insert(
+            tick.token_mint.clone(),
+            SimPosition {
+                opened_at_ms: tick.timestamp_ms,
+                invested_sol: size + fee,
+                entry_price: execution_price,
+                low_price: execution_price,
+                high_price: execution_price,
+                exits: Vec::new(),
+                tokens,
+                cost_basis: size + fee,
+                realized_pnl: 0.0,
+                last_price: tick.price,
+                stop_loss_percent,
+                trailing,
+                tp_ladder,
+                tp_triggered: 0,
+            },
+        );
+        session.fills.push(Fill {
+            timestamp_ms: tick.timestamp_ms,
+            token_mint: tick.token_mint.clone(),
+            kind: FillKind::Buy,
+            price: execution_price,
+            tokens,
+            sol: size,
+            fee,
+            pool_fee_sol,
+            realized_pnl: 0.0,
+        });
+
+        if self.verbose {
+            println!(
+                "   🟢 BUY {} @ {:.6} (Amt: {:.4})",
+                tick.token_mint, execution_price, tokens
+            );
+        }
+    }
+
+    /// Vende `amount_percent` del balance actual de la posición del mint
+    fn sell(&self, session: &mut Session, tick: &MarketData, amount_percent: u8, kind: FillKind) {
+        let Some(position) = session.positions.get_mut(&tick.token_mint) else {
+            return;
+        };
+        if amount_percent == 0 {
+            return;
+        }
+
+        let fraction = (amount_percent.min(100) as f64) / 100.0;
+        let tokens = position.tokens * fraction;
+        let cost = position.cost_basis * fraction;
+
+        let fee = self.costs.network_fee_sol();
+        let (proceeds, pool_fee_sol) =
+            self.quote_sell(session.pools.get_mut(&tick.token_mint), tick.price, tokens);
+        let execution_price = if tokens > 0.0 { proceeds / tokens } else { 0.0 };
+        let pnl = proceeds - fee - cost;
+
+        session.cash += proceeds - fee;
+        session.fees_paid += fee;
+        session.pool_fees += pool_fee_sol;
+        position.tokens -= tokens;
+        position.cost_basis -= cost;
+        position.realized_pnl += pnl;
+        position.exits.push(ExitLeg {
+            reason: kind.label().to_string(),
+            timestamp_ms: tick.timestamp_ms,
+            pnl_sol: pnl,
+        });
+
+        if self.verbose {
+            println!(
+                "   🔴 SELL {}% {} @ {:.6} (P/L: {:.2}%)",
+                amount_percent,
+                tick.token_mint,
+                execution_price,
+                (execution_price / position.entry_price - 1.0) * 100.0
+            );
+        }
+
+        let closed = (position.tokens <= DUST_TOKENS)
+            .then(|| position.summary(&tick.token_mint, tick.timestamp_ms));
+        session.fills.push(Fill {
+            timestamp_ms: tick.timestamp_ms,
+            token_mint: tick.token_mint.clone(),
+            kind,
+            price: execution_price,
+            tokens,
+            sol: proceeds,
+            fee,
+            pool_fee_sol,
+            realized_pnl: pnl,
+        });
+
+        if let Some(trade) = closed {
+            session.positions.remove(&tick.token_mint);
+            session.closed_trades += 1;
+            if trade.pnl_sol > 0.0 {
+                session.wins += 1;
+            }
+            session.trades.push(trade);
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Estrategia guionizada: acción por (mint, timestamp), Hold en el resto
+    #[derive(Debug, Default)]
+    struct Scripted {
+        actions: HashMap<(String, u64), TradeAction>,
+    }
+
+    impl Scripted {
+        fn on(mut self, mint: &str, timestamp_ms: u64, action: TradeAction) -> Self {
+            self.actions
+                .insert((mint.to_string(), timestamp_ms), action);
+            self
+        }
+    }
+
+    impl Strategy for Scripted {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        fn initialize(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn on_price_update(&self, data: &MarketData) -> Result<TradeAction> {
+            Ok(self
+                .actions
+                .get(&(data.token_mint.clone(), data.timestamp_ms))
+                .cloned()
+                .unwrap_or(TradeAction::Hold))
+        }
+    }
+
+    fn buy() -> TradeAction {
+        TradeAction::Buy {
+            confidence: 1.0,
+            target_price: None,
+            stop_loss: 0.0,
+        }
+    }
+
+    fn sell(amount_percent: u8) -> TradeAction {
+        TradeAction::Sell {
+            reason: SellReason::SignalReversal,
+            amount_percent,
+        }
+    }
+
+    fn tick(mint: &str, timestamp_ms: u64, price: f64) -> MarketData {
+        MarketData {
+            token_mint: mint.to_string(),
+            timestamp_ms,
+            price,
+            volume_24h: 0.0,
+            liquidity: 0.0,
+            pool_reserves: None,
+        }
+    }
+
+    /// Sin slippage ni fee de pool ni tip ni priority: solo queda la firma
+    fn simulator(plan: ExitPlan) -> MarketSimulator {
+        let mut sim = MarketSimulator::new(10.0)
+            .with_verbose(false)
+            .with_exit_plan(plan)
+            .with_costs(ExecutionCosts {
+                pool_fee_rate: 0.0,
+                priority_fee_microlamports: 0,
+                jito_tip_lamports: 0,
+                ..ExecutionCosts::default()
+            });
+        sim.slippage_taker = 0.0;
+        sim
+    }
+
+    /// Solo el SL base, lejos: ninguna salida automática
+    fn no_exits() -> ExitPlan {
+        ExitPlan {
+            stop_loss_percent: -90.0,
+            trailing_enabled: false,
+            tp_ladder: Vec::new(),
+            ..ExitPlan::default()
+        }
+    }
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_network_fee_matches_executor() {
+        // 2 × 5000 de firmas + 100k de tip, como el quote de `JitoBundled`,
+        // + 100k µL × 200k CU = 20k lamports de priority
+        assert!(approx(
+            ExecutionCosts::default().network_fee_sol(),
+            0.000130
+        ));
+        // Sin tip no hay TX del tip: una sola firma
+        let no_tip = ExecutionCosts {
+            jito_tip_lamports: 0,
+            ..ExecutionCosts::default()
+        };
+        assert!(approx(no_tip.network_fee_sol(), 0.000025));
+        // La priority se redondea hacia arriba al lamport
+        let odd = ExecutionCosts {
+            priority_fee_microlamports: 1,
+            compute_units: 1,
+            jito_tip_lamports: 0,
+            ..ExecutionCosts::default()
+        };
+        assert!(approx(odd.network_fee_sol(), 0.000005001));
+    }
+
+    #[test]
+    fn test_pool_fill_size_and_impact() {
+        let sim = simulator(no_exits())
+            .with_position_size(10.0)
+            .with_costs(ExecutionCosts {
+                pool_fee_rate: 0.0025,
+                priority_fee_microlamports: 0,
+                jito_tip_lamports: 0,
+                ..ExecutionCosts::default()
+            });
+        let mut strategy = Scripted::default().on("A", 1, buy());
+        // Spot 0.0001 SOL/token
+        let pool = PoolReserves {
+            sol: 100.0,
+            token: 1_000_000.0,
+        };
+        let data = vec![MarketData {
+            pool_reserves: Some(pool),
+            ..tick("A", 1, 0.0001)
+        }];
+
+        let result = sim.run(&mut strategy, &data).unwrap();
+
+        // 1 SOL (10% de 10): 1e6 × 0.9975 / (100 + 0.9975) tokens
+        let buy_fill = &result.fills[0];
+        assert!(approx(buy_fill.sol, 1.0));
+        assert!((buy_fill.tokens - 9_876.482_091_140_87).abs() < 1e-6);
+        assert!(approx(buy_fill.pool_fee_sol, 0.0025));
+        // Impacto: 1.2506% sobre el spot (0.25% de fee + 1% de la curva)
+        let impact_pct = (buy_fill.price / 0.0001 - 1.0) * 100.0;
+        assert!((impact_pct - 1.250_626_566).abs() < 1e-6);
+
+        // El cierre vende contra el pool ya movido por la compra (101 SOL)
+        let close = result.fills.last().unwrap();
+        assert_eq!(close.kind, FillKind::ForcedClose);
+        assert!((close.sol - 0.995_055_448_6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_partial_strategy_sell_keeps_position_open() {
+        let sim = simulator(no_exits());
+        let fee = sim.costs.network_fee_sol();
+        let mut strategy = Scripted::default().on("A", 1, buy()).on("A", 2, sell(50));
+        let data = vec![tick("A", 1, 1.0), tick("A", 2, 2.0), tick("A", 3, 2.0)];
+
+        let result = sim.run(&mut strategy, &data).unwrap();
+
+        let kinds: Vec<FillKind> = result.fills.iter().map(|f| f.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                FillKind::Buy,
+                FillKind::Sell(SellReason::SignalReversal),
+                FillKind::ForcedClose
+            ]
+        );
+        let bought = result.fills[0].tokens;
+        assert!(approx(result.fills[1].tokens, bought / 2.0));
+        // La mitad del coste (fee de compra incluida) contra lo recibido menos su fee
+        let half_cost = (result.fills[0].sol + fee) / 2.0;
+        assert!(approx(
+            result.fills[1].realized_pnl,
+            bought / 2.0 * 2.0 - fee - half_cost
+        ));
+        assert_eq!(result.equity_curve[1].open_positions, 1);
+        // Una posición con dos salidas
+        assert_eq!(result.total_trades, 1);
+        assert_eq!(result.trades[0].exits.len(), 2);
+    }
+
+    #[test]
+    fn test_two_mints_are_tracked_independently() {
+        let sim = simulator(ExitPlan {
+            stop_loss_percent: -30.0,
+            ..no_exits()
+        })
+        .with_position_size(40.0);
+        let mut strategy = Scripted::default().on("A", 1, buy()).on("B", 2, buy());
+        // B cae un 50% y salta su SL; A sigue a su precio
+        let data = vec![
+            tick("A", 1, 1.0),
+            tick("B", 2, 0.01),
+            tick("A", 3, 1.2),
+            tick("B", 4, 0.005),
+            tick("A", 5, 1.5),
+        ];
+
+        let result = sim.run(&mut strategy, &data).unwrap();
+
+        assert_eq!(result.equity_curve[2].open_positions, 2);
+        assert_eq!(result.equity_curve[3].open_positions, 1);
+        let stops: Vec<&Fill> = result
+            .fills
+            .iter()
+            .filter(|f| f.kind == FillKind::Sell(SellReason::StopLoss))
+            .collect();
+        assert_eq!(stops.len(), 1);
+        assert_eq!(stops[0].token_mint, "B");
+        assert!(approx(stops[0].price, 0.005));
+
+        let forced = result.fills.last().unwrap();
+        assert_eq!(forced.kind, FillKind::ForcedClose);
+        assert_eq!(forced.token_mint, "A");
+        assert!(approx(forced.price, 1.5));
+        assert_eq!(result.total_trades, 2);
+    }
+
+    #[test]
+    fn test_tp_ladder_fires_one_rung_per_tick() {
+        let sim = simulator(ExitPlan {
+            tp_ladder: vec![TpStep::new(50.0, 25.0), TpStep::new(100.0, 25.0)],
+            ..no_exits()
+        });
+        let mut strategy = Scripted::default().on("A", 1, buy());
+        // Salta los dos objetivos de golpe: un escalón en cada tick
+        let data = vec![tick("A", 1, 1.0), tick("A", 2, 3.0), tick("A", 3, 3.0)];
+
+        let result = sim.run(&mut strategy, &data).unwrap();
+
+        let tps: Vec<&Fill> = result
+            .fills
+            .iter()
+            .filter(|f| f.kind == FillKind::Sell(SellReason::TakeProfit))
+            .collect();
+        assert_eq!(tps.len(), 2);
+        assert_eq!((tps[0].timestamp_ms, tps[1].timestamp_ms), (2, 3));
+
+        // 25% del original; después 25 de 75 → 33% del balance
+        let bought = result.fills[0].tokens;
+        assert!(approx(tps[0].tokens, bought * 0.25));
+        assert!(approx(tps[1].tokens, bought * 0.75 * 0.33));
+    }
+
+    #[test]
+    fn test_trailing_activates_and_raises_the_stop() {
+        let sim = simulator(ExitPlan {
+            stop_loss_percent: -50.0,
+            trailing_enabled: true,
+            trailing_distance_percent: 10.0,
+            trailing_activation_threshold: 20.0,
+            tp_ladder: Vec::new(),
+        });
+        let mut strategy = Scripted::default().on("A", 1, buy());
+        let data = vec![
+            tick("A", 1, 1.0),
+            // +15%: sin activar, el retroceso a +5% no vende (manda el SL base)
+            tick("A", 2, 1.15),
+            tick("A", 3, 1.05),
+            // +50%: activo, stop en 1.35 (+35%)
+            tick("A", 4, 1.5),
+            tick("A", 5, 1.36),
+            tick("A", 6, 1.34),
+            tick("A", 7, 1.6),
+        ];
+
+        let result = sim.run(&mut strategy, &data).unwrap();
+
+        let stop = result
+            .fills
+            .iter()
+            .find(|f| f.kind == FillKind::Sell(SellReason::StopLoss))
+            .expect("el trailing debe vender");
+        assert_eq!(stop.timestamp_ms, 6);
+        assert!(approx(stop.price, 1.34));
+        assert_eq!(result.fills.len(), 2);
+    }
+
+    #[test]
+    fn test_drawdown_is_marked_to_market_without_sells() {
+        let sim = simulator(no_exits());
+        let fee = sim.costs.network_fee_sol();
+        let mut strategy = Scripted::default().on("A", 1, buy());
+        let data = vec![tick("A", 1, 1.0), tick("A", 2, 0.6), tick("A", 3, 1.0)];
+
+        let result = sim.run(&mut strategy, &data).unwrap();
+
+        // Sin ventas hasta el cierre forzado, pero la caída a 0.6 cuenta
+        assert_eq!(result.fills.len(), 2);
+        let tokens = 10.0 - fee;
+        let trough = tokens * 0.6;
+        assert!(approx(result.max_drawdown, (10.0 - trough) / 10.0 * 100.0));
+        assert!(approx(result.equity_curve[1].equity, trough));
+    }
+
+    #[test]
+    fn test_forced_close_pays_execution_costs() {
+        let mut sim = simulator(no_exits());
+        sim.costs = ExecutionCosts {
+            pool_fee_rate: 0.0025,
+            ..ExecutionCosts::default()
+        };
+        let fee = sim.costs.network_fee_sol();
+        assert!(fee > 0.0);
+        let mut strategy = Scripted::default().on("A", 1, buy());
+        let pool = PoolReserves {
+            sol: 100.0,
+            token: 100.0,
+        };
+        let data = vec![MarketData {
+            pool_reserves: Some(pool),
+            ..tick("A", 1, 1.0)
+        }];
+
+        let result = sim.run(&mut strategy, &data).unwrap();
+
+        let close = result.fills.last().unwrap();
+        assert_eq!(close.kind, FillKind::ForcedClose);
+        assert!(approx(close.fee, fee));
+        assert!(approx(result.total_fees_paid, 2.0 * fee));
+        // Se cierra contra las últimas reservas del mint: también paga fee de pool
+        assert!(close.pool_fee_sol > 0.0);
+        assert!(approx(
+            result.total_pool_fees,
+            result.fills[0].pool_fee_sol + close.pool_fee_sol
+        ));
+        assert!(approx(result.final_balance, close.sol - fee));
+        assert!(result.trades[0].pnl_sol < 0.0);
+    }
 }
@@ -5,11 +5,17 @@
 //! Módulos principales:
 //! - strategy_engine: Define la interfaz `Strategy` y estrategias comunes.
 //! - backtesting: Simulador de mercado para validar estrategias.
+//! - performance: Informe de rendimiento común a backtest y trading real.
+//! - optimizer: Barrido de parámetros de salida con validación walk-forward.
 //! - ml_bridge: (Futuro) Conexión con modelos Python vía FFI/IPC.
 
 pub mod strategy_engine;
 pub mod backtesting;
+pub mod performance;
+pub mod optimizer;
 
 // Re-exportar tipos comunes para facilitar uso
-pub use strategy_engine::{Strategy, MarketData, TradeAction};
-pub use backtesting::{MarketSimulator, BacktestResult};
+pub use strategy_engine::{Strategy, MarketData, PoolReserves, TradeAction};
+pub use backtesting::{MarketSimulator, BacktestResult, EquityPoint, ExecutionCosts, ExitPlan, Fill, FillKind, TpStep};
+pub use performance::{PerformanceReport, TradeSummary};
+pub use optimizer::{optimize, Objective, OptimizationReport, OptimizerConfig, ParamGrid, ParamSet};
@@ -0,0 +1,699 @@
+//! # Optimizer — Barrido de parámetros de salida con walk-forward
+//!
+//! Backtestea cada combinación de SL, trailing (distancia y activación) y
+//! escalera de TP sobre ticks grabados. Cada mint se compra en su primer tick
+//! de la ventana (`BuyAndManage`) y la salida queda en manos del `ExitPlan`,
+//! así solo se mide la gestión de la posición.
+//!
+//! Walk-forward anclado con `folds` ventanas: el periodo se parte en
+//! `folds + 1` tramos iguales y el fold `i` entrena sobre los tramos `0..=i` y
+//! evalúa fuera de muestra sobre el tramo `i + 1`:
+//!
+//! ```text
+//!   fold 0: [train ][test]
+//!   fold 1: [train       ][test]
+//!   fold 2: [train             ][test]
+//! ```
+//!
+//! La tabla se ordena por la métrica de entrenamiento y lleva al lado la de
+//! test: un candidato arriba en train y abajo en test está sobreajustado.
+//! Las combinaciones se reparten entre los núcleos con `std::thread::scope`.
+
+use crate::backtesting::{ExitPlan, MarketSimulator, TpStep};
+use crate::performance::PerformanceReport;
+use crate::strategy_engine::{MarketData, Strategy, TradeAction};
+use anyhow::{bail, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+
+// ============================================================================
+// ESPACIO DE PARÁMETROS
+// ============================================================================
+
+/// Una combinación de parámetros de salida
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamSet {
+    pub stop_loss_percent: f64,
+    pub trailing_distance_percent: f64,
+    pub trailing_activation_threshold: f64,
+    pub tp_ladder: Vec<TpStep>,
+}
+
+impl ParamSet {
+    pub fn exit_plan(&self) -> ExitPlan {
+        ExitPlan {
+            stop_loss_percent: self.stop_loss_percent,
+            trailing_enabled: true,
+            trailing_distance_percent: self.trailing_distance_percent,
+            trailing_activation_threshold: self.trailing_activation_threshold,
+            tp_ladder: self.tp_ladder.clone(),
+        }
+    }
+
+    /// Escalera en el formato de `/ladder` (`50:25,100:25`)
+    pub fn ladder_spec(&self) -> String {
+        self.tp_ladder
+            .iter()
+            .map(|step| format!("{}:{}", step.target_percent, step.sell_percent))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Valores a explorar de cada parámetro
+#[derive(Debug, Clone)]
+pub struct ParamGrid {
+    pub stop_loss_percent: Vec<f64>,
+    pub trailing_distance_percent: Vec<f64>,
+    pub trailing_activation_threshold: Vec<f64>,
+    pub tp_ladders: Vec<Vec<TpStep>>,
+}
+
+impl ParamGrid {
+    /// Producto cartesiano de todos los valores
+    pub fn grid(&self) -> Vec<ParamSet> {
+        let mut sets = Vec::new();
+        for &stop_loss_percent in &self.stop_loss_percent {
+            for &trailing_distance_percent in &self.trailing_distance_percent {
+                for &trailing_activation_threshold in &self.trailing_activation_threshold {
+                    for ladder in &self.tp_ladders {
+                        sets.push(ParamSet {
+                            stop_loss_percent,
+                            trailing_distance_percent,
+                            trailing_activation_threshold,
+                            tp_ladder: ladder.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        sets
+    }
+
+    /// Búsqueda aleatoria: cada parámetro numérico se muestrea uniforme entre
+    /// el mínimo y el máximo de su lista; la escalera, una de las dadas.
+    pub fn random(&self, samples: usize, seed: u64) -> Vec<ParamSet> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut sets = Vec::with_capacity(samples);
+        for _ in 0..samples {
+            let stop_loss_percent = sample_range(&mut rng, &self.stop_loss_percent);
+            let trailing_distance_percent = sample_range(&mut rng, &self.trailing_distance_percent);
+            let trailing_activation_threshold =
+                sample_range(&mut rng, &self.trailing_activation_threshold);
+            let ladder = &self.tp_ladders[rng.gen_range(0..self.tp_ladders.len())];
+            sets.push(ParamSet {
+                stop_loss_percent,
+                trailing_distance_percent,
+                trailing_activation_threshold,
+                tp_ladder: ladder.clone(),
+            });
+        }
+        sets
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.stop_loss_percent.is_empty()
+            || self.trailing_distance_percent.is_empty()
+            || self.trailing_activation_threshold.is_empty()
+            || self.tp_ladders.is_empty()
+        {
+            bail!("Cada parámetro necesita al menos un valor");
+        }
+        Ok(())
+    }
+}
+
+/// Métrica que se maximiza
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    NetPnl,
+    Sharpe,
+    ProfitFactor,
+    Expectancy,
+    WinRate,
+}
+
+impl Objective {
+    pub fn parse(label: &str) -> Option<Self> {
+        match label {
+            "net_pnl" | "pnl" => Some(Objective::NetPnl),
+            "sharpe" => Some(Objective::Sharpe),
+            "profit_factor" | "pf" => Some(Objective::ProfitFactor),
+            "expectancy" => Some(Objective::Expectancy),
+            "win_rate" => Some(Objective::WinRate),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Objective::NetPnl => "net_pnl",
+            Objective::Sharpe => "sharpe",
+            Objective::ProfitFactor => "profit_factor",
+            Objective::Expectancy => "expectancy",
+            Objective::WinRate => "win_rate",
+        }
+    }
+
+    /// Sin trades la puntuación es la peor posible
+    fn score(&self, report: &PerformanceReport) -> f64 {
+        if report.trades == 0 {
+            return f64::NEG_INFINITY;
+        }
+        match self {
+            Objective::NetPnl => report.net_pnl_sol,
+            Objective::Sharpe => report.sharpe.unwrap_or(0.0),
+            Objective::ProfitFactor => report.profit_factor.unwrap_or(
+                // Sin pérdidas: infinito si hubo beneficio
+                if report.gross_profit_sol > 0.0 {
+                    f64::INFINITY
+                } else {
+                    0.0
+                },
+            ),
+            Objective::Expectancy => report.expectancy_sol,
+            Objective::WinRate => report.win_rate,
+        }
+    }
+}
+
+/// Uniforme entre el mínimo y el máximo de `values`, redondeado a 0.1
+fn sample_range(rng: &mut StdRng, values: &[f64]) -> f64 {
+    let lo = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let value = if hi > lo { rng.gen_range(lo..=hi) } else { lo };
+    (value * 10.0).round() / 10.0
+}
+
+// ============================================================================
+// ESTRATEGIA DE ENTRADA
+// ============================================================================
+
+/// Compra cada mint en su primer tick y deja la salida al `ExitPlan`
+#[derive(Debug, Default)]
+pub struct BuyAndManage {
+    seen: RwLock<HashSet<String>>,
+}
+
+impl Strategy for BuyAndManage {
+    fn name(&self) -> &str {
+        "BuyAndManage"
+    }
+
+    fn initialize(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_price_update(&self, data: &MarketData) -> Result<TradeAction> {
+        let mut seen = self.seen.write().unwrap();
+        if seen.insert(data.token_mint.clone()) {
+            Ok(TradeAction::Buy {
+                confidence: 1.0,
+                target_price: None,
+                stop_loss: 0.0,
+            })
+        } else {
+            Ok(TradeAction::Hold)
+        }
+    }
+}
+
+// ============================================================================
+// WALK-FORWARD
+// ============================================================================
+
+/// Ventanas [inicio, fin) en ms
+#[derive(Debug, Clone, Copy)]
+pub struct WalkForwardWindow {
+    pub train: (u64, u64),
+    pub test: (u64, u64),
+}
+
+/// Ventanas ancladas sobre el periodo de `data`
+pub fn walk_forward_windows(data: &[MarketData], folds: usize) -> Vec<WalkForwardWindow> {
+    let (Some(start), Some(end)) = (
+        data.iter().map(|d| d.timestamp_ms).min(),
+        data.iter().map(|d| d.timestamp_ms).max(),
+    ) else {
+        return Vec::new();
+    };
+    let folds = folds.max(1) as u64;
+    // +1 para que el último tick caiga dentro de la última ventana
+    let segment = ((end + 1 - start) / (folds + 1)).max(1);
+
+    (0..folds)
+        .map(|i| {
+            let split = start + (i + 1) * segment;
+            let test_end = if i + 1 == folds {
+                end + 1
+            } else {
+                split + segment
+            };
+            WalkForwardWindow {
+                train: (start, split),
+                test: (split, test_end),
+            }
+        })
+        .collect()
+}
+
+fn slice(data: &[MarketData], (from, to): (u64, u64)) -> Vec<MarketData> {
+    data.iter()
+        .filter(|d| d.timestamp_ms >= from && d.timestamp_ms < to)
+        .cloned()
+        .collect()
+}
+
+// ============================================================================
+// OPTIMIZACIÓN
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct OptimizerConfig {
+    pub initial_balance: f64,
+    /// % de la equity por entrada (varias posiciones a la vez)
+    pub position_size_pct: f64,
+    pub objective: Objective,
+    pub folds: usize,
+    /// Hilos de trabajo (0 = todos los núcleos)
+    pub threads: usize,
+}
+
+/// Resultado de un candidato en una ventana
+#[derive(Debug, Clone)]
+pub struct WindowScore {
+    pub score: f64,
+    pub net_pnl_sol: f64,
+    pub trades: usize,
+    pub win_rate: f64,
+    pub max_drawdown: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CandidateResult {
+    pub params: ParamSet,
+    /// Una entrada por fold
+    pub train: Vec<WindowScore>,
+    pub test: Vec<WindowScore>,
+}
+
+impl CandidateResult {
+    pub fn train_score(&self) -> f64 {
+        mean_score(&self.train)
+    }
+
+    pub fn test_score(&self) -> f64 {
+        mean_score(&self.test)
+    }
+
+    pub fn test_pnl_sol(&self) -> f64 {
+        self.test.iter().map(|w| w.net_pnl_sol).sum()
+    }
+}
+
+/// Lo que el walk-forward habría desplegado en cada fold
+#[derive(Debug, Clone)]
+pub struct FoldResult {
+    pub window: WalkForwardWindow,
+    /// Índice en `OptimizationReport::candidates` del mejor en train
+    pub best: usize,
+    pub train: WindowScore,
+    pub test: WindowScore,
+}
+
+#[derive(Debug)]
+pub struct OptimizationReport {
+    pub objective: Objective,
+    /// Ordenados por puntuación media de entrenamiento (mejor primero)
+    pub candidates: Vec<CandidateResult>,
+    pub folds: Vec<FoldResult>,
+}
+
+/// Backtestea todos los candidatos en todas las ventanas, en paralelo
+pub fn optimize(
+    data: &[MarketData],
+    candidates: Vec<ParamSet>,
+    config: &OptimizerConfig,
+) -> Result<OptimizationReport> {
+    if candidates.is_empty() {
+        bail!("Sin combinaciones de parámetros que evaluar");
+    }
+    let windows = walk_forward_windows(data, config.folds);
+    if windows.is_empty() {
+        bail!("Sin ticks que evaluar");
+    }
+    let slices: Vec<(Vec<MarketData>, Vec<MarketData>)> = windows
+        .iter()
+        .map(|w| (slice(data, w.train), slice(data, w.test)))
+        .collect();
+
+    let threads = match config.threads {
+        0 => std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1),
+        n => n,
+    }
+    .min(candidates.len());
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<CandidateResult>>>> =
+        Mutex::new((0..candidates.len()).map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(params) = candidates.get(i) else {
+                    break;
+                };
+                let result = evaluate(params, &slices, config);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    let mut evaluated = Vec::with_capacity(candidates.len());
+    for result in results.into_inner().unwrap() {
+        match result {
+            Some(result) => evaluated.push(result?),
+            None => bail!("Un candidato quedó sin evaluar"),
+        }
+    }
+    evaluated.sort_by(|a, b| b.train_score().total_cmp(&a.train_score()));
+
+    // Walk-forward: en cada fold se despliega el mejor de train
+    let folds = windows
+        .iter()
+        .enumerate()
+        .filter_map(|(fold, window)| {
+            let best = (0..evaluated.len()).max_by(|&a, &b| {
+                evaluated[a].train[fold]
+                    .score
+                    .total_cmp(&evaluated[b].train[fold].score)
+            })?;
+            Some(FoldResult {
+                window: *window,
+                best,
+                train: evaluated[best].train[fold].clone(),
+                test: evaluated[best].test[fold].clone(),
+            })
+        })
+        .collect();
+
+    Ok(OptimizationReport {
+        objective: config.objective,
+        candidates: evaluated,
+        folds,
+    })
+}
+
+fn evaluate(
+    params: &ParamSet,
+    slices: &[(Vec<MarketData>, Vec<MarketData>)],
+    config: &OptimizerConfig,
+) -> Result<CandidateResult> {
+    let simulator = MarketSimulator::new(config.initial_balance)
+        .with_position_size(config.position_size_pct)
+        .with_exit_plan(params.exit_plan())
+        .with_verbose(false);
+
+    let run = |data: &[MarketData]| -> Result<WindowScore> {
+        let result = simulator.run(&mut BuyAndManage::default(), data)?;
+        let report = result.report();
+        Ok(WindowScore {
+            score: config.objective.score(&report),
+            net_pnl_sol: result.final_balance - config.initial_balance,
+            trades: report.trades,
+            win_rate: report.win_rate,
+            max_drawdown: result.max_drawdown,
+        })
+    };
+
+    let mut train = Vec::with_capacity(slices.len());
+    let mut test = Vec::with_capacity(slices.len());
+    for (train_data, test_data) in slices {
+        train.push(run(train_data)?);
+        test.push(run(test_data)?);
+    }
+    Ok(CandidateResult {
+        params: params.clone(),
+        train,
+        test,
+    })
+}
+
+/// Cota de las puntuaciones al promediar: un fold sin trades (−∞) junto a uno
+/// sin pérdidas (+∞ en profit factor) daría NaN y rompería el ranking
+const SCORE_BOUND: f64 = 1e9;
+
+fn mean_score(windows: &[WindowScore]) -> f64 {
+    if windows.is_empty() {
+        return f64::NEG_INFINITY;
+    }
+    windows
+        .iter()
+        .map(|w| w.score.clamp(-SCORE_BOUND, SCORE_BOUND))
+        .sum::<f64>()
+        / windows.len() as f64
+}
+
+// ============================================================================
+// EXPORT
+// ============================================================================
+
+impl OptimizationReport {
+    /// Tabla Markdown: ranking completo (o los `top` primeros) + walk-forward
+    pub fn to_markdown(&self, top: Option<usize>) -> String {
+        let objective = self.objective.as_str();
+        let mut md = format!(
+            "# Optimización de salidas ({} candidatos, objetivo: {})\n\n",
+            self.candidates.len(),
+            objective
+        );
+
+        md.push_str(&format!(
+            "| # | SL % | Trail dist % | Trail act % | TP ladder | Train {} | Test {} | Test PnL (SOL) | Trades train/test | Max DD test % |\n\
+             |---:|---:|---:|---:|---|---:|---:|---:|---:|---:|\n",
+            objective, objective
+        ));
+        let shown = top.unwrap_or(self.candidates.len());
+        for (rank, candidate) in self.candidates.iter().take(shown).enumerate() {
+            let p = &candidate.params;
+            let train_trades: usize = candidate.train.iter().map(|w| w.trades).sum();
+            let test_trades: usize = candidate.test.iter().map(|w| w.trades).sum();
+            let max_dd = candidate
+                .test
+                .iter()
+                .map(|w| w.max_drawdown)
+                .fold(0.0, f64::max);
+            md.push_str(&format!(
+                "| {} | {:.1} | {:.1} | {:.1} | `{}` | {} | {} | {:+.4} | {}/{} | {:.1} |\n",
+                rank + 1,
+                p.stop_loss_percent,
+                p.trailing_distance_percent,
+                p.trailing_activation_threshold,
+                p.ladder_spec(),
+                fmt_score(candidate.train_score()),
+                fmt_score(candidate.test_score()),
+                candidate.test_pnl_sol(),
+                train_trades,
+                test_trades,
+                max_dd
+            ));
+        }
+
+        md.push_str("\n## Walk-forward (mejor de train por fold)\n\n");
+        md.push_str(&format!(
+            "| Fold | Train | Test | Rank | Train {} | Test {} | Test PnL (SOL) |\n\
+             |---:|---|---|---:|---:|---:|---:|\n",
+            objective, objective
+        ));
+        for (i, fold) in self.folds.iter().enumerate() {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {:+.4} |\n",
+                i + 1,
+                fmt_window(fold.window.train),
+                fmt_window(fold.window.test),
+                fold.best + 1,
+                fmt_score(fold.train.score),
+                fmt_score(fold.test.score),
+                fold.test.net_pnl_sol
+            ));
+        }
+        let oos: f64 = self.folds.iter().map(|f| f.test.net_pnl_sol).sum();
+        md.push_str(&format!(
+            "\n**PnL fuera de muestra encadenado:** {:+.4} SOL\n",
+            oos
+        ));
+
+        md
+    }
+}
+
+fn fmt_score(score: f64) -> String {
+    if score.abs() < SCORE_BOUND {
+        format!("{:.4}", score)
+    } else if score > 0.0 {
+        "∞".to_string()
+    } else {
+        "—".to_string()
+    }
+}
+
+fn fmt_window((from, to): (u64, u64)) -> String {
+    let fmt = |ms: u64| {
+        chrono::DateTime::from_timestamp_millis(ms as i64)
+            .map(|t| t.format("%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    };
+    format!("{} → {}", fmt(from), fmt(to))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(ms: u64, price: f64) -> MarketData {
+        MarketData {
+            token_mint: "MINT".to_string(),
+            timestamp_ms: ms,
+            price,
+            volume_24h: 0.0,
+            liquidity: 0.0,
+            pool_reserves: None,
+        }
+    }
+
+    fn params_grid() -> ParamGrid {
+        ParamGrid {
+            stop_loss_percent: vec![-30.0, -50.0],
+            trailing_distance_percent: vec![10.0, 15.0, 20.0],
+            trailing_activation_threshold: vec![10.0],
+            tp_ladders: vec![
+                vec![TpStep::new(50.0, 100.0)],
+                vec![TpStep::new(25.0, 50.0), TpStep::new(100.0, 50.0)],
+            ],
+        }
+    }
+
+    fn window_score(score: f64) -> WindowScore {
+        WindowScore {
+            score,
+            net_pnl_sol: 0.0,
+            trades: 1,
+            win_rate: 0.0,
+            max_drawdown: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_walk_forward_windows_cover_every_tick_once_per_fold() {
+        let data: Vec<MarketData> = (0..=10).map(|i| tick(1_000 + i, 1.0)).collect();
+        let windows = walk_forward_windows(&data, 3);
+        assert_eq!(windows.len(), 3);
+
+        for (i, window) in windows.iter().enumerate() {
+            // Anclado al primer tick y sin solape train/test
+            assert_eq!(window.train.0, 1_000);
+            assert_eq!(window.train.1, window.test.0);
+            let train = slice(&data, window.train);
+            let test = slice(&data, window.test);
+            assert!(!test.is_empty());
+            assert!(train.last().unwrap().timestamp_ms < test[0].timestamp_ms);
+            if let Some(next) = windows.get(i + 1) {
+                assert_eq!(window.test.1, next.test.0);
+            }
+        }
+
+        // El último tick cae en el test del último fold
+        let last = windows.last().unwrap();
+        assert_eq!(last.test.1, 1_011);
+        assert_eq!(slice(&data, last.test).last().unwrap().timestamp_ms, 1_010);
+
+        assert!(walk_forward_windows(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_grid_is_the_full_cartesian_product() {
+        let grid = params_grid().grid();
+        assert_eq!(grid.len(), 2 * 3 * 2);
+        for (i, a) in grid.iter().enumerate() {
+            assert!(grid[i + 1..].iter().all(|b| a != b));
+        }
+    }
+
+    #[test]
+    fn test_random_search_is_reproducible_per_seed() {
+        let grid = params_grid();
+        let a = grid.random(20, 42);
+        assert_eq!(a, grid.random(20, 42));
+        assert_ne!(a, grid.random(20, 43));
+        assert!(a
+            .iter()
+            .all(|p| (-50.0..=-30.0).contains(&p.stop_loss_percent)
+                && (10.0..=20.0).contains(&p.trailing_distance_percent)
+                && p.trailing_activation_threshold == 10.0));
+    }
+
+    #[test]
+    fn test_mean_score_mixing_infinities_stays_finite() {
+        let mixed = [window_score(f64::NEG_INFINITY), window_score(f64::INFINITY)];
+        assert_eq!(mean_score(&mixed), 0.0);
+
+        // Un fold sin trades hunde la media pero no la vuelve NaN
+        let no_trades = [window_score(f64::NEG_INFINITY), window_score(0.5)];
+        assert!(mean_score(&no_trades) < mean_score(&[window_score(-0.5)]));
+        assert_eq!(
+            fmt_score(mean_score(&[window_score(f64::NEG_INFINITY)])),
+            "—"
+        );
+    }
+
+    #[test]
+    fn test_ranking_is_stable_across_threads() {
+        // Subida sostenida: el SL nunca salta, así los dos primeros empatan
+        let data: Vec<MarketData> = (0..30)
+            .map(|i| tick(i * 1_000, 1.0 + 0.05 * i as f64))
+            .collect();
+        let hold = |stop_loss_percent| ParamSet {
+            stop_loss_percent,
+            trailing_distance_percent: 50.0,
+            trailing_activation_threshold: 1_000.0,
+            tp_ladder: vec![TpStep::new(1_000.0, 100.0)],
+        };
+        let early_tp = ParamSet {
+            tp_ladder: vec![TpStep::new(10.0, 100.0)],
+            ..hold(-50.0)
+        };
+        let candidates = vec![hold(-50.0), early_tp.clone(), hold(-60.0)];
+
+        let ranking = |threads| {
+            let config = OptimizerConfig {
+                initial_balance: 10.0,
+                position_size_pct: 10.0,
+                objective: Objective::NetPnl,
+                folds: 2,
+                threads,
+            };
+            let report = optimize(&data, candidates.clone(), &config).unwrap();
+            report
+                .candidates
+                .iter()
+                .map(|c| c.params.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let ranked = ranking(1);
+        assert_eq!(ranked, vec![hold(-50.0), hold(-60.0), early_tp]);
+        for threads in [2, 4] {
+            assert_eq!(ranking(threads), ranked);
+        }
+    }
+}
@@ -0,0 +1,501 @@
+//! # Performance Report — Métricas comunes a backtest y trading real
+//!
+//! El informe se calcula sobre una lista de `TradeSummary` (una entrada con
+//! todas sus salidas), sin importar de dónde salgan:
+//!
+//! - Backtest: `BacktestResult::report()` (con MAE/MFE del recorrido de precio).
+//! - Real: el core agrupa `StateManager::get_trade_history` en ciclos
+//!   compra → ventas (sin trayectoria de precio no hay MAE/MFE).
+//!
+//! Sharpe y Sortino son POR TRADE (retorno = PnL neto / SOL invertido), sin
+//! anualizar: el histórico real no tiene curva de equity continua y así ambos
+//! informes son comparables. Se exporta en JSON y en Markdown.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Una salida (parcial o total) de un trade
+#[derive(Debug, Clone, Serialize)]
+pub struct ExitLeg {
+    /// Razón de la venta (`SellReason::as_str()`, o `MANUAL`, `END_OF_DATA`...)
+    pub reason: String,
+    pub timestamp_ms: u64,
+    /// PnL neto de esta salida (fees incluidos)
+    pub pnl_sol: f64,
+}
+
+/// Un trade cerrado: entrada + todas sus salidas
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeSummary {
+    pub token_mint: String,
+    pub opened_at_ms: u64,
+    pub closed_at_ms: u64,
+    /// SOL invertidos (fee de compra incluida)
+    pub invested_sol: f64,
+    /// PnL neto realizado (todas las salidas y fees)
+    pub pnl_sol: f64,
+    /// Máxima excursión adversa (% vs entrada, ≤ 0). `None` sin trayectoria de precio.
+    pub mae_percent: Option<f64>,
+    /// Máxima excursión favorable (% vs entrada, ≥ 0)
+    pub mfe_percent: Option<f64>,
+    pub exits: Vec<ExitLeg>,
+}
+
+impl TradeSummary {
+    /// Retorno neto del trade (%)
+    pub fn return_percent(&self) -> f64 {
+        if self.invested_sol > 0.0 {
+            self.pnl_sol / self.invested_sol * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn hold_secs(&self) -> f64 {
+        self.closed_at_ms.saturating_sub(self.opened_at_ms) as f64 / 1000.0
+    }
+}
+
+/// Desglose por razón de salida
+#[derive(Debug, Clone, Serialize)]
+pub struct ReasonBreakdown {
+    pub reason: String,
+    pub exits: usize,
+    pub wins: usize,
+    pub pnl_sol: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PerformanceReport {
+    pub trades: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub win_rate: f64,
+    pub net_pnl_sol: f64,
+    pub gross_profit_sol: f64,
+    pub gross_loss_sol: f64,
+    /// Beneficio bruto / pérdida bruta (`None` sin pérdidas)
+    pub profit_factor: Option<f64>,
+    /// PnL neto medio por trade (SOL)
+    pub expectancy_sol: f64,
+    /// Retorno medio por trade (%)
+    pub avg_return_percent: f64,
+    pub sharpe: Option<f64>,
+    pub sortino: Option<f64>,
+    pub avg_hold_secs: f64,
+    /// % del periodo con al menos una posición abierta
+    pub exposure_percent: f64,
+    pub longest_losing_streak: u32,
+    pub avg_mae_percent: Option<f64>,
+    pub avg_mfe_percent: Option<f64>,
+    pub by_reason: Vec<ReasonBreakdown>,
+    pub per_trade: Vec<TradeSummary>,
+}
+
+impl PerformanceReport {
+    /// Calcula el informe. `period_ms` es el intervalo observado (para la
+    /// exposición); por defecto, de la primera entrada a la última salida.
+    pub fn from_trades(trades: Vec<TradeSummary>, period_ms: Option<(u64, u64)>) -> Self {
+        let mut trades = trades;
+        trades.sort_by_key(|t| (t.closed_at_ms, t.opened_at_ms));
+
+        let n = trades.len();
+        let wins = trades.iter().filter(|t| t.pnl_sol > 0.0).count();
+        let gross_profit_sol: f64 = trades.iter().map(|t| t.pnl_sol.max(0.0)).sum();
+        let gross_loss_sol: f64 = trades.iter().map(|t| (-t.pnl_sol).max(0.0)).sum();
+        let net_pnl_sol = gross_profit_sol - gross_loss_sol;
+
+        let returns: Vec<f64> = trades.iter().map(|t| t.return_percent() / 100.0).collect();
+        let avg_return = mean(&returns).unwrap_or(0.0);
+
+        let sharpe = std_dev(&returns)
+            .filter(|sd| *sd > 0.0)
+            .map(|sd| avg_return / sd);
+        let downside = (!returns.is_empty())
+            .then(|| (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n as f64).sqrt());
+        let sortino = downside.filter(|dd| *dd > 0.0).map(|dd| avg_return / dd);
+
+        let mut longest_losing_streak = 0;
+        let mut streak = 0;
+        for trade in &trades {
+            if trade.pnl_sol < 0.0 {
+                streak += 1;
+                longest_losing_streak = longest_losing_streak.max(streak);
+            } else {
+                streak = 0;
+            }
+        }
+
+        let mut by_reason: BTreeMap<&str, ReasonBreakdown> = BTreeMap::new();
+        for leg in trades.iter().flat_map(|t| &t.exits) {
+            let entry = by_reason
+                .entry(leg.reason.as_str())
+                .or_insert_with(|| ReasonBreakdown {
+                    reason: leg.reason.clone(),
+                    exits: 0,
+                    wins: 0,
+                    pnl_sol: 0.0,
+                });
+            entry.exits += 1;
+            entry.pnl_sol += leg.pnl_sol;
+            if leg.pnl_sol > 0.0 {
+                entry.wins += 1;
+            }
+        }
+        let by_reason: Vec<ReasonBreakdown> = by_reason.into_values().collect();
+
+        let maes: Vec<f64> = trades.iter().filter_map(|t| t.mae_percent).collect();
+        let mfes: Vec<f64> = trades.iter().filter_map(|t| t.mfe_percent).collect();
+        let holds: Vec<f64> = trades.iter().map(TradeSummary::hold_secs).collect();
+
+        let period = period_ms.or_else(|| {
+            let start = trades.iter().map(|t| t.opened_at_ms).min()?;
+            let end = trades.iter().map(|t| t.closed_at_ms).max()?;
+            Some((start, end))
+        });
+
+        Self {
+            trades: n,
+            wins,
+            losses: trades.iter().filter(|t| t.pnl_sol < 0.0).count(),
+            win_rate: if n > 0 {
+                wins as f64 / n as f64 * 100.0
+            } else {
+                0.0
+            },
+            net_pnl_sol,
+            gross_profit_sol,
+            gross_loss_sol,
+            profit_factor: (gross_loss_sol > 0.0).then(|| gross_profit_sol / gross_loss_sol),
+            expectancy_sol: if n > 0 { net_pnl_sol / n as f64 } else { 0.0 },
+            avg_return_percent: avg_return * 100.0,
+            sharpe,
+            sortino,
+            avg_hold_secs: mean(&holds).unwrap_or(0.0),
+            exposure_percent: period
+                .map(|period| exposure_percent(&trades, period))
+                .unwrap_or(0.0),
+            longest_losing_streak,
+            avg_mae_percent: mean(&maes),
+            avg_mfe_percent: mean(&mfes),
+            by_reason,
+            per_trade: trades,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("# Performance Report\n\n");
+
+        md.push_str("| Métrica | Valor |\n|---|---|\n");
+        let rows = [
+            ("Trades", self.trades.to_string()),
+            (
+                "Win rate",
+                format!("{:.1}% ({}W / {}L)", self.win_rate, self.wins, self.losses),
+            ),
+            ("PnL neto", format!("{:+.4} SOL", self.net_pnl_sol)),
+            ("Profit factor", fmt_opt(self.profit_factor, 2)),
+            (
+                "Expectancy",
+                format!("{:+.4} SOL/trade", self.expectancy_sol),
+            ),
+            ("Retorno medio", format!("{:+.2}%", self.avg_return_percent)),
+            ("Sharpe (por trade)", fmt_opt(self.sharpe, 2)),
+            ("Sortino (por trade)", fmt_opt(self.sortino, 2)),
+            ("Hold medio", fmt_duration(self.avg_hold_secs)),
+            ("Exposición", format!("{:.1}%", self.exposure_percent)),
+            (
+                "Racha perdedora más larga",
+                self.longest_losing_streak.to_string(),
+            ),
+            ("MAE medio", fmt_opt_pct(self.avg_mae_percent)),
+            ("MFE medio", fmt_opt_pct(self.avg_mfe_percent)),
+        ];
+        for (name, value) in rows {
+            md.push_str(&format!("| {} | {} |\n", name, value));
+        }
+
+        md.push_str("\n## Por razón de salida\n\n");
+        md.push_str("| Razón | Salidas | Ganadoras | PnL (SOL) |\n|---|---:|---:|---:|\n");
+        for reason in &self.by_reason {
+            md.push_str(&format!(
+                "| {} | {} | {} | {:+.4} |\n",
+                reason.reason, reason.exits, reason.wins, reason.pnl_sol
+            ));
+        }
+
+        md.push_str("\n## Trades\n\n");
+        md.push_str(
+            "| Mint | Apertura | Hold | Invertido | PnL (SOL) | Retorno | MAE | MFE | Salidas |\n\
+             |---|---|---|---:|---:|---:|---:|---:|---|\n",
+        );
+        for trade in &self.per_trade {
+            let opened = chrono::DateTime::from_timestamp_millis(trade.opened_at_ms as i64)
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            let exits: Vec<&str> = trade.exits.iter().map(|e| e.reason.as_str()).collect();
+            md.push_str(&format!(
+                "| `{}` | {} | {} | {:.4} | {:+.4} | {:+.2}% | {} | {} | {} |\n",
+                trade.token_mint,
+                opened,
+                fmt_duration(trade.hold_secs()),
+                trade.invested_sol,
+                trade.pnl_sol,
+                trade.return_percent(),
+                fmt_opt_pct(trade.mae_percent),
+                fmt_opt_pct(trade.mfe_percent),
+                exits.join(", ")
+            ));
+        }
+
+        md
+    }
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Desviación típica muestral (n − 1)
+fn std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let m = mean(values)?;
+    let var = values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some(var.sqrt())
+}
+
+/// % de `period` cubierto por la unión de los intervalos de los trades
+fn exposure_percent(trades: &[TradeSummary], (start, end): (u64, u64)) -> f64 {
+    if end <= start {
+        return 0.0;
+    }
+
+    let mut intervals: Vec<(u64, u64)> = trades
+        .iter()
+        .map(|t| (t.opened_at_ms.max(start), t.closed_at_ms.min(end)))
+        .filter(|(open, close)| close > open)
+        .collect();
+    intervals.sort_unstable();
+
+    let mut covered = 0;
+    let mut cursor = start;
+    for (open, close) in intervals {
+        let open = open.max(cursor);
+        if close > open {
+            covered += close - open;
+            cursor = close;
+        }
+    }
+    covered as f64 / (end - start) as f64 * 100.0
+}
+
+fn fmt_opt(value: Option<f64>, decimals: usize) -> String {
+    value
+        .map(|v| format!("{:.*}", decimals, v))
+        .unwrap_or_else(|| "—".to_string())
+}
+
+fn fmt_opt_pct(value: Option<f64>) -> String {
+    value
+        .map(|v| format!("{:+.2}%", v))
+        .unwrap_or_else(|| "—".to_string())
+}
+
+fn fmt_duration(secs: f64) -> String {
+    let secs = secs.max(0.0) as u64;
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trade de 1 SOL invertido (retorno = PnL) con sus salidas
+    fn trade(
+        mint: &str,
+        opened_at_ms: u64,
+        closed_at_ms: u64,
+        exits: &[(&str, f64)],
+    ) -> TradeSummary {
+        TradeSummary {
+            token_mint: mint.to_string(),
+            opened_at_ms,
+            closed_at_ms,
+            invested_sol: 1.0,
+            pnl_sol: exits.iter().map(|(_, pnl)| pnl).sum(),
+            mae_percent: None,
+            mfe_percent: None,
+            exits: exits
+                .iter()
+                .map(|(reason, pnl_sol)| ExitLeg {
+                    reason: reason.to_string(),
+                    timestamp_ms: closed_at_ms,
+                    pnl_sol: *pnl_sol,
+                })
+                .collect(),
+        }
+    }
+
+    /// Retornos por orden de cierre: +0.5, -0.2, -0.1, +0.4, -0.1
+    /// (A y B se solapan entre 5s y 10s)
+    fn fixture() -> Vec<TradeSummary> {
+        let mut a = trade(
+            "A",
+            0,
+            10_000,
+            &[("TAKE_PROFIT", 0.2), ("TAKE_PROFIT", 0.3)],
+        );
+        a.mae_percent = Some(-5.0);
+        a.mfe_percent = Some(60.0);
+        let mut b = trade("B", 5_000, 20_000, &[("STOP_LOSS", -0.2)]);
+        b.mae_percent = Some(-25.0);
+        b.mfe_percent = Some(10.0);
+        // Desordenados: el informe ordena por cierre antes de contar rachas
+        vec![
+            trade("E", 70_000, 80_000, &[("STOP_LOSS", -0.1)]),
+            trade("D", 50_000, 60_000, &[("TAKE_PROFIT", 0.4)]),
+            b,
+            trade("C", 30_000, 40_000, &[("STOP_LOSS", -0.1)]),
+            a,
+        ]
+    }
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_report_on_hand_computed_trades() {
+        let report = PerformanceReport::from_trades(fixture(), None);
+
+        let order: Vec<&str> = report
+            .per_trade
+            .iter()
+            .map(|t| t.token_mint.as_str())
+            .collect();
+        assert_eq!(order, ["A", "B", "C", "D", "E"]);
+        assert_eq!((report.trades, report.wins, report.losses), (5, 2, 3));
+        assert!(approx(report.win_rate, 40.0));
+        assert!(approx(report.gross_profit_sol, 0.9));
+        assert!(approx(report.gross_loss_sol, 0.4));
+        assert!(approx(report.net_pnl_sol, 0.5));
+        assert!(approx(report.profit_factor.unwrap(), 2.25));
+        assert!(approx(report.expectancy_sol, 0.1));
+        assert!(approx(report.avg_return_percent, 10.0));
+
+        // Media 0.1; desviaciones² 0.16 + 0.09 + 0.04 + 0.09 + 0.04 = 0.42 → var 0.42 / 4
+        assert!(approx(report.sharpe.unwrap(), 0.1 / 0.105_f64.sqrt()));
+        // Solo las pérdidas: 0.04 + 0.01 + 0.01 = 0.06 → 0.06 / 5
+        assert!(approx(report.sortino.unwrap(), 0.1 / 0.012_f64.sqrt()));
+
+        // B, C pierden; D corta la racha; E empieza otra
+        assert_eq!(report.longest_losing_streak, 2);
+        // Holds de 10s, 15s, 10s, 10s, 10s
+        assert!(approx(report.avg_hold_secs, 11.0));
+        // MAE/MFE solo de los trades que los tienen (A y B)
+        assert!(approx(report.avg_mae_percent.unwrap(), -15.0));
+        assert!(approx(report.avg_mfe_percent.unwrap(), 35.0));
+    }
+
+    #[test]
+    fn test_exposure_counts_overlapping_trades_once() {
+        // A ∪ B cubre 0–20s, C, D y E 10s cada uno: 50s de 80s
+        let report = PerformanceReport::from_trades(fixture(), None);
+        assert!(approx(report.exposure_percent, 62.5));
+
+        // Periodo explícito más largo que los trades
+        let report = PerformanceReport::from_trades(fixture(), Some((0, 100_000)));
+        assert!(approx(report.exposure_percent, 50.0));
+
+        // Periodo que recorta A y B: solo cuentan 8s–20s de 8s–28s
+        let report = PerformanceReport::from_trades(fixture(), Some((8_000, 28_000)));
+        assert!(approx(report.exposure_percent, 60.0));
+    }
+
+    #[test]
+    fn test_by_reason_aggregates_every_exit_leg() {
+        let report = PerformanceReport::from_trades(fixture(), None);
+
+        assert_eq!(report.by_reason.len(), 2);
+        let stop = &report.by_reason[0];
+        assert_eq!(stop.reason, "STOP_LOSS");
+        assert_eq!((stop.exits, stop.wins), (3, 0));
+        assert!(approx(stop.pnl_sol, -0.4));
+        // Los dos escalones de A cuentan como salidas separadas
+        let take_profit = &report.by_reason[1];
+        assert_eq!(take_profit.reason, "TAKE_PROFIT");
+        assert_eq!((take_profit.exits, take_profit.wins), (3, 3));
+        assert!(approx(take_profit.pnl_sol, 0.9));
+    }
+
+    #[test]
+    fn test_without_losses_profit_factor_and_sortino_are_none() {
+        let trades = vec![
+            trade("A", 0, 1_000, &[("TAKE_PROFIT", 0.1)]),
+            trade("B", 1_000, 2_000, &[("TAKE_PROFIT", 0.3)]),
+        ];
+        let report = PerformanceReport::from_trades(trades, None);
+
+        assert_eq!(report.losses, 0);
+        assert_eq!(report.profit_factor, None);
+        assert_eq!(report.sortino, None);
+        assert_eq!(report.longest_losing_streak, 0);
+        // Retornos distintos: el Sharpe sí existe (media 0.2, sd √0.02)
+        assert!(approx(report.sharpe.unwrap(), 0.2 / 0.02_f64.sqrt()));
+        assert!(report.to_markdown().contains("| Profit factor | — |"));
+    }
+
+    #[test]
+    fn test_zero_std_dev_has_no_sharpe() {
+        let trades = vec![
+            trade("A", 0, 1_000, &[("STOP_LOSS", -0.1)]),
+            trade("B", 1_000, 2_000, &[("STOP_LOSS", -0.1)]),
+        ];
+        let report = PerformanceReport::from_trades(trades, None);
+
+        assert_eq!(report.sharpe, None);
+        // La desviación a la baja sí es > 0: −0.1 / √0.01
+        assert!(approx(report.sortino.unwrap(), -1.0));
+        assert!(approx(report.profit_factor.unwrap(), 0.0));
+
+        // Con un solo trade no hay desviación muestral
+        let single = vec![trade("A", 0, 1_000, &[("TAKE_PROFIT", 0.5)])];
+        assert_eq!(PerformanceReport::from_trades(single, None).sharpe, None);
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let report = PerformanceReport::from_trades(Vec::new(), None);
+
+        assert_eq!((report.trades, report.wins, report.losses), (0, 0, 0));
+        assert_eq!(report.win_rate, 0.0);
+        assert_eq!(report.net_pnl_sol, 0.0);
+        assert_eq!(report.expectancy_sol, 0.0);
+        assert_eq!(report.profit_factor, None);
+        assert_eq!(report.sharpe, None);
+        assert_eq!(report.sortino, None);
+        assert_eq!(report.avg_hold_secs, 0.0);
+        assert_eq!(report.exposure_percent, 0.0);
+        assert_eq!(report.avg_mae_percent, None);
+        assert_eq!(report.avg_mfe_percent, None);
+        assert!(report.by_reason.is_empty());
+        assert!(report.to_json().is_ok());
+    }
+}
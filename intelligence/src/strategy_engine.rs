@@ -4,7 +4,9 @@
 //! Permite backtesting seguro y ejecución en tiempo real con la misma lógica.
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::RwLock;
 
@@ -16,6 +18,47 @@ pub enum SellReason {
     MomentumLoss,
     SignalReversal,
     Emergency,
+    /// Superado el tiempo máximo en cartera
+    TimeLimit,
+    /// Stop movido a break-even y el precio volvió a la entrada
+    BreakEven,
+    /// Momentum plano durante demasiado tiempo
+    StaleMomentum,
+    /// La liquidez del pool cayó por debajo del mínimo
+    LiquidityFloor,
+}
+
+impl SellReason {
+    /// Etiqueta estable para logs y persistencia (ej: `trade_type`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SellReason::TakeProfit => "TAKE_PROFIT",
+            SellReason::StopLoss => "STOP_LOSS",
+            SellReason::MomentumLoss => "MOMENTUM_LOSS",
+            SellReason::SignalReversal => "SIGNAL_REVERSAL",
+            SellReason::Emergency => "EMERGENCY",
+            SellReason::TimeLimit => "TIME_LIMIT",
+            SellReason::BreakEven => "BREAK_EVEN",
+            SellReason::StaleMomentum => "STALE_MOMENTUM",
+            SellReason::LiquidityFloor => "LIQUIDITY_FLOOR",
+        }
+    }
+
+    pub fn parse(label: &str) -> Option<Self> {
+        [
+            SellReason::TakeProfit,
+            SellReason::StopLoss,
+            SellReason::MomentumLoss,
+            SellReason::SignalReversal,
+            SellReason::Emergency,
+            SellReason::TimeLimit,
+            SellReason::BreakEven,
+            SellReason::StaleMomentum,
+            SellReason::LiquidityFloor,
+        ]
+        .into_iter()
+        .find(|reason| reason.as_str() == label)
+    }
 }
 
 /// Representa una acción de trading sugerida por una estrategia
@@ -44,13 +87,36 @@ pub enum TradeAction {
 /// Datos de mercado estandarizados para alimentar las estrategias
 #[derive(Debug, Clone)]
 pub struct MarketData {
+    /// Mint del token (permite intercalar varios tokens en un mismo histórico)
+    pub token_mint: String,
     pub timestamp_ms: u64,
     pub price: f64,
     pub volume_24h: f64,
     pub liquidity: f64,
+    /// Reservas del pool en el tick (si se conocen): el backtester las usa
+    /// para simular el fill con x·y=k en lugar de un slippage fijo
+    pub pool_reserves: Option<PoolReserves>,
     // Puedes añadir más campos aquí (RSI, MA, sentiment, etc.)
 }
 
+/// Reservas de un pool token/SOL en unidades UI (no raw)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PoolReserves {
+    pub sol: f64,
+    pub token: f64,
+}
+
+impl PoolReserves {
+    /// Precio spot en SOL por token
+    pub fn price(&self) -> f64 {
+        if self.token > 0.0 {
+            self.sol / self.token
+        } else {
+            0.0
+        }
+    }
+}
+
 /// El Trait Sagrado que todas las estrategias deben implementar
 pub trait Strategy: Debug + Send + Sync {
     /// Nombre de la estrategia (para logs y reportes)
@@ -75,7 +141,8 @@ pub trait Strategy: Debug + Send + Sync {
 #[derive(Debug)]
 pub struct SimpleMomentumStrategy {
     symbol: String,
-    last_price: RwLock<Option<f64>>,
+    /// Último precio visto por mint
+    last_price: RwLock<HashMap<String, f64>>,
     momentum_threshold: f64,
 }
 
@@ -83,7 +150,7 @@ impl SimpleMomentumStrategy {
     pub fn new(symbol: String, threshold: f64) -> Self {
         Self {
             symbol,
-            last_price: RwLock::new(None),
+            last_price: RwLock::new(HashMap::new()),
             momentum_threshold: threshold,
         }
     }
@@ -104,7 +171,7 @@ impl Strategy for SimpleMomentumStrategy {
         
         let mut last_price_guard = self.last_price.write().unwrap();
         
-        let action = if let Some(last) = *last_price_guard {
+        let action = if let Some(&last) = last_price_guard.get(&data.token_mint) {
             let change_pct = (current_price - last) / last * 100.0;
             
             if change_pct > self.momentum_threshold {
@@ -125,7 +192,7 @@ impl Strategy for SimpleMomentumStrategy {
             TradeAction::Hold
         };
         
-        *last_price_guard = Some(current_price);
+        last_price_guard.insert(data.token_mint.clone(), current_price);
         Ok(action)
     }
 }